@@ -0,0 +1,116 @@
+//! Counts the heap allocations made while encoding responses.
+//!
+//! Allocation counts are deterministic, so this reports them directly rather
+//! than going through criterion.
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use bytes::{BufMut, BytesMut};
+use laconia_agent::{
+    KafkaMessageCodec, KafkaResponse, RequestHeader,
+    protocol::{Encoder, EncoderVersioned},
+};
+use tokio_util::codec::Encoder as _;
+
+use crate::common::metadata_response;
+
+mod common;
+
+const RESPONSES: usize = 10_000;
+
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+/// Runs `f` once per response and reports how many allocations it made. The
+/// inputs are built up front so that only `f` is counted.
+fn count_allocations<T>(name: &str, inputs: impl Fn() -> T, mut f: impl FnMut(T)) {
+    let inputs: Vec<T> = (0..RESPONSES).map(|_| inputs()).collect();
+
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    for input in inputs {
+        f(input);
+    }
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed) - before;
+
+    println!(
+        "{}: {} allocations per {} responses ({:.1} each)",
+        name,
+        allocations,
+        RESPONSES,
+        allocations as f64 / RESPONSES as f64
+    );
+}
+
+fn kafka_response() -> KafkaResponse {
+    let header = RequestHeader {
+        api_key: 3,
+        version: 12,
+        correlation_id: 1,
+        client_id: String::new(),
+        tagged_fields: Default::default(),
+    };
+
+    KafkaResponse::new(&header, 12, Box::new(metadata_response(1, 1)))
+}
+
+fn main() {
+    let response = metadata_response(100, 10);
+
+    count_allocations(
+        "metadata_response_encode/unsized",
+        || (),
+        |()| {
+            let mut buf = BytesMut::new();
+            response.encode(&mut buf, i16::MAX).unwrap();
+        },
+    );
+
+    count_allocations(
+        "metadata_response_encode/size_hint",
+        || (),
+        |()| {
+            let mut buf = BytesMut::with_capacity(response.size_hint(i16::MAX));
+            response.encode(&mut buf, i16::MAX).unwrap();
+        },
+    );
+
+    // The write buffer is drained after every response, like `Framed` does.
+    let mut dst = BytesMut::with_capacity(64 * 1024);
+
+    count_allocations("codec_encode/buffer_per_response", kafka_response, |item| {
+        let mut buf = BytesMut::with_capacity(item.size_hint());
+        item.encode(&mut buf).unwrap();
+        dst.put_i32(buf.len() as i32);
+        dst.put(buf);
+        dst.clear();
+    });
+
+    let mut codec = KafkaMessageCodec::new();
+    count_allocations("codec_encode/scratch_buffer", kafka_response, |item| {
+        codec.encode(item, &mut dst).unwrap();
+        dst.clear();
+    });
+}
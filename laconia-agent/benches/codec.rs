@@ -0,0 +1,92 @@
+//! Baseline throughput of the encode/decode hot paths.
+
+use bytes::{BufMut, BytesMut};
+use criterion::{BatchSize, Criterion, Throughput, criterion_group, criterion_main};
+use integer_encoding::VarIntWriter;
+use laconia_agent::{
+    KafkaMessageCodec,
+    protocol::{Decoder, EncoderVersioned, primitives::CompactArray},
+};
+use tokio_util::codec::Decoder as _;
+
+use crate::common::metadata_response;
+
+mod common;
+
+const ARRAY_LEN: usize = 64 * 1024;
+const FRAMES: usize = 10_000;
+const FRAME_LEN: usize = 128;
+
+fn metadata_response_encode(c: &mut Criterion) {
+    let response = metadata_response(100, 10);
+
+    let mut encoded = BytesMut::new();
+    response.encode(&mut encoded, 12).unwrap();
+
+    let mut group = c.benchmark_group("metadata_response_encode");
+    group.throughput(Throughput::Bytes(encoded.len() as u64));
+    group.bench_function("v12", |b| {
+        b.iter(|| {
+            let mut buf = BytesMut::with_capacity(encoded.len());
+            response.encode(&mut buf, 12).unwrap();
+            buf
+        })
+    });
+    group.finish();
+}
+
+fn compact_array_decode(c: &mut Criterion) {
+    let mut buf = BytesMut::new();
+    (&mut buf)
+        .writer()
+        .write_varint(ARRAY_LEN as u32 + 1)
+        .unwrap();
+    for i in 0..ARRAY_LEN {
+        buf.put_i16(i as i16);
+    }
+
+    let mut group = c.benchmark_group("compact_array_decode");
+    group.throughput(Throughput::Bytes(buf.len() as u64));
+    group.bench_function("i16", |b| {
+        b.iter_batched(
+            || buf.clone(),
+            |mut buf| CompactArray::<i16>::decode(&mut buf).unwrap(),
+            BatchSize::SmallInput,
+        )
+    });
+    group.finish();
+}
+
+fn codec_decode(c: &mut Criterion) {
+    let mut buf = BytesMut::new();
+    for _ in 0..FRAMES {
+        buf.put_i32(FRAME_LEN as i32);
+        buf.put_bytes(0, FRAME_LEN);
+    }
+
+    let mut group = c.benchmark_group("codec_decode");
+    group.throughput(Throughput::Bytes(buf.len() as u64));
+    group.bench_function("frames", |b| {
+        b.iter_batched(
+            || buf.clone(),
+            |mut buf| {
+                let mut codec = KafkaMessageCodec::new();
+                let mut frames = 0;
+                while codec.decode(&mut buf).unwrap().is_some() {
+                    frames += 1;
+                }
+                frames
+            },
+            BatchSize::SmallInput,
+        )
+    });
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    metadata_response_encode,
+    compact_array_decode,
+    codec_decode
+);
+criterion_main!(benches);
@@ -0,0 +1,53 @@
+use laconia_agent::protocol::{
+    error::ErrorCode,
+    messages::{
+        MetadataResponse, MetadataResponseBrokers, MetadataResponseTopic,
+        MetadataResponseTopicPartition,
+    },
+};
+use uuid::Uuid;
+
+/// A Metadata response for a three-broker cluster.
+pub fn metadata_response(topics: usize, partitions: i32) -> MetadataResponse {
+    let brokers = (0..3)
+        .map(|node_id| MetadataResponseBrokers {
+            node_id,
+            host: format!("broker-{}.example.com", node_id),
+            port: 9092,
+            rack: String::new(),
+            tagged_fields: Default::default(),
+        })
+        .collect();
+
+    let topics = (0..topics)
+        .map(|topic| MetadataResponseTopic {
+            error_code: ErrorCode::None,
+            name: format!("topic-{}", topic),
+            topic_id: Uuid::new_v4(),
+            is_internal: false,
+            partitions: (0..partitions)
+                .map(|partition_index| MetadataResponseTopicPartition {
+                    error_code: ErrorCode::None,
+                    partition_index,
+                    leader_id: partition_index % 3,
+                    leader_epoch: 0,
+                    replica_nodes: vec![0, 1, 2],
+                    isr_nodes: vec![0, 1, 2],
+                    offline_replicas: vec![],
+                    tagged_fields: Default::default(),
+                })
+                .collect(),
+            topic_authorized_operations: i32::MIN,
+            tagged_fields: Default::default(),
+        })
+        .collect();
+
+    MetadataResponse {
+        throttle_time_ms: 0,
+        brokers,
+        cluster_id: "laconia".to_string(),
+        controller_id: 0,
+        topics,
+        tagged_fields: Default::default(),
+    }
+}
@@ -0,0 +1,79 @@
+//! Compares decoding strings into an owned [`String`] against the zero-copy
+//! [`Str`] path.
+
+use bytes::{BufMut, BytesMut};
+use criterion::{BatchSize, Criterion, Throughput, criterion_group, criterion_main};
+use integer_encoding::VarIntWriter;
+use laconia_agent::protocol::{
+    Decoder,
+    primitives::{CompactStr, CompactString, Str},
+};
+
+const STRING_LEN: usize = 256;
+const STRINGS: usize = 1024;
+
+fn strings(compact: bool) -> BytesMut {
+    let value = "x".repeat(STRING_LEN);
+
+    let mut buf = BytesMut::new();
+    for _ in 0..STRINGS {
+        if compact {
+            (&mut buf)
+                .writer()
+                .write_varint(STRING_LEN as u32 + 1)
+                .unwrap();
+        } else {
+            buf.put_i16(STRING_LEN as i16);
+        }
+        buf.put_slice(value.as_bytes());
+    }
+    buf
+}
+
+fn decode_all<T: Decoder>(mut buf: BytesMut) -> Vec<T> {
+    let mut values = Vec::with_capacity(STRINGS);
+    for _ in 0..STRINGS {
+        values.push(T::decode(&mut buf).unwrap());
+    }
+    values
+}
+
+fn string_decode(c: &mut Criterion) {
+    let plain = strings(false);
+    let compact = strings(true);
+
+    let mut group = c.benchmark_group("string_decode");
+    group.throughput(Throughput::Bytes(plain.len() as u64));
+    group.bench_function("String", |b| {
+        b.iter_batched(
+            || plain.clone(),
+            decode_all::<String>,
+            BatchSize::SmallInput,
+        )
+    });
+    group.bench_function("Str", |b| {
+        b.iter_batched(|| plain.clone(), decode_all::<Str>, BatchSize::SmallInput)
+    });
+    group.finish();
+
+    let mut group = c.benchmark_group("compact_string_decode");
+    group.throughput(Throughput::Bytes(compact.len() as u64));
+    group.bench_function("CompactString", |b| {
+        b.iter_batched(
+            || compact.clone(),
+            decode_all::<CompactString>,
+            BatchSize::SmallInput,
+        )
+    });
+    group.bench_function("CompactStr", |b| {
+        b.iter_batched(
+            || compact.clone(),
+            decode_all::<CompactStr>,
+            BatchSize::SmallInput,
+        )
+    });
+    group.finish();
+}
+
+criterion_group!(benches, string_decode);
+criterion_main!(benches);
@@ -0,0 +1,18 @@
+//! Fuzzes request header and body decoding.
+//!
+//! Run from `laconia-agent/` with:
+//!
+//! ```sh
+//! cargo +nightly fuzz run decode_request
+//! ```
+//!
+//! The corpus in `fuzz/corpus/decode_request` is seeded with the requests
+//! librdkafka sends when connecting.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    laconia_agent::fuzz::decode_request(data);
+});
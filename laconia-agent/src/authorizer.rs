@@ -0,0 +1,407 @@
+use std::sync::RwLock;
+
+use serde::Deserialize;
+
+use crate::protocol::error::ErrorCode;
+
+/// The principal of connections that haven't authenticated.
+pub const ANONYMOUS_PRINCIPAL: &str = "User:ANONYMOUS";
+
+/// Something a request does to a resource, named as in Kafka's ACLs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Operation {
+    /// Matches every operation in an ACL. Requests never perform it.
+    All,
+    Read,
+    Write,
+    Create,
+    Delete,
+    Alter,
+    Describe,
+    ClusterAction,
+    DescribeConfigs,
+    AlterConfigs,
+    IdempotentWrite,
+}
+
+impl Operation {
+    pub fn from_i8(operation: i8) -> Option<Self> {
+        match operation {
+            2 => Some(Self::All),
+            3 => Some(Self::Read),
+            4 => Some(Self::Write),
+            5 => Some(Self::Create),
+            6 => Some(Self::Delete),
+            7 => Some(Self::Alter),
+            8 => Some(Self::Describe),
+            9 => Some(Self::ClusterAction),
+            10 => Some(Self::DescribeConfigs),
+            11 => Some(Self::AlterConfigs),
+            12 => Some(Self::IdempotentWrite),
+            _ => None,
+        }
+    }
+
+    pub fn code(self) -> i8 {
+        match self {
+            Self::All => 2,
+            Self::Read => 3,
+            Self::Write => 4,
+            Self::Create => 5,
+            Self::Delete => 6,
+            Self::Alter => 7,
+            Self::Describe => 8,
+            Self::ClusterAction => 9,
+            Self::DescribeConfigs => 10,
+            Self::AlterConfigs => 11,
+            Self::IdempotentWrite => 12,
+        }
+    }
+
+    /// Whether being allowed to perform this operation also allows
+    /// `operation`. Anything that reads or changes a resource may describe
+    /// it, and altering configs allows describing them.
+    fn implies(self, operation: Operation) -> bool {
+        match (self, operation) {
+            (Self::All, _) => true,
+            (Self::Read | Self::Write | Self::Delete | Self::Alter, Self::Describe) => true,
+            (Self::AlterConfigs, Self::DescribeConfigs) => true,
+            _ => self == operation,
+        }
+    }
+}
+
+/// The kind of a [`Resource`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ResourceKind {
+    Topic,
+    Group,
+    TransactionalId,
+    Cluster,
+}
+
+impl ResourceKind {
+    pub fn from_i8(resource_type: i8) -> Option<Self> {
+        match resource_type {
+            2 => Some(Self::Topic),
+            3 => Some(Self::Group),
+            4 => Some(Self::Cluster),
+            5 => Some(Self::TransactionalId),
+            _ => None,
+        }
+    }
+
+    pub fn code(self) -> i8 {
+        match self {
+            Self::Topic => 2,
+            Self::Group => 3,
+            Self::Cluster => 4,
+            Self::TransactionalId => 5,
+        }
+    }
+}
+
+/// Something a request acts on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Resource {
+    Topic(String),
+    Group(String),
+    TransactionalId(String),
+    Cluster,
+}
+
+impl Resource {
+    pub fn kind(&self) -> ResourceKind {
+        match self {
+            Self::Topic(_) => ResourceKind::Topic,
+            Self::Group(_) => ResourceKind::Group,
+            Self::TransactionalId(_) => ResourceKind::TransactionalId,
+            Self::Cluster => ResourceKind::Cluster,
+        }
+    }
+
+    /// The name of the resource. There's only one cluster, which Kafka names
+    /// `kafka-cluster`.
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Topic(name) | Self::Group(name) | Self::TransactionalId(name) => name,
+            Self::Cluster => "kafka-cluster",
+        }
+    }
+
+    /// The error a request fails with when it isn't authorized to act on the
+    /// resource.
+    pub fn denied_error_code(&self) -> ErrorCode {
+        match self {
+            Self::Topic(_) => ErrorCode::TopicAuthorizationFailed,
+            Self::Group(_) => ErrorCode::GroupAuthorizationFailed,
+            Self::TransactionalId(_) => ErrorCode::TransactionalIdAuthorizationFailed,
+            Self::Cluster => ErrorCode::ClusterAuthorizationFailed,
+        }
+    }
+}
+
+/// Decides whether principals may perform operations on resources.
+///
+/// Every request is checked against the connection's authorizer before it's
+/// handled, for each of its [operations](crate::protocol::request::Request::operations).
+pub trait Authorizer: Send + Sync {
+    fn authorize(&self, principal: &str, operation: Operation, resource: &Resource) -> bool;
+}
+
+/// Allows everything, for brokers that don't enforce ACLs.
+pub struct AllowAll;
+
+impl Authorizer for AllowAll {
+    fn authorize(&self, _principal: &str, _operation: Operation, _resource: &Resource) -> bool {
+        true
+    }
+}
+
+/// Whether an ACL names a single resource or every resource starting with a
+/// prefix.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PatternType {
+    #[default]
+    Literal,
+    Prefixed,
+}
+
+impl PatternType {
+    pub fn from_i8(pattern_type: i8) -> Option<Self> {
+        match pattern_type {
+            3 => Some(Self::Literal),
+            4 => Some(Self::Prefixed),
+            _ => None,
+        }
+    }
+
+    pub fn code(self) -> i8 {
+        match self {
+            Self::Literal => 3,
+            Self::Prefixed => 4,
+        }
+    }
+}
+
+/// Whether an ACL allows or denies what it matches.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Permission {
+    Deny,
+    #[default]
+    Allow,
+}
+
+impl Permission {
+    pub fn from_i8(permission: i8) -> Option<Self> {
+        match permission {
+            2 => Some(Self::Deny),
+            3 => Some(Self::Allow),
+            _ => None,
+        }
+    }
+
+    pub fn code(self) -> i8 {
+        match self {
+            Self::Deny => 2,
+            Self::Allow => 3,
+        }
+    }
+}
+
+/// An ACL allowing or denying a principal an operation on some resources.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct AclRule {
+    /// The principal matched, such as `User:alice`, or `*` or `User:*` for
+    /// every principal.
+    pub principal: String,
+    /// The host the principal connects from. Connections aren't told apart
+    /// by host, so this is only kept for the ACL admin APIs to report.
+    #[serde(default = "AclRule::wildcard")]
+    pub host: String,
+    pub operation: Operation,
+    #[serde(default)]
+    pub permission: Permission,
+    pub resource_kind: ResourceKind,
+    /// The name of the resource matched, its prefix if the pattern type is
+    /// prefixed, or `*` for every resource of its kind.
+    #[serde(default = "AclRule::wildcard")]
+    pub resource_name: String,
+    #[serde(default)]
+    pub pattern_type: PatternType,
+}
+
+impl AclRule {
+    fn wildcard() -> String {
+        "*".to_string()
+    }
+
+    /// Whether the rule applies to a resource of `kind` named `name`.
+    fn matches_resource(&self, kind: ResourceKind, name: &str) -> bool {
+        self.resource_kind == kind
+            && match self.pattern_type {
+                PatternType::Literal => self.resource_name == "*" || self.resource_name == name,
+                PatternType::Prefixed => name.starts_with(&self.resource_name),
+            }
+    }
+
+    fn matches(&self, principal: &str, operation: Operation, resource: &Resource) -> bool {
+        (self.principal == "*" || self.principal == "User:*" || self.principal == principal)
+            && self.operation.implies(operation)
+            && self.matches_resource(resource.kind(), resource.name())
+    }
+}
+
+/// How an [`AclFilter`] matches the resource pattern of ACLs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PatternFilter {
+    /// ACLs of any pattern type, named exactly as the filter's resource.
+    Any,
+    /// ACLs that apply to the filter's resource, including wildcard and
+    /// prefixed ones.
+    Match,
+    /// ACLs of the pattern type, named exactly as the filter's resource.
+    Exact(PatternType),
+}
+
+/// Selects ACLs, as DescribeAcls and DeleteAcls do. `None` fields match
+/// anything.
+#[derive(Debug, Clone)]
+pub struct AclFilter {
+    pub resource_kind: Option<ResourceKind>,
+    pub resource_name: Option<String>,
+    pub pattern: PatternFilter,
+    pub principal: Option<String>,
+    pub host: Option<String>,
+    pub operation: Option<Operation>,
+    pub permission: Option<Permission>,
+}
+
+impl AclFilter {
+    /// Builds a filter from its encoding in DescribeAcls and DeleteAcls, where
+    /// 1 matches any value of a code and empty strings match any string.
+    /// Returns `None` if a code is unknown.
+    pub fn from_codes(
+        resource_type: i8,
+        resource_name: &str,
+        pattern_type: i8,
+        principal: &str,
+        host: &str,
+        operation: i8,
+        permission_type: i8,
+    ) -> Option<Self> {
+        let any = |code: i8| code == 1;
+        let string = |value: &str| (!value.is_empty()).then(|| value.to_string());
+
+        Some(Self {
+            resource_kind: match resource_type {
+                code if any(code) => None,
+                code => Some(ResourceKind::from_i8(code)?),
+            },
+            resource_name: string(resource_name),
+            pattern: match pattern_type {
+                code if any(code) => PatternFilter::Any,
+                2 => PatternFilter::Match,
+                code => PatternFilter::Exact(PatternType::from_i8(code)?),
+            },
+            principal: string(principal),
+            host: string(host),
+            operation: match operation {
+                code if any(code) => None,
+                code => Some(Operation::from_i8(code)?),
+            },
+            permission: match permission_type {
+                code if any(code) => None,
+                code => Some(Permission::from_i8(code)?),
+            },
+        })
+    }
+
+    pub fn matches(&self, rule: &AclRule) -> bool {
+        let resource = match (self.pattern, &self.resource_name) {
+            (PatternFilter::Match, Some(name)) => rule.matches_resource(rule.resource_kind, name),
+            (PatternFilter::Exact(pattern_type), _) if pattern_type != rule.pattern_type => false,
+            (_, name) => name.as_ref().is_none_or(|name| *name == rule.resource_name),
+        };
+
+        resource
+            && self
+                .resource_kind
+                .is_none_or(|kind| kind == rule.resource_kind)
+            && self
+                .principal
+                .as_ref()
+                .is_none_or(|principal| *principal == rule.principal)
+            && self.host.as_ref().is_none_or(|host| *host == rule.host)
+            && self
+                .operation
+                .is_none_or(|operation| operation == rule.operation)
+            && self
+                .permission
+                .is_none_or(|permission| permission == rule.permission)
+    }
+}
+
+/// ACLs kept in memory, which both authorize requests and are managed through
+/// the ACL admin APIs.
+///
+/// A principal is authorized for an operation if some rule allows it and no
+/// rule denies it. Everything else is denied.
+pub struct AclStore {
+    rules: RwLock<Vec<AclRule>>,
+}
+
+impl AclStore {
+    pub fn new(rules: Vec<AclRule>) -> Self {
+        Self {
+            rules: RwLock::new(rules),
+        }
+    }
+
+    /// Adds `rule`, unless an identical one already exists.
+    pub fn create(&self, rule: AclRule) {
+        let mut rules = self.rules.write().unwrap();
+        if !rules.contains(&rule) {
+            rules.push(rule);
+        }
+    }
+
+    /// The rules matching `filter`.
+    pub fn describe(&self, filter: &AclFilter) -> Vec<AclRule> {
+        self.rules
+            .read()
+            .unwrap()
+            .iter()
+            .filter(|rule| filter.matches(rule))
+            .cloned()
+            .collect()
+    }
+
+    /// Removes the rules matching `filter`, and returns them.
+    pub fn delete(&self, filter: &AclFilter) -> Vec<AclRule> {
+        let mut rules = self.rules.write().unwrap();
+        let (deleted, kept) = rules.drain(..).partition(|rule| filter.matches(rule));
+        *rules = kept;
+        deleted
+    }
+}
+
+impl Authorizer for AclStore {
+    fn authorize(&self, principal: &str, operation: Operation, resource: &Resource) -> bool {
+        let mut allowed = false;
+        for rule in self.rules.read().unwrap().iter() {
+            if rule.matches(principal, operation, resource) {
+                match rule.permission {
+                    Permission::Deny => return false,
+                    Permission::Allow => allowed = true,
+                }
+            }
+        }
+
+        allowed
+    }
+}
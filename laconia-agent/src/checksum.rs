@@ -0,0 +1,30 @@
+//! CRC32C (Castagnoli) checksums, which record batches carry over everything
+//! from their attributes onwards.
+
+/// The CRC32C of `data`.
+pub fn compute(data: &[u8]) -> u32 {
+    crc32c::crc32c(data)
+}
+
+/// Computes a CRC32C over data arriving in chunks, such as a batch split
+/// across several buffers. The result is the same as [`compute`] over the
+/// chunks joined together.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Crc32c {
+    crc: u32,
+}
+
+impl Crc32c {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn update(&mut self, chunk: &[u8]) {
+        self.crc = crc32c::crc32c_append(self.crc, chunk);
+    }
+
+    /// The CRC32C of every chunk so far.
+    pub fn finish(&self) -> u32 {
+        self.crc
+    }
+}
@@ -0,0 +1,56 @@
+//! A source of the current time, so components measuring time can be tested
+//! without waiting for it to pass.
+
+use std::{fmt, time::Instant};
+
+#[cfg(any(test, feature = "test-util"))]
+use std::{sync::Mutex, time::Duration};
+
+pub trait Clock: fmt::Debug + Send + Sync {
+    fn now(&self) -> Instant;
+}
+
+/// The system's monotonic clock.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// A clock that only moves when it's advanced.
+#[cfg(any(test, feature = "test-util"))]
+#[derive(Debug)]
+pub struct TestClock {
+    now: Mutex<Instant>,
+}
+
+#[cfg(any(test, feature = "test-util"))]
+impl TestClock {
+    /// Creates a clock stopped at the current time.
+    pub fn new() -> Self {
+        Self {
+            now: Mutex::new(Instant::now()),
+        }
+    }
+
+    pub fn advance(&self, duration: Duration) {
+        *self.now.lock().unwrap() += duration;
+    }
+}
+
+#[cfg(any(test, feature = "test-util"))]
+impl Default for TestClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(any(test, feature = "test-util"))]
+impl Clock for TestClock {
+    fn now(&self) -> Instant {
+        *self.now.lock().unwrap()
+    }
+}
@@ -0,0 +1,113 @@
+use std::{collections::HashMap, sync::RwLock};
+
+/// A broker as advertised to clients.
+#[derive(Debug, Clone)]
+pub struct BrokerInfo {
+    pub node_id: i32,
+    pub host: String,
+    pub port: i32,
+    /// The broker's rack, or empty if it has none.
+    pub rack: String,
+}
+
+/// The brokers advertised to clients: this broker, along with any peers it
+/// was configured with.
+///
+/// Peers are only reported, so clients can be pointed at a view of a wider
+/// cluster. Partitions are assigned leaders among all of them, but every
+/// coordinator is still hosted by this broker.
+#[derive(Debug, Clone)]
+pub struct BrokerRegistry {
+    local_id: i32,
+    /// Every broker, ordered by node id.
+    brokers: Vec<BrokerInfo>,
+}
+
+impl BrokerRegistry {
+    /// A registry of `local` and `peers`. A peer with the same node id as
+    /// another broker is ignored.
+    pub fn new(local: BrokerInfo, peers: Vec<BrokerInfo>) -> Self {
+        let local_id = local.node_id;
+
+        let mut brokers = vec![local];
+        for peer in peers {
+            if brokers.iter().any(|broker| broker.node_id == peer.node_id) {
+                tracing::warn!(
+                    node_id = peer.node_id,
+                    "Ignoring broker with duplicate node id"
+                );
+                continue;
+            }
+            brokers.push(peer);
+        }
+        brokers.sort_by_key(|broker| broker.node_id);
+
+        Self { local_id, brokers }
+    }
+
+    /// This broker.
+    pub fn local(&self) -> &BrokerInfo {
+        self.get(self.local_id)
+            .expect("the local broker is always registered")
+    }
+
+    pub fn get(&self, node_id: i32) -> Option<&BrokerInfo> {
+        self.brokers.iter().find(|broker| broker.node_id == node_id)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &BrokerInfo> {
+        self.brokers.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.brokers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.brokers.is_empty()
+    }
+}
+
+/// The shape of the cluster as reported to clients doing broker discovery.
+pub struct ClusterInfo {
+    pub cluster_id: String,
+    pub controller_id: i32,
+    pub brokers: BrokerRegistry,
+    /// The broker leading each partition, keyed by topic and partition.
+    leaders: RwLock<HashMap<(String, i32), i32>>,
+}
+
+impl ClusterInfo {
+    /// A cluster made up of the brokers in `brokers`, with this broker as its
+    /// controller.
+    pub fn new(cluster_id: impl ToString, brokers: BrokerRegistry) -> Self {
+        Self {
+            cluster_id: cluster_id.to_string(),
+            controller_id: brokers.local().node_id,
+            brokers,
+            leaders: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// The broker leading `partition` of `topic`, which is also its only
+    /// replica.
+    ///
+    /// Leaders are assigned the first time a partition is asked about,
+    /// round-robin over the brokers in node id order, and stay put from then
+    /// on.
+    pub fn leader(&self, topic: &str, partition: i32) -> i32 {
+        let key = (topic.to_string(), partition);
+        if let Some(&leader) = self.leaders.read().unwrap().get(&key) {
+            return leader;
+        }
+
+        let index = partition.max(0) as usize % self.brokers.len();
+        let leader = self.brokers.brokers[index].node_id;
+        *self.leaders.write().unwrap().entry(key).or_insert(leader)
+    }
+
+    /// A cluster made up of just `broker`, which is also its controller.
+    pub fn single_broker(cluster_id: impl ToString, broker: BrokerInfo) -> Self {
+        Self::new(cluster_id, BrokerRegistry::new(broker, vec![]))
+    }
+}
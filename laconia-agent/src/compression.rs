@@ -0,0 +1,248 @@
+use std::io;
+
+/// A record batch compression codec, as stored in the lowest three bits of
+/// the batch attributes.
+///
+/// Each codec other than [`Compression::None`] is behind a cargo feature of
+/// the same name. Using a codec whose feature is disabled is an
+/// [`io::ErrorKind::Unsupported`] error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    None = 0,
+    Gzip = 1,
+    Snappy = 2,
+    Lz4 = 3,
+    Zstd = 4,
+}
+
+/// The attribute bits holding the compression codec.
+pub const CODEC_MASK: i16 = 0x07;
+
+impl Compression {
+    pub fn from_attributes(attributes: i16) -> Option<Self> {
+        match attributes & CODEC_MASK {
+            0 => Some(Self::None),
+            1 => Some(Self::Gzip),
+            2 => Some(Self::Snappy),
+            3 => Some(Self::Lz4),
+            4 => Some(Self::Zstd),
+            _ => None,
+        }
+    }
+
+    /// Parses a codec as named by the `compression.type` topic config. The
+    /// `producer` setting, which keeps whatever the producer used, isn't a
+    /// codec and so is `None`.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "uncompressed" => Some(Self::None),
+            "gzip" => Some(Self::Gzip),
+            "snappy" => Some(Self::Snappy),
+            "lz4" => Some(Self::Lz4),
+            "zstd" => Some(Self::Zstd),
+            _ => None,
+        }
+    }
+
+    pub fn compress(self, data: &[u8]) -> Result<Vec<u8>, io::Error> {
+        match self {
+            Self::None => Ok(data.to_vec()),
+            Self::Gzip => gzip::compress(data),
+            Self::Snappy => snappy::compress(data),
+            Self::Lz4 => lz4::compress(data),
+            Self::Zstd => zstd::compress(data),
+        }
+    }
+
+    pub fn decompress(self, data: &[u8]) -> Result<Vec<u8>, io::Error> {
+        match self {
+            Self::None => Ok(data.to_vec()),
+            Self::Gzip => gzip::decompress(data),
+            Self::Snappy => snappy::decompress(data),
+            Self::Lz4 => lz4::decompress(data),
+            Self::Zstd => zstd::decompress(data),
+        }
+    }
+}
+
+#[cfg(not(all(
+    feature = "gzip",
+    feature = "snappy",
+    feature = "lz4",
+    feature = "zstd"
+)))]
+fn unsupported(codec: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::Unsupported,
+        format!("{} compression support is not enabled", codec),
+    )
+}
+
+#[cfg(feature = "gzip")]
+mod gzip {
+    use std::io::{self, Read, Write};
+
+    use flate2::{Compression, read::GzDecoder, write::GzEncoder};
+
+    pub fn compress(data: &[u8]) -> Result<Vec<u8>, io::Error> {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(data)?;
+        encoder.finish()
+    }
+
+    pub fn decompress(data: &[u8]) -> Result<Vec<u8>, io::Error> {
+        let mut decompressed = Vec::new();
+        GzDecoder::new(data).read_to_end(&mut decompressed)?;
+        Ok(decompressed)
+    }
+}
+
+#[cfg(not(feature = "gzip"))]
+mod gzip {
+    use std::io;
+
+    pub fn compress(_data: &[u8]) -> Result<Vec<u8>, io::Error> {
+        Err(super::unsupported("gzip"))
+    }
+
+    pub fn decompress(_data: &[u8]) -> Result<Vec<u8>, io::Error> {
+        Err(super::unsupported("gzip"))
+    }
+}
+
+/// Kafka's Java clients frame snappy data the way the xerial snappy-java
+/// library does: a magic header followed by length-prefixed raw snappy
+/// blocks. Other clients send raw snappy, so both are accepted, and the
+/// xerial framing is always written since every client understands it.
+#[cfg(feature = "snappy")]
+mod snappy {
+    use std::io;
+
+    use snap::raw::{Decoder, Encoder};
+
+    const XERIAL_MAGIC: &[u8] = b"\x82SNAPPY\x00";
+    const XERIAL_HEADER_SIZE: usize = XERIAL_MAGIC.len() + 8;
+    const XERIAL_BLOCK_SIZE: usize = 32 * 1024;
+
+    pub fn compress(data: &[u8]) -> Result<Vec<u8>, io::Error> {
+        let mut encoder = Encoder::new();
+
+        let mut compressed = Vec::with_capacity(XERIAL_HEADER_SIZE + data.len());
+        compressed.extend_from_slice(XERIAL_MAGIC);
+        compressed.extend_from_slice(&1i32.to_be_bytes());
+        compressed.extend_from_slice(&1i32.to_be_bytes());
+
+        for block in data.chunks(XERIAL_BLOCK_SIZE) {
+            let block = encoder.compress_vec(block).map_err(io::Error::other)?;
+            compressed.extend_from_slice(&(block.len() as i32).to_be_bytes());
+            compressed.extend_from_slice(&block);
+        }
+
+        Ok(compressed)
+    }
+
+    pub fn decompress(data: &[u8]) -> Result<Vec<u8>, io::Error> {
+        let mut decoder = Decoder::new();
+
+        if !data.starts_with(XERIAL_MAGIC) {
+            return decoder.decompress_vec(data).map_err(invalid_data);
+        }
+
+        let mut decompressed = Vec::new();
+        let mut data = data
+            .get(XERIAL_HEADER_SIZE..)
+            .ok_or_else(|| invalid_data("truncated xerial snappy header"))?;
+
+        while !data.is_empty() {
+            let (length, rest) = data
+                .split_first_chunk::<4>()
+                .ok_or_else(|| invalid_data("truncated xerial snappy block length"))?;
+            let length = i32::from_be_bytes(*length);
+            let block = usize::try_from(length)
+                .ok()
+                .and_then(|length| rest.get(..length))
+                .ok_or_else(|| invalid_data("truncated xerial snappy block"))?;
+
+            decompressed.extend(decoder.decompress_vec(block).map_err(invalid_data)?);
+            data = &rest[block.len()..];
+        }
+
+        Ok(decompressed)
+    }
+
+    fn invalid_data(err: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> io::Error {
+        io::Error::new(io::ErrorKind::InvalidData, err)
+    }
+}
+
+#[cfg(not(feature = "snappy"))]
+mod snappy {
+    use std::io;
+
+    pub fn compress(_data: &[u8]) -> Result<Vec<u8>, io::Error> {
+        Err(super::unsupported("snappy"))
+    }
+
+    pub fn decompress(_data: &[u8]) -> Result<Vec<u8>, io::Error> {
+        Err(super::unsupported("snappy"))
+    }
+}
+
+/// Kafka uses the LZ4 frame format, not raw LZ4 blocks.
+#[cfg(feature = "lz4")]
+mod lz4 {
+    use std::io::{self, Read, Write};
+
+    use lz4_flex::frame::{FrameDecoder, FrameEncoder};
+
+    pub fn compress(data: &[u8]) -> Result<Vec<u8>, io::Error> {
+        let mut encoder = FrameEncoder::new(Vec::new());
+        encoder.write_all(data)?;
+        encoder.finish().map_err(io::Error::other)
+    }
+
+    pub fn decompress(data: &[u8]) -> Result<Vec<u8>, io::Error> {
+        let mut decompressed = Vec::new();
+        FrameDecoder::new(data).read_to_end(&mut decompressed)?;
+        Ok(decompressed)
+    }
+}
+
+#[cfg(not(feature = "lz4"))]
+mod lz4 {
+    use std::io;
+
+    pub fn compress(_data: &[u8]) -> Result<Vec<u8>, io::Error> {
+        Err(super::unsupported("lz4"))
+    }
+
+    pub fn decompress(_data: &[u8]) -> Result<Vec<u8>, io::Error> {
+        Err(super::unsupported("lz4"))
+    }
+}
+
+#[cfg(feature = "zstd")]
+mod zstd {
+    use std::io;
+
+    pub fn compress(data: &[u8]) -> Result<Vec<u8>, io::Error> {
+        ::zstd::encode_all(data, ::zstd::DEFAULT_COMPRESSION_LEVEL)
+    }
+
+    pub fn decompress(data: &[u8]) -> Result<Vec<u8>, io::Error> {
+        ::zstd::decode_all(data)
+    }
+}
+
+#[cfg(not(feature = "zstd"))]
+mod zstd {
+    use std::io;
+
+    pub fn compress(_data: &[u8]) -> Result<Vec<u8>, io::Error> {
+        Err(super::unsupported("zstd"))
+    }
+
+    pub fn decompress(_data: &[u8]) -> Result<Vec<u8>, io::Error> {
+        Err(super::unsupported("zstd"))
+    }
+}
@@ -0,0 +1,240 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::RwLock,
+};
+
+/// The kind of resource a config belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResourceType {
+    Topic = 2,
+    Broker = 4,
+}
+
+impl ResourceType {
+    pub fn from_i8(resource_type: i8) -> Option<Self> {
+        match resource_type {
+            2 => Some(Self::Topic),
+            4 => Some(Self::Broker),
+            _ => None,
+        }
+    }
+}
+
+/// The type of a config value, as reported to DescribeConfigs clients.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigType {
+    Boolean = 1,
+    String = 2,
+    Int = 3,
+    Long = 5,
+    Double = 6,
+    List = 7,
+    Password = 9,
+}
+
+/// Where a config value came from, as reported to DescribeConfigs clients.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigSource {
+    DynamicTopicConfig = 1,
+    StaticBrokerConfig = 4,
+    DefaultConfig = 5,
+}
+
+/// A topic config the broker understands, along with its default value.
+pub struct TopicConfigDef {
+    pub name: &'static str,
+    pub default: &'static str,
+    pub config_type: ConfigType,
+    pub documentation: &'static str,
+}
+
+pub const TOPIC_CONFIGS: &[TopicConfigDef] = &[
+    TopicConfigDef {
+        name: "cleanup.policy",
+        default: "delete",
+        config_type: ConfigType::List,
+        documentation: "The retention policy to use on log segments.",
+    },
+    TopicConfigDef {
+        name: "compression.type",
+        default: "producer",
+        config_type: ConfigType::String,
+        documentation: "The compression codec batches are stored with, or producer to keep the one the producer used.",
+    },
+    TopicConfigDef {
+        name: "max.message.bytes",
+        default: "1048588",
+        config_type: ConfigType::Int,
+        documentation: "The largest record batch size allowed.",
+    },
+    TopicConfigDef {
+        name: "retention.bytes",
+        default: "-1",
+        config_type: ConfigType::Long,
+        documentation: "The maximum size a partition can grow to before old log segments are discarded.",
+    },
+    TopicConfigDef {
+        name: "retention.ms",
+        default: "604800000",
+        config_type: ConfigType::Long,
+        documentation: "The maximum time a log segment is retained before it is discarded.",
+    },
+    TopicConfigDef {
+        name: "segment.bytes",
+        default: "1073741824",
+        config_type: ConfigType::Int,
+        documentation: "The segment file size for the log.",
+    },
+];
+
+/// A change to a single config value.
+#[derive(Debug, Clone)]
+pub enum ConfigOp {
+    Set(String),
+    Delete,
+    /// Appends an item to a list config.
+    Append(String),
+}
+
+/// A resolved config value, ready to be described to a client.
+#[derive(Debug, Clone)]
+pub struct ConfigEntry {
+    pub name: String,
+    pub value: String,
+    pub read_only: bool,
+    pub source: ConfigSource,
+    pub config_type: ConfigType,
+    pub documentation: String,
+}
+
+impl ConfigEntry {
+    /// A config set from the agent's own configuration. These can't be
+    /// changed at runtime.
+    pub fn static_broker(
+        name: &str,
+        value: impl ToString,
+        config_type: ConfigType,
+        documentation: &str,
+    ) -> Self {
+        Self {
+            name: name.to_string(),
+            value: value.to_string(),
+            read_only: true,
+            source: ConfigSource::StaticBrokerConfig,
+            config_type,
+            documentation: documentation.to_string(),
+        }
+    }
+
+    /// Whether the value must be hidden from clients.
+    pub fn is_sensitive(&self) -> bool {
+        self.config_type == ConfigType::Password
+    }
+}
+
+/// Broker and topic configs shared between connections.
+///
+/// Broker configs are fixed at startup. Topic configs start out at the
+/// defaults in [`TOPIC_CONFIGS`] and can be overridden per topic.
+pub struct ConfigStore {
+    broker_id: i32,
+    broker: Vec<ConfigEntry>,
+    topic_overrides: RwLock<HashMap<String, BTreeMap<String, String>>>,
+}
+
+impl ConfigStore {
+    pub fn new(broker_id: i32, broker: Vec<ConfigEntry>) -> Self {
+        Self {
+            broker_id,
+            broker,
+            topic_overrides: RwLock::new(HashMap::new()),
+        }
+    }
+
+    pub fn broker_id(&self) -> i32 {
+        self.broker_id
+    }
+
+    pub fn broker_configs(&self) -> &[ConfigEntry] {
+        &self.broker
+    }
+
+    /// The value of the broker config `name`, if it's set and parses as a `T`.
+    pub fn broker_config<T: std::str::FromStr>(&self, name: &str) -> Option<T> {
+        self.broker
+            .iter()
+            .find(|entry| entry.name == name)
+            .and_then(|entry| entry.value.parse().ok())
+    }
+
+    /// Applies `changes` to `topic`'s config overrides. Nothing is applied if
+    /// any change is invalid, or if `validate_only` is set.
+    pub fn alter_topic_configs(
+        &self,
+        topic: &str,
+        changes: &[(String, ConfigOp)],
+        validate_only: bool,
+    ) -> Result<(), String> {
+        let mut topic_overrides = self.topic_overrides.write().unwrap();
+        let mut overrides = topic_overrides.get(topic).cloned().unwrap_or_default();
+
+        for (name, op) in changes {
+            let Some(def) = TOPIC_CONFIGS.iter().find(|def| def.name == name) else {
+                return Err(format!("unknown topic config: {}", name));
+            };
+
+            match op {
+                ConfigOp::Set(value) => {
+                    overrides.insert(name.clone(), value.clone());
+                }
+                ConfigOp::Delete => {
+                    overrides.remove(name);
+                }
+                ConfigOp::Append(item) => {
+                    if def.config_type != ConfigType::List {
+                        return Err(format!("cannot append to non-list config: {}", name));
+                    }
+
+                    let current = overrides.get(name).map_or(def.default, String::as_str);
+                    let mut items: Vec<&str> =
+                        current.split(',').filter(|item| !item.is_empty()).collect();
+                    if !items.contains(&item.as_str()) {
+                        items.push(item);
+                    }
+
+                    overrides.insert(name.clone(), items.join(","));
+                }
+            }
+        }
+
+        if !validate_only {
+            topic_overrides.insert(topic.to_string(), overrides);
+        }
+
+        Ok(())
+    }
+
+    pub fn topic_configs(&self, topic: &str) -> Vec<ConfigEntry> {
+        let topic_overrides = self.topic_overrides.read().unwrap();
+        let overrides = topic_overrides.get(topic);
+
+        TOPIC_CONFIGS
+            .iter()
+            .map(|def| {
+                let (value, source) = match overrides.and_then(|o| o.get(def.name)) {
+                    Some(value) => (value.clone(), ConfigSource::DynamicTopicConfig),
+                    None => (def.default.to_string(), ConfigSource::DefaultConfig),
+                };
+
+                ConfigEntry {
+                    name: def.name.to_string(),
+                    value,
+                    read_only: false,
+                    source,
+                    config_type: def.config_type,
+                    documentation: def.documentation.to_string(),
+                }
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,116 @@
+//! Tracks the connections a server has open, so they can be listed for
+//! debugging.
+
+use std::{
+    collections::BTreeMap,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+};
+
+use crate::ConnectionState;
+
+/// What's known about an open connection.
+#[derive(Debug, Clone)]
+pub struct ConnectionInfo {
+    /// Unique among the connections of a registry, and never reused.
+    pub id: u64,
+    pub peer: String,
+    /// The client's software name and version, once it has sent them in
+    /// ApiVersions v3 or later.
+    pub client_software_name: Option<String>,
+    pub client_software_version: Option<String>,
+    pub principal: String,
+    pub request_count: u64,
+}
+
+/// The connections currently open. Each connection is registered for as
+/// long as its [`ConnectionGuard`] lives.
+#[derive(Debug, Default)]
+pub struct ConnectionRegistry {
+    next_id: AtomicU64,
+    connections: Mutex<BTreeMap<u64, ConnectionInfo>>,
+}
+
+impl ConnectionRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a connection from `peer`, which stays registered until the
+    /// returned guard is dropped, including while unwinding from a panic.
+    pub fn register(
+        self: &Arc<Self>,
+        peer: impl ToString,
+        state: &ConnectionState,
+    ) -> ConnectionGuard {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let info = ConnectionInfo {
+            id,
+            peer: peer.to_string(),
+            client_software_name: None,
+            client_software_version: None,
+            principal: String::new(),
+            request_count: 0,
+        };
+        self.connections.lock().unwrap().insert(id, info);
+
+        let guard = ConnectionGuard {
+            registry: self.clone(),
+            id,
+        };
+        guard.update(state);
+        guard
+    }
+
+    /// The connections open right now, ordered by id.
+    pub fn snapshot(&self) -> Vec<ConnectionInfo> {
+        self.connections.lock().unwrap().values().cloned().collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.connections.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Keeps a connection registered, removing it when dropped.
+pub struct ConnectionGuard {
+    registry: Arc<ConnectionRegistry>,
+    id: u64,
+}
+
+impl ConnectionGuard {
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// Refreshes what's recorded about the connection from its state.
+    pub fn update(&self, state: &ConnectionState) {
+        let mut connections = self.registry.connections.lock().unwrap();
+        let Some(info) = connections.get_mut(&self.id) else {
+            return;
+        };
+
+        info.client_software_name = state.client_software_name().map(str::to_string);
+        info.client_software_version = state.client_software_version().map(str::to_string);
+        info.principal = state.principal().to_string();
+        info.request_count = state.request_count();
+    }
+}
+
+impl Drop for ConnectionGuard {
+    fn drop(&mut self) {
+        // A panic elsewhere may have poisoned the lock, but the map itself is
+        // still consistent, so the connection is removed regardless.
+        let mut connections = match self.registry.connections.lock() {
+            Ok(connections) => connections,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        connections.remove(&self.id);
+    }
+}
@@ -0,0 +1,184 @@
+use std::{
+    fs, io,
+    path::Path,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::Duration,
+};
+
+use anyhow::Result;
+use laconia_liveness::liveness::{
+    CheckinReply, CheckinRequest, DeregisterRequest, liveness_client::LivenessClient,
+};
+use rand::Rng;
+use tokio::{
+    task::JoinHandle,
+    time::{self, Instant},
+};
+use tonic::transport::Channel;
+use uuid::Uuid;
+
+const INITIAL_BACKOFF: Duration = Duration::from_millis(100);
+const MAX_BACKOFF: Duration = Duration::from_secs(10);
+const MAX_CHECKIN_FAILURES: u32 = 3;
+
+/// The shortest interval the agent checks in at, however short an interval
+/// the control plane asks for.
+pub const MIN_CHECKIN_INTERVAL: Duration = Duration::from_secs(1);
+
+/// The interval the agent checks in at until the control plane asks for a
+/// valid one.
+pub const DEFAULT_CHECKIN_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Connects to the control plane and performs the initial checkin, retrying
+/// with jittered exponential backoff until `retry_window` has elapsed.
+pub async fn connect(
+    endpoint: String,
+    id: &str,
+    retry_window: Duration,
+) -> Result<(LivenessClient<Channel>, CheckinReply)> {
+    let deadline = Instant::now() + retry_window;
+    let mut backoff = INITIAL_BACKOFF;
+    let mut attempt = 1;
+
+    loop {
+        match try_connect(endpoint.clone(), id).await {
+            Ok(connected) => return Ok(connected),
+            Err(err) => {
+                let delay = backoff.mul_f64(rand::rng().random_range(0.5..=1.0));
+
+                if Instant::now() + delay > deadline {
+                    tracing::error!(
+                        "Control plane connection attempt {} failed, giving up: {}",
+                        attempt,
+                        err
+                    );
+                    return Err(err);
+                }
+
+                tracing::warn!(
+                    "Control plane connection attempt {} failed, retrying in {:?}: {}",
+                    attempt,
+                    delay,
+                    err
+                );
+
+                time::sleep(delay).await;
+                backoff = (backoff * 2).min(MAX_BACKOFF);
+                attempt += 1;
+            }
+        }
+    }
+}
+
+async fn try_connect(
+    endpoint: String,
+    id: &str,
+) -> Result<(LivenessClient<Channel>, CheckinReply)> {
+    let mut client = LivenessClient::connect(endpoint).await?;
+
+    let reply = client
+        .checkin(CheckinRequest { id: id.to_string() })
+        .await?
+        .into_inner();
+
+    Ok((client, reply))
+}
+
+/// Spawns a task that checks in with the control plane every `interval`,
+/// adopting whatever interval the control plane replies with. `healthy` is
+/// cleared after repeated failed checkins and set again on the next success.
+pub fn spawn_checkins(
+    mut client: LivenessClient<Channel>,
+    id: String,
+    mut interval: Duration,
+    healthy: Arc<AtomicBool>,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut failures = 0;
+
+        loop {
+            time::sleep(interval).await;
+
+            match client.checkin(CheckinRequest { id: id.clone() }).await {
+                Ok(reply) => {
+                    failures = 0;
+                    healthy.store(true, Ordering::Relaxed);
+                    interval = checkin_interval(&reply.into_inner(), interval);
+                }
+                Err(err) => {
+                    failures += 1;
+                    tracing::warn!(
+                        "Control plane checkin failed ({} in a row): {}",
+                        failures,
+                        err
+                    );
+
+                    if failures >= MAX_CHECKIN_FAILURES {
+                        healthy.store(false, Ordering::Relaxed);
+                    }
+                }
+            }
+        }
+    })
+}
+
+/// The interval the control plane asks to be checked in with at, no shorter
+/// than [`MIN_CHECKIN_INTERVAL`]. Keeps `previous` if the control plane asks
+/// for a non-positive interval.
+pub fn checkin_interval(reply: &CheckinReply, previous: Duration) -> Duration {
+    if reply.interval <= 0 {
+        tracing::warn!(
+            "Control plane asked for a checkin interval of {} ms, keeping {:?}",
+            reply.interval,
+            previous
+        );
+        return previous;
+    }
+
+    Duration::from_millis(reply.interval as u64).max(MIN_CHECKIN_INTERVAL)
+}
+
+/// Tells the control plane this agent is going away, so it doesn't have to
+/// wait for the agent's checkins to time out.
+pub async fn deregister(mut client: LivenessClient<Channel>, id: String) -> Result<()> {
+    client.deregister(DeregisterRequest { id }).await?;
+    Ok(())
+}
+
+/// Runs `serve` until it stops or `shutdown` completes, then stops the
+/// `checkins` and deregisters from the control plane.
+pub async fn run_until_shutdown(
+    serve: impl Future<Output = ()>,
+    shutdown: impl Future<Output = ()>,
+    checkins: JoinHandle<()>,
+    client: LivenessClient<Channel>,
+    id: String,
+) {
+    tokio::select! {
+        _ = serve => {}
+        _ = shutdown => tracing::info!("Shutting down"),
+    }
+
+    checkins.abort();
+
+    if let Err(err) = deregister(client, id).await {
+        tracing::error!("Failed to deregister from control plane: {}", err);
+    }
+}
+
+/// Reads the agent id persisted in `path`, so the agent keeps its identity
+/// across restarts. A new id is generated and persisted if there's none yet.
+pub fn load_or_create_agent_id(path: &Path) -> Result<Uuid> {
+    match fs::read_to_string(path) {
+        Ok(contents) => Ok(Uuid::parse_str(contents.trim())?),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => {
+            let id = Uuid::new_v4();
+            fs::write(path, id.to_string())?;
+            Ok(id)
+        }
+        Err(err) => Err(err.into()),
+    }
+}
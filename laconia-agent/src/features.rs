@@ -0,0 +1,51 @@
+//! Feature flags, advertised to clients in ApiVersions so they can tell which
+//! optional behaviors the broker supports.
+
+use std::collections::BTreeMap;
+
+use crate::VersionRange;
+
+/// The features a broker supports, each with the range of levels it can run
+/// at.
+///
+/// There's no controller to agree on a level with, so each feature is
+/// finalized at the highest level the broker supports.
+#[derive(Debug, Clone, Default)]
+pub struct Features {
+    features: BTreeMap<String, VersionRange>,
+}
+
+impl Features {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds feature `name`, supported at the levels in `levels`, replacing
+    /// any feature of the same name.
+    pub fn with_feature(mut self, name: impl ToString, levels: VersionRange) -> Self {
+        self.features.insert(name.to_string(), levels);
+        self
+    }
+
+    /// The levels feature `name` is supported at, or `None` if it isn't.
+    pub fn get(&self, name: &str) -> Option<&VersionRange> {
+        self.features.get(name)
+    }
+
+    /// The level feature `name` is finalized at, or `None` if it isn't
+    /// supported.
+    pub fn finalized_level(&self, name: &str) -> Option<i16> {
+        self.get(name).map(|levels| levels.max)
+    }
+
+    /// Every feature, ordered by name.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &VersionRange)> {
+        self.features
+            .iter()
+            .map(|(name, levels)| (name.as_str(), levels))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.features.is_empty()
+    }
+}
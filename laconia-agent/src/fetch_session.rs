@@ -0,0 +1,189 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::Mutex,
+};
+
+use crate::protocol::error::ErrorCode;
+
+/// A partition, by topic name and partition index.
+pub type TopicPartition = (String, i32);
+
+/// Where a fetch reads a partition from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FetchPosition {
+    pub fetch_offset: i64,
+    pub max_bytes: i32,
+}
+
+struct FetchSession {
+    /// The epoch the next incremental fetch of the session must have.
+    next_epoch: i32,
+    partitions: BTreeMap<TopicPartition, SessionPartition>,
+}
+
+struct SessionPartition {
+    position: FetchPosition,
+    /// The high watermark last returned for the partition, if any.
+    high_watermark: Option<i64>,
+}
+
+/// How many sessions a [`FetchSessionCache`] keeps by default, matching
+/// Kafka's `max.incremental.fetch.session.cache.slots`.
+pub const DEFAULT_MAX_FETCH_SESSIONS: usize = 1000;
+
+/// The incremental fetch sessions of a single broker, kept in memory.
+///
+/// A session remembers the partitions a client fetches, so that later fetches
+/// only need to send the partitions whose position changed, and responses can
+/// leave out the partitions that have nothing new. Sessions last until the
+/// client closes them. Once `max_sessions` are open, clients asking for a new
+/// one fall back to fetching without a session.
+pub struct FetchSessionCache {
+    max_sessions: usize,
+    inner: Mutex<FetchSessionCacheInner>,
+}
+
+struct FetchSessionCacheInner {
+    next_session_id: i32,
+    sessions: HashMap<i32, FetchSession>,
+}
+
+impl FetchSessionCache {
+    pub fn new(max_sessions: usize) -> Self {
+        Self {
+            max_sessions,
+            inner: Mutex::new(FetchSessionCacheInner {
+                next_session_id: 1,
+                sessions: HashMap::new(),
+            }),
+        }
+    }
+
+    /// Starts a session fetching `partitions`, and returns its id. Returns 0,
+    /// meaning no session, if the cache is full.
+    pub fn create(
+        &self,
+        partitions: impl IntoIterator<Item = (TopicPartition, FetchPosition)>,
+    ) -> i32 {
+        let mut inner = self.inner.lock().unwrap();
+        if inner.sessions.len() >= self.max_sessions {
+            return 0;
+        }
+
+        let session_id = inner.allocate_session_id();
+        let partitions = partitions
+            .into_iter()
+            .map(|(key, position)| {
+                let partition = SessionPartition {
+                    position,
+                    high_watermark: None,
+                };
+                (key, partition)
+            })
+            .collect();
+
+        inner.sessions.insert(
+            session_id,
+            FetchSession {
+                next_epoch: 1,
+                partitions,
+            },
+        );
+
+        session_id
+    }
+
+    /// Closes a session. Closing a session that doesn't exist is a no-op.
+    pub fn remove(&self, session_id: i32) {
+        self.inner.lock().unwrap().sessions.remove(&session_id);
+    }
+
+    /// Applies an incremental fetch to a session. `partitions` are added to
+    /// the session or have their position updated, and `forgotten` are
+    /// removed from it. Returns every partition in the session, to be
+    /// fetched.
+    pub fn update(
+        &self,
+        session_id: i32,
+        session_epoch: i32,
+        partitions: impl IntoIterator<Item = (TopicPartition, FetchPosition)>,
+        forgotten: impl IntoIterator<Item = TopicPartition>,
+    ) -> Result<Vec<(TopicPartition, FetchPosition)>, ErrorCode> {
+        let mut inner = self.inner.lock().unwrap();
+        let session = inner
+            .sessions
+            .get_mut(&session_id)
+            .ok_or(ErrorCode::FetchSessionIdNotFound)?;
+
+        if session.next_epoch != session_epoch {
+            return Err(ErrorCode::InvalidFetchSessionEpoch);
+        }
+
+        session.next_epoch = session.next_epoch.checked_add(1).unwrap_or(1);
+
+        for (key, position) in partitions {
+            session
+                .partitions
+                .entry(key)
+                .and_modify(|partition| partition.position = position)
+                .or_insert(SessionPartition {
+                    position,
+                    high_watermark: None,
+                });
+        }
+
+        for key in forgotten {
+            session.partitions.remove(&key);
+        }
+
+        Ok(session
+            .partitions
+            .iter()
+            .map(|(key, partition)| (key.clone(), partition.position))
+            .collect())
+    }
+
+    /// Records the high watermark returned for a partition of a session.
+    /// Returns whether it changed since the last time it was returned, which
+    /// is always the case for partitions not in the session.
+    pub fn update_high_watermark(
+        &self,
+        session_id: i32,
+        topic: &str,
+        partition: i32,
+        high_watermark: i64,
+    ) -> bool {
+        let mut inner = self.inner.lock().unwrap();
+        let Some(partition) = inner
+            .sessions
+            .get_mut(&session_id)
+            .and_then(|session| session.partitions.get_mut(&(topic.to_string(), partition)))
+        else {
+            return true;
+        };
+
+        partition.high_watermark.replace(high_watermark) != Some(high_watermark)
+    }
+
+    /// The number of open sessions.
+    pub fn len(&self) -> usize {
+        self.inner.lock().unwrap().sessions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl FetchSessionCacheInner {
+    /// Picks an unused, positive session id.
+    fn allocate_session_id(&mut self) -> i32 {
+        loop {
+            let session_id = self.next_session_id;
+            self.next_session_id = self.next_session_id.checked_add(1).unwrap_or(1);
+            if !self.sessions.contains_key(&session_id) {
+                return session_id;
+            }
+        }
+    }
+}
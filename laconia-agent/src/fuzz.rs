@@ -0,0 +1,36 @@
+//! Entry points for fuzzing the network-facing decode path. The fuzz targets
+//! in `fuzz/` are thin wrappers around these, so they are built and checked
+//! along with the rest of the crate.
+
+use std::sync::Arc;
+
+use bytes::BytesMut;
+
+use crate::{
+    cluster::{BrokerInfo, ClusterInfo},
+    configs::ConfigStore,
+    log::LogStore,
+    protocol::registry::MessageRegistry,
+    server::BrokerState,
+};
+
+/// Decodes `data` as a request frame, without its length prefix, using a
+/// registry with every handler registered. Malformed input must be rejected
+/// with an error rather than a panic.
+pub fn decode_request(data: &[u8]) {
+    let configs = Arc::new(ConfigStore::new(0, vec![]));
+    let cluster = Arc::new(ClusterInfo::single_broker(
+        "",
+        BrokerInfo {
+            node_id: 0,
+            host: String::new(),
+            port: -1,
+            rack: String::new(),
+        },
+    ));
+    let logs = Arc::new(LogStore::open(None, false, configs.clone()).unwrap());
+    let state = BrokerState::new(cluster, configs, logs);
+    let registry = MessageRegistry::with_default_handlers(&state);
+    let mut buf = BytesMut::from(data);
+    let _ = registry.decode_request_debug(&mut buf);
+}
@@ -0,0 +1,303 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use uuid::Uuid;
+
+use crate::{
+    clock::{Clock, SystemClock},
+    log::LogStore,
+    protocol::error::ErrorCode,
+};
+
+/// The member epoch a consumer heartbeats with to join its group.
+pub const JOIN_GROUP_MEMBER_EPOCH: i32 = 0;
+/// The member epoch a consumer heartbeats with to leave its group.
+pub const LEAVE_GROUP_MEMBER_EPOCH: i32 = -1;
+/// The member epoch a static member heartbeats with to leave its group for
+/// now, expecting to rejoin with the same instance id.
+pub const LEAVE_GROUP_STATIC_MEMBER_EPOCH: i32 = -2;
+/// How long a member may go without heartbeating before it's removed from
+/// its group, matching Kafka's default `group.consumer.session.timeout.ms`.
+pub const DEFAULT_SESSION_TIMEOUT: Duration = Duration::from_secs(45);
+
+/// Partitions assigned to a member, keyed by topic name.
+pub type Assignment = BTreeMap<String, BTreeSet<i32>>;
+
+/// What a heartbeat tells a member about its place in the group.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Heartbeat {
+    pub member_id: String,
+    pub member_epoch: i32,
+    /// The member's assignment, or `None` if it hasn't changed since the
+    /// member's last heartbeat.
+    pub assignment: Option<Assignment>,
+}
+
+struct Member {
+    epoch: i32,
+    subscribed_topics: BTreeSet<String>,
+    /// The partitions the member was last told it owns.
+    assignment: Assignment,
+    last_heartbeat: Instant,
+}
+
+#[derive(Default)]
+struct ConsumerGroup {
+    epoch: i32,
+    members: BTreeMap<String, Member>,
+    /// What each member is assigned once partitions have moved between
+    /// members, as of the group's epoch.
+    target: BTreeMap<String, Assignment>,
+}
+
+impl ConsumerGroup {
+    /// Removes the members that haven't heartbeated within `session_timeout`
+    /// of `now`, releasing their partitions.
+    fn expire_members(&mut self, now: Instant, session_timeout: Duration) {
+        self.members.retain(|member_id, member| {
+            let expired = now.duration_since(member.last_heartbeat) > session_timeout;
+            if expired {
+                tracing::debug!(member_id, "Consumer group member's session expired");
+            }
+            !expired
+        });
+    }
+
+    /// Recomputes the target assignment from the members' subscriptions and
+    /// the topics that exist, bumping the group's epoch if it changed.
+    fn rebalance(&mut self, topics: &BTreeMap<String, Vec<i32>>) {
+        let target = range_assignment(&self.members, topics);
+        if target != self.target {
+            self.epoch += 1;
+            self.target = target;
+            tracing::debug!(group_epoch = self.epoch, "Rebalanced consumer group");
+        }
+    }
+
+    /// Moves `member_id` towards its target assignment, bringing it up to the
+    /// group's epoch.
+    ///
+    /// Partitions the member no longer targets are revoked straight away,
+    /// but a partition moving to it from another member is only assigned
+    /// once that member has heartbeated and been told to give it up.
+    fn reconcile(&mut self, member_id: &str) -> Heartbeat {
+        let target = self.target.get(member_id).cloned().unwrap_or_default();
+
+        let mut assignment = Assignment::new();
+        for (topic, partitions) in target {
+            let released: BTreeSet<i32> = partitions
+                .into_iter()
+                .filter(|partition| {
+                    !self.members.iter().any(|(id, other)| {
+                        id != member_id
+                            && other
+                                .assignment
+                                .get(&topic)
+                                .is_some_and(|owned| owned.contains(partition))
+                    })
+                })
+                .collect();
+            if !released.is_empty() {
+                assignment.insert(topic, released);
+            }
+        }
+
+        let member = self
+            .members
+            .get_mut(member_id)
+            .expect("reconciled member is in the group");
+        let changed = member.epoch != self.epoch || member.assignment != assignment;
+        member.epoch = self.epoch;
+        member.assignment = assignment;
+
+        Heartbeat {
+            member_id: member_id.to_string(),
+            member_epoch: member.epoch,
+            assignment: changed.then(|| member.assignment.clone()),
+        }
+    }
+}
+
+/// The group coordinator for consumer groups using the consumer rebalance
+/// protocol of KIP-848, kept in memory.
+///
+/// The coordinator assigns partitions itself, with a range assignor: each
+/// topic's partitions are split into contiguous ranges across the members
+/// subscribed to it, in member id order. Members only move to a new
+/// assignment when they heartbeat, so a partition changes hands once its
+/// previous owner has been told to revoke it.
+///
+/// A member that goes longer than the session timeout without heartbeating
+/// is removed from its group the next time any member of the group
+/// heartbeats, and its partitions are reassigned. It's told its member id is
+/// unknown if it heartbeats again, and has to rejoin.
+pub struct GroupCoordinator {
+    groups: Mutex<HashMap<String, ConsumerGroup>>,
+    logs: Arc<LogStore>,
+    session_timeout: Duration,
+    clock: Arc<dyn Clock>,
+}
+
+impl GroupCoordinator {
+    pub fn new(logs: Arc<LogStore>) -> Self {
+        Self {
+            groups: Mutex::new(HashMap::new()),
+            logs,
+            session_timeout: DEFAULT_SESSION_TIMEOUT,
+            clock: Arc::new(SystemClock),
+        }
+    }
+
+    /// Sets how long a member may go without heartbeating before it's
+    /// removed from its group.
+    pub fn with_session_timeout(mut self, session_timeout: Duration) -> Self {
+        self.session_timeout = session_timeout;
+        self
+    }
+
+    /// Measures members' sessions with `clock` rather than the system clock.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Handles a heartbeat from a member of `group_id` at `member_epoch`.
+    ///
+    /// Joining members heartbeat at epoch 0 and must say which topics they
+    /// subscribe to; they're given a member id if they don't bring their own.
+    /// Other members may leave out their subscription if it hasn't changed.
+    pub fn heartbeat(
+        &self,
+        group_id: &str,
+        member_id: &str,
+        member_epoch: i32,
+        subscribed_topics: Option<&[String]>,
+    ) -> Result<Heartbeat, ErrorCode> {
+        if group_id.is_empty() {
+            return Err(ErrorCode::InvalidGroupId);
+        }
+
+        let mut groups = self.groups.lock().unwrap();
+        let topics = self.logs.topics();
+        let now = self.clock.now();
+
+        if let Some(group) = groups.get_mut(group_id) {
+            group.expire_members(now, self.session_timeout);
+            if group.members.is_empty() {
+                groups.remove(group_id);
+            }
+        }
+
+        if matches!(
+            member_epoch,
+            LEAVE_GROUP_MEMBER_EPOCH | LEAVE_GROUP_STATIC_MEMBER_EPOCH
+        ) {
+            let group = groups.get_mut(group_id).ok_or(ErrorCode::UnknownMemberId)?;
+            group
+                .members
+                .remove(member_id)
+                .ok_or(ErrorCode::UnknownMemberId)?;
+            group.rebalance(&topics);
+
+            if group.members.is_empty() {
+                groups.remove(group_id);
+            }
+
+            tracing::debug!(group_id, member_id, "Member left consumer group");
+            return Ok(Heartbeat {
+                member_id: member_id.to_string(),
+                member_epoch,
+                assignment: None,
+            });
+        }
+
+        let (group, member_id) = if member_epoch == JOIN_GROUP_MEMBER_EPOCH {
+            let subscribed_topics = subscribed_topics.ok_or(ErrorCode::InvalidRequest)?;
+            let member_id = if member_id.is_empty() {
+                Uuid::new_v4().to_string()
+            } else {
+                member_id.to_string()
+            };
+
+            // A member rejoining under its old id starts over, giving up
+            // whatever it owned.
+            let group = groups.entry(group_id.to_string()).or_default();
+            group.members.insert(
+                member_id.clone(),
+                Member {
+                    epoch: JOIN_GROUP_MEMBER_EPOCH,
+                    subscribed_topics: subscribed_topics.iter().cloned().collect(),
+                    assignment: Assignment::new(),
+                    last_heartbeat: now,
+                },
+            );
+
+            tracing::debug!(group_id, member_id, "Member joined consumer group");
+            (group, member_id)
+        } else {
+            let group = groups.get_mut(group_id).ok_or(ErrorCode::UnknownMemberId)?;
+            let member = group
+                .members
+                .get_mut(member_id)
+                .ok_or(ErrorCode::UnknownMemberId)?;
+            if member.epoch != member_epoch {
+                return Err(ErrorCode::FencedMemberEpoch);
+            }
+
+            member.last_heartbeat = now;
+
+            if let Some(subscribed_topics) = subscribed_topics {
+                member.subscribed_topics = subscribed_topics.iter().cloned().collect();
+            }
+
+            (group, member_id.to_string())
+        };
+
+        group.rebalance(&topics);
+        Ok(group.reconcile(&member_id))
+    }
+}
+
+/// Splits each topic's partitions into contiguous ranges across the members
+/// subscribed to it. Earlier members get one partition more when they don't
+/// split evenly.
+fn range_assignment(
+    members: &BTreeMap<String, Member>,
+    topics: &BTreeMap<String, Vec<i32>>,
+) -> BTreeMap<String, Assignment> {
+    let mut target: BTreeMap<String, Assignment> = members
+        .keys()
+        .map(|member_id| (member_id.clone(), Assignment::new()))
+        .collect();
+
+    for (topic, partitions) in topics {
+        let subscribers: Vec<&String> = members
+            .iter()
+            .filter(|(_, member)| member.subscribed_topics.contains(topic))
+            .map(|(member_id, _)| member_id)
+            .collect();
+        if subscribers.is_empty() {
+            continue;
+        }
+
+        let per_member = partitions.len() / subscribers.len();
+        let extra = partitions.len() % subscribers.len();
+        let mut partitions = partitions.iter().copied();
+
+        for (i, member_id) in subscribers.into_iter().enumerate() {
+            let count = per_member + usize::from(i < extra);
+            let range: BTreeSet<i32> = partitions.by_ref().take(count).collect();
+            if !range.is_empty() {
+                target
+                    .get_mut(member_id)
+                    .expect("every member has a target")
+                    .insert(topic.clone(), range);
+            }
+        }
+    }
+
+    target
+}
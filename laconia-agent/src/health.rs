@@ -0,0 +1,59 @@
+//! A plain HTTP health check, for load balancers that can't speak Kafka or
+//! gRPC to probe the agent.
+
+use std::{
+    io,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+};
+
+use axum::{Router, extract::State, http::StatusCode, routing::get};
+use tokio::{net::TcpListener, task::JoinHandle};
+
+/// What the agent's health is judged on.
+#[derive(Debug)]
+pub struct Health {
+    listener_bound: AtomicBool,
+    controlplane_healthy: Arc<AtomicBool>,
+}
+
+impl Health {
+    /// Creates the health of an agent whose Kafka listener isn't bound yet,
+    /// with `controlplane_healthy` tracking its control-plane connection.
+    pub fn new(controlplane_healthy: Arc<AtomicBool>) -> Self {
+        Self {
+            listener_bound: AtomicBool::new(false),
+            controlplane_healthy,
+        }
+    }
+
+    pub fn set_listener_bound(&self) {
+        self.listener_bound.store(true, Ordering::Relaxed);
+    }
+
+    /// Whether the Kafka listener is bound and the control plane is reachable.
+    pub fn is_healthy(&self) -> bool {
+        self.listener_bound.load(Ordering::Relaxed)
+            && self.controlplane_healthy.load(Ordering::Relaxed)
+    }
+}
+
+/// Serves `GET /healthz` on `listener`, answering 200 while `health` is
+/// healthy and 503 otherwise.
+pub fn serve(listener: TcpListener, health: Arc<Health>) -> JoinHandle<Result<(), io::Error>> {
+    let router = Router::new()
+        .route("/healthz", get(healthz))
+        .with_state(health);
+
+    tokio::spawn(async move { axum::serve(listener, router).await })
+}
+
+async fn healthz(State(health): State<Arc<Health>>) -> StatusCode {
+    if health.is_healthy() {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    }
+}
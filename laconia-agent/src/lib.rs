@@ -0,0 +1,756 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    io,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+use tracing::Instrument;
+
+use crate::{
+    authorizer::{ANONYMOUS_PRINCIPAL, AllowAll, Authorizer, Operation, Resource},
+    features::Features,
+    protocol::{
+        DecodeError, api_keys,
+        error::ErrorCode,
+        handlers::api_versions,
+        messages::{ApiVersionsApiKeys, ApiVersionsRequest, ApiVersionsResponse},
+        primitives::{CheckedGet, NullableString, decode_tagged_if_flexible, with_size_prefix},
+        registry::{API_VERSIONS_KEY, MessageRegistry},
+        request::Request,
+        response::AnyResponse,
+    },
+    server::SecurityProtocol,
+};
+
+pub use protocol::{Decoder, DecoderVersioned, Encoder, EncoderVersioned};
+
+pub mod authorizer;
+pub mod checksum;
+pub mod clock;
+pub mod cluster;
+pub mod compression;
+pub mod configs;
+pub mod connections;
+pub mod controlplane;
+pub mod features;
+pub mod fetch_session;
+#[cfg(feature = "fuzzing")]
+pub mod fuzz;
+pub mod groups;
+pub mod health;
+pub mod log;
+pub mod offsets;
+pub mod protocol;
+pub mod quota;
+pub mod server;
+pub mod telemetry;
+#[cfg(any(test, feature = "test-util"))]
+pub mod test_client;
+pub mod transactions;
+
+/// The largest request accepted, length prefix excluded, unless configured
+/// otherwise. The same as Kafka's `socket.request.max.bytes` default.
+pub const DEFAULT_MAX_REQUEST_SIZE: usize = 100 * 1024 * 1024;
+
+/// Frames Kafka messages on a connection.
+///
+/// Each connection owns its codec, so responses are encoded into a scratch
+/// buffer that is reused for the lifetime of the connection instead of being
+/// allocated per message.
+pub struct KafkaMessageCodec {
+    scratch: BytesMut,
+    max_frame_size: usize,
+}
+
+impl KafkaMessageCodec {
+    pub fn new() -> Self {
+        Self {
+            scratch: BytesMut::new(),
+            max_frame_size: DEFAULT_MAX_REQUEST_SIZE,
+        }
+    }
+
+    /// Sets the largest frame decoded, length prefix excluded. A frame
+    /// announcing a larger length is an error, before any room is made for
+    /// it, so a peer can't make the codec allocate more than this.
+    pub fn with_max_frame_size(mut self, max_frame_size: usize) -> Self {
+        self.max_frame_size = max_frame_size;
+        self
+    }
+}
+
+impl Default for KafkaMessageCodec {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl tokio_util::codec::Decoder for KafkaMessageCodec {
+    type Item = Bytes;
+    type Error = io::Error;
+
+    /// Splits the next frame off `src`, without its length prefix.
+    ///
+    /// A frame is only returned once all of it has arrived. Until then `src`
+    /// is left as is and this returns `Ok(None)`, so `tokio_util` reads more
+    /// and tries again. Only bytes that can never become a valid frame, such
+    /// as a negative length or one over the maximum frame size, are an
+    /// error. The frame's body isn't parsed here, so any validation of it
+    /// added later must likewise turn a [`DecodeError::Incomplete`] into
+    /// `Ok(None)` rather than an error.
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        Ok(decode_frame(src, self.max_frame_size)?.map(BytesMut::freeze))
+    }
+}
+
+/// Splits the next frame off `src`, as [`KafkaMessageCodec`] does.
+///
+/// Zero-length frames are skipped. They can't hold a request or response,
+/// which always start with a header, but they're harmless, so they aren't
+/// worth failing the connection over.
+fn decode_frame(src: &mut BytesMut, max_frame_size: usize) -> Result<Option<BytesMut>, io::Error> {
+    let len = loop {
+        match frame_length(src, max_frame_size) {
+            Ok(0) => {
+                tracing::debug!("Skipping zero-length frame");
+                src.advance(4);
+            }
+            Ok(len) => break len,
+            Err(DecodeError::Incomplete { needed }) => {
+                // Make room for the rest of the frame up front, so it's read
+                // in as few calls as possible. The length was checked against
+                // the maximum, so this is bounded by it.
+                src.reserve(needed);
+                return Ok(None);
+            }
+            Err(err) => return Err(err.into()),
+        }
+    };
+
+    src.advance(4);
+    let frame = src.split_to(len);
+
+    // The api key leads the request header, so it can be read without
+    // decoding the rest of the frame.
+    if let Some(api_key) = frame.first_chunk::<2>() {
+        telemetry::record_request_size(i16::from_be_bytes(*api_key), len);
+    }
+
+    Ok(Some(frame))
+}
+
+/// The length of the frame at the start of `src`, once all of it has arrived.
+fn frame_length(src: &BytesMut, max_frame_size: usize) -> Result<usize, DecodeError> {
+    let Some(prefix) = src.first_chunk::<4>() else {
+        return Err(DecodeError::Incomplete {
+            needed: 4 - src.len(),
+        });
+    };
+
+    let len = i32::from_be_bytes(*prefix);
+    if len < 0 {
+        return Err(DecodeError::invalid(format!(
+            "negative frame length: {}",
+            len
+        )));
+    }
+
+    let len = len as usize;
+    if len > max_frame_size {
+        return Err(DecodeError::invalid(format!(
+            "frame length {} exceeds the maximum of {}",
+            len, max_frame_size
+        )));
+    }
+
+    if src.len() - 4 < len {
+        return Err(DecodeError::Incomplete {
+            needed: len - (src.len() - 4),
+        });
+    }
+
+    Ok(len)
+}
+
+impl tokio_util::codec::Encoder<KafkaResponse> for KafkaMessageCodec {
+    type Error = io::Error;
+
+    fn encode(&mut self, item: KafkaResponse, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        self.scratch.clear();
+        self.scratch.reserve(item.size_hint());
+        item.encode(&mut self.scratch)?;
+        telemetry::record_response_size(item.api_key, self.scratch.len());
+
+        dst.reserve(4 + self.scratch.len());
+        dst.put_i32(self.scratch.len() as i32);
+        dst.extend_from_slice(&self.scratch);
+
+        Ok(())
+    }
+}
+
+/// Frames requests like [`KafkaMessageCodec`], and also decodes their
+/// header, yielding it alongside the rest of the frame.
+///
+/// The header's layout depends on the api key and version, so decoding it
+/// takes the registry of the handlers that will answer the request. A frame
+/// whose header can't be decoded is an error, since there's no correlation
+/// id to answer it with, as is a frame over the maximum request size.
+pub struct KafkaRequestCodec {
+    frames: KafkaMessageCodec,
+    registry: Arc<MessageRegistry>,
+    max_client_id_len: usize,
+    last_frame_len: usize,
+}
+
+impl KafkaRequestCodec {
+    pub fn new(registry: Arc<MessageRegistry>) -> Self {
+        Self {
+            frames: KafkaMessageCodec::new(),
+            registry,
+            max_client_id_len: DEFAULT_MAX_CLIENT_ID_LEN,
+            last_frame_len: 0,
+        }
+    }
+
+    /// The size of the last request decoded, length prefix included.
+    pub fn last_frame_len(&self) -> usize {
+        self.last_frame_len
+    }
+
+    /// Sets the longest client id accepted in request headers.
+    pub fn with_max_client_id_len(mut self, max_client_id_len: usize) -> Self {
+        self.max_client_id_len = max_client_id_len;
+        self
+    }
+
+    /// Sets the largest request accepted, length prefix excluded.
+    pub fn with_max_request_size(mut self, max_request_size: usize) -> Self {
+        self.frames = self.frames.with_max_frame_size(max_request_size);
+        self
+    }
+}
+
+impl tokio_util::codec::Decoder for KafkaRequestCodec {
+    type Item = (RequestHeader, BytesMut);
+    type Error = io::Error;
+
+    /// Splits the next frame off `src` and decodes its header, leaving the
+    /// request body in the returned buffer.
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        let Some(mut frame) = decode_frame(src, self.frames.max_frame_size)? else {
+            return Ok(None);
+        };
+        self.last_frame_len = 4 + frame.len();
+
+        let header = RequestHeader::decode(&mut frame, &self.registry, self.max_client_id_len)?;
+        Ok(Some((header, frame)))
+    }
+}
+
+impl tokio_util::codec::Encoder<KafkaResponse> for KafkaRequestCodec {
+    type Error = io::Error;
+
+    fn encode(&mut self, item: KafkaResponse, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        self.frames.encode(item, dst)
+    }
+}
+
+/// The longest client id accepted, unless configured otherwise.
+pub const DEFAULT_MAX_CLIENT_ID_LEN: usize = 1024;
+
+pub struct ConnectionState {
+    pub(crate) registry: Arc<MessageRegistry>,
+    security_protocol: SecurityProtocol,
+    max_client_id_len: usize,
+    /// Whether requests with bytes left over after decoding are rejected.
+    strict_decoding: bool,
+    authorizer: Arc<dyn Authorizer>,
+    /// The principal the client authenticated as, if it did.
+    principal: Option<String>,
+    client_software_name: Option<String>,
+    client_software_version: Option<String>,
+    max_versions: HashMap<i16, i16>,
+    features: Arc<Features>,
+    /// Whether the client has been advertised the features, which only
+    /// ApiVersions v3 and up do.
+    features_advertised: bool,
+    /// When the request being handled times out, if it carries a timeout.
+    deadline: Option<Instant>,
+    opened_at: Instant,
+    request_count: u64,
+    bytes_read: u64,
+}
+
+impl ConnectionState {
+    pub fn new(registry: Arc<MessageRegistry>) -> Self {
+        Self {
+            registry,
+            security_protocol: SecurityProtocol::Plaintext,
+            max_client_id_len: DEFAULT_MAX_CLIENT_ID_LEN,
+            strict_decoding: false,
+            authorizer: Arc::new(AllowAll),
+            principal: None,
+            client_software_name: None,
+            client_software_version: None,
+            max_versions: HashMap::new(),
+            features: Arc::new(Features::new()),
+            features_advertised: false,
+            deadline: None,
+            opened_at: Instant::now(),
+            request_count: 0,
+            bytes_read: 0,
+        }
+    }
+
+    /// Tags the connection as accepted by a listener with the given security
+    /// protocol. Connections are plaintext otherwise.
+    pub fn with_security_protocol(mut self, security_protocol: SecurityProtocol) -> Self {
+        self.security_protocol = security_protocol;
+        self
+    }
+
+    /// Sets the longest client id accepted in request headers. Requests with
+    /// a longer one fail to decode.
+    pub fn with_max_client_id_len(mut self, max_client_id_len: usize) -> Self {
+        self.max_client_id_len = max_client_id_len;
+        self
+    }
+
+    /// Rejects requests whose body isn't fully consumed by its decoder with
+    /// `InvalidRequest`, rather than ignoring the leftover bytes. Leftovers
+    /// point at a decoder bug or a client encoding a different version than
+    /// it claims, so this is meant for surfacing those during development.
+    pub fn with_strict_decoding(mut self, strict_decoding: bool) -> Self {
+        self.strict_decoding = strict_decoding;
+        self
+    }
+
+    /// Sets the authorizer requests are checked against before they're
+    /// handled. Everything is allowed otherwise.
+    pub fn with_authorizer(mut self, authorizer: Arc<dyn Authorizer>) -> Self {
+        self.authorizer = authorizer;
+        self
+    }
+
+    /// Sets the features advertised to the client in ApiVersions. There are
+    /// none otherwise.
+    pub fn with_features(mut self, features: Arc<Features>) -> Self {
+        self.features = features;
+        self
+    }
+
+    /// Counts a request of `bytes` bytes towards the connection's totals.
+    pub(crate) fn record_request(&mut self, bytes: usize) {
+        self.request_count += 1;
+        self.bytes_read += bytes as u64;
+    }
+
+    /// The number of requests read off the connection so far.
+    pub fn request_count(&self) -> u64 {
+        self.request_count
+    }
+
+    /// The total size of the requests read off the connection so far.
+    pub fn bytes_read(&self) -> u64 {
+        self.bytes_read
+    }
+
+    /// How long the connection has been open.
+    pub fn duration(&self) -> Duration {
+        self.opened_at.elapsed()
+    }
+
+    /// Whether requests with bytes left over after decoding are rejected.
+    pub fn strict_decoding(&self) -> bool {
+        self.strict_decoding
+    }
+
+    /// The security protocol of the listener that accepted the connection.
+    pub fn security_protocol(&self) -> SecurityProtocol {
+        self.security_protocol
+    }
+
+    /// The client's software name, if it sent one in ApiVersions v3 or later.
+    pub fn client_software_name(&self) -> Option<&str> {
+        self.client_software_name.as_deref()
+    }
+
+    /// The client's software version, if it sent one in ApiVersions v3 or
+    /// later.
+    pub fn client_software_version(&self) -> Option<&str> {
+        self.client_software_version.as_deref()
+    }
+
+    /// The principal the client authenticated as, or
+    /// [`ANONYMOUS_PRINCIPAL`] if it hasn't.
+    pub fn principal(&self) -> &str {
+        self.principal.as_deref().unwrap_or(ANONYMOUS_PRINCIPAL)
+    }
+
+    /// Records the principal the client authenticated as through SASL, which
+    /// later requests are authorized as.
+    pub fn set_principal(&mut self, principal: impl Into<String>) {
+        self.principal = Some(principal.into());
+    }
+
+    /// Checks that the client may perform each of `operations`, returning the
+    /// error to fail the request with if it may not.
+    pub(crate) fn authorize(&self, operations: &[(Operation, Resource)]) -> Option<ErrorCode> {
+        operations
+            .iter()
+            .find(|(operation, resource)| {
+                !self
+                    .authorizer
+                    .authorize(self.principal(), *operation, resource)
+            })
+            .map(|(_, resource)| resource.denied_error_code())
+    }
+
+    /// When the request being handled times out, derived from its
+    /// [timeout](crate::protocol::request::Request::timeout_ms). Handlers
+    /// that wait, such as for records to arrive, must respond by then.
+    /// `None` if the request carries no timeout.
+    pub fn deadline(&self) -> Option<Instant> {
+        self.deadline
+    }
+
+    /// Starts the deadline of a request that times out after `timeout_ms`.
+    pub(crate) fn start_deadline(&mut self, timeout_ms: Option<i32>) {
+        self.deadline = timeout_ms
+            .map(|timeout_ms| Instant::now() + Duration::from_millis(timeout_ms.max(0) as u64));
+    }
+
+    /// The highest version of `api_key` advertised to the client, or `None`
+    /// before the first ApiVersions exchange.
+    pub fn max_version(&self, api_key: i16) -> Option<i16> {
+        self.max_versions.get(&api_key).copied()
+    }
+
+    /// The features supported by the broker, advertised to clients in
+    /// ApiVersions.
+    pub fn features(&self) -> &Features {
+        &self.features
+    }
+
+    /// The level of feature `name` negotiated with the client, which is the
+    /// level the broker finalized it at. `None` if the broker doesn't support
+    /// it, or until the client has been advertised it by ApiVersions v3 or
+    /// later, so behaviors gated on a feature are left alone for clients
+    /// that can't know about it.
+    pub fn feature_level(&self, name: &str) -> Option<i16> {
+        if !self.features_advertised {
+            return None;
+        }
+        self.features.finalized_level(name)
+    }
+
+    /// Records what was learned from an ApiVersions exchange at `version`.
+    pub(crate) fn negotiate(
+        &mut self,
+        version: i16,
+        client_software_name: &str,
+        client_software_version: &str,
+        api_keys: &[ApiVersionsApiKeys],
+    ) {
+        if !client_software_name.is_empty() {
+            self.client_software_name = Some(client_software_name.to_string());
+            self.client_software_version = Some(client_software_version.to_string());
+        }
+
+        self.features_advertised = version > 2;
+
+        self.max_versions = api_keys
+            .iter()
+            .map(|key| (key.api_key, key.max_version))
+            .collect();
+    }
+}
+
+pub struct KafkaRequest {
+    pub header: RequestHeader,
+    /// The version to encode `response` at. This is the request's version,
+    /// except when falling back to a v0 ApiVersions response.
+    pub response_version: i16,
+    pub response: Box<dyn AnyResponse>,
+}
+
+impl KafkaRequest {
+    pub async fn decode_and_handle(
+        buf: &mut BytesMut,
+        registry: &MessageRegistry,
+        state: &mut ConnectionState,
+    ) -> Result<Self, io::Error> {
+        let header = RequestHeader::decode(buf, registry, state.max_client_id_len)?;
+        Self::handle(header, buf, registry, state).await
+    }
+
+    /// Handles a request whose header was already decoded, such as by
+    /// [`KafkaRequestCodec`], with `buf` holding the request body.
+    pub async fn handle(
+        header: RequestHeader,
+        buf: &mut BytesMut,
+        registry: &MessageRegistry,
+        state: &mut ConnectionState,
+    ) -> Result<Self, io::Error> {
+        // Clients open with the newest ApiVersions they know of and retry with
+        // v0 when told it's unsupported. The body of a version we don't know
+        // can't be decoded, so answer in v0 without looking at it. Api keys
+        // the protocol doesn't define have no response schema to answer with
+        // at all, so they get the same response, which at least lists the
+        // keys we do support. Keys it defines but we don't implement are
+        // answered by the registry.
+        let unsupported = match registry.versions(header.api_key) {
+            Ok(versions) => {
+                header.api_key == API_VERSIONS_KEY && !versions.contains(header.version)
+            }
+            Err(_) if api_keys::lookup(header.api_key).is_some() => false,
+            Err(err) => {
+                tracing::warn!(correlation_id = header.correlation_id, "{}", err);
+                true
+            }
+        };
+
+        if unsupported {
+            let response = ApiVersionsResponse {
+                api_keys: api_versions(registry),
+                ..ApiVersionsRequest::decode_error_response(ErrorCode::UnsupportedVersion)
+            };
+            telemetry::record_error(ErrorCode::UnsupportedVersion);
+
+            return Ok(Self {
+                header,
+                response_version: 0,
+                response: Box::new(response),
+            });
+        }
+
+        let span = tracing::debug_span!(
+            "request",
+            api_key = header.api_key,
+            api_version = header.version,
+            correlation_id = header.correlation_id,
+        );
+        let start = Instant::now();
+        let response = registry
+            .handle_request(buf, &header, state)
+            .instrument(span)
+            .await?;
+        telemetry::record_request(header.api_key, start.elapsed());
+        Ok(Self {
+            response_version: header.version,
+            header,
+            response,
+        })
+    }
+
+    /// Encodes the response to the request as a complete frame, like
+    /// [`KafkaResponse::to_bytes`].
+    pub fn response_bytes(&self) -> Result<Bytes, io::Error> {
+        let header = ResponseHeader {
+            correlation_id: self.header.correlation_id,
+        };
+        let header_version = ResponseHeader::version_for(&self.header);
+        let size_hint = 4 + self.response.size_hint_any(self.response_version);
+
+        encode_frame(size_hint, |buf| {
+            header.encode(buf, header_version)?;
+            self.response.encode_any(buf, self.response_version)
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct RequestHeader {
+    pub api_key: i16,
+    pub version: i16,
+    pub correlation_id: i32,
+    pub client_id: String,
+    pub tagged_fields: BTreeMap<i32, Bytes>,
+}
+
+impl RequestHeader {
+    fn decode(
+        buf: &mut BytesMut,
+        registry: &MessageRegistry,
+        max_client_id_len: usize,
+    ) -> Result<Self, io::Error> {
+        let api_key = buf.checked_get_i16()?;
+        let version = buf.checked_get_i16()?;
+        let correlation_id = buf.checked_get_i32()?;
+
+        // The header version depends on the api key. Every request for a key
+        // we don't know of is answered without reading its body, so only the
+        // client id matters and v1 is a safe guess.
+        let header_version = registry.header_version(api_key, version).unwrap_or(1);
+
+        let client_id = if header_version > 0 {
+            Self::decode_client_id(buf, max_client_id_len)?
+        } else {
+            String::new()
+        };
+
+        let tagged_fields = decode_tagged_if_flexible(buf, header_version, 2)?;
+
+        Ok(Self {
+            api_key,
+            version,
+            correlation_id,
+            client_id,
+            tagged_fields,
+        })
+    }
+
+    /// Decodes the client id, rejecting it by its length prefix alone when
+    /// it's longer than `max_len`, so it's never copied out of `buf`.
+    fn decode_client_id(buf: &mut BytesMut, max_len: usize) -> Result<String, io::Error> {
+        if let Some(len) = buf.first_chunk::<2>().map(|len| i16::from_be_bytes(*len))
+            && len > 0
+            && len as usize > max_len
+        {
+            return Err(DecodeError::invalid(format!(
+                "client id of {} bytes is longer than the maximum of {}",
+                len, max_len
+            ))
+            .into());
+        }
+
+        Ok(NullableString::decode(buf)?.0)
+    }
+}
+
+/// Encodes the header as a client sends it, at the header version given by
+/// the request's api key and version.
+impl EncoderVersioned for RequestHeader {
+    fn encode(&self, buf: &mut BytesMut, version: i16) -> Result<(), io::Error> {
+        buf.put_i16(self.api_key);
+        buf.put_i16(self.version);
+        buf.put_i32(self.correlation_id);
+
+        if version > 0 {
+            NullableString(self.client_id.clone()).encode(buf)?;
+        }
+
+        if version > 1 {
+            self.tagged_fields.encode(buf)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VersionRange {
+    pub min: i16,
+    pub max: i16,
+}
+
+impl VersionRange {
+    pub fn new(min: i16, max: i16) -> Self {
+        Self { min, max }
+    }
+
+    pub fn contains(&self, version: i16) -> bool {
+        self.min <= version && version <= self.max
+    }
+}
+
+pub trait Message: Sized {
+    const VERSIONS: VersionRange;
+    const DEPRECATED_VERSIONS: Option<VersionRange>;
+
+    fn header_version(version: i16) -> i16;
+}
+
+pub struct KafkaResponse {
+    pub api_key: i16,
+    pub header: ResponseHeader,
+    /// The version of `header`, which follows from the request's api key and
+    /// version.
+    pub header_version: i16,
+    pub version: i16,
+    pub response: Box<dyn AnyResponse>,
+}
+
+impl KafkaResponse {
+    pub fn new(header: &RequestHeader, version: i16, response: Box<dyn AnyResponse>) -> Self {
+        Self {
+            api_key: header.api_key,
+            header: ResponseHeader {
+                correlation_id: header.correlation_id,
+            },
+            header_version: ResponseHeader::version_for(header),
+            version,
+            response,
+        }
+    }
+
+    pub fn size_hint(&self) -> usize {
+        4 + self.response.size_hint_any(self.version)
+    }
+
+    /// Encodes the response as a complete frame, length prefix included,
+    /// exactly as the codec writes it to the connection.
+    pub fn to_bytes(&self) -> Result<Bytes, io::Error> {
+        encode_frame(self.size_hint(), |buf| self.encode(buf))
+    }
+}
+
+/// Runs `encode` on a buffer, then prefixes what it wrote with its length.
+fn encode_frame(
+    size_hint: usize,
+    encode: impl FnOnce(&mut BytesMut) -> Result<(), io::Error>,
+) -> Result<Bytes, io::Error> {
+    let mut buf = BytesMut::with_capacity(4 + size_hint);
+    with_size_prefix(&mut buf, encode)?;
+    Ok(buf.freeze())
+}
+
+impl Encoder for KafkaResponse {
+    fn encode(&self, buf: &mut BytesMut) -> Result<(), io::Error> {
+        self.header.encode(buf, self.header_version)?;
+        self.response.encode_any(buf, self.version)?;
+        Ok(())
+    }
+}
+
+pub struct ResponseHeader {
+    pub correlation_id: i32,
+}
+
+impl ResponseHeader {
+    /// The version of the header of the response to a request with `header`.
+    /// Flexible responses have a v1 header, ending in tagged fields, except
+    /// for ApiVersions. Responses to api keys the protocol doesn't define
+    /// have a v0 header.
+    pub fn version_for(header: &RequestHeader) -> i16 {
+        api_keys::lookup(header.api_key)
+            .map_or(0, |api_key| api_key.response_header_version(header.version))
+    }
+}
+
+impl EncoderVersioned for ResponseHeader {
+    fn encode(&self, buf: &mut BytesMut, version: i16) -> Result<(), io::Error> {
+        buf.put_i32(self.correlation_id);
+
+        if version > 0 {
+            BTreeMap::<i32, Bytes>::new().encode(buf)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Decodes the header as a client reads it. The header's tagged fields are
+/// skipped, as no response header field is defined as one.
+impl DecoderVersioned for ResponseHeader {
+    fn decode(buf: &mut BytesMut, version: i16) -> Result<Self, io::Error> {
+        let correlation_id = buf.checked_get_i32()?;
+
+        decode_tagged_if_flexible(buf, version, 1)?;
+
+        Ok(Self { correlation_id })
+    }
+}
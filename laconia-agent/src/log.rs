@@ -0,0 +1,636 @@
+use std::{fmt, io, sync::RwLock};
+
+use bytes::{BufMut, Bytes, BytesMut};
+use integer_encoding::{VarInt, VarIntWriter};
+
+use crate::{
+    checksum,
+    compression::{CODEC_MASK, Compression},
+    protocol::{error::ErrorCode, primitives::with_size_prefix},
+};
+
+mod epoch;
+pub use epoch::EpochCache;
+
+mod producers;
+pub use producers::{AbortedTransaction, ProducerState, ProducerStates};
+
+mod segment;
+pub use segment::SegmentLog;
+
+mod store;
+pub use store::LogStore;
+
+/// The leader epoch of every partition. This broker has led each one since it
+/// was created, so it's the only epoch batches are appended under.
+pub const LEADER_EPOCH: i32 = 0;
+
+/// The size of a record batch header, up to and including the record count.
+pub const BATCH_HEADER_SIZE: usize = 61;
+
+/// The attributes bit set on batches written as part of a transaction.
+const TRANSACTIONAL_FLAG: i16 = 0x10;
+
+/// The attributes bit set on control batches, such as transaction markers.
+const CONTROL_FLAG: i16 = 0x20;
+
+/// The type of the control record marking a transaction as aborted.
+const CONTROL_TYPE_ABORT: i16 = 0;
+
+/// A record batch in the Kafka v2 on-disk and wire format.
+///
+/// Only the header fields the log needs are interpreted; the records
+/// themselves are kept as opaque bytes.
+#[derive(Debug, Clone)]
+pub struct RecordBatch {
+    data: Bytes,
+}
+
+impl RecordBatch {
+    /// Wraps `data` as a record batch, checking that its header is complete
+    /// and agrees with its length.
+    pub fn new(data: Bytes) -> Result<Self, LogError> {
+        if data.len() < BATCH_HEADER_SIZE {
+            return Err(LogError::CorruptBatch(format!(
+                "batch of {} bytes is shorter than its header",
+                data.len()
+            )));
+        }
+
+        let batch = Self { data };
+
+        // The batch length counts everything after the length field itself.
+        let batch_length = batch.i32_at(8);
+        if batch_length < 0 || batch_length as usize + 12 != batch.data.len() {
+            return Err(LogError::CorruptBatch(format!(
+                "batch length {} doesn't match its size of {} bytes",
+                batch_length,
+                batch.data.len()
+            )));
+        }
+
+        if batch.last_offset_delta() < 0 {
+            return Err(LogError::CorruptBatch(format!(
+                "negative last offset delta: {}",
+                batch.last_offset_delta()
+            )));
+        }
+
+        Ok(batch)
+    }
+
+    /// Builds the control batch that marks the end of a producer's
+    /// transaction in a partition, holding a single commit or abort marker
+    /// record.
+    pub fn transaction_marker(
+        producer_id: i64,
+        producer_epoch: i16,
+        committed: bool,
+        timestamp: i64,
+    ) -> Self {
+        // The marker's key is its version and type, and its value its version
+        // and the coordinator epoch.
+        let mut record = Vec::new();
+        record.put_i8(0);
+        record.write_varint(0i64).unwrap();
+        record.write_varint(0i32).unwrap();
+        record.write_varint(4i32).unwrap();
+        record.put_i16(0);
+        record.put_i16(if committed { 1 } else { CONTROL_TYPE_ABORT });
+        record.write_varint(6i32).unwrap();
+        record.put_i16(0);
+        record.put_i32(0);
+        record.write_varint(0i32).unwrap();
+
+        let mut data = BytesMut::with_capacity(BATCH_HEADER_SIZE + record.len() + 1);
+        data.put_i64(0);
+
+        // The batch length counts everything after the length field itself.
+        with_size_prefix(&mut data, |data| {
+            data.put_i32(-1);
+            data.put_i8(2);
+            data.put_u32(0);
+            data.put_i16(TRANSACTIONAL_FLAG | CONTROL_FLAG);
+            data.put_i32(0);
+            data.put_i64(timestamp);
+            data.put_i64(timestamp);
+            data.put_i64(producer_id);
+            data.put_i16(producer_epoch);
+            data.put_i32(-1);
+            data.put_i32(1);
+            data.writer().write_varint(record.len() as i32)?;
+            data.extend_from_slice(&record);
+            Ok(())
+        })
+        .expect("a marker batch is far smaller than the largest batch length");
+
+        let crc = checksum::compute(&data[21..]);
+        data[17..21].copy_from_slice(&crc.to_be_bytes());
+
+        Self {
+            data: data.freeze(),
+        }
+    }
+
+    pub fn base_offset(&self) -> i64 {
+        self.i64_at(0)
+    }
+
+    pub fn attributes(&self) -> i16 {
+        i16::from_be_bytes(self.data[21..23].try_into().unwrap())
+    }
+
+    pub fn compression(&self) -> Result<Compression, LogError> {
+        Compression::from_attributes(self.attributes()).ok_or_else(|| {
+            LogError::CorruptBatch(format!(
+                "unknown compression codec: {}",
+                self.attributes() & CODEC_MASK
+            ))
+        })
+    }
+
+    pub fn is_transactional(&self) -> bool {
+        self.attributes() & TRANSACTIONAL_FLAG != 0
+    }
+
+    /// Whether the batch holds control records, like transaction markers,
+    /// rather than records produced by clients.
+    pub fn is_control(&self) -> bool {
+        self.attributes() & CONTROL_FLAG != 0
+    }
+
+    /// Whether the batch is the marker aborting its producer's transaction.
+    pub fn is_abort_marker(&self) -> bool {
+        self.is_control() && self.control_type() == Some(CONTROL_TYPE_ABORT)
+    }
+
+    /// The type of the batch's first control record, read from its key.
+    fn control_type(&self) -> Option<i16> {
+        let records = self.records().ok()?;
+
+        // The record's length, attributes, timestamp delta and offset delta
+        // come before its key.
+        let (_, len) = i32::decode_var(&records)?;
+        let mut rest = records.get(len + 1..)?;
+        let (_, len) = i64::decode_var(rest)?;
+        rest = &rest[len..];
+        let (_, len) = i32::decode_var(rest)?;
+        rest = &rest[len..];
+        let (key_len, len) = i32::decode_var(rest)?;
+        rest = &rest[len..];
+
+        // The key is the control record's version, followed by its type.
+        let key = rest.get(..key_len.max(0) as usize)?;
+        Some(i16::from_be_bytes(key.get(2..4)?.try_into().ok()?))
+    }
+
+    /// The id of the producer that wrote the batch, or -1 if it has none.
+    pub fn producer_id(&self) -> i64 {
+        self.i64_at(43)
+    }
+
+    pub fn producer_epoch(&self) -> i16 {
+        i16::from_be_bytes(self.data[51..53].try_into().unwrap())
+    }
+
+    /// The batch's records, decompressed.
+    pub fn records(&self) -> Result<Vec<u8>, LogError> {
+        self.compression()?
+            .decompress(&self.data[BATCH_HEADER_SIZE..])
+            .map_err(|err| LogError::CorruptBatch(format!("failed to decompress records: {}", err)))
+    }
+
+    /// Returns a copy of the batch with its records compressed with
+    /// `compression` instead, or the batch itself if it already is.
+    pub fn recompress(&self, compression: Compression) -> Result<Self, LogError> {
+        if self.compression()? == compression {
+            return Ok(self.clone());
+        }
+
+        let records = compression.compress(&self.records()?)?;
+
+        let mut data = BytesMut::with_capacity(BATCH_HEADER_SIZE + records.len());
+        data.extend_from_slice(&self.data[..BATCH_HEADER_SIZE]);
+        data.extend_from_slice(&records);
+
+        let batch_length = (data.len() - 12) as i32;
+        data[8..12].copy_from_slice(&batch_length.to_be_bytes());
+
+        let attributes = (self.attributes() & !CODEC_MASK) | compression as i16;
+        data[21..23].copy_from_slice(&attributes.to_be_bytes());
+
+        let crc = checksum::compute(&data[21..]);
+        data[17..21].copy_from_slice(&crc.to_be_bytes());
+
+        Ok(Self {
+            data: data.freeze(),
+        })
+    }
+
+    /// The epoch of the leader that appended the batch, or -1 if it hasn't
+    /// been appended yet.
+    pub fn partition_leader_epoch(&self) -> i32 {
+        self.i32_at(12)
+    }
+
+    pub fn last_offset_delta(&self) -> i32 {
+        self.i32_at(23)
+    }
+
+    /// The offset of the last record in the batch.
+    pub fn last_offset(&self) -> i64 {
+        self.base_offset() + self.last_offset_delta() as i64
+    }
+
+    pub fn base_timestamp(&self) -> i64 {
+        self.i64_at(27)
+    }
+
+    pub fn max_timestamp(&self) -> i64 {
+        self.i64_at(35)
+    }
+
+    /// The CRC32C stored in the batch header.
+    pub fn crc(&self) -> u32 {
+        self.i32_at(17) as u32
+    }
+
+    /// Checks the stored CRC against one computed over everything from the
+    /// attributes onwards, which is what the stored one covers.
+    pub fn verify_crc(&self) -> Result<(), LogError> {
+        let computed = checksum::compute(&self.data[21..]);
+        if computed != self.crc() {
+            return Err(LogError::CorruptBatch(format!(
+                "crc mismatch: stored {:#010x}, computed {:#010x}",
+                self.crc(),
+                computed
+            )));
+        }
+
+        Ok(())
+    }
+
+    pub fn record_count(&self) -> i32 {
+        self.i32_at(57)
+    }
+
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    pub fn as_bytes(&self) -> &Bytes {
+        &self.data
+    }
+
+    /// Returns a copy of the batch starting at `base_offset` instead.
+    pub(crate) fn with_base_offset(&self, base_offset: i64) -> Self {
+        let mut data = BytesMut::with_capacity(self.data.len());
+        data.put_i64(base_offset);
+        data.extend_from_slice(&self.data[8..]);
+        Self {
+            data: data.freeze(),
+        }
+    }
+
+    /// Returns a copy of the batch appended under `leader_epoch` instead. The
+    /// epoch isn't covered by the CRC, which stays valid.
+    pub(crate) fn with_leader_epoch(&self, leader_epoch: i32) -> Self {
+        let mut data = BytesMut::from(&self.data[..]);
+        data[12..16].copy_from_slice(&leader_epoch.to_be_bytes());
+        Self {
+            data: data.freeze(),
+        }
+    }
+
+    fn i32_at(&self, at: usize) -> i32 {
+        i32::from_be_bytes(self.data[at..at + 4].try_into().unwrap())
+    }
+
+    fn i64_at(&self, at: usize) -> i64 {
+        i64::from_be_bytes(self.data[at..at + 8].try_into().unwrap())
+    }
+}
+
+#[derive(Debug)]
+pub enum LogError {
+    OffsetOutOfRange {
+        offset: i64,
+        earliest: i64,
+        latest: i64,
+    },
+    CorruptBatch(String),
+    Io(io::Error),
+}
+
+impl LogError {
+    pub fn error_code(&self) -> ErrorCode {
+        match self {
+            Self::OffsetOutOfRange { .. } => ErrorCode::OffsetOutOfRange,
+            Self::CorruptBatch(_) => ErrorCode::CorruptMessage,
+            Self::Io(_) => ErrorCode::KafkaStorageError,
+        }
+    }
+}
+
+impl fmt::Display for LogError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::OffsetOutOfRange {
+                offset,
+                earliest,
+                latest,
+            } => write!(
+                f,
+                "offset {} is outside of the log's range [{}, {}]",
+                offset, earliest, latest
+            ),
+            Self::CorruptBatch(reason) => write!(f, "corrupt record batch: {}", reason),
+            Self::Io(err) => write!(f, "log I/O error: {}", err),
+        }
+    }
+}
+
+impl From<io::Error> for LogError {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+impl std::error::Error for LogError {}
+
+/// The log of a single partition.
+pub trait PartitionLog: Send + Sync {
+    /// Appends `batch`, assigning its records the next offsets in the log, and
+    /// returns the offset assigned to its first record.
+    fn append(&self, batch: RecordBatch) -> Result<i64, LogError>;
+
+    /// Reads the batches from the one containing `from_offset` onwards, up to
+    /// `max_bytes`. The first batch is returned even if it alone is larger
+    /// than `max_bytes`, so that readers always make progress. Reading at
+    /// [`latest_offset`](Self::latest_offset) returns no batches.
+    fn read(&self, from_offset: i64, max_bytes: usize) -> Result<Vec<RecordBatch>, LogError>;
+
+    /// The offset of the first record in the log.
+    fn earliest_offset(&self) -> i64;
+
+    /// The offset the next appended record will be assigned.
+    fn latest_offset(&self) -> i64;
+
+    /// The offset every transaction before has ended by: the start of the
+    /// oldest open transaction, or [`latest_offset`](Self::latest_offset)
+    /// if none is open. Consumers reading committed records only are never
+    /// shown records from it onwards.
+    fn last_stable_offset(&self) -> i64;
+
+    /// The aborted transactions with records between `from_offset` and
+    /// `to_offset`, exclusive, oldest first.
+    fn aborted_transactions(&self, from_offset: i64, to_offset: i64) -> Vec<AbortedTransaction>;
+
+    /// Deletes the records before `offset`, moving the start of the log up
+    /// to it. Segments left without any records are removed, but the active
+    /// segment is always kept. Offsets already before the start of the log
+    /// are a no-op, while those past its end are out of range. Returns the
+    /// new start of the log.
+    fn delete_records_before(&self, offset: i64) -> Result<i64, LogError>;
+
+    /// The leader epoch the latest batch was appended under, or
+    /// [`LEADER_EPOCH`] if none was appended yet.
+    fn leader_epoch(&self) -> i32;
+
+    /// Finds the largest leader epoch no larger than `leader_epoch` that
+    /// batches were appended under, and the offset it ends at, exclusive.
+    /// Returns `None` if no batch was appended under `leader_epoch` or an
+    /// older epoch.
+    fn end_offset_for_epoch(&self, leader_epoch: i32) -> Option<(i32, i64)>;
+
+    /// Deletes the oldest segments while their newest record is more than
+    /// `retention_ms` older than `now_ms`, or while the log would still be at
+    /// least `retention_bytes` large without them. A negative limit is
+    /// unlimited. The active segment is never deleted. Returns the number of
+    /// segments deleted.
+    fn enforce_retention(
+        &self,
+        now_ms: i64,
+        retention_ms: i64,
+        retention_bytes: i64,
+    ) -> Result<usize, LogError>;
+}
+
+/// Counts how many of the oldest `segments`, given as their size and newest
+/// timestamp in log order and excluding the active segment, fall outside the
+/// retention limits. `total_size` is the size of the whole log.
+fn expired_segments(
+    segments: impl IntoIterator<Item = (u64, i64)>,
+    total_size: u64,
+    now_ms: i64,
+    retention_ms: i64,
+    retention_bytes: i64,
+) -> usize {
+    let mut size = total_size;
+    let mut expired = 0;
+
+    for (segment_size, max_timestamp) in segments {
+        let expired_by_time = retention_ms >= 0 && max_timestamp < now_ms - retention_ms;
+        let expired_by_size = retention_bytes >= 0 && size - segment_size >= retention_bytes as u64;
+        if !expired_by_time && !expired_by_size {
+            break;
+        }
+
+        size -= segment_size;
+        expired += 1;
+    }
+
+    expired
+}
+
+/// A partition log kept entirely in memory.
+pub struct MemoryLog {
+    inner: RwLock<MemoryLogInner>,
+}
+
+struct MemoryLogInner {
+    batches: Vec<Bytes>,
+    /// The base offset of each batch in `batches`, in the same order.
+    index: Vec<i64>,
+    earliest_offset: i64,
+    latest_offset: i64,
+    epochs: EpochCache,
+    producers: ProducerStates,
+}
+
+impl MemoryLog {
+    pub fn new() -> Self {
+        Self {
+            inner: RwLock::new(MemoryLogInner {
+                batches: Vec::new(),
+                index: Vec::new(),
+                earliest_offset: 0,
+                latest_offset: 0,
+                epochs: EpochCache::new(),
+                producers: ProducerStates::new(),
+            }),
+        }
+    }
+}
+
+impl Default for MemoryLog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PartitionLog for MemoryLog {
+    fn append(&self, batch: RecordBatch) -> Result<i64, LogError> {
+        let mut inner = self.inner.write().unwrap();
+
+        let base_offset = inner.latest_offset;
+        let batch = batch.with_base_offset(base_offset);
+
+        inner
+            .epochs
+            .assign(batch.partition_leader_epoch(), base_offset);
+        inner.producers.track(&batch);
+        inner.latest_offset = batch.last_offset() + 1;
+        inner.index.push(base_offset);
+        inner.batches.push(batch.data);
+
+        Ok(base_offset)
+    }
+
+    fn read(&self, from_offset: i64, max_bytes: usize) -> Result<Vec<RecordBatch>, LogError> {
+        let inner = self.inner.read().unwrap();
+
+        if from_offset < inner.earliest_offset || from_offset > inner.latest_offset {
+            return Err(LogError::OffsetOutOfRange {
+                offset: from_offset,
+                earliest: inner.earliest_offset,
+                latest: inner.latest_offset,
+            });
+        }
+
+        // The batch containing `from_offset` is the last one starting at or
+        // before it.
+        let start = inner
+            .index
+            .partition_point(|&base_offset| base_offset <= from_offset)
+            .saturating_sub(1);
+
+        let mut batches = Vec::new();
+        let mut bytes = 0;
+        for data in &inner.batches[start..] {
+            let batch = RecordBatch { data: data.clone() };
+            if batch.last_offset() < from_offset {
+                continue;
+            }
+
+            if !batches.is_empty() && bytes + batch.len() > max_bytes {
+                break;
+            }
+
+            bytes += batch.len();
+            batches.push(batch);
+        }
+
+        Ok(batches)
+    }
+
+    fn earliest_offset(&self) -> i64 {
+        self.inner.read().unwrap().earliest_offset
+    }
+
+    fn latest_offset(&self) -> i64 {
+        self.inner.read().unwrap().latest_offset
+    }
+
+    fn last_stable_offset(&self) -> i64 {
+        let inner = self.inner.read().unwrap();
+        inner
+            .producers
+            .first_unstable_offset()
+            .unwrap_or(inner.latest_offset)
+    }
+
+    fn aborted_transactions(&self, from_offset: i64, to_offset: i64) -> Vec<AbortedTransaction> {
+        let inner = self.inner.read().unwrap();
+        inner.producers.aborted_transactions(from_offset, to_offset)
+    }
+
+    /// Each batch counts as a segment of its own, with the newest batch as
+    /// the active segment.
+    fn delete_records_before(&self, offset: i64) -> Result<i64, LogError> {
+        let mut inner = self.inner.write().unwrap();
+
+        if offset > inner.latest_offset {
+            return Err(LogError::OffsetOutOfRange {
+                offset,
+                earliest: inner.earliest_offset,
+                latest: inner.latest_offset,
+            });
+        }
+
+        if offset <= inner.earliest_offset {
+            return Ok(inner.earliest_offset);
+        }
+
+        // A batch has no records left once the next one starts at or before
+        // `offset`.
+        let deleted = inner
+            .index
+            .partition_point(|&base_offset| base_offset <= offset)
+            .saturating_sub(1);
+        inner.batches.drain(..deleted);
+        inner.index.drain(..deleted);
+        inner.earliest_offset = offset;
+
+        Ok(offset)
+    }
+
+    fn leader_epoch(&self) -> i32 {
+        let inner = self.inner.read().unwrap();
+        inner.epochs.latest_epoch().unwrap_or(LEADER_EPOCH)
+    }
+
+    fn end_offset_for_epoch(&self, leader_epoch: i32) -> Option<(i32, i64)> {
+        let inner = self.inner.read().unwrap();
+        inner
+            .epochs
+            .end_offset_for_epoch(leader_epoch, inner.latest_offset)
+    }
+
+    /// Each batch counts as a segment of its own, with the newest batch as
+    /// the active segment.
+    fn enforce_retention(
+        &self,
+        now_ms: i64,
+        retention_ms: i64,
+        retention_bytes: i64,
+    ) -> Result<usize, LogError> {
+        let mut inner = self.inner.write().unwrap();
+
+        let Some((_, inactive)) = inner.batches.split_last() else {
+            return Ok(0);
+        };
+
+        let total_size = inner.batches.iter().map(|data| data.len() as u64).sum();
+        let expired = expired_segments(
+            inactive.iter().map(|data| {
+                let batch = RecordBatch { data: data.clone() };
+                (batch.len() as u64, batch.max_timestamp())
+            }),
+            total_size,
+            now_ms,
+            retention_ms,
+            retention_bytes,
+        );
+
+        inner.batches.drain(..expired);
+        inner.index.drain(..expired);
+        inner.earliest_offset = inner.earliest_offset.max(inner.index[0]);
+
+        Ok(expired)
+    }
+}
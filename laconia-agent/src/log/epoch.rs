@@ -0,0 +1,155 @@
+use std::{
+    fs,
+    io::{self, Write},
+    path::Path,
+};
+
+use crate::log::LogError;
+
+/// The version written at the top of a leader epoch checkpoint file.
+const CHECKPOINT_VERSION: i32 = 0;
+
+/// The leader epochs of a partition log, each with the offset of the first
+/// record appended under it.
+///
+/// An epoch ends where the next one starts, and the latest epoch ends at the
+/// end of the log. Followers and consumers look these up to find out whether
+/// their copy of the log diverged from the leader's.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EpochCache {
+    /// `(leader_epoch, start_offset)` pairs, in increasing order of both.
+    entries: Vec<(i32, i64)>,
+}
+
+impl EpochCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records that `leader_epoch` starts at `start_offset`. Epochs no newer
+    /// than the latest one are already known, and are ignored. Returns whether
+    /// the cache changed.
+    pub fn assign(&mut self, leader_epoch: i32, start_offset: i64) -> bool {
+        if leader_epoch < 0
+            || self
+                .latest_epoch()
+                .is_some_and(|latest| leader_epoch <= latest)
+        {
+            return false;
+        }
+
+        self.entries.push((leader_epoch, start_offset));
+        true
+    }
+
+    /// The newest epoch, if any.
+    pub fn latest_epoch(&self) -> Option<i32> {
+        self.entries.last().map(|&(leader_epoch, _)| leader_epoch)
+    }
+
+    /// Finds the largest epoch no larger than `leader_epoch`, and the offset
+    /// it ends at, exclusive, given that the log ends at `log_end_offset`.
+    /// Returns `None` if `leader_epoch` is older than every known epoch.
+    pub fn end_offset_for_epoch(
+        &self,
+        leader_epoch: i32,
+        log_end_offset: i64,
+    ) -> Option<(i32, i64)> {
+        let next = self
+            .entries
+            .partition_point(|&(epoch, _)| epoch <= leader_epoch);
+        let (epoch, _) = *self.entries.get(next.checked_sub(1)?)?;
+        let end_offset = self
+            .entries
+            .get(next)
+            .map_or(log_end_offset, |&(_, start_offset)| start_offset);
+
+        Some((epoch, end_offset))
+    }
+
+    /// Forgets the epochs starting at or after `end_offset`, which a log
+    /// recovered from a crash may no longer reach.
+    pub fn truncate_from_end(&mut self, end_offset: i64) {
+        self.entries
+            .retain(|&(_, start_offset)| start_offset < end_offset);
+    }
+
+    /// Loads the checkpoint at `path`, or an empty cache if there's none.
+    ///
+    /// The checkpoint is text: a version line, a line with the number of
+    /// entries, then one `epoch start_offset` line per entry.
+    pub fn load(path: &Path) -> Result<Self, LogError> {
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Self::new()),
+            Err(err) => return Err(err.into()),
+        };
+
+        let corrupt = |reason: &str| {
+            LogError::CorruptBatch(format!(
+                "leader epoch checkpoint {}: {}",
+                path.display(),
+                reason
+            ))
+        };
+
+        let mut lines = text.lines();
+        let version: i32 = lines
+            .next()
+            .and_then(|line| line.trim().parse().ok())
+            .ok_or_else(|| corrupt("missing version"))?;
+        if version != CHECKPOINT_VERSION {
+            return Err(corrupt(&format!("unknown version {}", version)));
+        }
+
+        let count: usize = lines
+            .next()
+            .and_then(|line| line.trim().parse().ok())
+            .ok_or_else(|| corrupt("missing entry count"))?;
+
+        let mut cache = Self::new();
+        for line in lines.by_ref().take(count) {
+            let mut fields = line.split_whitespace();
+            let (Some(leader_epoch), Some(start_offset), None) =
+                (fields.next(), fields.next(), fields.next())
+            else {
+                return Err(corrupt(&format!("malformed entry {:?}", line)));
+            };
+
+            let leader_epoch = leader_epoch
+                .parse()
+                .map_err(|_| corrupt(&format!("malformed entry {:?}", line)))?;
+            let start_offset = start_offset
+                .parse()
+                .map_err(|_| corrupt(&format!("malformed entry {:?}", line)))?;
+            cache.assign(leader_epoch, start_offset);
+        }
+
+        if cache.entries.len() != count {
+            return Err(corrupt(&format!(
+                "expected {} entries, found {}",
+                count,
+                cache.entries.len()
+            )));
+        }
+
+        Ok(cache)
+    }
+
+    /// Writes the cache to `path`, replacing any previous checkpoint only
+    /// once the new one is complete.
+    pub fn write(&self, path: &Path) -> Result<(), LogError> {
+        let tmp = path.with_extension("tmp");
+
+        let mut file = fs::File::create(&tmp)?;
+        writeln!(file, "{}", CHECKPOINT_VERSION)?;
+        writeln!(file, "{}", self.entries.len())?;
+        for (leader_epoch, start_offset) in &self.entries {
+            writeln!(file, "{} {}", leader_epoch, start_offset)?;
+        }
+        file.sync_all()?;
+
+        fs::rename(&tmp, path)?;
+        Ok(())
+    }
+}
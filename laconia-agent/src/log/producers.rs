@@ -0,0 +1,97 @@
+use std::collections::BTreeMap;
+
+use crate::log::RecordBatch;
+
+/// What a partition log knows about a producer from the batches it appended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProducerState {
+    /// The epoch of the producer's latest batch.
+    pub producer_epoch: i16,
+    /// The offset of the first record of the producer's open transaction, or
+    /// `None` if it has none open.
+    pub txn_start_offset: Option<i64>,
+}
+
+/// A transaction that was aborted, which read_committed consumers skip the
+/// records of.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AbortedTransaction {
+    pub producer_id: i64,
+    /// The offset of the transaction's first record.
+    pub first_offset: i64,
+    /// The offset of the marker that aborted the transaction.
+    pub last_offset: i64,
+}
+
+/// The producers that appended to a partition log, keyed by producer id.
+///
+/// A transaction opens with the first transactional batch a producer appends
+/// after its previous one ended, and ends with the control batch the
+/// transaction coordinator appends as a commit or abort marker. The state is
+/// only kept in memory, since the coordinator forgets its transactions on
+/// restart too.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProducerStates {
+    producers: BTreeMap<i64, ProducerState>,
+    /// The transactions aborted so far, in the order they were.
+    aborted: Vec<AbortedTransaction>,
+}
+
+impl ProducerStates {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Updates the state of the producer that appended `batch`. Batches
+    /// without a producer id are ignored.
+    pub fn track(&mut self, batch: &RecordBatch) {
+        if batch.producer_id() < 0 {
+            return;
+        }
+
+        let producer = self
+            .producers
+            .entry(batch.producer_id())
+            .or_insert(ProducerState {
+                producer_epoch: batch.producer_epoch(),
+                txn_start_offset: None,
+            });
+        producer.producer_epoch = batch.producer_epoch();
+
+        if batch.is_control() {
+            if let Some(first_offset) = producer.txn_start_offset.take()
+                && batch.is_abort_marker()
+            {
+                self.aborted.push(AbortedTransaction {
+                    producer_id: batch.producer_id(),
+                    first_offset,
+                    last_offset: batch.base_offset(),
+                });
+            }
+        } else if batch.is_transactional() {
+            producer.txn_start_offset.get_or_insert(batch.base_offset());
+        }
+    }
+
+    /// The first offset of the oldest open transaction, if any is open.
+    pub fn first_unstable_offset(&self) -> Option<i64> {
+        self.producers
+            .values()
+            .filter_map(|producer| producer.txn_start_offset)
+            .min()
+    }
+
+    /// The aborted transactions with records between `from_offset` and
+    /// `to_offset`, exclusive, oldest first.
+    pub fn aborted_transactions(
+        &self,
+        from_offset: i64,
+        to_offset: i64,
+    ) -> Vec<AbortedTransaction> {
+        self.aborted
+            .iter()
+            .filter(|txn| txn.last_offset >= from_offset && txn.first_offset < to_offset)
+            .copied()
+            .collect()
+    }
+}
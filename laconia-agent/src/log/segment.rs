@@ -0,0 +1,511 @@
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use bytes::Bytes;
+
+use crate::log::{
+    AbortedTransaction, BATCH_HEADER_SIZE, EpochCache, LEADER_EPOCH, LogError, PartitionLog,
+    ProducerStates, RecordBatch, expired_segments,
+};
+
+/// How many bytes of batches are written between entries in a segment's
+/// offset index.
+const INDEX_INTERVAL_BYTES: u64 = 4096;
+
+/// The size of an offset index entry: a 4 byte offset relative to the
+/// segment's base offset, followed by a 4 byte position in the `.log` file.
+const INDEX_ENTRY_SIZE: usize = 8;
+
+/// The size of a batch's base offset and length fields, which together tell
+/// how many bytes the rest of the batch takes up.
+const BATCH_LENGTH_PREFIX: usize = 12;
+
+/// The file the log's leader epochs are checkpointed to.
+const EPOCH_CHECKPOINT_FILE: &str = "leader-epoch-checkpoint";
+
+/// The file the start of the log is checkpointed to, once records have been
+/// deleted from the middle of its first segment.
+const LOG_START_CHECKPOINT_FILE: &str = "log-start-offset-checkpoint";
+
+/// A partition log persisted to disk as a sequence of segments.
+///
+/// Each segment is a `.log` file of record batches, named after the offset of
+/// its first record, alongside a sparse `.index` file mapping offsets to
+/// positions in the `.log` file. Only the last segment is appended to, and a
+/// new one is rolled once it would grow beyond `segment_bytes`. The leader
+/// epochs are checkpointed to a `leader-epoch-checkpoint` file whenever a new
+/// one starts, and the start of the log to a `log-start-offset-checkpoint`
+/// file whenever records are deleted.
+pub struct SegmentLog {
+    dir: PathBuf,
+    segment_bytes: u64,
+    segments: Mutex<Vec<Segment>>,
+    /// The offset of the first record, which is in the first segment but not
+    /// necessarily at its start. Only locked while `segments` is.
+    log_start_offset: Mutex<i64>,
+    /// Only locked while `segments` is.
+    epochs: Mutex<EpochCache>,
+    /// The producers that appended since the log was opened. Only locked
+    /// while `segments` is.
+    producers: Mutex<ProducerStates>,
+}
+
+impl SegmentLog {
+    /// Opens the log in `dir`, creating it if it doesn't exist. Existing
+    /// segments are recovered, truncating any partially written batch left
+    /// behind by a crash.
+    pub fn open(dir: impl Into<PathBuf>, segment_bytes: u64) -> Result<Self, LogError> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+
+        let mut base_offsets = Vec::new();
+        for entry in fs::read_dir(&dir)? {
+            let path = entry?.path();
+            if path.extension().is_some_and(|extension| extension == "log")
+                && let Some(base_offset) = path
+                    .file_stem()
+                    .and_then(|stem| stem.to_str())
+                    .and_then(|stem| stem.parse::<i64>().ok())
+            {
+                base_offsets.push(base_offset);
+            }
+        }
+
+        base_offsets.sort_unstable();
+        if base_offsets.is_empty() {
+            base_offsets.push(0);
+        }
+
+        let segments = base_offsets
+            .into_iter()
+            .map(|base_offset| Segment::open(&dir, base_offset))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let latest = segments
+            .last()
+            .expect("log has an active segment")
+            .next_offset;
+
+        let log_start_offset = match fs::read_to_string(dir.join(LOG_START_CHECKPOINT_FILE)) {
+            Ok(text) => text.trim().parse::<i64>().map_err(|_| {
+                LogError::CorruptBatch(format!("malformed log start offset checkpoint {:?}", text))
+            })?,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => 0,
+            Err(err) => return Err(err.into()),
+        };
+        let log_start_offset = log_start_offset.clamp(segments[0].base_offset, latest);
+
+        let mut epochs = EpochCache::load(&dir.join(EPOCH_CHECKPOINT_FILE))?;
+        epochs.truncate_from_end(latest);
+
+        Ok(Self {
+            dir,
+            segment_bytes,
+            segments: Mutex::new(segments),
+            log_start_offset: Mutex::new(log_start_offset),
+            epochs: Mutex::new(epochs),
+            producers: Mutex::new(ProducerStates::new()),
+        })
+    }
+}
+
+impl PartitionLog for SegmentLog {
+    fn append(&self, batch: RecordBatch) -> Result<i64, LogError> {
+        let mut segments = self.segments.lock().unwrap();
+
+        let active = segments.last().expect("log has an active segment");
+        if active.size > 0 && active.size + batch.len() as u64 > self.segment_bytes {
+            let segment = Segment::open(&self.dir, active.next_offset)?;
+            segments.push(segment);
+        }
+
+        let active = segments.last_mut().expect("log has an active segment");
+        let base_offset = active.next_offset;
+        let batch = batch.with_base_offset(base_offset);
+        active.append(&batch)?;
+
+        let mut epochs = self.epochs.lock().unwrap();
+        if epochs.assign(batch.partition_leader_epoch(), base_offset) {
+            epochs.write(&self.dir.join(EPOCH_CHECKPOINT_FILE))?;
+        }
+
+        self.producers.lock().unwrap().track(&batch);
+
+        Ok(base_offset)
+    }
+
+    fn read(&self, from_offset: i64, max_bytes: usize) -> Result<Vec<RecordBatch>, LogError> {
+        let mut segments = self.segments.lock().unwrap();
+
+        let earliest = *self.log_start_offset.lock().unwrap();
+        let latest = segments
+            .last()
+            .expect("log has an active segment")
+            .next_offset;
+        if from_offset < earliest || from_offset > latest {
+            return Err(LogError::OffsetOutOfRange {
+                offset: from_offset,
+                earliest,
+                latest,
+            });
+        }
+
+        let start = segments
+            .partition_point(|segment| segment.base_offset <= from_offset)
+            .saturating_sub(1);
+
+        let mut batches = Vec::new();
+        let mut bytes = 0;
+        'segments: for segment in &mut segments[start..] {
+            let mut position = segment.position_of(from_offset);
+            while let Some(batch) = segment.read_batch_at(position)? {
+                position += batch.len() as u64;
+                if batch.last_offset() < from_offset {
+                    continue;
+                }
+
+                if !batches.is_empty() && bytes + batch.len() > max_bytes {
+                    break 'segments;
+                }
+
+                bytes += batch.len();
+                batches.push(batch);
+            }
+        }
+
+        Ok(batches)
+    }
+
+    fn earliest_offset(&self) -> i64 {
+        let _segments = self.segments.lock().unwrap();
+        *self.log_start_offset.lock().unwrap()
+    }
+
+    fn latest_offset(&self) -> i64 {
+        let segments = self.segments.lock().unwrap();
+        segments
+            .last()
+            .expect("log has an active segment")
+            .next_offset
+    }
+
+    fn last_stable_offset(&self) -> i64 {
+        let segments = self.segments.lock().unwrap();
+        let latest = segments
+            .last()
+            .expect("log has an active segment")
+            .next_offset;
+        self.producers
+            .lock()
+            .unwrap()
+            .first_unstable_offset()
+            .unwrap_or(latest)
+    }
+
+    fn aborted_transactions(&self, from_offset: i64, to_offset: i64) -> Vec<AbortedTransaction> {
+        let _segments = self.segments.lock().unwrap();
+        self.producers
+            .lock()
+            .unwrap()
+            .aborted_transactions(from_offset, to_offset)
+    }
+
+    fn leader_epoch(&self) -> i32 {
+        let _segments = self.segments.lock().unwrap();
+        self.epochs
+            .lock()
+            .unwrap()
+            .latest_epoch()
+            .unwrap_or(LEADER_EPOCH)
+    }
+
+    fn end_offset_for_epoch(&self, leader_epoch: i32) -> Option<(i32, i64)> {
+        let segments = self.segments.lock().unwrap();
+        let latest = segments
+            .last()
+            .expect("log has an active segment")
+            .next_offset;
+        self.epochs
+            .lock()
+            .unwrap()
+            .end_offset_for_epoch(leader_epoch, latest)
+    }
+
+    fn enforce_retention(
+        &self,
+        now_ms: i64,
+        retention_ms: i64,
+        retention_bytes: i64,
+    ) -> Result<usize, LogError> {
+        let mut segments = self.segments.lock().unwrap();
+
+        let (_, inactive) = segments.split_last().expect("log has an active segment");
+        let total_size = segments.iter().map(|segment| segment.size).sum();
+        let expired = expired_segments(
+            inactive
+                .iter()
+                .map(|segment| (segment.size, segment.max_timestamp)),
+            total_size,
+            now_ms,
+            retention_ms,
+            retention_bytes,
+        );
+
+        for segment in segments.drain(..expired) {
+            segment.delete(&self.dir)?;
+        }
+
+        let mut log_start_offset = self.log_start_offset.lock().unwrap();
+        *log_start_offset = (*log_start_offset).max(segments[0].base_offset);
+
+        Ok(expired)
+    }
+
+    fn delete_records_before(&self, offset: i64) -> Result<i64, LogError> {
+        let mut segments = self.segments.lock().unwrap();
+        let mut log_start_offset = self.log_start_offset.lock().unwrap();
+
+        let latest = segments
+            .last()
+            .expect("log has an active segment")
+            .next_offset;
+        if offset > latest {
+            return Err(LogError::OffsetOutOfRange {
+                offset,
+                earliest: *log_start_offset,
+                latest,
+            });
+        }
+
+        if offset <= *log_start_offset {
+            return Ok(*log_start_offset);
+        }
+
+        // The start of the log is checkpointed before any segment goes, so
+        // that a crash part way through can't bring deleted records back.
+        write_log_start_offset(&self.dir, offset)?;
+        *log_start_offset = offset;
+
+        // A segment has no records left once the next one starts at or
+        // before `offset`.
+        let deleted = segments
+            .partition_point(|segment| segment.base_offset <= offset)
+            .saturating_sub(1);
+        for segment in segments.drain(..deleted) {
+            segment.delete(&self.dir)?;
+        }
+
+        Ok(offset)
+    }
+}
+
+/// Checkpoints `log_start_offset` to `dir`, replacing any previous
+/// checkpoint only once the new one is complete.
+fn write_log_start_offset(dir: &Path, log_start_offset: i64) -> Result<(), LogError> {
+    let path = dir.join(LOG_START_CHECKPOINT_FILE);
+    let tmp = path.with_extension("tmp");
+
+    let mut file = File::create(&tmp)?;
+    writeln!(file, "{}", log_start_offset)?;
+    file.sync_all()?;
+
+    fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+/// The path of the segment file starting at `base_offset` with `extension`.
+fn segment_path(dir: &Path, base_offset: i64, extension: &str) -> PathBuf {
+    dir.join(format!("{:020}.{}", base_offset, extension))
+}
+
+struct Segment {
+    base_offset: i64,
+    log: File,
+    index: File,
+    size: u64,
+    /// The base offset and position of every indexed batch, in order.
+    entries: Vec<(i64, u64)>,
+    /// Bytes of batches written since the last index entry.
+    unindexed_bytes: u64,
+    /// The offset the next batch appended to this segment will start at.
+    next_offset: i64,
+    /// The newest timestamp of any record in the segment, or -1 if it's
+    /// empty.
+    max_timestamp: i64,
+}
+
+impl Segment {
+    /// Opens the segment starting at `base_offset`, creating it if it doesn't
+    /// exist.
+    fn open(dir: &Path, base_offset: i64) -> Result<Self, LogError> {
+        let options = {
+            let mut options = OpenOptions::new();
+            options.read(true).append(true).create(true);
+            options
+        };
+
+        let log = options.open(segment_path(dir, base_offset, "log"))?;
+        let mut index = options.open(segment_path(dir, base_offset, "index"))?;
+
+        let size = log.metadata()?.len();
+
+        let mut data = Vec::new();
+        index.read_to_end(&mut data)?;
+        let mut entries: Vec<(i64, u64)> = data
+            .chunks_exact(INDEX_ENTRY_SIZE)
+            .map(|entry| {
+                let relative_offset = u32::from_be_bytes(entry[..4].try_into().unwrap());
+                let position = u32::from_be_bytes(entry[4..].try_into().unwrap());
+                (base_offset + relative_offset as i64, position as u64)
+            })
+            .take_while(|&(_, position)| position < size)
+            .collect();
+
+        // Everything after the last indexed batch has to be scanned to find
+        // where the segment ends. That batch is indexed again as the scan
+        // starts.
+        let start = entries.pop().map_or(0, |(_, position)| position);
+        index.set_len((entries.len() * INDEX_ENTRY_SIZE) as u64)?;
+
+        let mut segment = Self {
+            base_offset,
+            log,
+            index,
+            size,
+            entries,
+            unindexed_bytes: INDEX_INTERVAL_BYTES,
+            next_offset: base_offset,
+            max_timestamp: -1,
+        };
+        segment.scan_timestamps(start)?;
+        segment.recover(start)?;
+
+        Ok(segment)
+    }
+
+    /// Scans the batches from `position` to the end of the segment, truncating
+    /// the segment after the last complete batch.
+    fn recover(&mut self, mut position: u64) -> Result<(), LogError> {
+        loop {
+            let batch = match self.read_batch_at(position) {
+                Ok(Some(batch)) => batch,
+                Ok(None) | Err(LogError::CorruptBatch(_)) => break,
+                Err(err) => return Err(err),
+            };
+
+            self.track(&batch, position)?;
+            position += batch.len() as u64;
+        }
+
+        if position < self.size {
+            tracing::warn!(
+                base_offset = self.base_offset,
+                "Truncating {} trailing bytes from log segment",
+                self.size - position
+            );
+            self.log.set_len(position)?;
+            self.size = position;
+        }
+
+        Ok(())
+    }
+
+    /// Finds the newest timestamp among the batches before `end`, reading
+    /// only their headers.
+    fn scan_timestamps(&mut self, end: u64) -> Result<(), LogError> {
+        let mut position = 0;
+        let mut header = [0; BATCH_HEADER_SIZE];
+
+        while position < end {
+            self.log.seek(SeekFrom::Start(position))?;
+            self.log.read_exact(&mut header)?;
+
+            let length = i32::from_be_bytes(header[8..12].try_into().unwrap());
+            let max_timestamp = i64::from_be_bytes(header[35..43].try_into().unwrap());
+            self.max_timestamp = self.max_timestamp.max(max_timestamp);
+
+            position += BATCH_LENGTH_PREFIX as u64 + length.max(0) as u64;
+        }
+
+        Ok(())
+    }
+
+    fn delete(self, dir: &Path) -> Result<(), LogError> {
+        drop(self.log);
+        drop(self.index);
+        fs::remove_file(segment_path(dir, self.base_offset, "log"))?;
+        fs::remove_file(segment_path(dir, self.base_offset, "index"))?;
+        Ok(())
+    }
+
+    fn append(&mut self, batch: &RecordBatch) -> Result<(), LogError> {
+        if let Err(err) = self.log.write_all(batch.as_bytes()) {
+            // Don't leave a partial batch behind for the next append to
+            // follow.
+            self.log.set_len(self.size)?;
+            return Err(err.into());
+        }
+
+        let position = self.size;
+        self.size += batch.len() as u64;
+        self.track(batch, position)
+    }
+
+    /// Records that `batch` is stored at `position`, indexing it if enough
+    /// bytes have been written since the last index entry.
+    fn track(&mut self, batch: &RecordBatch, position: u64) -> Result<(), LogError> {
+        if self.unindexed_bytes >= INDEX_INTERVAL_BYTES {
+            let relative_offset = (batch.base_offset() - self.base_offset) as u32;
+            let mut entry = [0; INDEX_ENTRY_SIZE];
+            entry[..4].copy_from_slice(&relative_offset.to_be_bytes());
+            entry[4..].copy_from_slice(&(position as u32).to_be_bytes());
+            self.index.write_all(&entry)?;
+
+            self.entries.push((batch.base_offset(), position));
+            self.unindexed_bytes = 0;
+        }
+
+        self.unindexed_bytes += batch.len() as u64;
+        self.next_offset = batch.last_offset() + 1;
+        self.max_timestamp = self.max_timestamp.max(batch.max_timestamp());
+        Ok(())
+    }
+
+    /// The position of the last indexed batch starting at or before `offset`.
+    fn position_of(&self, offset: i64) -> u64 {
+        match self
+            .entries
+            .partition_point(|&(base_offset, _)| base_offset <= offset)
+        {
+            0 => 0,
+            i => self.entries[i - 1].1,
+        }
+    }
+
+    /// Reads the batch at `position`, or `None` if the segment ends before a
+    /// complete batch does.
+    fn read_batch_at(&mut self, position: u64) -> Result<Option<RecordBatch>, LogError> {
+        if position + BATCH_LENGTH_PREFIX as u64 > self.size {
+            return Ok(None);
+        }
+
+        let mut prefix = [0; BATCH_LENGTH_PREFIX];
+        self.log.seek(SeekFrom::Start(position))?;
+        self.log.read_exact(&mut prefix)?;
+
+        let length = i32::from_be_bytes(prefix[8..].try_into().unwrap());
+        if length < 0 || position + (BATCH_LENGTH_PREFIX as u64) + length as u64 > self.size {
+            return Ok(None);
+        }
+
+        let mut data = vec![0; BATCH_LENGTH_PREFIX + length as usize];
+        data[..BATCH_LENGTH_PREFIX].copy_from_slice(&prefix);
+        self.log.read_exact(&mut data[BATCH_LENGTH_PREFIX..])?;
+
+        RecordBatch::new(Bytes::from(data)).map(Some)
+    }
+}
@@ -0,0 +1,266 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    path::PathBuf,
+    sync::{Arc, RwLock},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use tokio::{task::JoinHandle, time};
+use uuid::Uuid;
+
+use crate::{
+    compression::Compression,
+    configs::ConfigStore,
+    log::{LEADER_EPOCH, LogError, MemoryLog, PartitionLog, RecordBatch, SegmentLog},
+};
+
+/// The segment size used when a topic's `segment.bytes` can't be parsed.
+const DEFAULT_SEGMENT_BYTES: u64 = 1 << 30;
+
+/// The partition logs hosted by the agent.
+///
+/// Partitions are kept in memory unless a data directory is configured, in
+/// which case each one is persisted as a [`SegmentLog`] in its own
+/// `<topic>-<partition>` directory.
+///
+/// Each topic is given a random id when its first partition is created or
+/// loaded. Ids aren't persisted, so a restarted agent gives its topics new
+/// ones.
+pub struct LogStore {
+    data_dir: Option<PathBuf>,
+    validate_crc: bool,
+    configs: Arc<ConfigStore>,
+    partitions: RwLock<HashMap<(String, i32), Arc<dyn PartitionLog>>>,
+    topic_ids: RwLock<HashMap<String, Uuid>>,
+}
+
+impl LogStore {
+    /// Opens the store, loading every partition already in `data_dir`.
+    ///
+    /// When `validate_crc` is set, appended batches with a mismatched CRC are
+    /// rejected. Otherwise they're only logged, which is handy when testing
+    /// with hand-crafted batches.
+    pub fn open(
+        data_dir: Option<PathBuf>,
+        validate_crc: bool,
+        configs: Arc<ConfigStore>,
+    ) -> Result<Self, LogError> {
+        let store = Self {
+            data_dir,
+            validate_crc,
+            configs,
+            partitions: RwLock::new(HashMap::new()),
+            topic_ids: RwLock::new(HashMap::new()),
+        };
+
+        let Some(data_dir) = &store.data_dir else {
+            return Ok(store);
+        };
+
+        fs::create_dir_all(data_dir)?;
+
+        let mut partitions = store.partitions.write().unwrap();
+        for entry in fs::read_dir(data_dir)? {
+            let entry = entry?;
+            if !entry.file_type()?.is_dir() {
+                continue;
+            }
+
+            let name = entry.file_name();
+            let Some((topic, partition)) = name.to_str().and_then(|name| name.rsplit_once('-'))
+            else {
+                continue;
+            };
+            let Ok(partition) = partition.parse::<i32>() else {
+                continue;
+            };
+
+            let log = SegmentLog::open(entry.path(), store.segment_bytes(topic))?;
+            partitions.insert((topic.to_string(), partition), Arc::new(log));
+            store.assign_topic_id(topic);
+        }
+        drop(partitions);
+
+        Ok(store)
+    }
+
+    /// The number of partitions in the store.
+    pub fn len(&self) -> usize {
+        self.partitions.read().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Every topic in the store, with its partitions in order.
+    pub fn topics(&self) -> BTreeMap<String, Vec<i32>> {
+        let mut topics: BTreeMap<String, Vec<i32>> = BTreeMap::new();
+        for (topic, partition) in self.partitions.read().unwrap().keys() {
+            topics.entry(topic.clone()).or_default().push(*partition);
+        }
+
+        for partitions in topics.values_mut() {
+            partitions.sort_unstable();
+        }
+
+        topics
+    }
+
+    pub fn partition(&self, topic: &str, partition: i32) -> Option<Arc<dyn PartitionLog>> {
+        let partitions = self.partitions.read().unwrap();
+        partitions.get(&(topic.to_string(), partition)).cloned()
+    }
+
+    /// Returns the log of `partition` in `topic`, creating an empty one if it
+    /// doesn't exist yet.
+    pub fn get_or_create(
+        &self,
+        topic: &str,
+        partition: i32,
+    ) -> Result<Arc<dyn PartitionLog>, LogError> {
+        if let Some(log) = self.partition(topic, partition) {
+            return Ok(log);
+        }
+
+        let mut partitions = self.partitions.write().unwrap();
+        let key = (topic.to_string(), partition);
+        if let Some(log) = partitions.get(&key) {
+            return Ok(log.clone());
+        }
+
+        let log: Arc<dyn PartitionLog> = match &self.data_dir {
+            Some(data_dir) => Arc::new(SegmentLog::open(
+                data_dir.join(format!("{}-{}", topic, partition)),
+                self.segment_bytes(topic),
+            )?),
+            None => Arc::new(MemoryLog::new()),
+        };
+
+        partitions.insert(key, log.clone());
+        self.assign_topic_id(topic);
+        Ok(log)
+    }
+
+    /// The id of `topic`, if it has any partitions.
+    pub fn topic_id(&self, topic: &str) -> Option<Uuid> {
+        self.topic_ids.read().unwrap().get(topic).copied()
+    }
+
+    fn assign_topic_id(&self, topic: &str) {
+        let mut topic_ids = self.topic_ids.write().unwrap();
+        if !topic_ids.contains_key(topic) {
+            topic_ids.insert(topic.to_string(), Uuid::new_v4());
+        }
+    }
+
+    /// Appends `batch` to `partition` in `topic`, creating the partition if
+    /// it doesn't exist yet. The batch is recompressed first if the topic's
+    /// `compression.type` calls for a different codec. Returns the offset
+    /// assigned to the batch's first record.
+    pub fn append(&self, topic: &str, partition: i32, batch: RecordBatch) -> Result<i64, LogError> {
+        if let Err(err) = batch.verify_crc() {
+            if self.validate_crc {
+                return Err(err);
+            }
+
+            tracing::warn!(topic, partition, "Appending batch anyway: {}", err);
+        }
+
+        let compression_type = self
+            .topic_config::<String>(topic, "compression.type")
+            .unwrap_or_default();
+        let batch = match Compression::from_name(&compression_type) {
+            Some(compression) => batch.recompress(compression)?,
+            None => batch,
+        };
+
+        let batch = batch.with_leader_epoch(LEADER_EPOCH);
+        self.get_or_create(topic, partition)?.append(batch)
+    }
+
+    /// Appends the marker ending a producer's transaction to `partition` in
+    /// `topic`. Partitions that don't exist have nothing to mark, and are
+    /// skipped.
+    pub fn write_transaction_marker(
+        &self,
+        topic: &str,
+        partition: i32,
+        producer_id: i64,
+        producer_epoch: i16,
+        committed: bool,
+    ) -> Result<(), LogError> {
+        let Some(log) = self.partition(topic, partition) else {
+            return Ok(());
+        };
+
+        let now_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as i64;
+        let marker =
+            RecordBatch::transaction_marker(producer_id, producer_epoch, committed, now_ms);
+        log.append(marker.with_leader_epoch(LEADER_EPOCH))?;
+        Ok(())
+    }
+
+    /// Deletes expired segments from every partition, according to its
+    /// topic's `retention.ms` and `retention.bytes`.
+    pub fn enforce_retention(&self) {
+        let now_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as i64;
+
+        let partitions: Vec<_> = self
+            .partitions
+            .read()
+            .unwrap()
+            .iter()
+            .map(|(key, log)| (key.clone(), log.clone()))
+            .collect();
+
+        for ((topic, partition), log) in partitions {
+            let retention_ms = self.topic_config(&topic, "retention.ms").unwrap_or(-1);
+            let retention_bytes = self.topic_config(&topic, "retention.bytes").unwrap_or(-1);
+
+            match log.enforce_retention(now_ms, retention_ms, retention_bytes) {
+                Ok(0) => {}
+                Ok(deleted) => tracing::info!(
+                    topic,
+                    partition,
+                    "Deleted {} segments past retention",
+                    deleted
+                ),
+                Err(err) => {
+                    tracing::error!(topic, partition, "Failed to enforce retention: {}", err)
+                }
+            }
+        }
+    }
+
+    /// Spawns a task that enforces retention every `interval`.
+    pub fn spawn_retention(self: Arc<Self>, interval: Duration) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            loop {
+                time::sleep(interval).await;
+                self.enforce_retention();
+            }
+        })
+    }
+
+    fn segment_bytes(&self, topic: &str) -> u64 {
+        self.topic_config(topic, "segment.bytes")
+            .unwrap_or(DEFAULT_SEGMENT_BYTES)
+    }
+
+    /// The value of the topic config `name`, or `None` if it can't be parsed.
+    fn topic_config<T: std::str::FromStr>(&self, topic: &str, name: &str) -> Option<T> {
+        self.configs
+            .topic_configs(topic)
+            .into_iter()
+            .find(|entry| entry.name == name)
+            .and_then(|entry| entry.value.parse().ok())
+    }
+}
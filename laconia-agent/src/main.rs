@@ -1,295 +1,441 @@
-use std::{collections::BTreeMap, io, sync::Arc};
+use std::{
+    collections::BTreeMap,
+    net::{Ipv6Addr, SocketAddr},
+    path::PathBuf,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::Duration,
+};
 
 use anyhow::Result;
-use bytes::{Buf, BufMut, Bytes, BytesMut};
 use figment::{
     Figment,
     providers::{Env, Format, Toml},
 };
-use futures::{SinkExt, StreamExt};
-use laconia_liveness::liveness::{CheckinRequest, liveness_client::LivenessClient};
-use serde::Deserialize;
-use tokio::{
-    net::{TcpListener, ToSocketAddrs},
-    time,
+use laconia_agent::{
+    DEFAULT_MAX_CLIENT_ID_LEN, DEFAULT_MAX_REQUEST_SIZE, VersionRange,
+    authorizer::{AclRule, AclStore},
+    cluster::{BrokerInfo, BrokerRegistry, ClusterInfo},
+    configs::{ConfigEntry, ConfigStore, ConfigType},
+    controlplane,
+    features::Features,
+    fetch_session::{DEFAULT_MAX_FETCH_SESSIONS, FetchSessionCache},
+    health::{self, Health},
+    log::LogStore,
+    protocol::api_keys::ApiKey,
+    quota::QuotaManager,
+    server::{BrokerState, KafkaServer, KafkaServerBuilder},
+    telemetry,
 };
-use tokio_util::codec::Decoder as _;
+use serde::Deserialize;
+use tokio::net::TcpListener;
+use tracing_subscriber::EnvFilter;
 use uuid::Uuid;
 
-use crate::protocol::{
-    Decoder, Encoder, EncoderVersioned,
-    handlers::{ApiVersionsHandler, FindCoordinatorHandler, MetadataHandler},
-    primitives::NullableString,
-    registry::MessageRegistry,
-    response::AnyResponse,
-};
+#[derive(Deserialize)]
+struct Config {
+    controlplane: String,
+    #[serde(default = "Config::default_idle_timeout_ms")]
+    idle_timeout_ms: u64,
+    #[serde(default = "Config::default_max_connections")]
+    max_connections: usize,
+    #[serde(default = "Config::default_max_client_id_len")]
+    max_client_id_len: usize,
+    #[serde(default = "Config::default_max_request_size")]
+    max_request_size: usize,
+    quota_requests_per_sec: Option<f64>,
+    #[serde(default = "Config::default_controlplane_retry_window_ms")]
+    controlplane_retry_window_ms: u64,
+    agent_id: Option<String>,
+    #[serde(default = "Config::default_agent_id_file")]
+    agent_id_file: PathBuf,
+    metrics_addr: Option<SocketAddr>,
+    /// Where to serve the HTTP `/healthz` check. Not served if unset.
+    health_addr: Option<SocketAddr>,
+    #[serde(default = "Config::default_cluster_id")]
+    cluster_id: String,
+    /// Where partition logs are persisted. Logs are kept in memory if unset.
+    data_dir: Option<PathBuf>,
+    #[serde(default = "Config::default_retention_check_interval_ms")]
+    retention_check_interval_ms: u64,
+    /// Whether to reject produced batches with a mismatched CRC.
+    #[serde(default = "Config::default_validate_crc")]
+    validate_crc: bool,
+    #[serde(default = "Config::default_max_fetch_sessions")]
+    max_fetch_sessions: usize,
+    /// Whether Metadata requests may create the unknown topics they ask for.
+    #[serde(default)]
+    auto_create_topics: bool,
+    /// The number of partitions topics are created with when the request
+    /// creating them doesn't say.
+    #[serde(default = "Config::default_num_partitions")]
+    num_partitions: i32,
+    /// The replication factor topics are created with when the request
+    /// creating them doesn't say. Every partition only has a replica on this
+    /// broker, so it's only advertised.
+    #[serde(default = "Config::default_replication_factor")]
+    default_replication_factor: i16,
+    /// Caps the advertised max version of api keys, to work around broken
+    /// clients. Keyed by api key, as config keys are always strings.
+    #[serde(default)]
+    api_version_overrides: BTreeMap<String, i16>,
+    /// The initial ACLs requests are checked against, which the ACL admin
+    /// APIs manage from then on. If unset, everything is allowed and the ACL
+    /// admin APIs are disabled.
+    acls: Option<Vec<AclRule>>,
+    /// Peer brokers advertised to clients alongside this one.
+    #[serde(default)]
+    brokers: Vec<PeerBroker>,
+    /// Whether to reject requests with bytes left over after decoding, to
+    /// surface decoder bugs during development.
+    #[serde(default)]
+    strict_decoding: bool,
+    /// Feature flags advertised to clients in ApiVersions, keyed by name.
+    #[serde(default)]
+    features: BTreeMap<String, FeatureLevels>,
+}
 
-mod protocol;
+/// A broker reported to clients as part of the cluster, without this agent
+/// talking to it.
+#[derive(Deserialize)]
+struct PeerBroker {
+    id: i32,
+    host: String,
+    port: i32,
+    #[serde(default)]
+    rack: String,
+}
 
-pub struct KafkaMessageCodec;
+/// The range of levels a feature flag is supported at.
+#[derive(Deserialize)]
+struct FeatureLevels {
+    min_version: i16,
+    max_version: i16,
+}
 
-impl tokio_util::codec::Decoder for KafkaMessageCodec {
-    type Item = Bytes;
-    type Error = io::Error;
+impl Config {
+    fn from_figment() -> Result<Self> {
+        let figment = Figment::new()
+            .merge(Toml::file("config.toml"))
+            .merge(Env::prefixed("LACONIA"));
 
-    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
-        if src.len() < 4 {
-            return Ok(None);
-        }
+        let config: Self = figment.extract()?;
 
-        let len = i32::from_be_bytes(src[..4].try_into().unwrap()) as usize;
-        if src.len() - 4 < len {
-            return Ok(None);
+        if let Some(rate) = config.quota_requests_per_sec
+            && !(rate.is_finite() && rate > 0.0)
+        {
+            anyhow::bail!(
+                "quota_requests_per_sec must be positive and finite, got {}",
+                rate
+            );
         }
 
-        src.advance(4);
-        Ok(Some(src.split_to(len).freeze()))
+        Ok(config)
     }
-}
-
-impl tokio_util::codec::Encoder<KafkaResponse> for KafkaMessageCodec {
-    type Error = io::Error;
 
-    fn encode(&mut self, item: KafkaResponse, dst: &mut BytesMut) -> Result<(), Self::Error> {
-        let mut buf = BytesMut::new();
-        item.encode(&mut buf)?;
-
-        dst.put_i32(buf.len() as i32);
-        dst.put(buf);
-
-        Ok(())
+    fn default_idle_timeout_ms() -> u64 {
+        600_000
     }
-}
-
-pub struct ConnectionState {
-    pub(crate) registry: Arc<MessageRegistry>,
-}
 
-impl ConnectionState {
-    pub fn new(registry: Arc<MessageRegistry>) -> Self {
-        Self { registry }
+    fn default_max_connections() -> usize {
+        1024
     }
-}
 
-pub struct KafkaRequest {
-    pub header: RequestHeader,
-    pub response: Box<dyn AnyResponse>,
-}
+    fn default_max_client_id_len() -> usize {
+        DEFAULT_MAX_CLIENT_ID_LEN
+    }
 
-impl KafkaRequest {
-    pub async fn decode_and_handle(
-        buf: &mut BytesMut,
-        registry: &MessageRegistry,
-        state: &mut ConnectionState,
-    ) -> Result<Self, io::Error> {
-        let header = RequestHeader::decode(buf, registry)?;
-        let response = registry.handle_request(buf, &header, state).await?;
-        Ok(Self { header, response })
+    fn default_max_request_size() -> usize {
+        DEFAULT_MAX_REQUEST_SIZE
     }
-}
 
-#[derive(Debug)]
-pub struct RequestHeader {
-    pub api_key: i16,
-    pub version: i16,
-    pub correlation_id: i32,
-    pub client_id: String,
-    pub tagged_fields: BTreeMap<i32, Bytes>,
-}
+    fn default_controlplane_retry_window_ms() -> u64 {
+        60_000
+    }
 
-impl RequestHeader {
-    fn decode(buf: &mut BytesMut, registry: &MessageRegistry) -> Result<Self, io::Error> {
-        if buf.len() < 8 {
-            return Err(io::Error::new(
-                io::ErrorKind::InvalidData,
-                "not enough data for v1 kafka header",
+    /// The agent's settings as reported to DescribeConfigs clients.
+    fn config_store(&self) -> ConfigStore {
+        let mut entries = vec![
+            ConfigEntry::static_broker(
+                "connections.max.idle.ms",
+                self.idle_timeout_ms,
+                ConfigType::Long,
+                "Idle connections timeout.",
+            ),
+            ConfigEntry::static_broker(
+                "max.connections",
+                self.max_connections,
+                ConfigType::Int,
+                "The maximum number of connections allowed at any time.",
+            ),
+            ConfigEntry::static_broker(
+                "socket.request.max.bytes",
+                self.max_request_size,
+                ConfigType::Int,
+                "The maximum number of bytes in a socket request.",
+            ),
+            ConfigEntry::static_broker(
+                "log.retention.check.interval.ms",
+                self.retention_check_interval_ms,
+                ConfigType::Long,
+                "The frequency in milliseconds that topic retention.ms and retention.bytes are enforced at.",
+            ),
+            ConfigEntry::static_broker(
+                "max.incremental.fetch.session.cache.slots",
+                self.max_fetch_sessions,
+                ConfigType::Int,
+                "The maximum number of incremental fetch sessions that we will maintain.",
+            ),
+            ConfigEntry::static_broker(
+                "auto.create.topics.enable",
+                self.auto_create_topics,
+                ConfigType::Boolean,
+                "Enable auto creation of topic on the server.",
+            ),
+            ConfigEntry::static_broker(
+                "num.partitions",
+                self.num_partitions,
+                ConfigType::Int,
+                "The default number of log partitions per topic.",
+            ),
+            ConfigEntry::static_broker(
+                "default.replication.factor",
+                self.default_replication_factor,
+                ConfigType::Int,
+                "The default replication factors for automatically created topics.",
+            ),
+        ];
+
+        if let Some(rate) = self.quota_requests_per_sec {
+            entries.push(ConfigEntry::static_broker(
+                "quota.requests.per.sec",
+                rate,
+                ConfigType::Double,
+                "The number of requests per second each client id may make.",
             ));
         }
 
-        let api_key = buf.get_i16();
-        let version = buf.get_i16();
-        let correlation_id = buf.get_i32();
-
-        registry.versions(api_key)?;
-
-        let client_id = NullableString::decode(buf)?.0;
-
-        let header_version = registry.header_version(api_key, version)?;
+        ConfigStore::new(0, entries)
+    }
 
-        let mut tagged_fields = BTreeMap::new();
-        if header_version > 1 {
-            tagged_fields = Decoder::decode(buf)?;
+    /// The feature flags advertised to clients.
+    fn features(&self) -> Result<Features> {
+        let mut features = Features::new();
+        for (name, levels) in &self.features {
+            if levels.min_version > levels.max_version {
+                anyhow::bail!(
+                    "feature {} has min_version {} above max_version {}",
+                    name,
+                    levels.min_version,
+                    levels.max_version
+                );
+            }
+            features = features.with_feature(
+                name,
+                VersionRange::new(levels.min_version, levels.max_version),
+            );
         }
-
-        Ok(Self {
-            api_key,
-            version,
-            correlation_id,
-            client_id,
-            tagged_fields,
-        })
+        Ok(features)
     }
-}
-
-pub struct VersionRange {
-    pub min: i16,
-    pub max: i16,
-}
 
-impl VersionRange {
-    pub fn new(min: i16, max: i16) -> Self {
-        Self { min, max }
+    /// Checks the defaults topics are created with, which Kafka requires to
+    /// be positive.
+    fn validate_topic_defaults(&self) -> Result<()> {
+        if self.num_partitions < 1 {
+            anyhow::bail!(
+                "num_partitions must be positive, got {}",
+                self.num_partitions
+            );
+        }
+        if self.default_replication_factor < 1 {
+            anyhow::bail!(
+                "default_replication_factor must be positive, got {}",
+                self.default_replication_factor
+            );
+        }
+        Ok(())
     }
 
-    pub fn contains(&self, version: i16) -> bool {
-        self.min <= version && version <= self.max
+    fn default_num_partitions() -> i32 {
+        1
     }
-}
-
-pub trait Message: Sized {
-    const VERSIONS: VersionRange;
-    const DEPRECATED_VERSIONS: Option<VersionRange>;
 
-    fn header_version(version: i16) -> i16;
-}
-
-pub struct KafkaResponse {
-    pub header: ResponseHeader,
-    pub response: Box<dyn AnyResponse>,
-}
-
-impl KafkaResponse {
-    pub fn new(header: &RequestHeader, response: Box<dyn AnyResponse>) -> Self {
-        Self {
-            header: ResponseHeader {
-                correlation_id: header.correlation_id,
-            },
-            response,
-        }
+    fn default_replication_factor() -> i16 {
+        1
     }
-}
 
-impl Encoder for KafkaResponse {
-    fn encode(&self, buf: &mut BytesMut) -> Result<(), io::Error> {
-        self.header.encode(buf, 1)?; // TODO(herbstein): determine header version
-        self.response.encode_any(buf, i16::MAX)?; // TODO(herbstein): determine response version
-        Ok(())
+    fn default_retention_check_interval_ms() -> u64 {
+        300_000
     }
-}
 
-pub struct ResponseHeader {
-    pub correlation_id: i32,
-}
+    fn default_validate_crc() -> bool {
+        true
+    }
 
-impl EncoderVersioned for ResponseHeader {
-    fn encode(&self, buf: &mut BytesMut, version: i16) -> Result<(), io::Error> {
-        buf.put_i32(self.correlation_id);
-        Ok(())
+    fn default_max_fetch_sessions() -> usize {
+        DEFAULT_MAX_FETCH_SESSIONS
     }
-}
 
-#[derive(Deserialize)]
-struct Config {
-    controlplane: String,
-}
+    fn default_cluster_id() -> String {
+        "laconia".to_string()
+    }
 
-impl Config {
-    fn from_figment() -> Result<Self> {
-        let figment = Figment::new()
-            .merge(Toml::file("config.toml"))
-            .merge(Env::prefixed("LACONIA"));
+    fn default_agent_id_file() -> PathBuf {
+        PathBuf::from("agent_id")
+    }
 
-        let config = figment.extract()?;
+    /// Returns the configured agent id, falling back to the one persisted in
+    /// `agent_id_file`. A new id is generated and persisted if neither exists.
+    fn agent_id(&self) -> Result<Uuid> {
+        if let Some(id) = &self.agent_id {
+            return Ok(Uuid::parse_str(id)?);
+        }
 
-        Ok(config)
+        controlplane::load_or_create_agent_id(&self.agent_id_file)
     }
 }
 
-struct KafkaServer {
-    registry: Arc<MessageRegistry>,
-    listener: TcpListener,
-}
-
-impl KafkaServer {
-    async fn build(addr: impl ToSocketAddrs) -> Self {
-        let mut registry = MessageRegistry::new();
-        registry.register(3, MetadataHandler);
-        registry.register(10, FindCoordinatorHandler);
-        registry.register(18, ApiVersionsHandler);
-
-        let registry = Arc::new(registry);
-
-        let listener = TcpListener::bind(addr).await.unwrap();
-
-        Self { registry, listener }
+/// Builds the agent's Kafka server listening on `addr`, with every handler
+/// the agent supports.
+async fn build_server(addr: SocketAddr, config: &Config) -> Result<KafkaServer> {
+    config.validate_topic_defaults()?;
+    let configs = Arc::new(config.config_store());
+
+    let logs = Arc::new(LogStore::open(
+        config.data_dir.clone(),
+        config.validate_crc,
+        configs.clone(),
+    )?);
+    tracing::info!("Loaded {} partition logs", logs.len());
+    logs.clone()
+        .spawn_retention(Duration::from_millis(config.retention_check_interval_ms));
+
+    let peers = config
+        .brokers
+        .iter()
+        .map(|peer| BrokerInfo {
+            node_id: peer.id,
+            host: peer.host.clone(),
+            port: peer.port,
+            rack: peer.rack.clone(),
+        })
+        .collect();
+    let brokers = BrokerRegistry::new(
+        BrokerInfo {
+            node_id: configs.broker_id(),
+            host: addr.ip().to_string(),
+            port: addr.port() as i32,
+            rack: String::new(),
+        },
+        peers,
+    );
+    tracing::info!("Advertising {} brokers", brokers.len());
+    let cluster = Arc::new(ClusterInfo::new(&config.cluster_id, brokers));
+
+    let acls = config
+        .acls
+        .clone()
+        .map(|rules| Arc::new(AclStore::new(rules)));
+
+    let state = BrokerState {
+        fetch_sessions: Arc::new(FetchSessionCache::new(config.max_fetch_sessions)),
+        acls: acls.clone(),
+        ..BrokerState::new(cluster, configs, logs)
+    };
+
+    let mut builder = KafkaServerBuilder::with_default_handlers(state)
+        .bind(addr)
+        .idle_timeout(Duration::from_millis(config.idle_timeout_ms))
+        .max_connections(config.max_connections)
+        .max_client_id_len(config.max_client_id_len)
+        .max_request_size(config.max_request_size)
+        .strict_decoding(config.strict_decoding)
+        .quotas(QuotaManager::new(config.quota_requests_per_sec)?)
+        .features(config.features()?);
+
+    if let Some(acls) = acls {
+        tracing::info!("Enforcing ACLs");
+        builder = builder.authorizer(acls);
     }
 
-    async fn accept(&self) -> Result<()> {
-        let (stream, _) = self.listener.accept().await?;
-
-        let registry = self.registry.clone();
-        let mut connection_state = ConnectionState::new(registry.clone());
-
-        let mut stream = KafkaMessageCodec.framed(stream);
-
-        tokio::spawn(async move {
-            while let Some(message) = stream.next().await {
-                let message = match message {
-                    Ok(message) => message,
-                    Err(err) => {
-                        eprintln!("Kafka protocol error: {}", err);
-                        break;
-                    }
-                };
-
-                let mut message = BytesMut::from(message);
-
-                let request =
-                    KafkaRequest::decode_and_handle(&mut message, &registry, &mut connection_state)
-                        .await
-                        .unwrap();
-
-                let response = KafkaResponse::new(&request.header, request.response);
+    for (api_key, &max_version) in &config.api_version_overrides {
+        let api_key = api_key
+            .parse()
+            .ok()
+            .and_then(ApiKey::from_i16)
+            .ok_or_else(|| {
+                anyhow::anyhow!("invalid api key in api_version_overrides: {}", api_key)
+            })?;
+        tracing::info!(%api_key, max_version, "Capping api key version");
+        builder = builder.max_version(api_key, max_version);
+    }
 
-                stream.send(response).await.unwrap();
-            }
-        });
+    let server = builder.build().await?;
 
-        Ok(())
-    }
+    Ok(server)
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    let config = Config::from_figment()?;
+    tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::from_default_env())
+        .init();
 
-    let kafka_server = KafkaServer::build("[::1]:8080").await;
-
-    let id = Uuid::new_v4();
+    let config = Config::from_figment()?;
 
-    let mut liveness_client = LivenessClient::connect(config.controlplane).await?;
+    if let Some(addr) = config.metrics_addr {
+        telemetry::install(addr)?;
+    }
 
-    let checkin_reply = liveness_client
-        .checkin(CheckinRequest { id: id.to_string() })
-        .await?;
+    let kafka_server = build_server(SocketAddr::from((Ipv6Addr::LOCALHOST, 8080)), &config).await?;
 
-    let interval = checkin_reply.get_ref().interval;
+    let controlplane_healthy = Arc::new(AtomicBool::new(false));
+    let health = Arc::new(Health::new(controlplane_healthy.clone()));
+    health.set_listener_bound();
 
-    println!("checkin interval: {:?}", interval);
+    if let Some(addr) = config.health_addr {
+        let listener = TcpListener::bind(addr).await?;
+        tracing::info!("Serving health checks on {}", listener.local_addr()?);
+        health::serve(listener, health.clone());
+    }
 
-    loop {
-        tokio::select! {
-            res = kafka_server.accept() => {
-                match res {
-                    Ok(_) => {}
-                    Err(err) => {
-                        eprintln!("Error accepting connection: {}", err);
-                        break;
-                    }
-                }
+    let id = config.agent_id()?;
+
+    let (liveness_client, checkin_reply) = controlplane::connect(
+        config.controlplane.clone(),
+        &id.to_string(),
+        Duration::from_millis(config.controlplane_retry_window_ms),
+    )
+    .await?;
+
+    let interval =
+        controlplane::checkin_interval(&checkin_reply, controlplane::DEFAULT_CHECKIN_INTERVAL);
+
+    tracing::info!("checkin interval: {:?}", interval);
+
+    controlplane_healthy.store(true, Ordering::Relaxed);
+    let checkins = controlplane::spawn_checkins(
+        liveness_client.clone(),
+        id.to_string(),
+        interval,
+        controlplane_healthy.clone(),
+    );
+
+    let serve = async {
+        loop {
+            if let Err(err) = kafka_server.accept().await {
+                tracing::error!("Error accepting connection: {}", err);
+                break;
             }
         }
-    }
+    };
+    let shutdown = async {
+        let _ = tokio::signal::ctrl_c().await;
+    };
+    controlplane::run_until_shutdown(serve, shutdown, checkins, liveness_client, id.to_string())
+        .await;
 
     Ok(())
 }
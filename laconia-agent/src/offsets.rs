@@ -0,0 +1,72 @@
+use std::{collections::HashMap, sync::RwLock};
+
+/// An offset committed by a consumer group for a partition.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommittedOffset {
+    pub offset: i64,
+    /// The leader epoch of the last consumed record, or -1 if unknown.
+    pub leader_epoch: i32,
+    pub metadata: String,
+}
+
+/// The offsets committed by consumer groups, kept in memory and keyed by
+/// group, topic and partition.
+///
+/// Only committed offsets are kept here; offsets committed inside a
+/// transaction are staged by the
+/// [`TransactionManager`](crate::transactions::TransactionManager) until the
+/// transaction commits.
+pub struct OffsetStore {
+    offsets: RwLock<HashMap<(String, String, i32), CommittedOffset>>,
+}
+
+impl OffsetStore {
+    pub fn new() -> Self {
+        Self {
+            offsets: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Commits `offset` for the group's partition, replacing any offset
+    /// committed before.
+    pub fn commit(&self, group_id: &str, topic: &str, partition: i32, offset: CommittedOffset) {
+        self.offsets
+            .write()
+            .unwrap()
+            .insert((group_id.to_string(), topic.to_string(), partition), offset);
+    }
+
+    /// The offset the group last committed for the partition, if any.
+    pub fn fetch(&self, group_id: &str, topic: &str, partition: i32) -> Option<CommittedOffset> {
+        self.offsets
+            .read()
+            .unwrap()
+            .get(&(group_id.to_string(), topic.to_string(), partition))
+            .cloned()
+    }
+
+    /// Deletes the offset the group committed for the partition, returning
+    /// it if there was one.
+    pub fn delete(&self, group_id: &str, topic: &str, partition: i32) -> Option<CommittedOffset> {
+        self.offsets
+            .write()
+            .unwrap()
+            .remove(&(group_id.to_string(), topic.to_string(), partition))
+    }
+
+    /// Whether the group has committed any offsets. Without a group
+    /// coordinator, that's the only way a group is known to exist.
+    pub fn has_group(&self, group_id: &str) -> bool {
+        self.offsets
+            .read()
+            .unwrap()
+            .keys()
+            .any(|(group, _, _)| group == group_id)
+    }
+}
+
+impl Default for OffsetStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
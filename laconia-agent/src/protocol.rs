@@ -1,13 +1,18 @@
-use std::io;
+use std::{error::Error, fmt, io};
 
 use bytes::BytesMut;
 
+pub mod api_keys;
+pub mod error;
 pub mod handlers;
 pub mod messages;
+pub mod prelude;
 pub mod primitives;
 pub mod registry;
 pub mod request;
 pub mod response;
+#[cfg(feature = "roundtrip")]
+pub mod roundtrip;
 
 pub trait Encoder {
     fn encode(&self, buf: &mut BytesMut) -> Result<(), io::Error>;
@@ -15,6 +20,12 @@ pub trait Encoder {
 
 pub trait EncoderVersioned {
     fn encode(&self, buf: &mut BytesMut, version: i16) -> Result<(), io::Error>;
+
+    /// Estimates how many bytes `encode` will write, so callers can allocate
+    /// up front. It's only a hint; encoding is correct even when it's wrong.
+    fn size_hint(&self, _version: i16) -> usize {
+        0
+    }
 }
 
 pub trait Decoder: Sized {
@@ -24,3 +35,70 @@ pub trait Decoder: Sized {
 pub trait DecoderVersioned: Sized {
     fn decode(buf: &mut BytesMut, version: i16) -> Result<Self, io::Error>;
 }
+
+/// Why a message couldn't be decoded.
+///
+/// Decoders return [`io::Error`], which this converts into with a matching
+/// [`io::ErrorKind`]. Use [`DecodeError::from_io`] to get it back out.
+#[derive(Debug)]
+pub enum DecodeError {
+    /// The buffer ended before the message did, and at least `needed` more
+    /// bytes are required to continue.
+    Incomplete { needed: usize },
+    /// The bytes don't form a valid message.
+    Invalid(Box<dyn Error + Send + Sync>),
+    /// The message is valid, but uses a version or feature that isn't
+    /// supported.
+    Unsupported(Box<dyn Error + Send + Sync>),
+}
+
+impl DecodeError {
+    pub fn invalid(err: impl Into<Box<dyn Error + Send + Sync>>) -> Self {
+        Self::Invalid(err.into())
+    }
+
+    pub fn unsupported(err: impl Into<Box<dyn Error + Send + Sync>>) -> Self {
+        Self::Unsupported(err.into())
+    }
+
+    /// The decode error behind `err`, if it came from a decoder.
+    pub fn from_io(err: &io::Error) -> Option<&Self> {
+        err.get_ref()?.downcast_ref()
+    }
+
+    pub fn kind(&self) -> io::ErrorKind {
+        match self {
+            Self::Incomplete { .. } => io::ErrorKind::UnexpectedEof,
+            Self::Invalid(_) => io::ErrorKind::InvalidData,
+            Self::Unsupported(_) => io::ErrorKind::Unsupported,
+        }
+    }
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Incomplete { needed } => {
+                write!(f, "not enough data: needed {} more bytes", needed)
+            }
+            Self::Invalid(err) => write!(f, "invalid data: {}", err),
+            Self::Unsupported(err) => write!(f, "unsupported: {}", err),
+        }
+    }
+}
+
+impl Error for DecodeError {}
+
+impl From<bytes::TryGetError> for DecodeError {
+    fn from(err: bytes::TryGetError) -> Self {
+        Self::Incomplete {
+            needed: err.requested - err.available,
+        }
+    }
+}
+
+impl From<DecodeError> for io::Error {
+    fn from(err: DecodeError) -> Self {
+        io::Error::new(err.kind(), err)
+    }
+}
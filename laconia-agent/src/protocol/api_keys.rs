@@ -0,0 +1,181 @@
+use std::fmt;
+
+/// An api key defined by the Kafka protocol, whether or not the agent
+/// implements it.
+#[derive(Debug, Clone, Copy)]
+pub struct ApiKeyInfo {
+    pub key: ApiKey,
+    pub name: &'static str,
+    /// The first version using the flexible encoding, with compact fields and
+    /// tagged fields, or `None` if no version does.
+    pub flexible_from: Option<i16>,
+}
+
+impl ApiKeyInfo {
+    pub fn is_flexible(&self, version: i16) -> bool {
+        self.flexible_from.is_some_and(|first| version >= first)
+    }
+
+    /// The version of the header of requests at `version`.
+    pub fn request_header_version(&self, version: i16) -> i16 {
+        match (self.key, self.is_flexible(version)) {
+            // ControlledShutdown v0 predates the client id.
+            (ApiKey::ControlledShutdown, _) if version == 0 => 0,
+            (_, true) => 2,
+            (_, false) => 1,
+        }
+    }
+
+    /// The version of the header of responses at `version`.
+    pub fn response_header_version(&self, version: i16) -> i16 {
+        match (self.key, self.is_flexible(version)) {
+            // Clients read ApiVersions responses before knowing which
+            // versions the broker speaks, so their header is never flexible.
+            (ApiKey::ApiVersions, _) => 0,
+            (_, true) => 1,
+            (_, false) => 0,
+        }
+    }
+}
+
+macro_rules! api_keys {
+    ($($name:ident = $key:literal => $flexible_from:expr,)*) => {
+        /// The api keys of the Kafka protocol, numbered as on the wire.
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        #[repr(i16)]
+        pub enum ApiKey {
+            $($name = $key,)*
+        }
+
+        impl ApiKey {
+            /// The api key numbered `key`, or `None` if the protocol doesn't
+            /// define one.
+            pub fn from_i16(key: i16) -> Option<Self> {
+                match key {
+                    $($key => Some(Self::$name),)*
+                    _ => None,
+                }
+            }
+        }
+
+        /// Every api key in the Kafka protocol, ordered by key.
+        pub const API_KEYS: &[ApiKeyInfo] = &[
+            $(ApiKeyInfo {
+                key: ApiKey::$name,
+                name: stringify!($name),
+                flexible_from: $flexible_from,
+            },)*
+        ];
+    };
+}
+
+impl ApiKey {
+    pub const fn as_i16(self) -> i16 {
+        self as i16
+    }
+
+    pub fn info(self) -> &'static ApiKeyInfo {
+        &API_KEYS[self as usize]
+    }
+
+    pub fn name(self) -> &'static str {
+        self.info().name
+    }
+}
+
+impl From<ApiKey> for i16 {
+    fn from(key: ApiKey) -> Self {
+        key.as_i16()
+    }
+}
+
+impl fmt::Display for ApiKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+api_keys! {
+    Produce = 0 => Some(9),
+    Fetch = 1 => Some(12),
+    ListOffsets = 2 => Some(6),
+    Metadata = 3 => Some(9),
+    LeaderAndIsr = 4 => Some(4),
+    StopReplica = 5 => Some(2),
+    UpdateMetadata = 6 => Some(6),
+    ControlledShutdown = 7 => Some(3),
+    OffsetCommit = 8 => Some(8),
+    OffsetFetch = 9 => Some(6),
+    FindCoordinator = 10 => Some(3),
+    JoinGroup = 11 => Some(6),
+    Heartbeat = 12 => Some(4),
+    LeaveGroup = 13 => Some(4),
+    SyncGroup = 14 => Some(4),
+    DescribeGroups = 15 => Some(5),
+    ListGroups = 16 => Some(3),
+    SaslHandshake = 17 => None,
+    ApiVersions = 18 => Some(3),
+    CreateTopics = 19 => Some(5),
+    DeleteTopics = 20 => Some(4),
+    DeleteRecords = 21 => Some(2),
+    InitProducerId = 22 => Some(2),
+    OffsetForLeaderEpoch = 23 => Some(4),
+    AddPartitionsToTxn = 24 => Some(3),
+    AddOffsetsToTxn = 25 => Some(3),
+    EndTxn = 26 => Some(3),
+    WriteTxnMarkers = 27 => Some(1),
+    TxnOffsetCommit = 28 => Some(3),
+    DescribeAcls = 29 => Some(2),
+    CreateAcls = 30 => Some(2),
+    DeleteAcls = 31 => Some(2),
+    DescribeConfigs = 32 => Some(4),
+    AlterConfigs = 33 => Some(2),
+    AlterReplicaLogDirs = 34 => Some(2),
+    DescribeLogDirs = 35 => Some(2),
+    SaslAuthenticate = 36 => Some(2),
+    CreatePartitions = 37 => Some(2),
+    CreateDelegationToken = 38 => Some(2),
+    RenewDelegationToken = 39 => Some(2),
+    ExpireDelegationToken = 40 => Some(2),
+    DescribeDelegationToken = 41 => Some(2),
+    DeleteGroups = 42 => Some(2),
+    ElectLeaders = 43 => Some(2),
+    IncrementalAlterConfigs = 44 => Some(1),
+    AlterPartitionReassignments = 45 => Some(0),
+    ListPartitionReassignments = 46 => Some(0),
+    OffsetDelete = 47 => None,
+    DescribeClientQuotas = 48 => Some(1),
+    AlterClientQuotas = 49 => Some(1),
+    DescribeUserScramCredentials = 50 => Some(0),
+    AlterUserScramCredentials = 51 => Some(0),
+    Vote = 52 => Some(0),
+    BeginQuorumEpoch = 53 => Some(1),
+    EndQuorumEpoch = 54 => Some(1),
+    DescribeQuorum = 55 => Some(0),
+    AlterPartition = 56 => Some(0),
+    UpdateFeatures = 57 => Some(0),
+    Envelope = 58 => Some(0),
+    FetchSnapshot = 59 => Some(0),
+    DescribeCluster = 60 => Some(0),
+    DescribeProducers = 61 => Some(0),
+    BrokerRegistration = 62 => Some(0),
+    BrokerHeartbeat = 63 => Some(0),
+    UnregisterBroker = 64 => Some(0),
+    DescribeTransactions = 65 => Some(0),
+    ListTransactions = 66 => Some(0),
+    AllocateProducerIds = 67 => Some(0),
+    ConsumerGroupHeartbeat = 68 => Some(0),
+    ConsumerGroupDescribe = 69 => Some(0),
+    ControllerRegistration = 70 => Some(0),
+    GetTelemetrySubscriptions = 71 => Some(0),
+    PushTelemetry = 72 => Some(0),
+    AssignReplicasToDirs = 73 => Some(0),
+    ListClientMetricsResources = 74 => Some(0),
+    DescribeTopicPartitions = 75 => Some(0),
+}
+
+/// Looks up an api key in the protocol, returning `None` for keys it
+/// doesn't define.
+pub fn lookup(key: i16) -> Option<&'static ApiKeyInfo> {
+    ApiKey::from_i16(key).map(ApiKey::info)
+}
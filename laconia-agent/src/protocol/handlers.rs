@@ -5,11 +5,19 @@ use bytes::BytesMut;
 
 use crate::{
     ConnectionState, RequestHeader, VersionRange,
-    protocol::{request::Request, response::AnyResponse},
+    protocol::{
+        error::ErrorCode,
+        request::{self, Request},
+        response::AnyResponse,
+    },
+    telemetry,
 };
 
+mod fetch;
+pub use fetch::FetchHandler;
+
 mod api_versions;
-pub use api_versions::ApiVersionsHandler;
+pub use api_versions::{ApiVersionsHandler, api_versions};
 
 mod metadata;
 pub use metadata::MetadataHandler;
@@ -17,12 +25,79 @@ pub use metadata::MetadataHandler;
 mod find_coordinator;
 pub use find_coordinator::FindCoordinatorHandler;
 
+mod describe_configs;
+pub use describe_configs::DescribeConfigsHandler;
+
+mod incremental_alter_configs;
+pub use incremental_alter_configs::IncrementalAlterConfigsHandler;
+
+mod describe_cluster;
+pub use describe_cluster::DescribeClusterHandler;
+
+mod describe_topic_partitions;
+pub use describe_topic_partitions::DescribeTopicPartitionsHandler;
+
+mod init_producer_id;
+pub use init_producer_id::InitProducerIdHandler;
+
+mod offset_for_leader_epoch;
+pub use offset_for_leader_epoch::OffsetForLeaderEpochHandler;
+
+mod delete_records;
+pub use delete_records::DeleteRecordsHandler;
+
+mod describe_acls;
+pub use describe_acls::DescribeAclsHandler;
+
+mod create_acls;
+pub use create_acls::CreateAclsHandler;
+
+mod delete_acls;
+pub use delete_acls::DeleteAclsHandler;
+
+mod add_partitions_to_txn;
+pub use add_partitions_to_txn::AddPartitionsToTxnHandler;
+
+mod end_txn;
+pub use end_txn::EndTxnHandler;
+
+mod txn_offset_commit;
+pub use txn_offset_commit::TxnOffsetCommitHandler;
+
+mod controlled_shutdown;
+pub use controlled_shutdown::ControlledShutdownHandler;
+
+mod sasl_authenticate;
+pub use sasl_authenticate::SaslAuthenticateHandler;
+
+mod elect_leaders;
+pub use elect_leaders::ElectLeadersHandler;
+
+mod offset_delete;
+pub use offset_delete::OffsetDeleteHandler;
+
+mod describe_producers;
+pub use describe_producers::DescribeProducersHandler;
+
+mod consumer_group_heartbeat;
+pub use consumer_group_heartbeat::ConsumerGroupHeartbeatHandler;
+
 pub trait RequestHandler<Req: Request>: Send + Sync {
+    /// Handles a decoded request. The header is passed along so handlers can
+    /// make use of its client id and tagged fields.
     fn handle(
         &self,
-        request: Req,
+        header: &RequestHeader,
+        request: &Req,
         state: &mut ConnectionState,
     ) -> impl Future<Output = Result<Req::Response, io::Error>> + Send;
+
+    /// The versions this handler implements, as advertised in ApiVersions.
+    /// Defaults to every version the request can be decoded at, and should
+    /// only ever narrow that range.
+    fn supported_versions(&self) -> VersionRange {
+        Req::VERSIONS
+    }
 }
 
 #[async_trait]
@@ -36,11 +111,17 @@ pub(crate) trait AnyRequestHandler: Send + Sync {
 
     fn header_version(&self, version: i16) -> i16;
 
+    fn decode_debug(&self, buf: &mut BytesMut, version: i16) -> Result<String, io::Error>;
+
     fn versions(&self) -> VersionRange;
+
+    /// Narrows [`versions`](Self::versions) to at most `max_version`.
+    fn cap_max_version(&mut self, max_version: i16);
 }
 
 pub(crate) struct TypedRequestHandler<Req: Request, H: RequestHandler<Req>> {
     handler: H,
+    max_version: Option<i16>,
     _phantom: PhantomData<Req>,
 }
 
@@ -48,6 +129,7 @@ impl<Req: Request, H: RequestHandler<Req>> TypedRequestHandler<Req, H> {
     pub fn new(handler: H) -> Self {
         Self {
             handler,
+            max_version: None,
             _phantom: PhantomData,
         }
     }
@@ -66,8 +148,53 @@ where
         header: &RequestHeader,
         state: &mut ConnectionState,
     ) -> Result<Box<dyn AnyResponse>, io::Error> {
-        let request = Req::decode(buf, header.version)?;
-        let response = self.handler.handle(request, state).await?;
+        if !self.versions().contains(header.version) {
+            tracing::warn!("Unsupported request version");
+            telemetry::record_error(ErrorCode::UnsupportedVersion);
+            return Ok(Box::new(Req::decode_error_response(
+                ErrorCode::UnsupportedVersion,
+            )));
+        }
+
+        let request = match Req::decode(buf, header.version) {
+            Ok(request) => request,
+            Err(err) => {
+                let error_code = ErrorCode::from(err.kind());
+                tracing::warn!(%error_code, "Failed to decode request: {}", err);
+                telemetry::record_error(error_code);
+                return Ok(Box::new(Req::decode_error_response(error_code)));
+            }
+        };
+        tracing::debug!("Decoded request: {}", request::describe(&request));
+        if !buf.is_empty() {
+            if state.strict_decoding() {
+                tracing::warn!(
+                    remaining = buf.len(),
+                    "Request body wasn't fully consumed by its decoder"
+                );
+                telemetry::record_error(ErrorCode::InvalidRequest);
+                return Ok(Box::new(request.error_response(ErrorCode::InvalidRequest)));
+            }
+            tracing::debug!(
+                remaining = buf.len(),
+                "Ignoring bytes left over after decoding request"
+            );
+        }
+        if let Some(error_code) = state.authorize(&request.operations()) {
+            tracing::warn!(principal = state.principal(), %error_code, "Denying request");
+            telemetry::record_error(error_code);
+            return Ok(Box::new(request.error_response(error_code)));
+        }
+        state.start_deadline(request.timeout_ms());
+        let response = match self.handler.handle(header, &request, state).await {
+            Ok(response) => response,
+            Err(err) => {
+                let error_code = ErrorCode::from(err.kind());
+                tracing::error!(%error_code, "Handler failed: {}", err);
+                telemetry::record_error(error_code);
+                request.error_response(error_code)
+            }
+        };
         Ok(Box::new(response))
     }
 
@@ -75,7 +202,20 @@ where
         Req::header_version(version)
     }
 
+    fn decode_debug(&self, buf: &mut BytesMut, version: i16) -> Result<String, io::Error> {
+        let request = Req::decode(buf, version)?;
+        Ok(request::describe(&request))
+    }
+
     fn versions(&self) -> VersionRange {
-        Req::VERSIONS
+        let versions = self.handler.supported_versions();
+        match self.max_version {
+            Some(max_version) => VersionRange::new(versions.min, versions.max.min(max_version)),
+            None => versions,
+        }
+    }
+
+    fn cap_max_version(&mut self, max_version: i16) {
+        self.max_version = Some(max_version);
     }
 }
@@ -0,0 +1,56 @@
+use std::{io, sync::Arc};
+
+use crate::{
+    ConnectionState, RequestHeader,
+    protocol::{
+        error::ErrorCode,
+        handlers::RequestHandler,
+        messages::{AddPartitionsToTxnRequest, AddPartitionsToTxnResponse},
+        request::Request,
+    },
+    transactions::TransactionManager,
+};
+
+pub struct AddPartitionsToTxnHandler {
+    transactions: Arc<TransactionManager>,
+}
+
+impl AddPartitionsToTxnHandler {
+    pub fn new(transactions: Arc<TransactionManager>) -> Self {
+        Self { transactions }
+    }
+}
+
+impl RequestHandler<AddPartitionsToTxnRequest> for AddPartitionsToTxnHandler {
+    async fn handle(
+        &self,
+        header: &RequestHeader,
+        request: &AddPartitionsToTxnRequest,
+        _state: &mut ConnectionState,
+    ) -> Result<AddPartitionsToTxnResponse, io::Error> {
+        tracing::debug!("Handling AddPartitionsToTxnRequest");
+
+        let partitions = request.topics.iter().flat_map(|topic| {
+            topic
+                .partitions
+                .iter()
+                .map(|&partition| (topic.name.clone(), partition))
+        });
+
+        // Every partition in the request shares the outcome, since they're
+        // all added to the same transaction.
+        let error_code = match self.transactions.add_partitions(
+            &request.transactional_id,
+            request.producer_id,
+            request.producer_epoch,
+            partitions,
+        ) {
+            Ok(()) => ErrorCode::None,
+            // Versions before 2 predate PRODUCER_FENCED.
+            Err(ErrorCode::ProducerFenced) if header.version < 2 => ErrorCode::InvalidProducerEpoch,
+            Err(error_code) => error_code,
+        };
+
+        Ok(request.error_response(error_code))
+    }
+}
@@ -1,37 +1,81 @@
 use std::io;
 
 use crate::{
-    ConnectionState,
+    ConnectionState, RequestHeader,
     protocol::{
+        error::ErrorCode,
         handlers::RequestHandler,
-        messages::{ApiVersionsApiKeys, ApiVersionsRequest, ApiVersionsResponse},
+        messages::{
+            ApiVersionsApiKeys, ApiVersionsFinalizedFeature, ApiVersionsRequest,
+            ApiVersionsResponse, ApiVersionsSupportedFeature,
+        },
+        registry::MessageRegistry,
     },
 };
 
 pub struct ApiVersionsHandler;
 
+/// The supported version range of every api key in `registry`.
+pub fn api_versions(registry: &MessageRegistry) -> Vec<ApiVersionsApiKeys> {
+    registry
+        .all_api_keys()
+        .map(|key| {
+            let versions = registry.versions(key).expect("registry returned key");
+            ApiVersionsApiKeys {
+                api_key: key,
+                min_version: versions.min,
+                max_version: versions.max,
+                tagged_fields: Default::default(),
+            }
+        })
+        .collect()
+}
+
 impl RequestHandler<ApiVersionsRequest> for ApiVersionsHandler {
     async fn handle(
         &self,
-        _request: ApiVersionsRequest,
+        header: &RequestHeader,
+        request: &ApiVersionsRequest,
         state: &mut ConnectionState,
     ) -> Result<ApiVersionsResponse, io::Error> {
-        println!("Handling ApiVersionsRequest");
+        tracing::debug!("Handling ApiVersionsRequest");
 
-        let api_versions = state.registry.all_api_keys().map(|key| {
-            let versions = state.registry.versions(key).expect("registry returned key");
-            ApiVersionsApiKeys {
-                api_key: key,
-                min_version: versions.min,
-                max_version: versions.max,
+        let api_keys = api_versions(&state.registry);
+        state.negotiate(
+            header.version,
+            &request.client_software_name,
+            &request.client_software_version,
+            &api_keys,
+        );
+
+        let features = state.features();
+        let supported_features = features
+            .iter()
+            .map(|(name, levels)| ApiVersionsSupportedFeature {
+                name: name.to_string(),
+                min_version: levels.min,
+                max_version: levels.max,
                 tagged_fields: Default::default(),
-            }
-        });
+            })
+            .collect();
+        let finalized_features = features
+            .iter()
+            .map(|(name, levels)| ApiVersionsFinalizedFeature {
+                name: name.to_string(),
+                max_version_level: levels.max,
+                min_version_level: levels.max,
+                tagged_fields: Default::default(),
+            })
+            .collect();
 
         Ok(ApiVersionsResponse {
-            error_code: 0,
-            api_keys: api_versions.collect(),
+            error_code: ErrorCode::None,
+            api_keys,
             throttle_time_ms: 0,
+            supported_features,
+            // The features are finalized once, from the broker's config.
+            finalized_features_epoch: if features.is_empty() { -1 } else { 0 },
+            finalized_features,
             tagged_fields: Default::default(),
         })
     }
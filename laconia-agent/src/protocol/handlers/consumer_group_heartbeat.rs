@@ -0,0 +1,80 @@
+use std::{io, sync::Arc};
+
+use crate::{
+    ConnectionState, RequestHeader,
+    groups::GroupCoordinator,
+    log::LogStore,
+    protocol::{
+        error::ErrorCode,
+        handlers::RequestHandler,
+        messages::{
+            ConsumerGroupHeartbeatAssignment, ConsumerGroupHeartbeatRequest,
+            ConsumerGroupHeartbeatResponse, ConsumerGroupHeartbeatTopicPartitions,
+        },
+        request::Request,
+    },
+};
+
+/// How often members are asked to heartbeat, matching Kafka's default
+/// `group.consumer.heartbeat.interval.ms`.
+const HEARTBEAT_INTERVAL_MS: i32 = 5000;
+
+/// Answers consumer group heartbeats from the group coordinator, naming the
+/// topics of assignments by their ids.
+pub struct ConsumerGroupHeartbeatHandler {
+    groups: Arc<GroupCoordinator>,
+    logs: Arc<LogStore>,
+}
+
+impl ConsumerGroupHeartbeatHandler {
+    pub fn new(groups: Arc<GroupCoordinator>, logs: Arc<LogStore>) -> Self {
+        Self { groups, logs }
+    }
+}
+
+impl RequestHandler<ConsumerGroupHeartbeatRequest> for ConsumerGroupHeartbeatHandler {
+    async fn handle(
+        &self,
+        _header: &RequestHeader,
+        request: &ConsumerGroupHeartbeatRequest,
+        _state: &mut ConnectionState,
+    ) -> Result<ConsumerGroupHeartbeatResponse, io::Error> {
+        tracing::debug!("Handling ConsumerGroupHeartbeatRequest");
+
+        let heartbeat = match self.groups.heartbeat(
+            &request.group_id,
+            &request.member_id,
+            request.member_epoch,
+            request.subscribed_topic_names.as_deref(),
+        ) {
+            Ok(heartbeat) => heartbeat,
+            Err(error_code) => return Ok(request.error_response(error_code)),
+        };
+
+        // A topic deleted since the assignment was made has no id left to
+        // name it by, and is left out.
+        let assignment = heartbeat
+            .assignment
+            .map(|assignment| ConsumerGroupHeartbeatAssignment {
+                topic_partitions: assignment
+                    .into_iter()
+                    .filter_map(|(topic, partitions)| {
+                        Some(ConsumerGroupHeartbeatTopicPartitions {
+                            topic_id: self.logs.topic_id(&topic)?,
+                            partitions: partitions.into_iter().collect(),
+                            tagged_fields: Default::default(),
+                        })
+                    })
+                    .collect(),
+                tagged_fields: Default::default(),
+            });
+
+        Ok(ConsumerGroupHeartbeatResponse {
+            member_id: heartbeat.member_id,
+            member_epoch: heartbeat.member_epoch,
+            heartbeat_interval_ms: HEARTBEAT_INTERVAL_MS,
+            assignment,
+            ..ConsumerGroupHeartbeatRequest::decode_error_response(ErrorCode::None)
+        })
+    }
+}
@@ -0,0 +1,54 @@
+use std::{
+    io,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+};
+
+use crate::{
+    ConnectionState, RequestHeader,
+    cluster::ClusterInfo,
+    protocol::{
+        error::ErrorCode,
+        handlers::RequestHandler,
+        messages::{ControlledShutdownRequest, ControlledShutdownResponse},
+        request::Request,
+    },
+};
+
+/// Marks the broker as shutting down. Every partition is led by the one
+/// broker, so there's nothing to hand off and no partitions remain.
+pub struct ControlledShutdownHandler {
+    cluster: Arc<ClusterInfo>,
+    shutting_down: Arc<AtomicBool>,
+}
+
+impl ControlledShutdownHandler {
+    pub fn new(cluster: Arc<ClusterInfo>, shutting_down: Arc<AtomicBool>) -> Self {
+        Self {
+            cluster,
+            shutting_down,
+        }
+    }
+}
+
+impl RequestHandler<ControlledShutdownRequest> for ControlledShutdownHandler {
+    async fn handle(
+        &self,
+        _header: &RequestHeader,
+        request: &ControlledShutdownRequest,
+        _state: &mut ConnectionState,
+    ) -> Result<ControlledShutdownResponse, io::Error> {
+        tracing::debug!("Handling ControlledShutdownRequest");
+
+        if request.broker_id != self.cluster.brokers.local().node_id {
+            return Ok(request.error_response(ErrorCode::BrokerNotAvailable));
+        }
+
+        tracing::info!(broker_id = request.broker_id, "Broker is shutting down");
+        self.shutting_down.store(true, Ordering::SeqCst);
+
+        Ok(request.error_response(ErrorCode::None))
+    }
+}
@@ -0,0 +1,84 @@
+use std::{io, sync::Arc};
+
+use crate::{
+    ConnectionState, RequestHeader,
+    authorizer::{AclRule, AclStore, Operation, PatternType, Permission, ResourceKind},
+    protocol::{
+        error::ErrorCode,
+        handlers::RequestHandler,
+        messages::{AclCreation, AclCreationResult, CreateAclsRequest, CreateAclsResponse},
+        request::Request,
+    },
+};
+
+/// Adds ACLs to the broker's ACL store. Fails with `SECURITY_DISABLED` when
+/// the broker doesn't enforce ACLs.
+pub struct CreateAclsHandler {
+    acls: Option<Arc<AclStore>>,
+}
+
+impl CreateAclsHandler {
+    pub fn new(acls: Option<Arc<AclStore>>) -> Self {
+        Self { acls }
+    }
+}
+
+/// Builds the rule a creation asks for, or describes why it can't.
+fn rule(creation: &AclCreation) -> Result<AclRule, String> {
+    let resource_kind = ResourceKind::from_i8(creation.resource_type)
+        .ok_or_else(|| format!("unsupported resource type {}", creation.resource_type))?;
+    let pattern_type = PatternType::from_i8(creation.resource_pattern_type)
+        .ok_or_else(|| format!("invalid pattern type {}", creation.resource_pattern_type))?;
+    let operation = Operation::from_i8(creation.operation)
+        .ok_or_else(|| format!("invalid operation {}", creation.operation))?;
+    let permission = Permission::from_i8(creation.permission_type)
+        .ok_or_else(|| format!("invalid permission type {}", creation.permission_type))?;
+
+    if creation.resource_name.is_empty() {
+        return Err("resource name must not be empty".to_string());
+    }
+
+    Ok(AclRule {
+        principal: creation.principal.clone(),
+        host: creation.host.clone(),
+        operation,
+        permission,
+        resource_kind,
+        resource_name: creation.resource_name.clone(),
+        pattern_type,
+    })
+}
+
+impl RequestHandler<CreateAclsRequest> for CreateAclsHandler {
+    async fn handle(
+        &self,
+        _header: &RequestHeader,
+        request: &CreateAclsRequest,
+        _state: &mut ConnectionState,
+    ) -> Result<CreateAclsResponse, io::Error> {
+        tracing::debug!("Handling CreateAclsRequest");
+
+        let Some(acls) = &self.acls else {
+            return Ok(request.error_response(ErrorCode::SecurityDisabled));
+        };
+
+        let results = request
+            .creations
+            .iter()
+            .map(|creation| match rule(creation) {
+                Ok(rule) => {
+                    tracing::info!(?rule, "Creating ACL");
+                    acls.create(rule);
+                    AclCreationResult::error(ErrorCode::None, String::new())
+                }
+                Err(message) => AclCreationResult::error(ErrorCode::InvalidRequest, message),
+            })
+            .collect();
+
+        Ok(CreateAclsResponse {
+            throttle_time_ms: 0,
+            results,
+            tagged_fields: Default::default(),
+        })
+    }
+}
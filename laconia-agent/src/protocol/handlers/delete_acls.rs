@@ -0,0 +1,95 @@
+use std::{io, sync::Arc};
+
+use crate::{
+    ConnectionState, RequestHeader,
+    authorizer::{AclFilter, AclStore},
+    protocol::{
+        error::ErrorCode,
+        handlers::RequestHandler,
+        messages::{
+            DeleteAclsFilter, DeleteAclsFilterResult, DeleteAclsMatchingAcl, DeleteAclsRequest,
+            DeleteAclsResponse,
+        },
+        request::Request,
+    },
+};
+
+/// Removes ACLs from the broker's ACL store. Fails with `SECURITY_DISABLED`
+/// when the broker doesn't enforce ACLs.
+pub struct DeleteAclsHandler {
+    acls: Option<Arc<AclStore>>,
+}
+
+impl DeleteAclsHandler {
+    pub fn new(acls: Option<Arc<AclStore>>) -> Self {
+        Self { acls }
+    }
+}
+
+/// Deletes the ACLs matching `filter` from `acls`.
+fn delete(acls: &AclStore, filter: &DeleteAclsFilter) -> DeleteAclsFilterResult {
+    let Some(filter) = AclFilter::from_codes(
+        filter.resource_type_filter,
+        &filter.resource_name_filter,
+        filter.pattern_type_filter,
+        &filter.principal_filter,
+        &filter.host_filter,
+        filter.operation,
+        filter.permission_type,
+    ) else {
+        return DeleteAclsFilterResult::error(
+            ErrorCode::InvalidRequest,
+            "unknown ACL filter code".to_string(),
+        );
+    };
+
+    let matching_acls = acls
+        .delete(&filter)
+        .into_iter()
+        .map(|rule| {
+            tracing::info!(?rule, "Deleted ACL");
+            DeleteAclsMatchingAcl {
+                error_code: ErrorCode::None,
+                error_message: String::new(),
+                resource_type: rule.resource_kind.code(),
+                resource_name: rule.resource_name,
+                pattern_type: rule.pattern_type.code(),
+                principal: rule.principal,
+                host: rule.host,
+                operation: rule.operation.code(),
+                permission_type: rule.permission.code(),
+                tagged_fields: Default::default(),
+            }
+        })
+        .collect();
+
+    DeleteAclsFilterResult {
+        matching_acls,
+        ..DeleteAclsFilterResult::error(ErrorCode::None, String::new())
+    }
+}
+
+impl RequestHandler<DeleteAclsRequest> for DeleteAclsHandler {
+    async fn handle(
+        &self,
+        _header: &RequestHeader,
+        request: &DeleteAclsRequest,
+        _state: &mut ConnectionState,
+    ) -> Result<DeleteAclsResponse, io::Error> {
+        tracing::debug!("Handling DeleteAclsRequest");
+
+        let Some(acls) = &self.acls else {
+            return Ok(request.error_response(ErrorCode::SecurityDisabled));
+        };
+
+        Ok(DeleteAclsResponse {
+            throttle_time_ms: 0,
+            filter_results: request
+                .filters
+                .iter()
+                .map(|filter| delete(acls, filter))
+                .collect(),
+            tagged_fields: Default::default(),
+        })
+    }
+}
@@ -0,0 +1,90 @@
+use std::{io, sync::Arc};
+
+use crate::{
+    ConnectionState, RequestHeader,
+    log::LogStore,
+    protocol::{
+        error::ErrorCode,
+        handlers::RequestHandler,
+        messages::{
+            DeleteRecordsPartition, DeleteRecordsPartitionResult, DeleteRecordsRequest,
+            DeleteRecordsResponse, DeleteRecordsTopicResult,
+        },
+    },
+};
+
+/// Deletes the records before an offset in each partition. There are no
+/// followers to replicate the deletion to, so the timeout is never waited on.
+pub struct DeleteRecordsHandler {
+    logs: Arc<LogStore>,
+}
+
+impl DeleteRecordsHandler {
+    pub fn new(logs: Arc<LogStore>) -> Self {
+        Self { logs }
+    }
+
+    fn delete_records(
+        &self,
+        topic: &str,
+        request: &DeleteRecordsPartition,
+    ) -> DeleteRecordsPartitionResult {
+        let partition = request.partition_index;
+        let Some(log) = self.logs.partition(topic, partition) else {
+            return DeleteRecordsPartitionResult::error(
+                partition,
+                ErrorCode::UnknownTopicOrPartition,
+            );
+        };
+
+        // -1 deletes everything up to the high watermark.
+        let offset = match request.offset {
+            -1 => log.latest_offset(),
+            offset => offset,
+        };
+
+        match log.delete_records_before(offset) {
+            Ok(low_watermark) => DeleteRecordsPartitionResult {
+                partition_index: partition,
+                low_watermark,
+                error_code: ErrorCode::None,
+                tagged_fields: Default::default(),
+            },
+            Err(err) => {
+                tracing::debug!(topic, partition, "Failed to delete records: {}", err);
+                DeleteRecordsPartitionResult::error(partition, err.error_code())
+            }
+        }
+    }
+}
+
+impl RequestHandler<DeleteRecordsRequest> for DeleteRecordsHandler {
+    async fn handle(
+        &self,
+        _header: &RequestHeader,
+        request: &DeleteRecordsRequest,
+        _state: &mut ConnectionState,
+    ) -> Result<DeleteRecordsResponse, io::Error> {
+        tracing::debug!("Handling DeleteRecordsRequest");
+
+        let topics = request
+            .topics
+            .iter()
+            .map(|topic| DeleteRecordsTopicResult {
+                name: topic.name.clone(),
+                partitions: topic
+                    .partitions
+                    .iter()
+                    .map(|partition| self.delete_records(&topic.name, partition))
+                    .collect(),
+                tagged_fields: Default::default(),
+            })
+            .collect();
+
+        Ok(DeleteRecordsResponse {
+            throttle_time_ms: 0,
+            topics,
+            tagged_fields: Default::default(),
+        })
+    }
+}
@@ -0,0 +1,93 @@
+use std::{io, sync::Arc};
+
+use crate::{
+    ConnectionState, RequestHeader,
+    authorizer::{AclFilter, AclStore},
+    protocol::{
+        error::ErrorCode,
+        handlers::RequestHandler,
+        messages::{
+            AclDescription, DescribeAclsRequest, DescribeAclsResource, DescribeAclsResponse,
+        },
+        request::Request,
+    },
+};
+
+/// Lists the ACLs in the broker's ACL store. Fails with `SECURITY_DISABLED`
+/// when the broker doesn't enforce ACLs.
+pub struct DescribeAclsHandler {
+    acls: Option<Arc<AclStore>>,
+}
+
+impl DescribeAclsHandler {
+    pub fn new(acls: Option<Arc<AclStore>>) -> Self {
+        Self { acls }
+    }
+}
+
+impl RequestHandler<DescribeAclsRequest> for DescribeAclsHandler {
+    async fn handle(
+        &self,
+        _header: &RequestHeader,
+        request: &DescribeAclsRequest,
+        _state: &mut ConnectionState,
+    ) -> Result<DescribeAclsResponse, io::Error> {
+        tracing::debug!("Handling DescribeAclsRequest");
+
+        let Some(acls) = &self.acls else {
+            return Ok(request.error_response(ErrorCode::SecurityDisabled));
+        };
+
+        let Some(filter) = AclFilter::from_codes(
+            request.resource_type_filter,
+            &request.resource_name_filter,
+            request.pattern_type_filter,
+            &request.principal_filter,
+            &request.host_filter,
+            request.operation,
+            request.permission_type,
+        ) else {
+            return Ok(DescribeAclsResponse {
+                error_message: "unknown ACL filter code".to_string(),
+                ..request.error_response(ErrorCode::InvalidRequest)
+            });
+        };
+
+        // ACLs are grouped by the resource pattern they're on.
+        let mut resources: Vec<DescribeAclsResource> = Vec::new();
+        for rule in acls.describe(&filter) {
+            let acl = AclDescription {
+                principal: rule.principal,
+                host: rule.host,
+                operation: rule.operation.code(),
+                permission_type: rule.permission.code(),
+                tagged_fields: Default::default(),
+            };
+
+            let resource_type = rule.resource_kind.code();
+            let pattern_type = rule.pattern_type.code();
+            match resources.iter_mut().find(|resource| {
+                resource.resource_type == resource_type
+                    && resource.resource_name == rule.resource_name
+                    && resource.pattern_type == pattern_type
+            }) {
+                Some(resource) => resource.acls.push(acl),
+                None => resources.push(DescribeAclsResource {
+                    resource_type,
+                    resource_name: rule.resource_name,
+                    pattern_type,
+                    acls: vec![acl],
+                    tagged_fields: Default::default(),
+                }),
+            }
+        }
+
+        Ok(DescribeAclsResponse {
+            throttle_time_ms: 0,
+            error_code: ErrorCode::None,
+            error_message: String::new(),
+            resources,
+            tagged_fields: Default::default(),
+        })
+    }
+}
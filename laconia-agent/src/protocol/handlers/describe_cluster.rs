@@ -0,0 +1,71 @@
+use std::{io, sync::Arc};
+
+use crate::{
+    ConnectionState, RequestHeader,
+    cluster::ClusterInfo,
+    protocol::{
+        error::ErrorCode,
+        handlers::RequestHandler,
+        messages::{DescribeClusterBroker, DescribeClusterRequest, DescribeClusterResponse},
+    },
+};
+
+/// The operations a client may perform on the cluster. There are no ACLs, so
+/// this is every operation that applies to a cluster resource: CREATE,
+/// ALTER, DESCRIBE, CLUSTER_ACTION, DESCRIBE_CONFIGS, ALTER_CONFIGS and
+/// IDEMPOTENT_WRITE.
+const CLUSTER_AUTHORIZED_OPERATIONS: i32 =
+    1 << 5 | 1 << 7 | 1 << 8 | 1 << 9 | 1 << 10 | 1 << 11 | 1 << 12;
+
+pub struct DescribeClusterHandler {
+    cluster: Arc<ClusterInfo>,
+}
+
+impl DescribeClusterHandler {
+    pub fn new(cluster: Arc<ClusterInfo>) -> Self {
+        Self { cluster }
+    }
+}
+
+impl RequestHandler<DescribeClusterRequest> for DescribeClusterHandler {
+    async fn handle(
+        &self,
+        _header: &RequestHeader,
+        request: &DescribeClusterRequest,
+        _state: &mut ConnectionState,
+    ) -> Result<DescribeClusterResponse, io::Error> {
+        tracing::debug!("Handling DescribeClusterRequest");
+
+        let brokers = self
+            .cluster
+            .brokers
+            .iter()
+            .map(|broker| DescribeClusterBroker {
+                broker_id: broker.node_id,
+                host: broker.host.clone(),
+                port: broker.port,
+                rack: broker.rack.clone(),
+                is_fenced: false,
+                tagged_fields: Default::default(),
+            })
+            .collect();
+
+        let cluster_authorized_operations = if request.include_cluster_authorized_operations {
+            CLUSTER_AUTHORIZED_OPERATIONS
+        } else {
+            i32::MIN
+        };
+
+        Ok(DescribeClusterResponse {
+            throttle_time_ms: 0,
+            error_code: ErrorCode::None,
+            error_message: String::new(),
+            endpoint_type: request.endpoint_type,
+            cluster_id: self.cluster.cluster_id.clone(),
+            controller_id: self.cluster.controller_id,
+            brokers,
+            cluster_authorized_operations,
+            tagged_fields: Default::default(),
+        })
+    }
+}
@@ -0,0 +1,131 @@
+use std::{io, sync::Arc};
+
+use crate::{
+    ConnectionState, RequestHeader,
+    configs::{ConfigEntry, ConfigSource, ConfigStore, ResourceType},
+    log::LogStore,
+    protocol::{
+        error::ErrorCode,
+        handlers::RequestHandler,
+        messages::{
+            DescribeConfigsRequest, DescribeConfigsResource, DescribeConfigsResourceResult,
+            DescribeConfigsResponse, DescribeConfigsResult,
+        },
+    },
+};
+
+pub struct DescribeConfigsHandler {
+    configs: Arc<ConfigStore>,
+    logs: Arc<LogStore>,
+}
+
+impl DescribeConfigsHandler {
+    pub fn new(configs: Arc<ConfigStore>, logs: Arc<LogStore>) -> Self {
+        Self { configs, logs }
+    }
+
+    fn describe(
+        &self,
+        resource: &DescribeConfigsResource,
+    ) -> Result<Vec<ConfigEntry>, (ErrorCode, String)> {
+        let name = &resource.resource_name;
+
+        let mut entries = match ResourceType::from_i8(resource.resource_type) {
+            Some(ResourceType::Topic) if name.is_empty() => {
+                return Err((
+                    ErrorCode::InvalidTopicException,
+                    "topic name must not be empty".to_string(),
+                ));
+            }
+            Some(ResourceType::Topic) if !self.logs.topics().contains_key(name) => {
+                return Err((
+                    ErrorCode::UnknownTopicOrPartition,
+                    format!("unknown topic: {}", name),
+                ));
+            }
+            Some(ResourceType::Topic) => self.configs.topic_configs(name),
+            Some(ResourceType::Broker) => {
+                if !name.is_empty() && *name != self.configs.broker_id().to_string() {
+                    return Err((
+                        ErrorCode::InvalidRequest,
+                        format!("unexpected broker id: {}", name),
+                    ));
+                }
+                self.configs.broker_configs().to_vec()
+            }
+            None => {
+                return Err((
+                    ErrorCode::InvalidRequest,
+                    format!("unsupported resource type: {}", resource.resource_type),
+                ));
+            }
+        };
+
+        if let Some(keys) = &resource.configuration_keys {
+            entries.retain(|entry| keys.contains(&entry.name));
+        }
+
+        Ok(entries)
+    }
+}
+
+impl RequestHandler<DescribeConfigsRequest> for DescribeConfigsHandler {
+    async fn handle(
+        &self,
+        _header: &RequestHeader,
+        request: &DescribeConfigsRequest,
+        _state: &mut ConnectionState,
+    ) -> Result<DescribeConfigsResponse, io::Error> {
+        tracing::debug!("Handling DescribeConfigsRequest");
+
+        let results = request
+            .resources
+            .iter()
+            .map(|resource| {
+                let (error_code, error_message, entries) = match self.describe(resource) {
+                    Ok(entries) => (ErrorCode::None, String::new(), entries),
+                    Err((error_code, error_message)) => (error_code, error_message, vec![]),
+                };
+
+                let configs = entries
+                    .into_iter()
+                    .map(|entry| DescribeConfigsResourceResult {
+                        is_sensitive: entry.is_sensitive(),
+                        value: if entry.is_sensitive() {
+                            String::new()
+                        } else {
+                            entry.value
+                        },
+                        name: entry.name,
+                        read_only: entry.read_only,
+                        is_default: entry.source == ConfigSource::DefaultConfig,
+                        config_source: entry.source as i8,
+                        synonyms: vec![],
+                        config_type: entry.config_type as i8,
+                        documentation: if request.include_documentation {
+                            entry.documentation
+                        } else {
+                            String::new()
+                        },
+                        tagged_fields: Default::default(),
+                    })
+                    .collect();
+
+                DescribeConfigsResult {
+                    error_code,
+                    error_message,
+                    resource_type: resource.resource_type,
+                    resource_name: resource.resource_name.clone(),
+                    configs,
+                    tagged_fields: Default::default(),
+                }
+            })
+            .collect();
+
+        Ok(DescribeConfigsResponse {
+            throttle_time_ms: 0,
+            results,
+            tagged_fields: Default::default(),
+        })
+    }
+}
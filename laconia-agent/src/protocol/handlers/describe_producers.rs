@@ -0,0 +1,88 @@
+use std::{io, sync::Arc};
+
+use crate::{
+    ConnectionState, RequestHeader,
+    log::LogStore,
+    protocol::{
+        error::ErrorCode,
+        handlers::RequestHandler,
+        messages::{
+            DescribeProducersRequest, DescribeProducersResponse,
+            DescribeProducersResponsePartition, DescribeProducersResponseTopic, ProducerState,
+        },
+    },
+    transactions::TransactionManager,
+};
+
+/// Lists the producers with a transaction ongoing over each partition.
+/// Sequences and timestamps aren't tracked, so those are reported as -1.
+pub struct DescribeProducersHandler {
+    logs: Arc<LogStore>,
+    transactions: Arc<TransactionManager>,
+}
+
+impl DescribeProducersHandler {
+    pub fn new(logs: Arc<LogStore>, transactions: Arc<TransactionManager>) -> Self {
+        Self { logs, transactions }
+    }
+
+    fn describe(&self, topic: &str, partition_index: i32) -> DescribeProducersResponsePartition {
+        if self.logs.partition(topic, partition_index).is_none() {
+            return DescribeProducersResponsePartition::new(
+                partition_index,
+                ErrorCode::UnknownTopicOrPartition,
+            );
+        }
+
+        let active_producers = self
+            .transactions
+            .producers(topic, partition_index)
+            .into_iter()
+            .map(|(producer_id, producer_epoch)| ProducerState {
+                producer_id,
+                producer_epoch: producer_epoch.into(),
+                last_sequence: -1,
+                last_timestamp: -1,
+                coordinator_epoch: -1,
+                current_txn_start_offset: -1,
+                tagged_fields: Default::default(),
+            })
+            .collect();
+
+        DescribeProducersResponsePartition {
+            active_producers,
+            ..DescribeProducersResponsePartition::new(partition_index, ErrorCode::None)
+        }
+    }
+}
+
+impl RequestHandler<DescribeProducersRequest> for DescribeProducersHandler {
+    async fn handle(
+        &self,
+        _header: &RequestHeader,
+        request: &DescribeProducersRequest,
+        _state: &mut ConnectionState,
+    ) -> Result<DescribeProducersResponse, io::Error> {
+        tracing::debug!("Handling DescribeProducersRequest");
+
+        let topics = request
+            .topics
+            .iter()
+            .map(|topic| DescribeProducersResponseTopic {
+                name: topic.name.clone(),
+                partitions: topic
+                    .partition_indexes
+                    .iter()
+                    .map(|&partition_index| self.describe(&topic.name, partition_index))
+                    .collect(),
+                tagged_fields: Default::default(),
+            })
+            .collect();
+
+        Ok(DescribeProducersResponse {
+            throttle_time_ms: 0,
+            topics,
+            tagged_fields: Default::default(),
+        })
+    }
+}
@@ -0,0 +1,137 @@
+use std::{io, sync::Arc};
+
+use crate::{
+    ConnectionState, RequestHeader,
+    cluster::ClusterInfo,
+    log::LogStore,
+    protocol::{
+        error::ErrorCode,
+        handlers::RequestHandler,
+        messages::{
+            DescribeTopicPartitionsCursor, DescribeTopicPartitionsRequest,
+            DescribeTopicPartitionsResponse, DescribeTopicPartitionsResponsePartition,
+            DescribeTopicPartitionsResponseTopic,
+        },
+    },
+};
+
+/// Describes topics in name order, starting from the request's cursor. Once
+/// `response_partition_limit` partitions have been described, the rest are
+/// left for a follow-up request resuming from the returned cursor.
+pub struct DescribeTopicPartitionsHandler {
+    logs: Arc<LogStore>,
+    cluster: Arc<ClusterInfo>,
+}
+
+impl DescribeTopicPartitionsHandler {
+    pub fn new(logs: Arc<LogStore>, cluster: Arc<ClusterInfo>) -> Self {
+        Self { logs, cluster }
+    }
+}
+
+impl RequestHandler<DescribeTopicPartitionsRequest> for DescribeTopicPartitionsHandler {
+    async fn handle(
+        &self,
+        _header: &RequestHeader,
+        request: &DescribeTopicPartitionsRequest,
+        _state: &mut ConnectionState,
+    ) -> Result<DescribeTopicPartitionsResponse, io::Error> {
+        tracing::debug!("Handling DescribeTopicPartitionsRequest");
+
+        let all_topics = self.logs.topics();
+        let mut names: Vec<&str> = if request.topics.is_empty() {
+            all_topics.keys().map(String::as_str).collect()
+        } else {
+            request
+                .topics
+                .iter()
+                .map(|topic| topic.name.as_str())
+                .collect()
+        };
+        names.sort_unstable();
+        names.dedup();
+
+        if let Some(cursor) = &request.cursor {
+            names.retain(|&name| name >= cursor.topic_name.as_str());
+        }
+
+        let mut remaining = request.response_partition_limit.max(1) as usize;
+        let mut topics = Vec::new();
+        let mut next_cursor = None;
+
+        for name in names {
+            let Some(partitions) = all_topics.get(name) else {
+                topics.push(DescribeTopicPartitionsResponseTopic::new(
+                    name,
+                    ErrorCode::UnknownTopicOrPartition,
+                ));
+                continue;
+            };
+
+            let start = match &request.cursor {
+                Some(cursor) if cursor.topic_name == name => cursor.partition_index,
+                _ => 0,
+            };
+            let mut partitions = partitions
+                .iter()
+                .copied()
+                .filter(|&partition| partition >= start)
+                .peekable();
+
+            if remaining == 0 {
+                if let Some(&partition_index) = partitions.peek() {
+                    next_cursor = Some(DescribeTopicPartitionsCursor {
+                        topic_name: name.to_string(),
+                        partition_index,
+                        tagged_fields: Default::default(),
+                    });
+                }
+                break;
+            }
+
+            let mut topic = DescribeTopicPartitionsResponseTopic {
+                topic_id: self.logs.topic_id(name).unwrap_or_default(),
+                ..DescribeTopicPartitionsResponseTopic::new(name, ErrorCode::None)
+            };
+
+            while remaining > 0
+                && let Some(partition_index) = partitions.next()
+            {
+                let leader_id = self.cluster.leader(name, partition_index);
+                topic
+                    .partitions
+                    .push(DescribeTopicPartitionsResponsePartition {
+                        error_code: ErrorCode::None,
+                        partition_index,
+                        leader_id,
+                        leader_epoch: 0,
+                        replica_nodes: vec![leader_id],
+                        isr_nodes: vec![leader_id],
+                        eligible_leader_replicas: Some(vec![]),
+                        last_known_elr: Some(vec![]),
+                        offline_replicas: vec![],
+                        tagged_fields: Default::default(),
+                    });
+                remaining -= 1;
+            }
+
+            topics.push(topic);
+
+            if let Some(partition_index) = partitions.next() {
+                next_cursor = Some(DescribeTopicPartitionsCursor {
+                    topic_name: name.to_string(),
+                    partition_index,
+                    tagged_fields: Default::default(),
+                });
+                break;
+            }
+        }
+
+        Ok(DescribeTopicPartitionsResponse {
+            throttle_time_ms: 0,
+            topics,
+            next_cursor,
+            tagged_fields: Default::default(),
+        })
+    }
+}
@@ -0,0 +1,78 @@
+use std::{io, sync::Arc};
+
+use crate::{
+    ConnectionState, RequestHeader,
+    log::LogStore,
+    protocol::{
+        error::ErrorCode,
+        handlers::RequestHandler,
+        messages::{
+            ELECTION_PREFERRED, ELECTION_UNCLEAN, ElectLeadersPartitionResult, ElectLeadersRequest,
+            ElectLeadersResponse, ElectLeadersTopicResult,
+        },
+        request::Request,
+    },
+};
+
+/// Answers leader elections. Every partition's only replica is its leader,
+/// which is also its preferred replica, so there's never anything to elect
+/// and each existing partition succeeds as is.
+pub struct ElectLeadersHandler {
+    logs: Arc<LogStore>,
+}
+
+impl ElectLeadersHandler {
+    pub fn new(logs: Arc<LogStore>) -> Self {
+        Self { logs }
+    }
+
+    fn elect(&self, topic: &str, partition_id: i32) -> ElectLeadersPartitionResult {
+        let error_code = match self.logs.partition(topic, partition_id) {
+            Some(_) => ErrorCode::None,
+            None => ErrorCode::UnknownTopicOrPartition,
+        };
+        ElectLeadersPartitionResult::new(partition_id, error_code)
+    }
+}
+
+impl RequestHandler<ElectLeadersRequest> for ElectLeadersHandler {
+    async fn handle(
+        &self,
+        _header: &RequestHeader,
+        request: &ElectLeadersRequest,
+        _state: &mut ConnectionState,
+    ) -> Result<ElectLeadersResponse, io::Error> {
+        tracing::debug!("Handling ElectLeadersRequest");
+
+        if !matches!(request.election_type, ELECTION_PREFERRED | ELECTION_UNCLEAN) {
+            return Ok(request.error_response(ErrorCode::InvalidRequest));
+        }
+
+        let topic_partitions: Vec<(String, Vec<i32>)> = match &request.topic_partitions {
+            Some(topics) => topics
+                .iter()
+                .map(|topic| (topic.topic.clone(), topic.partitions.clone()))
+                .collect(),
+            None => self.logs.topics().into_iter().collect(),
+        };
+
+        let replica_election_results = topic_partitions
+            .into_iter()
+            .map(|(topic, partitions)| ElectLeadersTopicResult {
+                partition_results: partitions
+                    .into_iter()
+                    .map(|partition_id| self.elect(&topic, partition_id))
+                    .collect(),
+                topic,
+                tagged_fields: Default::default(),
+            })
+            .collect();
+
+        Ok(ElectLeadersResponse {
+            throttle_time_ms: 0,
+            error_code: ErrorCode::None,
+            replica_election_results,
+            tagged_fields: Default::default(),
+        })
+    }
+}
@@ -0,0 +1,47 @@
+use std::{io, sync::Arc};
+
+use crate::{
+    ConnectionState, RequestHeader,
+    protocol::{
+        error::ErrorCode,
+        handlers::RequestHandler,
+        messages::{EndTxnRequest, EndTxnResponse},
+        request::Request,
+    },
+    transactions::TransactionManager,
+};
+
+pub struct EndTxnHandler {
+    transactions: Arc<TransactionManager>,
+}
+
+impl EndTxnHandler {
+    pub fn new(transactions: Arc<TransactionManager>) -> Self {
+        Self { transactions }
+    }
+}
+
+impl RequestHandler<EndTxnRequest> for EndTxnHandler {
+    async fn handle(
+        &self,
+        header: &RequestHeader,
+        request: &EndTxnRequest,
+        _state: &mut ConnectionState,
+    ) -> Result<EndTxnResponse, io::Error> {
+        tracing::debug!("Handling EndTxnRequest");
+
+        let error_code = match self.transactions.end_transaction(
+            &request.transactional_id,
+            request.producer_id,
+            request.producer_epoch,
+            request.committed,
+        ) {
+            Ok(()) => ErrorCode::None,
+            // Versions before 2 predate PRODUCER_FENCED.
+            Err(ErrorCode::ProducerFenced) if header.version < 2 => ErrorCode::InvalidProducerEpoch,
+            Err(error_code) => error_code,
+        };
+
+        Ok(request.error_response(error_code))
+    }
+}
@@ -0,0 +1,236 @@
+use std::{io, sync::Arc, time::Duration};
+
+use bytes::BytesMut;
+use tokio::time::{self, Instant};
+
+use crate::{
+    ConnectionState, RequestHeader,
+    fetch_session::{FetchPosition, FetchSessionCache},
+    log::{LogStore, RecordBatch},
+    protocol::{
+        error::ErrorCode,
+        handlers::RequestHandler,
+        messages::{
+            FetchAbortedTransaction, FetchRequest, FetchResponse, FetchResponsePartition,
+            FetchResponseTopic,
+        },
+        request::Request,
+    },
+};
+
+/// The isolation level of fetches that only see committed records.
+const READ_COMMITTED: i8 = 1;
+
+/// How often a waiting fetch checks the logs for new records.
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Reads records from the partition logs.
+///
+/// Fetches wait for `min_bytes` of records to arrive, polling the logs until
+/// the request's `max_wait_ms` deadline passes, and are answered right away
+/// if a partition fails. Incremental fetches only get back the
+/// partitions that have records, an error, or a high watermark that moved
+/// since the session's previous response. Fetches at the read_committed
+/// isolation level stop at the last stable offset, before any transaction
+/// that's still open, and are told which of the transactions they read were
+/// aborted.
+pub struct FetchHandler {
+    logs: Arc<LogStore>,
+    sessions: Arc<FetchSessionCache>,
+}
+
+impl FetchHandler {
+    pub fn new(logs: Arc<LogStore>, sessions: Arc<FetchSessionCache>) -> Self {
+        Self { logs, sessions }
+    }
+
+    /// Reads a partition from `position`, taking no more than `remaining`
+    /// bytes off the response's budget.
+    fn fetch_partition(
+        &self,
+        topic: &str,
+        partition: i32,
+        position: FetchPosition,
+        read_committed: bool,
+        remaining: &mut usize,
+    ) -> FetchResponsePartition {
+        let Some(log) = self.logs.partition(topic, partition) else {
+            return FetchResponsePartition::error(partition, ErrorCode::UnknownTopicOrPartition);
+        };
+
+        // Taken before reading, so that a transaction ending in between can't
+        // expose batches past the offset reported.
+        let last_stable_offset = log.last_stable_offset();
+
+        let mut batches = if *remaining == 0 {
+            vec![]
+        } else {
+            let max_bytes = (position.max_bytes.max(0) as usize).min(*remaining);
+            match log.read(position.fetch_offset, max_bytes) {
+                Ok(batches) => batches,
+                Err(err) => {
+                    tracing::debug!(topic, partition, "Failed to read partition: {}", err);
+                    return FetchResponsePartition::error(partition, err.error_code());
+                }
+            }
+        };
+
+        let aborted_transactions = read_committed.then(|| {
+            batches.retain(|batch| batch.base_offset() < last_stable_offset);
+
+            let to_offset = batches
+                .last()
+                .map_or(position.fetch_offset, |batch| batch.last_offset() + 1);
+            log.aborted_transactions(position.fetch_offset, to_offset)
+                .into_iter()
+                .map(|txn| FetchAbortedTransaction {
+                    producer_id: txn.producer_id,
+                    first_offset: txn.first_offset,
+                    tagged_fields: Default::default(),
+                })
+                .collect()
+        });
+
+        let mut records = BytesMut::with_capacity(batches.iter().map(RecordBatch::len).sum());
+        for batch in &batches {
+            records.extend_from_slice(batch.as_bytes());
+        }
+        *remaining = remaining.saturating_sub(records.len());
+
+        let high_watermark = log.latest_offset();
+        FetchResponsePartition {
+            partition_index: partition,
+            error_code: ErrorCode::None,
+            high_watermark,
+            last_stable_offset,
+            log_start_offset: log.earliest_offset(),
+            aborted_transactions,
+            preferred_read_replica: -1,
+            records: records.freeze(),
+            tagged_fields: Default::default(),
+        }
+    }
+}
+
+impl RequestHandler<FetchRequest> for FetchHandler {
+    async fn handle(
+        &self,
+        _header: &RequestHeader,
+        request: &FetchRequest,
+        state: &mut ConnectionState,
+    ) -> Result<FetchResponse, io::Error> {
+        tracing::debug!("Handling FetchRequest");
+
+        let requested = request.topics.iter().flat_map(|topic| {
+            topic.partitions.iter().map(|partition| {
+                let position = FetchPosition {
+                    fetch_offset: partition.fetch_offset,
+                    max_bytes: partition.partition_max_bytes,
+                };
+                ((topic.topic.clone(), partition.partition), position)
+            })
+        });
+
+        let (session_id, incremental, partitions) = match (
+            request.session_id,
+            request.session_epoch,
+        ) {
+            (session_id, -1) => {
+                self.sessions.remove(session_id);
+                (0, false, requested.collect::<Vec<_>>())
+            }
+            (session_id, 0) => {
+                self.sessions.remove(session_id);
+                let partitions: Vec<_> = requested.collect();
+                let session_id = self.sessions.create(partitions.iter().cloned());
+                (session_id, false, partitions)
+            }
+            (session_id, session_epoch) => {
+                let forgotten = request.forgotten_topics_data.iter().flat_map(|topic| {
+                    topic
+                        .partitions
+                        .iter()
+                        .map(|&partition| (topic.topic.clone(), partition))
+                });
+
+                match self
+                    .sessions
+                    .update(session_id, session_epoch, requested, forgotten)
+                {
+                    Ok(partitions) => (session_id, true, partitions),
+                    Err(error_code) => {
+                        tracing::debug!(session_id, session_epoch, %error_code, "Rejecting fetch");
+                        return Ok(FetchRequest::decode_error_response(error_code));
+                    }
+                }
+            }
+        };
+
+        let read_committed = request.isolation_level == READ_COMMITTED;
+        let deadline = state.deadline().map(Instant::from_std);
+        let fetched = loop {
+            let mut remaining = request.max_bytes.max(0) as usize;
+            let fetched: Vec<_> = partitions
+                .iter()
+                .map(|((topic, partition), position)| {
+                    self.fetch_partition(
+                        topic,
+                        *partition,
+                        *position,
+                        read_committed,
+                        &mut remaining,
+                    )
+                })
+                .collect();
+
+            let bytes: usize = fetched.iter().map(|response| response.records.len()).sum();
+            let failed = fetched
+                .iter()
+                .any(|response| response.error_code != ErrorCode::None);
+            let now = Instant::now();
+            match deadline {
+                Some(deadline)
+                    if now < deadline && !failed && bytes < request.min_bytes.max(0) as usize =>
+                {
+                    time::sleep_until(deadline.min(now + POLL_INTERVAL)).await;
+                }
+                _ => break fetched,
+            }
+        };
+
+        let mut responses: Vec<FetchResponseTopic> = Vec::new();
+        for (((topic, partition), _), response) in partitions.into_iter().zip(fetched) {
+            let changed = session_id == 0
+                || self.sessions.update_high_watermark(
+                    session_id,
+                    &topic,
+                    partition,
+                    response.high_watermark,
+                );
+            if incremental
+                && !changed
+                && response.records.is_empty()
+                && response.error_code == ErrorCode::None
+            {
+                continue;
+            }
+
+            match responses.last_mut() {
+                Some(last) if last.topic == topic => last.partitions.push(response),
+                _ => responses.push(FetchResponseTopic {
+                    topic,
+                    partitions: vec![response],
+                    tagged_fields: Default::default(),
+                }),
+            }
+        }
+
+        Ok(FetchResponse {
+            throttle_time_ms: 0,
+            error_code: ErrorCode::None,
+            session_id,
+            responses,
+            tagged_fields: Default::default(),
+        })
+    }
+}
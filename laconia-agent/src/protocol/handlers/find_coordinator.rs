@@ -1,21 +1,73 @@
-use std::io;
+use std::{io, sync::Arc};
 
 use crate::{
-    ConnectionState,
+    ConnectionState, RequestHeader,
+    cluster::ClusterInfo,
     protocol::{
+        error::ErrorCode,
         handlers::RequestHandler,
-        messages::{FindCoordinatorRequest, FindCoordinatorResponse},
+        messages::{
+            COORDINATOR_GROUP, COORDINATOR_TRANSACTION, FindCoordinatorRequest,
+            FindCoordinatorResponse, FindCoordinatorResponseCoordinator,
+        },
+        request::Request,
     },
 };
 
-pub struct FindCoordinatorHandler;
+/// Points clients at the coordinator of their consumer groups and
+/// transactional ids. This broker holds every group's offsets and every
+/// transaction, so it's the coordinator of all of them.
+pub struct FindCoordinatorHandler {
+    cluster: Arc<ClusterInfo>,
+}
+
+impl FindCoordinatorHandler {
+    pub fn new(cluster: Arc<ClusterInfo>) -> Self {
+        Self { cluster }
+    }
+}
 
 impl RequestHandler<FindCoordinatorRequest> for FindCoordinatorHandler {
     async fn handle(
         &self,
-        request: FindCoordinatorRequest,
-        state: &mut ConnectionState,
+        _header: &RequestHeader,
+        request: &FindCoordinatorRequest,
+        _state: &mut ConnectionState,
     ) -> Result<FindCoordinatorResponse, io::Error> {
-        unimplemented!();
+        tracing::debug!("Handling FindCoordinatorRequest");
+
+        if !matches!(
+            request.key_type,
+            COORDINATOR_GROUP | COORDINATOR_TRANSACTION
+        ) {
+            tracing::debug!(key_type = request.key_type, "Unknown coordinator key type");
+            return Ok(request.error_response(ErrorCode::InvalidRequest));
+        }
+
+        let coordinator = self.cluster.brokers.local();
+        let coordinators = request
+            .coordinator_keys
+            .iter()
+            .map(|key| FindCoordinatorResponseCoordinator {
+                key: key.clone(),
+                node_id: coordinator.node_id,
+                host: coordinator.host.clone(),
+                port: coordinator.port,
+                error_code: ErrorCode::None,
+                error_message: String::new(),
+                tagged_fields: Default::default(),
+            })
+            .collect();
+
+        Ok(FindCoordinatorResponse {
+            throttle_time_ms: 0,
+            error_code: ErrorCode::None,
+            error_message: String::new(),
+            node_id: coordinator.node_id,
+            host: coordinator.host.clone(),
+            port: coordinator.port,
+            coordinators,
+            tagged_fields: Default::default(),
+        })
     }
 }
@@ -0,0 +1,111 @@
+use std::{io, sync::Arc};
+
+use crate::{
+    ConnectionState, RequestHeader,
+    configs::{ConfigOp, ConfigStore, ResourceType},
+    protocol::{
+        error::ErrorCode,
+        handlers::RequestHandler,
+        messages::{
+            IncrementalAlterConfigsRequest, IncrementalAlterConfigsResource,
+            IncrementalAlterConfigsResponse, IncrementalAlterConfigsResult,
+        },
+    },
+};
+
+pub struct IncrementalAlterConfigsHandler {
+    configs: Arc<ConfigStore>,
+}
+
+impl IncrementalAlterConfigsHandler {
+    pub fn new(configs: Arc<ConfigStore>) -> Self {
+        Self { configs }
+    }
+
+    fn alter(
+        &self,
+        resource: &IncrementalAlterConfigsResource,
+        validate_only: bool,
+    ) -> Result<(), (ErrorCode, String)> {
+        match ResourceType::from_i8(resource.resource_type) {
+            Some(ResourceType::Topic) if resource.resource_name.is_empty() => {
+                return Err((
+                    ErrorCode::InvalidTopicException,
+                    "topic name must not be empty".to_string(),
+                ));
+            }
+            Some(ResourceType::Topic) => {}
+            Some(ResourceType::Broker) => {
+                return Err((
+                    ErrorCode::InvalidRequest,
+                    "broker configs are read-only".to_string(),
+                ));
+            }
+            None => {
+                return Err((
+                    ErrorCode::InvalidRequest,
+                    format!("unsupported resource type: {}", resource.resource_type),
+                ));
+            }
+        }
+
+        let changes = resource
+            .configs
+            .iter()
+            .map(|config| {
+                let op = match config.config_operation {
+                    0 if config.value.is_empty() => {
+                        return Err(format!("missing value for config: {}", config.name));
+                    }
+                    0 => ConfigOp::Set(config.value.clone()),
+                    1 => ConfigOp::Delete,
+                    2 => ConfigOp::Append(config.value.clone()),
+                    op => return Err(format!("unsupported config operation: {}", op)),
+                };
+                Ok((config.name.clone(), op))
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|err| (ErrorCode::InvalidConfig, err))?;
+
+        self.configs
+            .alter_topic_configs(&resource.resource_name, &changes, validate_only)
+            .map_err(|err| (ErrorCode::InvalidConfig, err))
+    }
+}
+
+impl RequestHandler<IncrementalAlterConfigsRequest> for IncrementalAlterConfigsHandler {
+    async fn handle(
+        &self,
+        _header: &RequestHeader,
+        request: &IncrementalAlterConfigsRequest,
+        _state: &mut ConnectionState,
+    ) -> Result<IncrementalAlterConfigsResponse, io::Error> {
+        tracing::debug!("Handling IncrementalAlterConfigsRequest");
+
+        let responses = request
+            .resources
+            .iter()
+            .map(|resource| {
+                let (error_code, error_message) = match self.alter(resource, request.validate_only)
+                {
+                    Ok(()) => (ErrorCode::None, String::new()),
+                    Err(err) => err,
+                };
+
+                IncrementalAlterConfigsResult {
+                    error_code,
+                    error_message,
+                    resource_type: resource.resource_type,
+                    resource_name: resource.resource_name.clone(),
+                    tagged_fields: Default::default(),
+                }
+            })
+            .collect();
+
+        Ok(IncrementalAlterConfigsResponse {
+            throttle_time_ms: 0,
+            responses,
+            tagged_fields: Default::default(),
+        })
+    }
+}
@@ -0,0 +1,49 @@
+use std::{io, sync::Arc};
+
+use crate::{
+    ConnectionState, RequestHeader,
+    protocol::{
+        error::ErrorCode,
+        handlers::RequestHandler,
+        messages::{InitProducerIdRequest, InitProducerIdResponse},
+        request::Request,
+    },
+    transactions::TransactionManager,
+};
+
+pub struct InitProducerIdHandler {
+    transactions: Arc<TransactionManager>,
+}
+
+impl InitProducerIdHandler {
+    pub fn new(transactions: Arc<TransactionManager>) -> Self {
+        Self { transactions }
+    }
+}
+
+impl RequestHandler<InitProducerIdRequest> for InitProducerIdHandler {
+    async fn handle(
+        &self,
+        _header: &RequestHeader,
+        request: &InitProducerIdRequest,
+        _state: &mut ConnectionState,
+    ) -> Result<InitProducerIdResponse, io::Error> {
+        tracing::debug!("Handling InitProducerIdRequest");
+
+        let transactional_id = Some(request.transactional_id.as_str()).filter(|id| !id.is_empty());
+
+        if transactional_id.is_some() && request.transaction_timeout_ms <= 0 {
+            return Ok(request.error_response(ErrorCode::InvalidTransactionTimeout));
+        }
+
+        let (producer_id, producer_epoch) = self.transactions.init_producer_id(transactional_id);
+
+        Ok(InitProducerIdResponse {
+            throttle_time_ms: 0,
+            error_code: ErrorCode::None,
+            producer_id,
+            producer_epoch,
+            tagged_fields: Default::default(),
+        })
+    }
+}
@@ -1,29 +1,174 @@
-use std::io;
+use std::{io, sync::Arc};
+
+use uuid::Uuid;
 
 use crate::{
-    ConnectionState,
+    ConnectionState, RequestHeader,
+    cluster::ClusterInfo,
+    configs::ConfigStore,
+    log::LogStore,
     protocol::{
+        error::ErrorCode,
         handlers::RequestHandler,
-        messages::{MetadataRequest, MetadataResponse},
+        messages::{
+            MetadataRequest, MetadataResponse, MetadataResponseBrokers, MetadataResponseTopic,
+            MetadataResponseTopicPartition,
+        },
     },
 };
 
-pub struct MetadataHandler;
+/// Describes the brokers and the topics in the partition logs. A request
+/// without a topic list describes every topic, while one with an empty list
+/// describes none. Requested topics that don't exist are described with an
+/// error, unless both the request and the broker's
+/// `auto.create.topics.enable` config allow creating them.
+pub struct MetadataHandler {
+    logs: Arc<LogStore>,
+    cluster: Arc<ClusterInfo>,
+    configs: Arc<ConfigStore>,
+}
+
+impl MetadataHandler {
+    pub fn new(logs: Arc<LogStore>, cluster: Arc<ClusterInfo>, configs: Arc<ConfigStore>) -> Self {
+        Self {
+            logs,
+            cluster,
+            configs,
+        }
+    }
+
+    /// Creates `name` with the broker's `num.partitions` config of
+    /// partitions, or a single one if it isn't set.
+    fn create_topic(&self, name: &str) -> MetadataResponseTopic {
+        if !is_valid_topic_name(name) {
+            return unknown_topic(name, ErrorCode::InvalidTopicException);
+        }
+
+        let num_partitions = self
+            .configs
+            .broker_config::<i32>("num.partitions")
+            .filter(|&num_partitions| num_partitions > 0)
+            .unwrap_or(1);
+        let partitions: Vec<i32> = (0..num_partitions).collect();
+
+        for &partition in &partitions {
+            if let Err(err) = self.logs.get_or_create(name, partition) {
+                tracing::warn!(
+                    topic = name,
+                    partition,
+                    "Failed to auto-create topic: {}",
+                    err
+                );
+                return unknown_topic(name, err.error_code());
+            }
+        }
+
+        tracing::info!(topic = name, num_partitions, "Auto-created topic");
+        self.describe_topic(name, &partitions)
+    }
+
+    fn describe_topic(&self, name: &str, partitions: &[i32]) -> MetadataResponseTopic {
+        let partitions = partitions
+            .iter()
+            .map(|&partition| {
+                let log = self.logs.partition(name, partition);
+                let leader_id = self.cluster.leader(name, partition);
+                MetadataResponseTopicPartition::single_broker(partition, log.as_deref(), leader_id)
+            })
+            .collect();
+
+        MetadataResponseTopic {
+            error_code: ErrorCode::None,
+            name: name.to_string(),
+            topic_id: self.logs.topic_id(name).unwrap_or_default(),
+            is_internal: false,
+            partitions,
+            topic_authorized_operations: i32::MIN,
+            tagged_fields: Default::default(),
+        }
+    }
+}
 
 impl RequestHandler<MetadataRequest> for MetadataHandler {
     async fn handle(
         &self,
-        request: MetadataRequest,
-        state: &mut ConnectionState,
+        _header: &RequestHeader,
+        request: &MetadataRequest,
+        _state: &mut ConnectionState,
     ) -> Result<MetadataResponse, io::Error> {
-        println!("Handling MetadataRequest");
+        tracing::debug!("Handling MetadataRequest");
+
+        let auto_create = request.allow_auto_topic_creation
+            && self
+                .configs
+                .broker_config("auto.create.topics.enable")
+                .unwrap_or(false);
+
+        let all_topics = self.logs.topics();
+        let topics = match &request.topics {
+            None => all_topics
+                .iter()
+                .map(|(name, partitions)| self.describe_topic(name, partitions))
+                .collect(),
+            Some(requested) => requested
+                .iter()
+                .map(|topic| match all_topics.get(&topic.name) {
+                    Some(partitions) => self.describe_topic(&topic.name, partitions),
+                    None if auto_create => self.create_topic(&topic.name),
+                    None => MetadataResponseTopic {
+                        topic_id: topic.topic_id,
+                        ..unknown_topic(&topic.name, ErrorCode::UnknownTopicOrPartition)
+                    },
+                })
+                .collect(),
+        };
+
+        let brokers = self
+            .cluster
+            .brokers
+            .iter()
+            .map(|broker| MetadataResponseBrokers {
+                node_id: broker.node_id,
+                host: broker.host.clone(),
+                port: broker.port,
+                rack: broker.rack.clone(),
+                tagged_fields: Default::default(),
+            })
+            .collect();
+
         Ok(MetadataResponse {
             throttle_time_ms: 0,
-            brokers: vec![],
-            cluster_id: "".to_string(),
-            controller_id: 0,
-            topics: vec![],
+            brokers,
+            cluster_id: self.cluster.cluster_id.clone(),
+            controller_id: self.cluster.controller_id,
+            topics,
             tagged_fields: Default::default(),
         })
     }
 }
+
+/// A topic described with no partitions, failed with `error_code`.
+fn unknown_topic(name: &str, error_code: ErrorCode) -> MetadataResponseTopic {
+    MetadataResponseTopic {
+        error_code,
+        name: name.to_string(),
+        topic_id: Uuid::nil(),
+        is_internal: false,
+        partitions: vec![],
+        topic_authorized_operations: i32::MIN,
+        tagged_fields: Default::default(),
+    }
+}
+
+/// Whether `name` is a legal Kafka topic name. Partition logs are stored in
+/// directories named after their topic, so this also keeps names from
+/// escaping the data directory.
+fn is_valid_topic_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.len() <= 249
+        && name != "."
+        && name != ".."
+        && name
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'.' | b'_' | b'-'))
+}
@@ -0,0 +1,74 @@
+use std::{io, sync::Arc};
+
+use crate::{
+    ConnectionState, RequestHeader,
+    offsets::OffsetStore,
+    protocol::{
+        error::ErrorCode,
+        handlers::RequestHandler,
+        messages::{
+            OffsetDeleteRequest, OffsetDeleteResponse, OffsetDeleteResponsePartition,
+            OffsetDeleteResponseTopic,
+        },
+        request::Request,
+    },
+};
+
+/// Deletes committed offsets from the offset store. There's no group
+/// coordinator tracking subscriptions, so offsets are deleted even for topics
+/// the group still consumes.
+pub struct OffsetDeleteHandler {
+    offsets: Arc<OffsetStore>,
+}
+
+impl OffsetDeleteHandler {
+    pub fn new(offsets: Arc<OffsetStore>) -> Self {
+        Self { offsets }
+    }
+}
+
+impl RequestHandler<OffsetDeleteRequest> for OffsetDeleteHandler {
+    async fn handle(
+        &self,
+        _header: &RequestHeader,
+        request: &OffsetDeleteRequest,
+        _state: &mut ConnectionState,
+    ) -> Result<OffsetDeleteResponse, io::Error> {
+        tracing::debug!("Handling OffsetDeleteRequest");
+
+        if !self.offsets.has_group(&request.group_id) {
+            return Ok(request.error_response(ErrorCode::GroupIdNotFound));
+        }
+
+        // Deleting an offset that was never committed leaves nothing to do,
+        // so every partition succeeds.
+        let topics = request
+            .topics
+            .iter()
+            .map(|topic| OffsetDeleteResponseTopic {
+                name: topic.name.clone(),
+                partitions: topic
+                    .partitions
+                    .iter()
+                    .map(|partition| {
+                        self.offsets.delete(
+                            &request.group_id,
+                            &topic.name,
+                            partition.partition_index,
+                        );
+                        OffsetDeleteResponsePartition {
+                            partition_index: partition.partition_index,
+                            error_code: ErrorCode::None,
+                        }
+                    })
+                    .collect(),
+            })
+            .collect();
+
+        Ok(OffsetDeleteResponse {
+            error_code: ErrorCode::None,
+            throttle_time_ms: 0,
+            topics,
+        })
+    }
+}
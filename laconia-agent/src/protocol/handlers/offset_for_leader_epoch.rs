@@ -0,0 +1,92 @@
+use std::{cmp::Ordering, io, sync::Arc};
+
+use crate::{
+    ConnectionState, RequestHeader,
+    log::{LEADER_EPOCH, LogStore},
+    protocol::{
+        error::ErrorCode,
+        handlers::RequestHandler,
+        messages::{
+            EpochEndOffset, OffsetForLeaderEpochRequest, OffsetForLeaderEpochResponse,
+            OffsetForLeaderPartition, OffsetForLeaderTopicResult,
+        },
+    },
+};
+
+pub struct OffsetForLeaderEpochHandler {
+    logs: Arc<LogStore>,
+}
+
+impl OffsetForLeaderEpochHandler {
+    pub fn new(logs: Arc<LogStore>) -> Self {
+        Self { logs }
+    }
+
+    fn end_offset(&self, topic: &str, request: &OffsetForLeaderPartition) -> EpochEndOffset {
+        let partition = request.partition;
+        let Some(log) = self.logs.partition(topic, partition) else {
+            return EpochEndOffset::error(partition, ErrorCode::UnknownTopicOrPartition);
+        };
+
+        // A client ahead of us has heard of a leader we haven't, while one
+        // behind us is fenced. -1 skips the check.
+        if request.current_leader_epoch >= 0 {
+            match request.current_leader_epoch.cmp(&LEADER_EPOCH) {
+                Ordering::Greater => {
+                    return EpochEndOffset::error(partition, ErrorCode::UnknownLeaderEpoch);
+                }
+                Ordering::Less => {
+                    return EpochEndOffset::error(partition, ErrorCode::FencedLeaderEpoch);
+                }
+                Ordering::Equal => {}
+            }
+        }
+
+        // Epochs older than the first one have no end offset, while every
+        // later epoch hasn't started yet and so ends where the current one
+        // does.
+        let Some((leader_epoch, end_offset)) = log.end_offset_for_epoch(request.leader_epoch)
+        else {
+            return EpochEndOffset::error(partition, ErrorCode::None);
+        };
+
+        EpochEndOffset {
+            error_code: ErrorCode::None,
+            partition,
+            leader_epoch,
+            end_offset,
+            tagged_fields: Default::default(),
+        }
+    }
+}
+
+impl RequestHandler<OffsetForLeaderEpochRequest> for OffsetForLeaderEpochHandler {
+    async fn handle(
+        &self,
+        _header: &RequestHeader,
+        request: &OffsetForLeaderEpochRequest,
+        _state: &mut ConnectionState,
+    ) -> Result<OffsetForLeaderEpochResponse, io::Error> {
+        tracing::debug!("Handling OffsetForLeaderEpochRequest");
+
+        let topics = request
+            .topics
+            .iter()
+            .map(|topic| OffsetForLeaderTopicResult {
+                topic: topic.topic.clone(),
+                partitions: topic
+                    .partitions
+                    .iter()
+                    .map(|partition| self.end_offset(&topic.topic, partition))
+                    .collect(),
+                tagged_fields: Default::default(),
+            })
+            .collect();
+
+        Ok(OffsetForLeaderEpochResponse {
+            throttle_time_ms: 0,
+            topics,
+            tagged_fields: Default::default(),
+        })
+    }
+}
@@ -0,0 +1,31 @@
+use std::io;
+
+use crate::{
+    ConnectionState, RequestHeader,
+    protocol::{
+        error::ErrorCode,
+        handlers::RequestHandler,
+        messages::{SaslAuthenticateRequest, SaslAuthenticateResponse},
+        request::Request,
+    },
+};
+
+/// Rejects every SASL authentication attempt, since the agent has no SASL
+/// mechanisms enabled.
+pub struct SaslAuthenticateHandler;
+
+impl RequestHandler<SaslAuthenticateRequest> for SaslAuthenticateHandler {
+    async fn handle(
+        &self,
+        _header: &RequestHeader,
+        request: &SaslAuthenticateRequest,
+        _state: &mut ConnectionState,
+    ) -> Result<SaslAuthenticateResponse, io::Error> {
+        tracing::debug!("Handling SaslAuthenticateRequest");
+
+        Ok(SaslAuthenticateResponse {
+            error_message: "SASL authentication is not enabled".to_string(),
+            ..request.error_response(ErrorCode::SaslAuthenticationFailed)
+        })
+    }
+}
@@ -0,0 +1,66 @@
+use std::{io, sync::Arc};
+
+use crate::{
+    ConnectionState, RequestHeader,
+    offsets::CommittedOffset,
+    protocol::{
+        error::ErrorCode,
+        handlers::RequestHandler,
+        messages::{TxnOffsetCommitRequest, TxnOffsetCommitResponse},
+        request::Request,
+    },
+    transactions::TransactionManager,
+};
+
+pub struct TxnOffsetCommitHandler {
+    transactions: Arc<TransactionManager>,
+}
+
+impl TxnOffsetCommitHandler {
+    pub fn new(transactions: Arc<TransactionManager>) -> Self {
+        Self { transactions }
+    }
+}
+
+impl RequestHandler<TxnOffsetCommitRequest> for TxnOffsetCommitHandler {
+    async fn handle(
+        &self,
+        header: &RequestHeader,
+        request: &TxnOffsetCommitRequest,
+        _state: &mut ConnectionState,
+    ) -> Result<TxnOffsetCommitResponse, io::Error> {
+        tracing::debug!("Handling TxnOffsetCommitRequest");
+
+        let offsets = request.topics.iter().flat_map(|topic| {
+            topic.partitions.iter().map(|partition| {
+                (
+                    topic.name.clone(),
+                    partition.partition_index,
+                    CommittedOffset {
+                        offset: partition.committed_offset,
+                        leader_epoch: partition.committed_leader_epoch,
+                        metadata: partition.committed_metadata.clone(),
+                    },
+                )
+            })
+        });
+
+        // There's no group coordinator to check the generation and member id
+        // against, so the offsets are staged as long as the producer owns the
+        // transaction. They share its outcome, like AddPartitionsToTxn.
+        let error_code = match self.transactions.add_offsets(
+            &request.transactional_id,
+            request.producer_id,
+            request.producer_epoch,
+            &request.group_id,
+            offsets,
+        ) {
+            Ok(()) => ErrorCode::None,
+            // Versions before 3 predate PRODUCER_FENCED.
+            Err(ErrorCode::ProducerFenced) if header.version < 3 => ErrorCode::InvalidProducerEpoch,
+            Err(error_code) => error_code,
+        };
+
+        Ok(request.error_response(error_code))
+    }
+}
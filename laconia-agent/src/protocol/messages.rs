@@ -1,3 +1,6 @@
+mod fetch;
+pub use fetch::*;
+
 mod api_versions;
 pub use api_versions::*;
 
@@ -6,3 +9,60 @@ pub use metadata::*;
 
 mod find_coordinator;
 pub use find_coordinator::*;
+
+mod describe_configs;
+pub use describe_configs::*;
+
+mod incremental_alter_configs;
+pub use incremental_alter_configs::*;
+
+mod describe_cluster;
+pub use describe_cluster::*;
+
+mod describe_topic_partitions;
+pub use describe_topic_partitions::*;
+
+mod init_producer_id;
+pub use init_producer_id::*;
+
+mod offset_for_leader_epoch;
+pub use offset_for_leader_epoch::*;
+
+mod delete_records;
+pub use delete_records::*;
+
+mod describe_acls;
+pub use describe_acls::*;
+
+mod create_acls;
+pub use create_acls::*;
+
+mod delete_acls;
+pub use delete_acls::*;
+
+mod add_partitions_to_txn;
+pub use add_partitions_to_txn::*;
+
+mod end_txn;
+pub use end_txn::*;
+
+mod txn_offset_commit;
+pub use txn_offset_commit::*;
+
+mod controlled_shutdown;
+pub use controlled_shutdown::*;
+
+mod sasl_authenticate;
+pub use sasl_authenticate::*;
+
+mod elect_leaders;
+pub use elect_leaders::*;
+
+mod offset_delete;
+pub use offset_delete::*;
+
+mod describe_producers;
+pub use describe_producers::*;
+
+mod consumer_group_heartbeat;
+pub use consumer_group_heartbeat::*;
@@ -0,0 +1,230 @@
+use std::{collections::BTreeMap, io};
+
+use bytes::{Bytes, BytesMut};
+
+use crate::{
+    Message, VersionRange,
+    authorizer::{Operation, Resource},
+    protocol::{
+        DecodeError, Decoder, DecoderVersioned, Encoder, EncoderVersioned,
+        error::ErrorCode,
+        primitives::{ArrayRef, CompactArray, CompactArrayRef, CompactString, string_size_hint},
+        request::Request,
+        response::Response,
+    },
+};
+
+/// A producer adding partitions to its transaction. Versions 4 and up batch
+/// several transactions together and are only sent between brokers, so they
+/// aren't supported.
+#[derive(Debug)]
+pub struct AddPartitionsToTxnRequest {
+    pub transactional_id: String,
+    pub producer_id: i64,
+    pub producer_epoch: i16,
+    pub topics: Vec<AddPartitionsToTxnTopic>,
+    pub tagged_fields: BTreeMap<i32, Bytes>,
+}
+
+impl Message for AddPartitionsToTxnRequest {
+    const VERSIONS: VersionRange = VersionRange { min: 0, max: 3 };
+    const DEPRECATED_VERSIONS: Option<VersionRange> = None;
+
+    fn header_version(version: i16) -> i16 {
+        if version < 3 { 1 } else { 2 }
+    }
+}
+
+impl Request for AddPartitionsToTxnRequest {
+    type Response = AddPartitionsToTxnResponse;
+
+    fn error_response(&self, error_code: ErrorCode) -> AddPartitionsToTxnResponse {
+        let results = self
+            .topics
+            .iter()
+            .map(|topic| AddPartitionsToTxnTopicResult {
+                name: topic.name.clone(),
+                results: topic
+                    .partitions
+                    .iter()
+                    .map(|&partition_index| AddPartitionsToTxnPartitionResult {
+                        partition_index,
+                        error_code,
+                        tagged_fields: Default::default(),
+                    })
+                    .collect(),
+                tagged_fields: Default::default(),
+            })
+            .collect();
+
+        AddPartitionsToTxnResponse {
+            results,
+            ..Self::decode_error_response(error_code)
+        }
+    }
+
+    fn decode_error_response(_error_code: ErrorCode) -> AddPartitionsToTxnResponse {
+        AddPartitionsToTxnResponse {
+            throttle_time_ms: 0,
+            results: vec![],
+            tagged_fields: Default::default(),
+        }
+    }
+
+    fn operations(&self) -> Vec<(Operation, Resource)> {
+        let transactional_id = Resource::TransactionalId(self.transactional_id.clone());
+        std::iter::once((Operation::Write, transactional_id))
+            .chain(
+                self.topics
+                    .iter()
+                    .map(|topic| (Operation::Write, Resource::Topic(topic.name.clone()))),
+            )
+            .collect()
+    }
+}
+
+impl DecoderVersioned for AddPartitionsToTxnRequest {
+    fn decode(buf: &mut BytesMut, version: i16) -> Result<Self, io::Error> {
+        if !Self::VERSIONS.contains(version) {
+            return Err(DecodeError::unsupported("unsupported version").into());
+        }
+
+        let transactional_id = if version < 3 {
+            String::decode(buf)?
+        } else {
+            CompactString::decode(buf)?.0
+        };
+
+        let producer_id = i64::decode(buf)?;
+        let producer_epoch = i16::decode(buf)?;
+
+        let topics = if version < 3 {
+            Vec::<AddPartitionsToTxnTopic>::decode(buf, version)?
+        } else {
+            CompactArray::<AddPartitionsToTxnTopic>::decode(buf, version)?.0
+        };
+
+        let mut tagged_fields = BTreeMap::new();
+        if version > 2 {
+            tagged_fields = Decoder::decode(buf)?;
+        }
+
+        Ok(Self {
+            transactional_id,
+            producer_id,
+            producer_epoch,
+            topics,
+            tagged_fields,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct AddPartitionsToTxnTopic {
+    pub name: String,
+    pub partitions: Vec<i32>,
+    pub tagged_fields: BTreeMap<i32, Bytes>,
+}
+
+impl DecoderVersioned for AddPartitionsToTxnTopic {
+    fn decode(buf: &mut BytesMut, version: i16) -> Result<Self, io::Error> {
+        let (name, partitions) = if version < 3 {
+            (String::decode(buf)?, Vec::<i32>::decode(buf)?)
+        } else {
+            (
+                CompactString::decode(buf)?.0,
+                CompactArray::<i32>::decode(buf)?.0,
+            )
+        };
+
+        let mut tagged_fields = BTreeMap::new();
+        if version > 2 {
+            tagged_fields = Decoder::decode(buf)?;
+        }
+
+        Ok(Self {
+            name,
+            partitions,
+            tagged_fields,
+        })
+    }
+}
+
+pub struct AddPartitionsToTxnResponse {
+    pub throttle_time_ms: i32,
+    pub results: Vec<AddPartitionsToTxnTopicResult>,
+    pub tagged_fields: BTreeMap<i32, Bytes>,
+}
+
+impl EncoderVersioned for AddPartitionsToTxnResponse {
+    fn encode(&self, buf: &mut BytesMut, version: i16) -> Result<(), io::Error> {
+        self.throttle_time_ms.encode(buf)?;
+
+        if version < 3 {
+            ArrayRef(&self.results).encode(buf, version)?;
+        } else {
+            CompactArrayRef(&self.results).encode(buf, version)?;
+            self.tagged_fields.encode(buf)?;
+        }
+
+        Ok(())
+    }
+
+    fn size_hint(&self, version: i16) -> usize {
+        4 + CompactArrayRef(&self.results).size_hint(version) + 1
+    }
+}
+
+impl Response for AddPartitionsToTxnResponse {
+    fn set_throttle_time_ms(&mut self, throttle_time_ms: i32) {
+        self.throttle_time_ms = throttle_time_ms;
+    }
+}
+
+pub struct AddPartitionsToTxnTopicResult {
+    pub name: String,
+    pub results: Vec<AddPartitionsToTxnPartitionResult>,
+    pub tagged_fields: BTreeMap<i32, Bytes>,
+}
+
+impl EncoderVersioned for AddPartitionsToTxnTopicResult {
+    fn encode(&self, buf: &mut BytesMut, version: i16) -> Result<(), io::Error> {
+        if version < 3 {
+            self.name.encode(buf)?;
+            ArrayRef(&self.results).encode(buf, version)?;
+        } else {
+            CompactString(self.name.clone()).encode(buf)?;
+            CompactArrayRef(&self.results).encode(buf, version)?;
+            self.tagged_fields.encode(buf)?;
+        }
+
+        Ok(())
+    }
+
+    fn size_hint(&self, version: i16) -> usize {
+        string_size_hint(&self.name) + CompactArrayRef(&self.results).size_hint(version) + 1
+    }
+}
+
+pub struct AddPartitionsToTxnPartitionResult {
+    pub partition_index: i32,
+    pub error_code: ErrorCode,
+    pub tagged_fields: BTreeMap<i32, Bytes>,
+}
+
+impl EncoderVersioned for AddPartitionsToTxnPartitionResult {
+    fn encode(&self, buf: &mut BytesMut, version: i16) -> Result<(), io::Error> {
+        self.partition_index.encode(buf)?;
+        self.error_code.encode(buf)?;
+
+        if version > 2 {
+            self.tagged_fields.encode(buf)?;
+        }
+
+        Ok(())
+    }
+
+    fn size_hint(&self, _version: i16) -> usize {
+        4 + 2 + 1
+    }
+}
@@ -5,8 +5,12 @@ use bytes::{BufMut, Bytes, BytesMut};
 use crate::{
     Message, VersionRange,
     protocol::{
-        Decoder, DecoderVersioned, Encoder, EncoderVersioned,
-        primitives::{CompactArrayRef, CompactString},
+        DecodeError, Decoder, DecoderVersioned, Encoder, EncoderVersioned,
+        error::ErrorCode,
+        primitives::{
+            ArrayRef, CompactArray, CompactArrayRef, CompactString, TaggedFields,
+            decode_tagged_if_flexible,
+        },
         request::Request,
         response::Response,
     },
@@ -42,9 +46,17 @@ impl DecoderVersioned for ApiVersionsRequest {
             CompactString::decode(buf)?.0
         };
 
-        let mut tagged_fields = BTreeMap::new();
-        if version > 2 {
-            tagged_fields = Decoder::decode(buf)?;
+        let tagged_fields = decode_tagged_if_flexible(buf, version, 3)?;
+
+        // Versions before 3 have an empty body, so anything left over points
+        // at a client encoding a newer version than it claims to.
+        if version < 3 && !buf.is_empty() {
+            return Err(DecodeError::invalid(format!(
+                "{} trailing bytes after ApiVersions v{} request",
+                buf.len(),
+                version
+            ))
+            .into());
         }
 
         Ok(Self {
@@ -55,31 +67,153 @@ impl DecoderVersioned for ApiVersionsRequest {
     }
 }
 
+impl EncoderVersioned for ApiVersionsRequest {
+    fn encode(&self, buf: &mut BytesMut, version: i16) -> Result<(), io::Error> {
+        if version > 2 {
+            CompactString(self.client_software_name.clone()).encode(buf)?;
+            CompactString(self.client_software_version.clone()).encode(buf)?;
+            self.tagged_fields.encode(buf)?;
+        }
+
+        Ok(())
+    }
+}
+
 impl Request for ApiVersionsRequest {
     type Response = ApiVersionsResponse;
+
+    fn error_response(&self, error_code: ErrorCode) -> ApiVersionsResponse {
+        Self::decode_error_response(error_code)
+    }
+
+    fn decode_error_response(error_code: ErrorCode) -> ApiVersionsResponse {
+        ApiVersionsResponse {
+            error_code,
+            api_keys: vec![],
+            throttle_time_ms: 0,
+            supported_features: vec![],
+            finalized_features_epoch: -1,
+            finalized_features: vec![],
+            tagged_fields: Default::default(),
+        }
+    }
 }
 
+/// Tags of the feature fields, which versions 3 and up carry as tagged
+/// fields.
+const SUPPORTED_FEATURES_TAG: i32 = 0;
+const FINALIZED_FEATURES_EPOCH_TAG: i32 = 1;
+const FINALIZED_FEATURES_TAG: i32 = 2;
+
+#[derive(Debug)]
 pub struct ApiVersionsResponse {
-    pub error_code: i16,
+    pub error_code: ErrorCode,
     pub api_keys: Vec<ApiVersionsApiKeys>,
     pub throttle_time_ms: i32,
+    /// The features the broker supports. Only sent in versions 3 and up.
+    pub supported_features: Vec<ApiVersionsSupportedFeature>,
+    /// The epoch of the finalized features, or -1 if none are.
+    pub finalized_features_epoch: i64,
+    /// The level each feature is finalized at. Only sent in versions 3 and
+    /// up.
+    pub finalized_features: Vec<ApiVersionsFinalizedFeature>,
     pub tagged_fields: BTreeMap<i32, Bytes>,
 }
 
 impl EncoderVersioned for ApiVersionsResponse {
     fn encode(&self, buf: &mut BytesMut, version: i16) -> Result<(), io::Error> {
-        buf.put_i16(self.error_code);
-        CompactArrayRef(&self.api_keys).encode(buf, version)?;
-        buf.put_i32(self.throttle_time_ms);
-        self.tagged_fields.encode(buf)?;
+        self.error_code.encode(buf)?;
+
+        if version < 3 {
+            ArrayRef(&self.api_keys).encode(buf, version)?;
+        } else {
+            CompactArrayRef(&self.api_keys).encode(buf, version)?;
+        }
+
+        if version > 0 {
+            buf.put_i32(self.throttle_time_ms);
+        }
+
+        if version > 2 {
+            let mut tagged_fields = self.tagged_fields.clone();
+            if !self.supported_features.is_empty() {
+                tagged_fields.encode_tag(
+                    SUPPORTED_FEATURES_TAG,
+                    &CompactArrayRef(&self.supported_features),
+                )?;
+            }
+            if self.finalized_features_epoch != -1 {
+                tagged_fields
+                    .encode_tag(FINALIZED_FEATURES_EPOCH_TAG, &self.finalized_features_epoch)?;
+            }
+            if !self.finalized_features.is_empty() {
+                tagged_fields.encode_tag(
+                    FINALIZED_FEATURES_TAG,
+                    &CompactArrayRef(&self.finalized_features),
+                )?;
+            }
+            tagged_fields.encode(buf)?;
+        }
 
         Ok(())
     }
 }
 
-impl Response for ApiVersionsResponse {}
+/// Lets clients read the response. Whatever the request's version, an
+/// `UnsupportedVersion` error is answered in v0.
+impl DecoderVersioned for ApiVersionsResponse {
+    fn decode(buf: &mut BytesMut, version: i16) -> Result<Self, io::Error> {
+        let error_code = ErrorCode::decode(buf)?;
+
+        let api_keys = if version < 3 {
+            Vec::<ApiVersionsApiKeys>::decode(buf, version)?
+        } else {
+            CompactArray::<ApiVersionsApiKeys>::decode(buf, version)?.0
+        };
+
+        let throttle_time_ms = if version > 0 { i32::decode(buf)? } else { 0 };
+
+        let mut tagged_fields = decode_tagged_if_flexible(buf, version, 3)?;
+
+        let supported_features = tagged_fields
+            .decode_tag::<CompactArray<ApiVersionsSupportedFeature>>(SUPPORTED_FEATURES_TAG)
+            .transpose()?
+            .map_or_else(Vec::new, |features| features.0);
+        let finalized_features_epoch = tagged_fields
+            .decode_tag(FINALIZED_FEATURES_EPOCH_TAG)
+            .transpose()?
+            .unwrap_or(-1);
+        let finalized_features = tagged_fields
+            .decode_tag::<CompactArray<ApiVersionsFinalizedFeature>>(FINALIZED_FEATURES_TAG)
+            .transpose()?
+            .map_or_else(Vec::new, |features| features.0);
+        for tag in [
+            SUPPORTED_FEATURES_TAG,
+            FINALIZED_FEATURES_EPOCH_TAG,
+            FINALIZED_FEATURES_TAG,
+        ] {
+            tagged_fields.remove(&tag);
+        }
+
+        Ok(Self {
+            error_code,
+            api_keys,
+            throttle_time_ms,
+            supported_features,
+            finalized_features_epoch,
+            finalized_features,
+            tagged_fields,
+        })
+    }
+}
+
+impl Response for ApiVersionsResponse {
+    fn set_throttle_time_ms(&mut self, throttle_time_ms: i32) {
+        self.throttle_time_ms = throttle_time_ms;
+    }
+}
 
-#[derive(Clone)]
+#[derive(Debug, Clone)]
 pub struct ApiVersionsApiKeys {
     pub api_key: i16,
     pub min_version: i16,
@@ -92,8 +226,88 @@ impl EncoderVersioned for ApiVersionsApiKeys {
         buf.put_i16(self.api_key);
         buf.put_i16(self.min_version);
         buf.put_i16(self.max_version);
+
+        if version > 2 {
+            self.tagged_fields.encode(buf)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl DecoderVersioned for ApiVersionsApiKeys {
+    fn decode(buf: &mut BytesMut, version: i16) -> Result<Self, io::Error> {
+        let api_key = i16::decode(buf)?;
+        let min_version = i16::decode(buf)?;
+        let max_version = i16::decode(buf)?;
+
+        let tagged_fields = decode_tagged_if_flexible(buf, version, 3)?;
+
+        Ok(Self {
+            api_key,
+            min_version,
+            max_version,
+            tagged_fields,
+        })
+    }
+}
+
+/// A feature the broker supports, with the range of levels it can run at.
+#[derive(Debug, Clone)]
+pub struct ApiVersionsSupportedFeature {
+    pub name: String,
+    pub min_version: i16,
+    pub max_version: i16,
+    pub tagged_fields: BTreeMap<i32, Bytes>,
+}
+
+impl Encoder for ApiVersionsSupportedFeature {
+    fn encode(&self, buf: &mut BytesMut) -> Result<(), io::Error> {
+        CompactString(self.name.clone()).encode(buf)?;
+        buf.put_i16(self.min_version);
+        buf.put_i16(self.max_version);
         self.tagged_fields.encode(buf)?;
+        Ok(())
+    }
+}
 
+impl Decoder for ApiVersionsSupportedFeature {
+    fn decode(buf: &mut BytesMut) -> Result<Self, io::Error> {
+        Ok(Self {
+            name: CompactString::decode(buf)?.0,
+            min_version: i16::decode(buf)?,
+            max_version: i16::decode(buf)?,
+            tagged_fields: Decoder::decode(buf)?,
+        })
+    }
+}
+
+/// The level a feature is finalized at.
+#[derive(Debug, Clone)]
+pub struct ApiVersionsFinalizedFeature {
+    pub name: String,
+    pub max_version_level: i16,
+    pub min_version_level: i16,
+    pub tagged_fields: BTreeMap<i32, Bytes>,
+}
+
+impl Encoder for ApiVersionsFinalizedFeature {
+    fn encode(&self, buf: &mut BytesMut) -> Result<(), io::Error> {
+        CompactString(self.name.clone()).encode(buf)?;
+        buf.put_i16(self.max_version_level);
+        buf.put_i16(self.min_version_level);
+        self.tagged_fields.encode(buf)?;
         Ok(())
     }
 }
+
+impl Decoder for ApiVersionsFinalizedFeature {
+    fn decode(buf: &mut BytesMut) -> Result<Self, io::Error> {
+        Ok(Self {
+            name: CompactString::decode(buf)?.0,
+            max_version_level: i16::decode(buf)?,
+            min_version_level: i16::decode(buf)?,
+            tagged_fields: Decoder::decode(buf)?,
+        })
+    }
+}
@@ -0,0 +1,263 @@
+use std::{collections::BTreeMap, io};
+
+use bytes::{Bytes, BytesMut};
+use uuid::Uuid;
+
+use crate::{
+    Message, VersionRange,
+    authorizer::{Operation, Resource},
+    protocol::{
+        DecodeError, Decoder, DecoderVersioned, Encoder, EncoderVersioned,
+        error::ErrorCode,
+        primitives::{
+            CompactArray, CompactArrayRef, CompactNullableArray, CompactNullableArrayRef,
+            CompactNullableString, CompactString,
+        },
+        request::Request,
+        response::Response,
+    },
+};
+
+/// Joins, stays in or leaves a consumer group using the consumer rebalance
+/// protocol of KIP-848, where the coordinator assigns partitions and members
+/// only heartbeat.
+#[derive(Debug)]
+pub struct ConsumerGroupHeartbeatRequest {
+    pub group_id: String,
+    /// Empty when joining, for the coordinator to pick one.
+    pub member_id: String,
+    /// 0 to join, -1 to leave, -2 for a static member to leave for now, and
+    /// the member's current epoch otherwise.
+    pub member_epoch: i32,
+    /// Empty unless the member is static.
+    pub instance_id: String,
+    pub rack_id: String,
+    pub rebalance_timeout_ms: i32,
+    /// The topics the member subscribes to, or `None` if unchanged since the
+    /// last heartbeat.
+    pub subscribed_topic_names: Option<Vec<String>>,
+    pub server_assignor: String,
+    /// The partitions the member owns, or `None` if unchanged since the last
+    /// heartbeat.
+    pub topic_partitions: Option<Vec<ConsumerGroupHeartbeatTopicPartitions>>,
+    pub tagged_fields: BTreeMap<i32, Bytes>,
+}
+
+impl Message for ConsumerGroupHeartbeatRequest {
+    const VERSIONS: VersionRange = VersionRange { min: 0, max: 0 };
+    const DEPRECATED_VERSIONS: Option<VersionRange> = None;
+
+    fn header_version(_version: i16) -> i16 {
+        2
+    }
+}
+
+impl Request for ConsumerGroupHeartbeatRequest {
+    type Response = ConsumerGroupHeartbeatResponse;
+
+    fn error_response(&self, error_code: ErrorCode) -> ConsumerGroupHeartbeatResponse {
+        Self::decode_error_response(error_code)
+    }
+
+    fn decode_error_response(error_code: ErrorCode) -> ConsumerGroupHeartbeatResponse {
+        ConsumerGroupHeartbeatResponse {
+            throttle_time_ms: 0,
+            error_code,
+            error_message: String::new(),
+            member_id: String::new(),
+            member_epoch: 0,
+            heartbeat_interval_ms: 0,
+            assignment: None,
+            tagged_fields: Default::default(),
+        }
+    }
+
+    fn operations(&self) -> Vec<(Operation, Resource)> {
+        vec![(Operation::Read, Resource::Group(self.group_id.clone()))]
+    }
+}
+
+impl DecoderVersioned for ConsumerGroupHeartbeatRequest {
+    fn decode(buf: &mut BytesMut, version: i16) -> Result<Self, io::Error> {
+        if !Self::VERSIONS.contains(version) {
+            return Err(DecodeError::unsupported("unsupported version").into());
+        }
+
+        let group_id = CompactString::decode(buf)?.0;
+        let member_id = CompactString::decode(buf)?.0;
+        let member_epoch = i32::decode(buf)?;
+        let instance_id = CompactNullableString::decode(buf)?.0;
+        let rack_id = CompactNullableString::decode(buf)?.0;
+        let rebalance_timeout_ms = i32::decode(buf)?;
+        let subscribed_topic_names = CompactNullableArray::<CompactString>::decode(buf)?
+            .0
+            .map(|names| names.into_iter().map(|name| name.0).collect());
+        let server_assignor = CompactNullableString::decode(buf)?.0;
+        let topic_partitions =
+            CompactNullableArray::<ConsumerGroupHeartbeatTopicPartitions>::decode(buf)?.0;
+        let tagged_fields = Decoder::decode(buf)?;
+
+        Ok(Self {
+            group_id,
+            member_id,
+            member_epoch,
+            instance_id,
+            rack_id,
+            rebalance_timeout_ms,
+            subscribed_topic_names,
+            server_assignor,
+            topic_partitions,
+            tagged_fields,
+        })
+    }
+}
+
+impl EncoderVersioned for ConsumerGroupHeartbeatRequest {
+    fn encode(&self, buf: &mut BytesMut, _version: i16) -> Result<(), io::Error> {
+        CompactString(self.group_id.clone()).encode(buf)?;
+        CompactString(self.member_id.clone()).encode(buf)?;
+        self.member_epoch.encode(buf)?;
+        CompactNullableString(self.instance_id.clone()).encode(buf)?;
+        CompactNullableString(self.rack_id.clone()).encode(buf)?;
+        self.rebalance_timeout_ms.encode(buf)?;
+
+        let subscribed_topic_names: Option<Vec<CompactString>> = self
+            .subscribed_topic_names
+            .as_ref()
+            .map(|names| names.iter().cloned().map(CompactString).collect());
+        CompactNullableArrayRef(subscribed_topic_names.as_deref()).encode(buf)?;
+
+        CompactNullableString(self.server_assignor.clone()).encode(buf)?;
+        CompactNullableArrayRef(self.topic_partitions.as_deref()).encode(buf)?;
+        self.tagged_fields.encode(buf)?;
+
+        Ok(())
+    }
+}
+
+/// Partitions of a topic, identified by its id.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConsumerGroupHeartbeatTopicPartitions {
+    pub topic_id: Uuid,
+    pub partitions: Vec<i32>,
+    pub tagged_fields: BTreeMap<i32, Bytes>,
+}
+
+impl Decoder for ConsumerGroupHeartbeatTopicPartitions {
+    fn decode(buf: &mut BytesMut) -> Result<Self, io::Error> {
+        let topic_id = Uuid::decode(buf)?;
+        let partitions = CompactArray::<i32>::decode(buf)?.0;
+        let tagged_fields = Decoder::decode(buf)?;
+
+        Ok(Self {
+            topic_id,
+            partitions,
+            tagged_fields,
+        })
+    }
+}
+
+impl Encoder for ConsumerGroupHeartbeatTopicPartitions {
+    fn encode(&self, buf: &mut BytesMut) -> Result<(), io::Error> {
+        self.topic_id.encode(buf)?;
+        CompactArrayRef(&self.partitions).encode(buf)?;
+        self.tagged_fields.encode(buf)?;
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub struct ConsumerGroupHeartbeatResponse {
+    pub throttle_time_ms: i32,
+    pub error_code: ErrorCode,
+    pub error_message: String,
+    pub member_id: String,
+    pub member_epoch: i32,
+    pub heartbeat_interval_ms: i32,
+    /// The member's assignment, or `None` if it hasn't changed.
+    pub assignment: Option<ConsumerGroupHeartbeatAssignment>,
+    pub tagged_fields: BTreeMap<i32, Bytes>,
+}
+
+impl EncoderVersioned for ConsumerGroupHeartbeatResponse {
+    fn encode(&self, buf: &mut BytesMut, _version: i16) -> Result<(), io::Error> {
+        self.throttle_time_ms.encode(buf)?;
+        self.error_code.encode(buf)?;
+        CompactNullableString(self.error_message.clone()).encode(buf)?;
+        CompactNullableString(self.member_id.clone()).encode(buf)?;
+        self.member_epoch.encode(buf)?;
+        self.heartbeat_interval_ms.encode(buf)?;
+        self.assignment.encode(buf)?;
+        self.tagged_fields.encode(buf)?;
+
+        Ok(())
+    }
+}
+
+impl DecoderVersioned for ConsumerGroupHeartbeatResponse {
+    fn decode(buf: &mut BytesMut, _version: i16) -> Result<Self, io::Error> {
+        let throttle_time_ms = i32::decode(buf)?;
+        let error_code = ErrorCode::decode(buf)?;
+        let error_message = CompactNullableString::decode(buf)?.0;
+        let member_id = CompactNullableString::decode(buf)?.0;
+        let member_epoch = i32::decode(buf)?;
+        let heartbeat_interval_ms = i32::decode(buf)?;
+        let assignment = Decoder::decode(buf)?;
+        let tagged_fields = Decoder::decode(buf)?;
+
+        Ok(Self {
+            throttle_time_ms,
+            error_code,
+            error_message,
+            member_id,
+            member_epoch,
+            heartbeat_interval_ms,
+            assignment,
+            tagged_fields,
+        })
+    }
+}
+
+impl Response for ConsumerGroupHeartbeatResponse {
+    fn set_throttle_time_ms(&mut self, throttle_time_ms: i32) {
+        self.throttle_time_ms = throttle_time_ms;
+    }
+}
+
+/// The partitions a member is assigned.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConsumerGroupHeartbeatAssignment {
+    pub topic_partitions: Vec<ConsumerGroupHeartbeatTopicPartitions>,
+    pub tagged_fields: BTreeMap<i32, Bytes>,
+}
+
+/// A nullable struct is prefixed with -1 when null and 1 otherwise.
+impl Decoder for Option<ConsumerGroupHeartbeatAssignment> {
+    fn decode(buf: &mut BytesMut) -> Result<Self, io::Error> {
+        if i8::decode(buf)? < 0 {
+            return Ok(None);
+        }
+
+        let topic_partitions =
+            CompactArray::<ConsumerGroupHeartbeatTopicPartitions>::decode(buf)?.0;
+        let tagged_fields = Decoder::decode(buf)?;
+
+        Ok(Some(ConsumerGroupHeartbeatAssignment {
+            topic_partitions,
+            tagged_fields,
+        }))
+    }
+}
+
+impl Encoder for Option<ConsumerGroupHeartbeatAssignment> {
+    fn encode(&self, buf: &mut BytesMut) -> Result<(), io::Error> {
+        let Some(assignment) = self else {
+            return (-1i8).encode(buf);
+        };
+
+        1i8.encode(buf)?;
+        CompactArrayRef(&assignment.topic_partitions).encode(buf)?;
+        assignment.tagged_fields.encode(buf)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,133 @@
+use std::{collections::BTreeMap, io};
+
+use bytes::{Bytes, BytesMut};
+
+use crate::{
+    Message, VersionRange,
+    authorizer::{Operation, Resource},
+    protocol::{
+        DecodeError, Decoder, DecoderVersioned, Encoder, EncoderVersioned,
+        error::ErrorCode,
+        primitives::{ArrayRef, CompactArrayRef, CompactString, string_size_hint},
+        request::Request,
+        response::Response,
+    },
+};
+
+#[derive(Debug)]
+pub struct ControlledShutdownRequest {
+    pub broker_id: i32,
+    /// The broker's epoch, or -1 before v2.
+    pub broker_epoch: i64,
+    pub tagged_fields: BTreeMap<i32, Bytes>,
+}
+
+impl Message for ControlledShutdownRequest {
+    const VERSIONS: VersionRange = VersionRange { min: 0, max: 3 };
+    const DEPRECATED_VERSIONS: Option<VersionRange> = None;
+
+    /// Version 0 predates the client id in the request header.
+    fn header_version(version: i16) -> i16 {
+        match version {
+            0 => 0,
+            1 | 2 => 1,
+            _ => 2,
+        }
+    }
+}
+
+impl Request for ControlledShutdownRequest {
+    type Response = ControlledShutdownResponse;
+
+    fn error_response(&self, error_code: ErrorCode) -> ControlledShutdownResponse {
+        Self::decode_error_response(error_code)
+    }
+
+    fn decode_error_response(error_code: ErrorCode) -> ControlledShutdownResponse {
+        ControlledShutdownResponse {
+            error_code,
+            remaining_partitions: vec![],
+            tagged_fields: Default::default(),
+        }
+    }
+
+    fn operations(&self) -> Vec<(Operation, Resource)> {
+        vec![(Operation::ClusterAction, Resource::Cluster)]
+    }
+}
+
+impl DecoderVersioned for ControlledShutdownRequest {
+    fn decode(buf: &mut BytesMut, version: i16) -> Result<Self, io::Error> {
+        if !Self::VERSIONS.contains(version) {
+            return Err(DecodeError::unsupported("unsupported version").into());
+        }
+
+        let broker_id = i32::decode(buf)?;
+
+        let broker_epoch = if version < 2 { -1 } else { i64::decode(buf)? };
+
+        let mut tagged_fields = BTreeMap::new();
+        if version > 2 {
+            tagged_fields = Decoder::decode(buf)?;
+        }
+
+        Ok(Self {
+            broker_id,
+            broker_epoch,
+            tagged_fields,
+        })
+    }
+}
+
+pub struct ControlledShutdownResponse {
+    pub error_code: ErrorCode,
+    /// The partitions the broker still leads and couldn't hand off.
+    pub remaining_partitions: Vec<ControlledShutdownRemainingPartition>,
+    pub tagged_fields: BTreeMap<i32, Bytes>,
+}
+
+impl EncoderVersioned for ControlledShutdownResponse {
+    fn encode(&self, buf: &mut BytesMut, version: i16) -> Result<(), io::Error> {
+        self.error_code.encode(buf)?;
+
+        if version < 3 {
+            ArrayRef(&self.remaining_partitions).encode(buf, version)?;
+        } else {
+            CompactArrayRef(&self.remaining_partitions).encode(buf, version)?;
+            self.tagged_fields.encode(buf)?;
+        }
+
+        Ok(())
+    }
+
+    fn size_hint(&self, version: i16) -> usize {
+        2 + CompactArrayRef(&self.remaining_partitions).size_hint(version) + 1
+    }
+}
+
+impl Response for ControlledShutdownResponse {}
+
+pub struct ControlledShutdownRemainingPartition {
+    pub topic_name: String,
+    pub partition_index: i32,
+    pub tagged_fields: BTreeMap<i32, Bytes>,
+}
+
+impl EncoderVersioned for ControlledShutdownRemainingPartition {
+    fn encode(&self, buf: &mut BytesMut, version: i16) -> Result<(), io::Error> {
+        if version < 3 {
+            self.topic_name.encode(buf)?;
+            self.partition_index.encode(buf)?;
+        } else {
+            CompactString(self.topic_name.clone()).encode(buf)?;
+            self.partition_index.encode(buf)?;
+            self.tagged_fields.encode(buf)?;
+        }
+
+        Ok(())
+    }
+
+    fn size_hint(&self, _version: i16) -> usize {
+        string_size_hint(&self.topic_name) + 4 + 1
+    }
+}
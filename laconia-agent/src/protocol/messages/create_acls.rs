@@ -0,0 +1,202 @@
+use std::{collections::BTreeMap, io};
+
+use bytes::{Bytes, BytesMut};
+
+use crate::{
+    Message, VersionRange,
+    authorizer::{Operation, Resource},
+    protocol::{
+        DecodeError, Decoder, DecoderVersioned, Encoder, EncoderVersioned,
+        error::ErrorCode,
+        primitives::{
+            ArrayRef, CompactArray, CompactArrayRef, CompactNullableString, CompactString,
+            NullableString, string_size_hint,
+        },
+        request::Request,
+        response::Response,
+    },
+};
+
+/// Adds ACLs.
+#[derive(Debug)]
+pub struct CreateAclsRequest {
+    pub creations: Vec<AclCreation>,
+    pub tagged_fields: BTreeMap<i32, Bytes>,
+}
+
+impl Message for CreateAclsRequest {
+    const VERSIONS: VersionRange = VersionRange { min: 0, max: 3 };
+    const DEPRECATED_VERSIONS: Option<VersionRange> = None;
+
+    fn header_version(version: i16) -> i16 {
+        if version < 2 { 1 } else { 2 }
+    }
+}
+
+impl Request for CreateAclsRequest {
+    type Response = CreateAclsResponse;
+
+    fn error_response(&self, error_code: ErrorCode) -> CreateAclsResponse {
+        let results = self
+            .creations
+            .iter()
+            .map(|_| AclCreationResult::error(error_code, String::new()))
+            .collect();
+
+        CreateAclsResponse {
+            results,
+            ..Self::decode_error_response(error_code)
+        }
+    }
+
+    fn decode_error_response(_error_code: ErrorCode) -> CreateAclsResponse {
+        CreateAclsResponse {
+            throttle_time_ms: 0,
+            results: vec![],
+            tagged_fields: Default::default(),
+        }
+    }
+
+    fn operations(&self) -> Vec<(Operation, Resource)> {
+        vec![(Operation::Alter, Resource::Cluster)]
+    }
+}
+
+impl DecoderVersioned for CreateAclsRequest {
+    fn decode(buf: &mut BytesMut, version: i16) -> Result<Self, io::Error> {
+        if !Self::VERSIONS.contains(version) {
+            return Err(DecodeError::unsupported("unsupported version").into());
+        }
+
+        let creations = if version < 2 {
+            Vec::<AclCreation>::decode(buf, version)?
+        } else {
+            CompactArray::<AclCreation>::decode(buf, version)?.0
+        };
+
+        let mut tagged_fields = BTreeMap::new();
+        if version > 1 {
+            tagged_fields = Decoder::decode(buf)?;
+        }
+
+        Ok(Self {
+            creations,
+            tagged_fields,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct AclCreation {
+    pub resource_type: i8,
+    pub resource_name: String,
+    /// 3 for literal or 4 for prefixed. Literal before v1.
+    pub resource_pattern_type: i8,
+    pub principal: String,
+    pub host: String,
+    pub operation: i8,
+    pub permission_type: i8,
+    pub tagged_fields: BTreeMap<i32, Bytes>,
+}
+
+impl DecoderVersioned for AclCreation {
+    fn decode(buf: &mut BytesMut, version: i16) -> Result<Self, io::Error> {
+        let resource_type = i8::decode(buf)?;
+        let resource_name = if version < 2 {
+            String::decode(buf)?
+        } else {
+            CompactString::decode(buf)?.0
+        };
+        let resource_pattern_type = if version < 1 { 3 } else { i8::decode(buf)? };
+        let (principal, host) = if version < 2 {
+            (String::decode(buf)?, String::decode(buf)?)
+        } else {
+            (CompactString::decode(buf)?.0, CompactString::decode(buf)?.0)
+        };
+        let operation = i8::decode(buf)?;
+        let permission_type = i8::decode(buf)?;
+
+        let mut tagged_fields = BTreeMap::new();
+        if version > 1 {
+            tagged_fields = Decoder::decode(buf)?;
+        }
+
+        Ok(Self {
+            resource_type,
+            resource_name,
+            resource_pattern_type,
+            principal,
+            host,
+            operation,
+            permission_type,
+            tagged_fields,
+        })
+    }
+}
+
+pub struct CreateAclsResponse {
+    pub throttle_time_ms: i32,
+    /// The result of each creation, in the order they were requested.
+    pub results: Vec<AclCreationResult>,
+    pub tagged_fields: BTreeMap<i32, Bytes>,
+}
+
+impl EncoderVersioned for CreateAclsResponse {
+    fn encode(&self, buf: &mut BytesMut, version: i16) -> Result<(), io::Error> {
+        self.throttle_time_ms.encode(buf)?;
+
+        if version < 2 {
+            ArrayRef(&self.results).encode(buf, version)?;
+        } else {
+            CompactArrayRef(&self.results).encode(buf, version)?;
+            self.tagged_fields.encode(buf)?;
+        }
+
+        Ok(())
+    }
+
+    fn size_hint(&self, version: i16) -> usize {
+        4 + ArrayRef(&self.results).size_hint(version) + 1
+    }
+}
+
+impl Response for CreateAclsResponse {
+    fn set_throttle_time_ms(&mut self, throttle_time_ms: i32) {
+        self.throttle_time_ms = throttle_time_ms;
+    }
+}
+
+pub struct AclCreationResult {
+    pub error_code: ErrorCode,
+    pub error_message: String,
+    pub tagged_fields: BTreeMap<i32, Bytes>,
+}
+
+impl AclCreationResult {
+    pub fn error(error_code: ErrorCode, error_message: String) -> Self {
+        Self {
+            error_code,
+            error_message,
+            tagged_fields: Default::default(),
+        }
+    }
+}
+
+impl EncoderVersioned for AclCreationResult {
+    fn encode(&self, buf: &mut BytesMut, version: i16) -> Result<(), io::Error> {
+        self.error_code.encode(buf)?;
+
+        if version < 2 {
+            NullableString(self.error_message.clone()).encode(buf)?;
+        } else {
+            CompactNullableString(self.error_message.clone()).encode(buf)?;
+            self.tagged_fields.encode(buf)?;
+        }
+
+        Ok(())
+    }
+
+    fn size_hint(&self, _version: i16) -> usize {
+        2 + string_size_hint(&self.error_message) + 1
+    }
+}
@@ -0,0 +1,282 @@
+use std::{collections::BTreeMap, io};
+
+use bytes::{Bytes, BytesMut};
+
+use crate::{
+    Message, VersionRange,
+    authorizer::{Operation, Resource},
+    protocol::{
+        DecodeError, Decoder, DecoderVersioned, Encoder, EncoderVersioned,
+        error::ErrorCode,
+        primitives::{
+            ArrayRef, CompactArray, CompactArrayRef, CompactNullableString, CompactString,
+            NullableString, string_size_hint,
+        },
+        request::Request,
+        response::Response,
+    },
+};
+
+/// Removes the ACLs matching each of a list of filters.
+#[derive(Debug)]
+pub struct DeleteAclsRequest {
+    pub filters: Vec<DeleteAclsFilter>,
+    pub tagged_fields: BTreeMap<i32, Bytes>,
+}
+
+impl Message for DeleteAclsRequest {
+    const VERSIONS: VersionRange = VersionRange { min: 0, max: 3 };
+    const DEPRECATED_VERSIONS: Option<VersionRange> = None;
+
+    fn header_version(version: i16) -> i16 {
+        if version < 2 { 1 } else { 2 }
+    }
+}
+
+impl Request for DeleteAclsRequest {
+    type Response = DeleteAclsResponse;
+
+    fn error_response(&self, error_code: ErrorCode) -> DeleteAclsResponse {
+        let filter_results = self
+            .filters
+            .iter()
+            .map(|_| DeleteAclsFilterResult::error(error_code, String::new()))
+            .collect();
+
+        DeleteAclsResponse {
+            filter_results,
+            ..Self::decode_error_response(error_code)
+        }
+    }
+
+    fn decode_error_response(_error_code: ErrorCode) -> DeleteAclsResponse {
+        DeleteAclsResponse {
+            throttle_time_ms: 0,
+            filter_results: vec![],
+            tagged_fields: Default::default(),
+        }
+    }
+
+    fn operations(&self) -> Vec<(Operation, Resource)> {
+        vec![(Operation::Alter, Resource::Cluster)]
+    }
+}
+
+impl DecoderVersioned for DeleteAclsRequest {
+    fn decode(buf: &mut BytesMut, version: i16) -> Result<Self, io::Error> {
+        if !Self::VERSIONS.contains(version) {
+            return Err(DecodeError::unsupported("unsupported version").into());
+        }
+
+        let filters = if version < 2 {
+            Vec::<DeleteAclsFilter>::decode(buf, version)?
+        } else {
+            CompactArray::<DeleteAclsFilter>::decode(buf, version)?.0
+        };
+
+        let mut tagged_fields = BTreeMap::new();
+        if version > 1 {
+            tagged_fields = Decoder::decode(buf)?;
+        }
+
+        Ok(Self {
+            filters,
+            tagged_fields,
+        })
+    }
+}
+
+/// Matches ACLs the same way as a
+/// [`DescribeAclsRequest`](super::DescribeAclsRequest).
+#[derive(Debug)]
+pub struct DeleteAclsFilter {
+    pub resource_type_filter: i8,
+    pub resource_name_filter: String,
+    pub pattern_type_filter: i8,
+    pub principal_filter: String,
+    pub host_filter: String,
+    pub operation: i8,
+    pub permission_type: i8,
+    pub tagged_fields: BTreeMap<i32, Bytes>,
+}
+
+impl DecoderVersioned for DeleteAclsFilter {
+    fn decode(buf: &mut BytesMut, version: i16) -> Result<Self, io::Error> {
+        let resource_type_filter = i8::decode(buf)?;
+        let resource_name_filter = if version < 2 {
+            NullableString::decode(buf)?.0
+        } else {
+            CompactNullableString::decode(buf)?.0
+        };
+        let pattern_type_filter = if version < 1 { 3 } else { i8::decode(buf)? };
+        let (principal_filter, host_filter) = if version < 2 {
+            (
+                NullableString::decode(buf)?.0,
+                NullableString::decode(buf)?.0,
+            )
+        } else {
+            (
+                CompactNullableString::decode(buf)?.0,
+                CompactNullableString::decode(buf)?.0,
+            )
+        };
+        let operation = i8::decode(buf)?;
+        let permission_type = i8::decode(buf)?;
+
+        let mut tagged_fields = BTreeMap::new();
+        if version > 1 {
+            tagged_fields = Decoder::decode(buf)?;
+        }
+
+        Ok(Self {
+            resource_type_filter,
+            resource_name_filter,
+            pattern_type_filter,
+            principal_filter,
+            host_filter,
+            operation,
+            permission_type,
+            tagged_fields,
+        })
+    }
+}
+
+pub struct DeleteAclsResponse {
+    pub throttle_time_ms: i32,
+    /// The result of each filter, in the order they were requested.
+    pub filter_results: Vec<DeleteAclsFilterResult>,
+    pub tagged_fields: BTreeMap<i32, Bytes>,
+}
+
+impl EncoderVersioned for DeleteAclsResponse {
+    fn encode(&self, buf: &mut BytesMut, version: i16) -> Result<(), io::Error> {
+        self.throttle_time_ms.encode(buf)?;
+
+        if version < 2 {
+            ArrayRef(&self.filter_results).encode(buf, version)?;
+        } else {
+            CompactArrayRef(&self.filter_results).encode(buf, version)?;
+            self.tagged_fields.encode(buf)?;
+        }
+
+        Ok(())
+    }
+
+    fn size_hint(&self, version: i16) -> usize {
+        4 + ArrayRef(&self.filter_results).size_hint(version) + 1
+    }
+}
+
+impl Response for DeleteAclsResponse {
+    fn set_throttle_time_ms(&mut self, throttle_time_ms: i32) {
+        self.throttle_time_ms = throttle_time_ms;
+    }
+}
+
+pub struct DeleteAclsFilterResult {
+    pub error_code: ErrorCode,
+    pub error_message: String,
+    pub matching_acls: Vec<DeleteAclsMatchingAcl>,
+    pub tagged_fields: BTreeMap<i32, Bytes>,
+}
+
+impl DeleteAclsFilterResult {
+    pub fn error(error_code: ErrorCode, error_message: String) -> Self {
+        Self {
+            error_code,
+            error_message,
+            matching_acls: vec![],
+            tagged_fields: Default::default(),
+        }
+    }
+}
+
+impl EncoderVersioned for DeleteAclsFilterResult {
+    fn encode(&self, buf: &mut BytesMut, version: i16) -> Result<(), io::Error> {
+        self.error_code.encode(buf)?;
+
+        if version < 2 {
+            NullableString(self.error_message.clone()).encode(buf)?;
+            ArrayRef(&self.matching_acls).encode(buf, version)?;
+        } else {
+            CompactNullableString(self.error_message.clone()).encode(buf)?;
+            CompactArrayRef(&self.matching_acls).encode(buf, version)?;
+            self.tagged_fields.encode(buf)?;
+        }
+
+        Ok(())
+    }
+
+    fn size_hint(&self, version: i16) -> usize {
+        2 + string_size_hint(&self.error_message)
+            + ArrayRef(&self.matching_acls).size_hint(version)
+            + 1
+    }
+}
+
+/// An ACL that was deleted.
+pub struct DeleteAclsMatchingAcl {
+    pub error_code: ErrorCode,
+    pub error_message: String,
+    pub resource_type: i8,
+    pub resource_name: String,
+    pub pattern_type: i8,
+    pub principal: String,
+    pub host: String,
+    pub operation: i8,
+    pub permission_type: i8,
+    pub tagged_fields: BTreeMap<i32, Bytes>,
+}
+
+impl EncoderVersioned for DeleteAclsMatchingAcl {
+    fn encode(&self, buf: &mut BytesMut, version: i16) -> Result<(), io::Error> {
+        self.error_code.encode(buf)?;
+
+        if version < 2 {
+            NullableString(self.error_message.clone()).encode(buf)?;
+        } else {
+            CompactNullableString(self.error_message.clone()).encode(buf)?;
+        }
+
+        self.resource_type.encode(buf)?;
+
+        if version < 2 {
+            self.resource_name.encode(buf)?;
+        } else {
+            CompactString(self.resource_name.clone()).encode(buf)?;
+        }
+
+        if version > 0 {
+            self.pattern_type.encode(buf)?;
+        }
+
+        if version < 2 {
+            self.principal.encode(buf)?;
+            self.host.encode(buf)?;
+        } else {
+            CompactString(self.principal.clone()).encode(buf)?;
+            CompactString(self.host.clone()).encode(buf)?;
+        }
+
+        self.operation.encode(buf)?;
+        self.permission_type.encode(buf)?;
+
+        if version > 1 {
+            self.tagged_fields.encode(buf)?;
+        }
+
+        Ok(())
+    }
+
+    fn size_hint(&self, _version: i16) -> usize {
+        2 + string_size_hint(&self.error_message)
+            + 1
+            + string_size_hint(&self.resource_name)
+            + 1
+            + string_size_hint(&self.principal)
+            + string_size_hint(&self.host)
+            + 1
+            + 1
+            + 1
+    }
+}
@@ -0,0 +1,261 @@
+use std::{collections::BTreeMap, io};
+
+use bytes::{Bytes, BytesMut};
+
+use crate::{
+    Message, VersionRange,
+    authorizer::{Operation, Resource},
+    protocol::{
+        DecodeError, Decoder, DecoderVersioned, Encoder, EncoderVersioned,
+        error::ErrorCode,
+        primitives::{ArrayRef, CompactArray, CompactArrayRef, CompactString, string_size_hint},
+        request::Request,
+        response::Response,
+    },
+};
+
+/// Asks to delete the records before an offset in each partition, moving the
+/// start of its log up to that offset.
+#[derive(Debug)]
+pub struct DeleteRecordsRequest {
+    pub topics: Vec<DeleteRecordsTopic>,
+    /// How long to wait for the deletion to be replicated.
+    pub timeout_ms: i32,
+    pub tagged_fields: BTreeMap<i32, Bytes>,
+}
+
+impl Message for DeleteRecordsRequest {
+    const VERSIONS: VersionRange = VersionRange { min: 0, max: 2 };
+    const DEPRECATED_VERSIONS: Option<VersionRange> = None;
+
+    fn header_version(version: i16) -> i16 {
+        if version < 2 { 1 } else { 2 }
+    }
+}
+
+impl Request for DeleteRecordsRequest {
+    type Response = DeleteRecordsResponse;
+
+    fn error_response(&self, error_code: ErrorCode) -> DeleteRecordsResponse {
+        let topics = self
+            .topics
+            .iter()
+            .map(|topic| DeleteRecordsTopicResult {
+                name: topic.name.clone(),
+                partitions: topic
+                    .partitions
+                    .iter()
+                    .map(|partition| {
+                        DeleteRecordsPartitionResult::error(partition.partition_index, error_code)
+                    })
+                    .collect(),
+                tagged_fields: Default::default(),
+            })
+            .collect();
+
+        DeleteRecordsResponse {
+            topics,
+            ..Self::decode_error_response(error_code)
+        }
+    }
+
+    fn decode_error_response(_error_code: ErrorCode) -> DeleteRecordsResponse {
+        DeleteRecordsResponse {
+            throttle_time_ms: 0,
+            topics: vec![],
+            tagged_fields: Default::default(),
+        }
+    }
+
+    fn operations(&self) -> Vec<(Operation, Resource)> {
+        self.topics
+            .iter()
+            .map(|topic| (Operation::Delete, Resource::Topic(topic.name.clone())))
+            .collect()
+    }
+
+    fn timeout_ms(&self) -> Option<i32> {
+        Some(self.timeout_ms)
+    }
+}
+
+impl DecoderVersioned for DeleteRecordsRequest {
+    fn decode(buf: &mut BytesMut, version: i16) -> Result<Self, io::Error> {
+        if !Self::VERSIONS.contains(version) {
+            return Err(DecodeError::unsupported("unsupported version").into());
+        }
+
+        let topics = if version < 2 {
+            Vec::<DeleteRecordsTopic>::decode(buf, version)?
+        } else {
+            CompactArray::<DeleteRecordsTopic>::decode(buf, version)?.0
+        };
+
+        let timeout_ms = i32::decode(buf)?;
+
+        let mut tagged_fields = BTreeMap::new();
+        if version > 1 {
+            tagged_fields = Decoder::decode(buf)?;
+        }
+
+        Ok(Self {
+            topics,
+            timeout_ms,
+            tagged_fields,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct DeleteRecordsTopic {
+    pub name: String,
+    pub partitions: Vec<DeleteRecordsPartition>,
+    pub tagged_fields: BTreeMap<i32, Bytes>,
+}
+
+impl DecoderVersioned for DeleteRecordsTopic {
+    fn decode(buf: &mut BytesMut, version: i16) -> Result<Self, io::Error> {
+        let (name, partitions) = if version < 2 {
+            (
+                String::decode(buf)?,
+                Vec::<DeleteRecordsPartition>::decode(buf, version)?,
+            )
+        } else {
+            (
+                CompactString::decode(buf)?.0,
+                CompactArray::<DeleteRecordsPartition>::decode(buf, version)?.0,
+            )
+        };
+
+        let mut tagged_fields = BTreeMap::new();
+        if version > 1 {
+            tagged_fields = Decoder::decode(buf)?;
+        }
+
+        Ok(Self {
+            name,
+            partitions,
+            tagged_fields,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct DeleteRecordsPartition {
+    pub partition_index: i32,
+    /// The offset to delete the records before, or -1 to delete up to the
+    /// high watermark.
+    pub offset: i64,
+    pub tagged_fields: BTreeMap<i32, Bytes>,
+}
+
+impl DecoderVersioned for DeleteRecordsPartition {
+    fn decode(buf: &mut BytesMut, version: i16) -> Result<Self, io::Error> {
+        let partition_index = i32::decode(buf)?;
+        let offset = i64::decode(buf)?;
+
+        let mut tagged_fields = BTreeMap::new();
+        if version > 1 {
+            tagged_fields = Decoder::decode(buf)?;
+        }
+
+        Ok(Self {
+            partition_index,
+            offset,
+            tagged_fields,
+        })
+    }
+}
+
+pub struct DeleteRecordsResponse {
+    pub throttle_time_ms: i32,
+    pub topics: Vec<DeleteRecordsTopicResult>,
+    pub tagged_fields: BTreeMap<i32, Bytes>,
+}
+
+impl EncoderVersioned for DeleteRecordsResponse {
+    fn encode(&self, buf: &mut BytesMut, version: i16) -> Result<(), io::Error> {
+        self.throttle_time_ms.encode(buf)?;
+
+        if version < 2 {
+            ArrayRef(&self.topics).encode(buf, version)?;
+        } else {
+            CompactArrayRef(&self.topics).encode(buf, version)?;
+            self.tagged_fields.encode(buf)?;
+        }
+
+        Ok(())
+    }
+
+    fn size_hint(&self, version: i16) -> usize {
+        4 + ArrayRef(&self.topics).size_hint(version) + 1
+    }
+}
+
+impl Response for DeleteRecordsResponse {
+    fn set_throttle_time_ms(&mut self, throttle_time_ms: i32) {
+        self.throttle_time_ms = throttle_time_ms;
+    }
+}
+
+pub struct DeleteRecordsTopicResult {
+    pub name: String,
+    pub partitions: Vec<DeleteRecordsPartitionResult>,
+    pub tagged_fields: BTreeMap<i32, Bytes>,
+}
+
+impl EncoderVersioned for DeleteRecordsTopicResult {
+    fn encode(&self, buf: &mut BytesMut, version: i16) -> Result<(), io::Error> {
+        if version < 2 {
+            self.name.encode(buf)?;
+            ArrayRef(&self.partitions).encode(buf, version)?;
+        } else {
+            CompactString(self.name.clone()).encode(buf)?;
+            CompactArrayRef(&self.partitions).encode(buf, version)?;
+            self.tagged_fields.encode(buf)?;
+        }
+
+        Ok(())
+    }
+
+    fn size_hint(&self, version: i16) -> usize {
+        string_size_hint(&self.name) + ArrayRef(&self.partitions).size_hint(version) + 1
+    }
+}
+
+pub struct DeleteRecordsPartitionResult {
+    pub partition_index: i32,
+    /// The start of the partition's log after the deletion, or -1 on error.
+    pub low_watermark: i64,
+    pub error_code: ErrorCode,
+    pub tagged_fields: BTreeMap<i32, Bytes>,
+}
+
+impl DeleteRecordsPartitionResult {
+    pub fn error(partition_index: i32, error_code: ErrorCode) -> Self {
+        Self {
+            partition_index,
+            low_watermark: -1,
+            error_code,
+            tagged_fields: Default::default(),
+        }
+    }
+}
+
+impl EncoderVersioned for DeleteRecordsPartitionResult {
+    fn encode(&self, buf: &mut BytesMut, version: i16) -> Result<(), io::Error> {
+        self.partition_index.encode(buf)?;
+        self.low_watermark.encode(buf)?;
+        self.error_code.encode(buf)?;
+
+        if version > 1 {
+            self.tagged_fields.encode(buf)?;
+        }
+
+        Ok(())
+    }
+
+    fn size_hint(&self, _version: i16) -> usize {
+        4 + 8 + 2 + 1
+    }
+}
@@ -0,0 +1,226 @@
+use std::{collections::BTreeMap, io};
+
+use bytes::{Bytes, BytesMut};
+
+use crate::{
+    Message, VersionRange,
+    authorizer::{Operation, Resource},
+    protocol::{
+        DecodeError, Decoder, DecoderVersioned, Encoder, EncoderVersioned,
+        error::ErrorCode,
+        primitives::{
+            ArrayRef, CompactArrayRef, CompactNullableString, CompactString, NullableString,
+            string_size_hint,
+        },
+        request::Request,
+        response::Response,
+    },
+};
+
+/// Lists the ACLs matching a filter.
+#[derive(Debug)]
+pub struct DescribeAclsRequest {
+    /// The resource type to match, or 1 for any.
+    pub resource_type_filter: i8,
+    /// The resource name to match, or empty for any.
+    pub resource_name_filter: String,
+    /// The pattern type to match: 1 for any, 2 for every ACL that applies
+    /// to the resource name, 3 for literal or 4 for prefixed. Literal before
+    /// v1.
+    pub pattern_type_filter: i8,
+    /// The principal to match, or empty for any.
+    pub principal_filter: String,
+    /// The host to match, or empty for any.
+    pub host_filter: String,
+    /// The operation to match, or 1 for any.
+    pub operation: i8,
+    /// The permission type to match, or 1 for any.
+    pub permission_type: i8,
+    pub tagged_fields: BTreeMap<i32, Bytes>,
+}
+
+impl Message for DescribeAclsRequest {
+    const VERSIONS: VersionRange = VersionRange { min: 0, max: 3 };
+    const DEPRECATED_VERSIONS: Option<VersionRange> = None;
+
+    fn header_version(version: i16) -> i16 {
+        if version < 2 { 1 } else { 2 }
+    }
+}
+
+impl Request for DescribeAclsRequest {
+    type Response = DescribeAclsResponse;
+
+    fn error_response(&self, error_code: ErrorCode) -> DescribeAclsResponse {
+        Self::decode_error_response(error_code)
+    }
+
+    fn decode_error_response(error_code: ErrorCode) -> DescribeAclsResponse {
+        DescribeAclsResponse {
+            throttle_time_ms: 0,
+            error_code,
+            error_message: String::new(),
+            resources: vec![],
+            tagged_fields: Default::default(),
+        }
+    }
+
+    fn operations(&self) -> Vec<(Operation, Resource)> {
+        vec![(Operation::Describe, Resource::Cluster)]
+    }
+}
+
+impl DecoderVersioned for DescribeAclsRequest {
+    fn decode(buf: &mut BytesMut, version: i16) -> Result<Self, io::Error> {
+        if !Self::VERSIONS.contains(version) {
+            return Err(DecodeError::unsupported("unsupported version").into());
+        }
+
+        let resource_type_filter = i8::decode(buf)?;
+        let resource_name_filter = if version < 2 {
+            NullableString::decode(buf)?.0
+        } else {
+            CompactNullableString::decode(buf)?.0
+        };
+        let pattern_type_filter = if version < 1 { 3 } else { i8::decode(buf)? };
+        let (principal_filter, host_filter) = if version < 2 {
+            (
+                NullableString::decode(buf)?.0,
+                NullableString::decode(buf)?.0,
+            )
+        } else {
+            (
+                CompactNullableString::decode(buf)?.0,
+                CompactNullableString::decode(buf)?.0,
+            )
+        };
+        let operation = i8::decode(buf)?;
+        let permission_type = i8::decode(buf)?;
+
+        let mut tagged_fields = BTreeMap::new();
+        if version > 1 {
+            tagged_fields = Decoder::decode(buf)?;
+        }
+
+        Ok(Self {
+            resource_type_filter,
+            resource_name_filter,
+            pattern_type_filter,
+            principal_filter,
+            host_filter,
+            operation,
+            permission_type,
+            tagged_fields,
+        })
+    }
+}
+
+pub struct DescribeAclsResponse {
+    pub throttle_time_ms: i32,
+    pub error_code: ErrorCode,
+    pub error_message: String,
+    pub resources: Vec<DescribeAclsResource>,
+    pub tagged_fields: BTreeMap<i32, Bytes>,
+}
+
+impl EncoderVersioned for DescribeAclsResponse {
+    fn encode(&self, buf: &mut BytesMut, version: i16) -> Result<(), io::Error> {
+        self.throttle_time_ms.encode(buf)?;
+        self.error_code.encode(buf)?;
+
+        if version < 2 {
+            NullableString(self.error_message.clone()).encode(buf)?;
+            ArrayRef(&self.resources).encode(buf, version)?;
+        } else {
+            CompactNullableString(self.error_message.clone()).encode(buf)?;
+            CompactArrayRef(&self.resources).encode(buf, version)?;
+            self.tagged_fields.encode(buf)?;
+        }
+
+        Ok(())
+    }
+
+    fn size_hint(&self, version: i16) -> usize {
+        4 + 2
+            + string_size_hint(&self.error_message)
+            + ArrayRef(&self.resources).size_hint(version)
+            + 1
+    }
+}
+
+impl Response for DescribeAclsResponse {
+    fn set_throttle_time_ms(&mut self, throttle_time_ms: i32) {
+        self.throttle_time_ms = throttle_time_ms;
+    }
+}
+
+/// A resource pattern, and the ACLs on it.
+pub struct DescribeAclsResource {
+    pub resource_type: i8,
+    pub resource_name: String,
+    pub pattern_type: i8,
+    pub acls: Vec<AclDescription>,
+    pub tagged_fields: BTreeMap<i32, Bytes>,
+}
+
+impl EncoderVersioned for DescribeAclsResource {
+    fn encode(&self, buf: &mut BytesMut, version: i16) -> Result<(), io::Error> {
+        self.resource_type.encode(buf)?;
+
+        if version < 2 {
+            self.resource_name.encode(buf)?;
+        } else {
+            CompactString(self.resource_name.clone()).encode(buf)?;
+        }
+
+        if version > 0 {
+            self.pattern_type.encode(buf)?;
+        }
+
+        if version < 2 {
+            ArrayRef(&self.acls).encode(buf, version)?;
+        } else {
+            CompactArrayRef(&self.acls).encode(buf, version)?;
+            self.tagged_fields.encode(buf)?;
+        }
+
+        Ok(())
+    }
+
+    fn size_hint(&self, version: i16) -> usize {
+        1 + string_size_hint(&self.resource_name) + 1 + ArrayRef(&self.acls).size_hint(version) + 1
+    }
+}
+
+pub struct AclDescription {
+    pub principal: String,
+    pub host: String,
+    pub operation: i8,
+    pub permission_type: i8,
+    pub tagged_fields: BTreeMap<i32, Bytes>,
+}
+
+impl EncoderVersioned for AclDescription {
+    fn encode(&self, buf: &mut BytesMut, version: i16) -> Result<(), io::Error> {
+        if version < 2 {
+            self.principal.encode(buf)?;
+            self.host.encode(buf)?;
+        } else {
+            CompactString(self.principal.clone()).encode(buf)?;
+            CompactString(self.host.clone()).encode(buf)?;
+        }
+
+        self.operation.encode(buf)?;
+        self.permission_type.encode(buf)?;
+
+        if version > 1 {
+            self.tagged_fields.encode(buf)?;
+        }
+
+        Ok(())
+    }
+
+    fn size_hint(&self, _version: i16) -> usize {
+        string_size_hint(&self.principal) + string_size_hint(&self.host) + 1 + 1 + 1
+    }
+}
@@ -0,0 +1,167 @@
+use std::{collections::BTreeMap, io};
+
+use bytes::{Bytes, BytesMut};
+
+use crate::{
+    Message, VersionRange,
+    authorizer::{Operation, Resource},
+    protocol::{
+        DecodeError, Decoder, DecoderVersioned, Encoder, EncoderVersioned,
+        error::ErrorCode,
+        primitives::{CompactArrayRef, CompactNullableString, CompactString, string_size_hint},
+        request::Request,
+        response::Response,
+    },
+};
+
+#[derive(Debug)]
+pub struct DescribeClusterRequest {
+    pub include_cluster_authorized_operations: bool,
+    /// 1 for brokers and 2 for controllers.
+    pub endpoint_type: i8,
+    pub include_fenced_brokers: bool,
+    pub tagged_fields: BTreeMap<i32, Bytes>,
+}
+
+impl Message for DescribeClusterRequest {
+    const VERSIONS: VersionRange = VersionRange { min: 0, max: 2 };
+    const DEPRECATED_VERSIONS: Option<VersionRange> = None;
+
+    fn header_version(_version: i16) -> i16 {
+        2
+    }
+}
+
+impl Request for DescribeClusterRequest {
+    type Response = DescribeClusterResponse;
+
+    fn error_response(&self, error_code: ErrorCode) -> DescribeClusterResponse {
+        DescribeClusterResponse {
+            endpoint_type: self.endpoint_type,
+            ..Self::decode_error_response(error_code)
+        }
+    }
+
+    fn decode_error_response(error_code: ErrorCode) -> DescribeClusterResponse {
+        DescribeClusterResponse {
+            throttle_time_ms: 0,
+            error_code,
+            error_message: String::new(),
+            endpoint_type: 1,
+            cluster_id: String::new(),
+            controller_id: -1,
+            brokers: vec![],
+            cluster_authorized_operations: i32::MIN,
+            tagged_fields: Default::default(),
+        }
+    }
+
+    fn operations(&self) -> Vec<(Operation, Resource)> {
+        vec![(Operation::Describe, Resource::Cluster)]
+    }
+}
+
+impl DecoderVersioned for DescribeClusterRequest {
+    fn decode(buf: &mut BytesMut, version: i16) -> Result<Self, io::Error> {
+        if !Self::VERSIONS.contains(version) {
+            return Err(DecodeError::unsupported("unsupported version").into());
+        }
+
+        let include_cluster_authorized_operations = bool::decode(buf)?;
+
+        let endpoint_type = if version < 1 { 1 } else { i8::decode(buf)? };
+
+        let include_fenced_brokers = if version < 2 {
+            false
+        } else {
+            bool::decode(buf)?
+        };
+
+        let tagged_fields = Decoder::decode(buf)?;
+
+        Ok(Self {
+            include_cluster_authorized_operations,
+            endpoint_type,
+            include_fenced_brokers,
+            tagged_fields,
+        })
+    }
+}
+
+pub struct DescribeClusterResponse {
+    pub throttle_time_ms: i32,
+    pub error_code: ErrorCode,
+    pub error_message: String,
+    pub endpoint_type: i8,
+    pub cluster_id: String,
+    pub controller_id: i32,
+    pub brokers: Vec<DescribeClusterBroker>,
+    pub cluster_authorized_operations: i32,
+    pub tagged_fields: BTreeMap<i32, Bytes>,
+}
+
+impl EncoderVersioned for DescribeClusterResponse {
+    fn encode(&self, buf: &mut BytesMut, version: i16) -> Result<(), io::Error> {
+        self.throttle_time_ms.encode(buf)?;
+        self.error_code.encode(buf)?;
+        CompactNullableString(self.error_message.clone()).encode(buf)?;
+
+        if version > 0 {
+            self.endpoint_type.encode(buf)?;
+        }
+
+        CompactString(self.cluster_id.clone()).encode(buf)?;
+        self.controller_id.encode(buf)?;
+        CompactArrayRef(&self.brokers).encode(buf, version)?;
+        self.cluster_authorized_operations.encode(buf)?;
+        self.tagged_fields.encode(buf)?;
+
+        Ok(())
+    }
+
+    fn size_hint(&self, version: i16) -> usize {
+        4 + 2
+            + string_size_hint(&self.error_message)
+            + 1
+            + string_size_hint(&self.cluster_id)
+            + 4
+            + CompactArrayRef(&self.brokers).size_hint(version)
+            + 4
+            + 1
+    }
+}
+
+impl Response for DescribeClusterResponse {
+    fn set_throttle_time_ms(&mut self, throttle_time_ms: i32) {
+        self.throttle_time_ms = throttle_time_ms;
+    }
+}
+
+pub struct DescribeClusterBroker {
+    pub broker_id: i32,
+    pub host: String,
+    pub port: i32,
+    pub rack: String,
+    pub is_fenced: bool,
+    pub tagged_fields: BTreeMap<i32, Bytes>,
+}
+
+impl EncoderVersioned for DescribeClusterBroker {
+    fn encode(&self, buf: &mut BytesMut, version: i16) -> Result<(), io::Error> {
+        self.broker_id.encode(buf)?;
+        CompactString(self.host.clone()).encode(buf)?;
+        self.port.encode(buf)?;
+        CompactNullableString(self.rack.clone()).encode(buf)?;
+
+        if version > 1 {
+            self.is_fenced.encode(buf)?;
+        }
+
+        self.tagged_fields.encode(buf)?;
+        Ok(())
+    }
+
+    fn size_hint(&self, _version: i16) -> usize {
+        4 + string_size_hint(&self.host) + 4 + string_size_hint(&self.rack) + 1 + 1
+    }
+}
@@ -0,0 +1,329 @@
+use std::{collections::BTreeMap, io};
+
+use bytes::{Bytes, BytesMut};
+
+use crate::{
+    Message, VersionRange,
+    authorizer::{Operation, Resource},
+    configs::ResourceType,
+    protocol::{
+        DecodeError, Decoder, DecoderVersioned, Encoder, EncoderVersioned,
+        error::ErrorCode,
+        primitives::{
+            ArrayRef, CompactArray, CompactArrayRef, CompactNullableArray, CompactNullableString,
+            CompactString, NullableArray, NullableString, string_size_hint,
+        },
+        request::Request,
+        response::Response,
+    },
+};
+
+#[derive(Debug)]
+pub struct DescribeConfigsRequest {
+    pub resources: Vec<DescribeConfigsResource>,
+    pub include_synonyms: bool,
+    pub include_documentation: bool,
+    pub tagged_fields: BTreeMap<i32, Bytes>,
+}
+
+impl Message for DescribeConfigsRequest {
+    const VERSIONS: VersionRange = VersionRange { min: 0, max: 4 };
+    const DEPRECATED_VERSIONS: Option<VersionRange> = None;
+
+    fn header_version(version: i16) -> i16 {
+        if version < 4 { 1 } else { 2 }
+    }
+}
+
+impl Request for DescribeConfigsRequest {
+    type Response = DescribeConfigsResponse;
+
+    fn error_response(&self, error_code: ErrorCode) -> DescribeConfigsResponse {
+        let results = self
+            .resources
+            .iter()
+            .map(|resource| DescribeConfigsResult {
+                error_code,
+                error_message: String::new(),
+                resource_type: resource.resource_type,
+                resource_name: resource.resource_name.clone(),
+                configs: vec![],
+                tagged_fields: Default::default(),
+            })
+            .collect();
+
+        DescribeConfigsResponse {
+            results,
+            ..Self::decode_error_response(error_code)
+        }
+    }
+
+    fn decode_error_response(_error_code: ErrorCode) -> DescribeConfigsResponse {
+        DescribeConfigsResponse {
+            throttle_time_ms: 0,
+            results: vec![],
+            tagged_fields: Default::default(),
+        }
+    }
+
+    /// Broker configs belong to the cluster. Resources of unknown types are
+    /// left for the handler to reject.
+    fn operations(&self) -> Vec<(Operation, Resource)> {
+        self.resources
+            .iter()
+            .filter_map(
+                |resource| match ResourceType::from_i8(resource.resource_type)? {
+                    ResourceType::Topic => Some(Resource::Topic(resource.resource_name.clone())),
+                    ResourceType::Broker => Some(Resource::Cluster),
+                },
+            )
+            .map(|resource| (Operation::DescribeConfigs, resource))
+            .collect()
+    }
+}
+
+impl DecoderVersioned for DescribeConfigsRequest {
+    fn decode(buf: &mut BytesMut, version: i16) -> Result<Self, io::Error> {
+        if !Self::VERSIONS.contains(version) {
+            return Err(DecodeError::unsupported("unsupported version").into());
+        }
+
+        let resources = if version < 4 {
+            Vec::<DescribeConfigsResource>::decode(buf, version)?
+        } else {
+            CompactArray::<DescribeConfigsResource>::decode(buf, version)?.0
+        };
+
+        let include_synonyms = if version < 1 {
+            false
+        } else {
+            bool::decode(buf)?
+        };
+
+        let include_documentation = if version < 3 {
+            false
+        } else {
+            bool::decode(buf)?
+        };
+
+        let mut tagged_fields = BTreeMap::new();
+        if version > 3 {
+            tagged_fields = Decoder::decode(buf)?;
+        }
+
+        Ok(Self {
+            resources,
+            include_synonyms,
+            include_documentation,
+            tagged_fields,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct DescribeConfigsResource {
+    pub resource_type: i8,
+    pub resource_name: String,
+    /// The config keys to describe, or `None` to describe all of them.
+    pub configuration_keys: Option<Vec<String>>,
+    pub tagged_fields: BTreeMap<i32, Bytes>,
+}
+
+impl DecoderVersioned for DescribeConfigsResource {
+    fn decode(buf: &mut BytesMut, version: i16) -> Result<Self, io::Error> {
+        let resource_type = i8::decode(buf)?;
+
+        let resource_name = if version < 4 {
+            String::decode(buf)?
+        } else {
+            CompactString::decode(buf)?.0
+        };
+
+        let configuration_keys = if version < 4 {
+            NullableArray::<String>::decode(buf)?.0
+        } else {
+            CompactNullableArray::<CompactString>::decode(buf)?
+                .0
+                .map(|keys| keys.into_iter().map(|key| key.0).collect())
+        };
+
+        let mut tagged_fields = BTreeMap::new();
+        if version > 3 {
+            tagged_fields = Decoder::decode(buf)?;
+        }
+
+        Ok(Self {
+            resource_type,
+            resource_name,
+            configuration_keys,
+            tagged_fields,
+        })
+    }
+}
+
+pub struct DescribeConfigsResponse {
+    pub throttle_time_ms: i32,
+    pub results: Vec<DescribeConfigsResult>,
+    pub tagged_fields: BTreeMap<i32, Bytes>,
+}
+
+impl EncoderVersioned for DescribeConfigsResponse {
+    fn encode(&self, buf: &mut BytesMut, version: i16) -> Result<(), io::Error> {
+        self.throttle_time_ms.encode(buf)?;
+
+        if version < 4 {
+            ArrayRef(&self.results).encode(buf, version)?;
+        } else {
+            CompactArrayRef(&self.results).encode(buf, version)?;
+            self.tagged_fields.encode(buf)?;
+        }
+
+        Ok(())
+    }
+
+    fn size_hint(&self, version: i16) -> usize {
+        4 + CompactArrayRef(&self.results).size_hint(version) + 1
+    }
+}
+
+impl Response for DescribeConfigsResponse {
+    fn set_throttle_time_ms(&mut self, throttle_time_ms: i32) {
+        self.throttle_time_ms = throttle_time_ms;
+    }
+}
+
+pub struct DescribeConfigsResult {
+    pub error_code: ErrorCode,
+    pub error_message: String,
+    pub resource_type: i8,
+    pub resource_name: String,
+    pub configs: Vec<DescribeConfigsResourceResult>,
+    pub tagged_fields: BTreeMap<i32, Bytes>,
+}
+
+impl EncoderVersioned for DescribeConfigsResult {
+    fn encode(&self, buf: &mut BytesMut, version: i16) -> Result<(), io::Error> {
+        self.error_code.encode(buf)?;
+
+        if version < 4 {
+            NullableString(self.error_message.clone()).encode(buf)?;
+            self.resource_type.encode(buf)?;
+            self.resource_name.encode(buf)?;
+            ArrayRef(&self.configs).encode(buf, version)?;
+        } else {
+            CompactNullableString(self.error_message.clone()).encode(buf)?;
+            self.resource_type.encode(buf)?;
+            CompactString(self.resource_name.clone()).encode(buf)?;
+            CompactArrayRef(&self.configs).encode(buf, version)?;
+            self.tagged_fields.encode(buf)?;
+        }
+
+        Ok(())
+    }
+
+    fn size_hint(&self, version: i16) -> usize {
+        2 + string_size_hint(&self.error_message)
+            + 1
+            + string_size_hint(&self.resource_name)
+            + CompactArrayRef(&self.configs).size_hint(version)
+            + 1
+    }
+}
+
+pub struct DescribeConfigsResourceResult {
+    pub name: String,
+    /// The config value, or empty if it is unset or sensitive.
+    pub value: String,
+    pub read_only: bool,
+    pub is_default: bool,
+    pub config_source: i8,
+    pub is_sensitive: bool,
+    pub synonyms: Vec<DescribeConfigsSynonym>,
+    pub config_type: i8,
+    pub documentation: String,
+    pub tagged_fields: BTreeMap<i32, Bytes>,
+}
+
+impl EncoderVersioned for DescribeConfigsResourceResult {
+    fn encode(&self, buf: &mut BytesMut, version: i16) -> Result<(), io::Error> {
+        if version < 4 {
+            self.name.encode(buf)?;
+            NullableString(self.value.clone()).encode(buf)?;
+        } else {
+            CompactString(self.name.clone()).encode(buf)?;
+            CompactNullableString(self.value.clone()).encode(buf)?;
+        }
+
+        self.read_only.encode(buf)?;
+
+        if version < 1 {
+            self.is_default.encode(buf)?;
+        } else {
+            self.config_source.encode(buf)?;
+        }
+
+        self.is_sensitive.encode(buf)?;
+
+        if version > 0 {
+            if version < 4 {
+                ArrayRef(&self.synonyms).encode(buf, version)?;
+            } else {
+                CompactArrayRef(&self.synonyms).encode(buf, version)?;
+            }
+        }
+
+        if version > 2 {
+            self.config_type.encode(buf)?;
+
+            if version < 4 {
+                NullableString(self.documentation.clone()).encode(buf)?;
+            } else {
+                CompactNullableString(self.documentation.clone()).encode(buf)?;
+            }
+        }
+
+        if version > 3 {
+            self.tagged_fields.encode(buf)?;
+        }
+
+        Ok(())
+    }
+
+    fn size_hint(&self, version: i16) -> usize {
+        string_size_hint(&self.name)
+            + string_size_hint(&self.value)
+            + 4
+            + CompactArrayRef(&self.synonyms).size_hint(version)
+            + 1
+            + string_size_hint(&self.documentation)
+            + 1
+    }
+}
+
+pub struct DescribeConfigsSynonym {
+    pub name: String,
+    pub value: String,
+    pub source: i8,
+    pub tagged_fields: BTreeMap<i32, Bytes>,
+}
+
+impl EncoderVersioned for DescribeConfigsSynonym {
+    fn encode(&self, buf: &mut BytesMut, version: i16) -> Result<(), io::Error> {
+        if version < 4 {
+            self.name.encode(buf)?;
+            NullableString(self.value.clone()).encode(buf)?;
+            self.source.encode(buf)?;
+        } else {
+            CompactString(self.name.clone()).encode(buf)?;
+            CompactNullableString(self.value.clone()).encode(buf)?;
+            self.source.encode(buf)?;
+            self.tagged_fields.encode(buf)?;
+        }
+
+        Ok(())
+    }
+
+    fn size_hint(&self, _version: i16) -> usize {
+        string_size_hint(&self.name) + string_size_hint(&self.value) + 1 + 1
+    }
+}
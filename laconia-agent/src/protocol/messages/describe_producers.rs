@@ -0,0 +1,301 @@
+use std::{collections::BTreeMap, io};
+
+use bytes::{Bytes, BytesMut};
+
+use crate::{
+    Message, VersionRange,
+    authorizer::{Operation, Resource},
+    protocol::{
+        DecodeError, Decoder, DecoderVersioned, Encoder, EncoderVersioned,
+        error::ErrorCode,
+        primitives::{CompactArray, CompactArrayRef, CompactNullableString, CompactString},
+        request::Request,
+        response::Response,
+    },
+};
+
+/// Asks for the producers with state on partitions, as admin tools do to
+/// find the producer holding up a stuck transaction.
+#[derive(Debug)]
+pub struct DescribeProducersRequest {
+    pub topics: Vec<DescribeProducersRequestTopic>,
+    pub tagged_fields: BTreeMap<i32, Bytes>,
+}
+
+impl Message for DescribeProducersRequest {
+    const VERSIONS: VersionRange = VersionRange { min: 0, max: 0 };
+    const DEPRECATED_VERSIONS: Option<VersionRange> = None;
+
+    fn header_version(_version: i16) -> i16 {
+        2
+    }
+}
+
+impl Request for DescribeProducersRequest {
+    type Response = DescribeProducersResponse;
+
+    fn error_response(&self, error_code: ErrorCode) -> DescribeProducersResponse {
+        let topics = self
+            .topics
+            .iter()
+            .map(|topic| DescribeProducersResponseTopic {
+                name: topic.name.clone(),
+                partitions: topic
+                    .partition_indexes
+                    .iter()
+                    .map(|&partition_index| {
+                        DescribeProducersResponsePartition::new(partition_index, error_code)
+                    })
+                    .collect(),
+                tagged_fields: Default::default(),
+            })
+            .collect();
+
+        DescribeProducersResponse {
+            topics,
+            ..Self::decode_error_response(error_code)
+        }
+    }
+
+    fn decode_error_response(_error_code: ErrorCode) -> DescribeProducersResponse {
+        DescribeProducersResponse {
+            throttle_time_ms: 0,
+            topics: vec![],
+            tagged_fields: Default::default(),
+        }
+    }
+
+    fn operations(&self) -> Vec<(Operation, Resource)> {
+        self.topics
+            .iter()
+            .map(|topic| (Operation::Read, Resource::Topic(topic.name.clone())))
+            .collect()
+    }
+}
+
+impl DecoderVersioned for DescribeProducersRequest {
+    fn decode(buf: &mut BytesMut, version: i16) -> Result<Self, io::Error> {
+        if !Self::VERSIONS.contains(version) {
+            return Err(DecodeError::unsupported("unsupported version").into());
+        }
+
+        let topics = CompactArray::<DescribeProducersRequestTopic>::decode(buf, version)?.0;
+        let tagged_fields = Decoder::decode(buf)?;
+
+        Ok(Self {
+            topics,
+            tagged_fields,
+        })
+    }
+}
+
+impl EncoderVersioned for DescribeProducersRequest {
+    fn encode(&self, buf: &mut BytesMut, version: i16) -> Result<(), io::Error> {
+        CompactArrayRef(&self.topics).encode(buf, version)?;
+        self.tagged_fields.encode(buf)?;
+
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub struct DescribeProducersRequestTopic {
+    pub name: String,
+    pub partition_indexes: Vec<i32>,
+    pub tagged_fields: BTreeMap<i32, Bytes>,
+}
+
+impl DecoderVersioned for DescribeProducersRequestTopic {
+    fn decode(buf: &mut BytesMut, _version: i16) -> Result<Self, io::Error> {
+        let name = CompactString::decode(buf)?.0;
+        let partition_indexes = CompactArray::<i32>::decode(buf)?.0;
+        let tagged_fields = Decoder::decode(buf)?;
+
+        Ok(Self {
+            name,
+            partition_indexes,
+            tagged_fields,
+        })
+    }
+}
+
+impl EncoderVersioned for DescribeProducersRequestTopic {
+    fn encode(&self, buf: &mut BytesMut, _version: i16) -> Result<(), io::Error> {
+        CompactString(self.name.clone()).encode(buf)?;
+        CompactArrayRef(&self.partition_indexes).encode(buf)?;
+        self.tagged_fields.encode(buf)?;
+
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub struct DescribeProducersResponse {
+    pub throttle_time_ms: i32,
+    pub topics: Vec<DescribeProducersResponseTopic>,
+    pub tagged_fields: BTreeMap<i32, Bytes>,
+}
+
+impl EncoderVersioned for DescribeProducersResponse {
+    fn encode(&self, buf: &mut BytesMut, version: i16) -> Result<(), io::Error> {
+        self.throttle_time_ms.encode(buf)?;
+        CompactArrayRef(&self.topics).encode(buf, version)?;
+        self.tagged_fields.encode(buf)?;
+
+        Ok(())
+    }
+}
+
+impl DecoderVersioned for DescribeProducersResponse {
+    fn decode(buf: &mut BytesMut, version: i16) -> Result<Self, io::Error> {
+        let throttle_time_ms = i32::decode(buf)?;
+        let topics = CompactArray::<DescribeProducersResponseTopic>::decode(buf, version)?.0;
+        let tagged_fields = Decoder::decode(buf)?;
+
+        Ok(Self {
+            throttle_time_ms,
+            topics,
+            tagged_fields,
+        })
+    }
+}
+
+impl Response for DescribeProducersResponse {
+    fn set_throttle_time_ms(&mut self, throttle_time_ms: i32) {
+        self.throttle_time_ms = throttle_time_ms;
+    }
+}
+
+#[derive(Debug)]
+pub struct DescribeProducersResponseTopic {
+    pub name: String,
+    pub partitions: Vec<DescribeProducersResponsePartition>,
+    pub tagged_fields: BTreeMap<i32, Bytes>,
+}
+
+impl EncoderVersioned for DescribeProducersResponseTopic {
+    fn encode(&self, buf: &mut BytesMut, version: i16) -> Result<(), io::Error> {
+        CompactString(self.name.clone()).encode(buf)?;
+        CompactArrayRef(&self.partitions).encode(buf, version)?;
+        self.tagged_fields.encode(buf)?;
+
+        Ok(())
+    }
+}
+
+impl DecoderVersioned for DescribeProducersResponseTopic {
+    fn decode(buf: &mut BytesMut, version: i16) -> Result<Self, io::Error> {
+        let name = CompactString::decode(buf)?.0;
+        let partitions =
+            CompactArray::<DescribeProducersResponsePartition>::decode(buf, version)?.0;
+        let tagged_fields = Decoder::decode(buf)?;
+
+        Ok(Self {
+            name,
+            partitions,
+            tagged_fields,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct DescribeProducersResponsePartition {
+    pub partition_index: i32,
+    pub error_code: ErrorCode,
+    pub error_message: String,
+    pub active_producers: Vec<ProducerState>,
+    pub tagged_fields: BTreeMap<i32, Bytes>,
+}
+
+impl DescribeProducersResponsePartition {
+    pub fn new(partition_index: i32, error_code: ErrorCode) -> Self {
+        Self {
+            partition_index,
+            error_code,
+            error_message: String::new(),
+            active_producers: vec![],
+            tagged_fields: Default::default(),
+        }
+    }
+}
+
+impl EncoderVersioned for DescribeProducersResponsePartition {
+    fn encode(&self, buf: &mut BytesMut, version: i16) -> Result<(), io::Error> {
+        self.partition_index.encode(buf)?;
+        self.error_code.encode(buf)?;
+        CompactNullableString(self.error_message.clone()).encode(buf)?;
+        CompactArrayRef(&self.active_producers).encode(buf, version)?;
+        self.tagged_fields.encode(buf)?;
+
+        Ok(())
+    }
+}
+
+impl DecoderVersioned for DescribeProducersResponsePartition {
+    fn decode(buf: &mut BytesMut, version: i16) -> Result<Self, io::Error> {
+        let partition_index = i32::decode(buf)?;
+        let error_code = ErrorCode::decode(buf)?;
+        let error_message = CompactNullableString::decode(buf)?.0;
+        let active_producers = CompactArray::<ProducerState>::decode(buf, version)?.0;
+        let tagged_fields = Decoder::decode(buf)?;
+
+        Ok(Self {
+            partition_index,
+            error_code,
+            error_message,
+            active_producers,
+            tagged_fields,
+        })
+    }
+}
+
+/// A producer with state on a partition. Fields the broker doesn't know are
+/// -1.
+#[derive(Debug)]
+pub struct ProducerState {
+    pub producer_id: i64,
+    pub producer_epoch: i32,
+    pub last_sequence: i32,
+    pub last_timestamp: i64,
+    pub coordinator_epoch: i32,
+    /// The offset the producer's ongoing transaction started at, or -1 if
+    /// it has none.
+    pub current_txn_start_offset: i64,
+    pub tagged_fields: BTreeMap<i32, Bytes>,
+}
+
+impl EncoderVersioned for ProducerState {
+    fn encode(&self, buf: &mut BytesMut, _version: i16) -> Result<(), io::Error> {
+        self.producer_id.encode(buf)?;
+        self.producer_epoch.encode(buf)?;
+        self.last_sequence.encode(buf)?;
+        self.last_timestamp.encode(buf)?;
+        self.coordinator_epoch.encode(buf)?;
+        self.current_txn_start_offset.encode(buf)?;
+        self.tagged_fields.encode(buf)?;
+
+        Ok(())
+    }
+}
+
+impl DecoderVersioned for ProducerState {
+    fn decode(buf: &mut BytesMut, _version: i16) -> Result<Self, io::Error> {
+        let producer_id = i64::decode(buf)?;
+        let producer_epoch = i32::decode(buf)?;
+        let last_sequence = i32::decode(buf)?;
+        let last_timestamp = i64::decode(buf)?;
+        let coordinator_epoch = i32::decode(buf)?;
+        let current_txn_start_offset = i64::decode(buf)?;
+        let tagged_fields = Decoder::decode(buf)?;
+
+        Ok(Self {
+            producer_id,
+            producer_epoch,
+            last_sequence,
+            last_timestamp,
+            coordinator_epoch,
+            current_txn_start_offset,
+            tagged_fields,
+        })
+    }
+}
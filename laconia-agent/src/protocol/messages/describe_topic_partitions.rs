@@ -0,0 +1,276 @@
+use std::{collections::BTreeMap, io};
+
+use bytes::{Bytes, BytesMut};
+use uuid::Uuid;
+
+use crate::{
+    Message, VersionRange,
+    authorizer::{Operation, Resource},
+    protocol::{
+        DecodeError, Decoder, DecoderVersioned, Encoder, EncoderVersioned,
+        error::ErrorCode,
+        primitives::{
+            CompactArray, CompactArrayRef, CompactNullableArrayRef, CompactNullableString,
+            CompactString, string_size_hint,
+        },
+        request::Request,
+        response::Response,
+    },
+};
+
+#[derive(Debug)]
+pub struct DescribeTopicPartitionsRequest {
+    /// The topics to describe, or empty for all topics.
+    pub topics: Vec<DescribeTopicPartitionsRequestTopic>,
+    pub response_partition_limit: i32,
+    /// Where to resume from, as returned by a previous response.
+    pub cursor: Option<DescribeTopicPartitionsCursor>,
+    pub tagged_fields: BTreeMap<i32, Bytes>,
+}
+
+impl Message for DescribeTopicPartitionsRequest {
+    const VERSIONS: VersionRange = VersionRange { min: 0, max: 0 };
+    const DEPRECATED_VERSIONS: Option<VersionRange> = None;
+
+    fn header_version(_version: i16) -> i16 {
+        2
+    }
+}
+
+impl Request for DescribeTopicPartitionsRequest {
+    type Response = DescribeTopicPartitionsResponse;
+
+    fn error_response(&self, error_code: ErrorCode) -> DescribeTopicPartitionsResponse {
+        let topics = self
+            .topics
+            .iter()
+            .map(|topic| DescribeTopicPartitionsResponseTopic::new(&topic.name, error_code))
+            .collect();
+
+        DescribeTopicPartitionsResponse {
+            topics,
+            ..Self::decode_error_response(error_code)
+        }
+    }
+
+    fn decode_error_response(_error_code: ErrorCode) -> DescribeTopicPartitionsResponse {
+        DescribeTopicPartitionsResponse {
+            throttle_time_ms: 0,
+            topics: vec![],
+            next_cursor: None,
+            tagged_fields: Default::default(),
+        }
+    }
+
+    /// Listing every topic describes the cluster.
+    fn operations(&self) -> Vec<(Operation, Resource)> {
+        if self.topics.is_empty() {
+            return vec![(Operation::Describe, Resource::Cluster)];
+        }
+
+        self.topics
+            .iter()
+            .map(|topic| (Operation::Describe, Resource::Topic(topic.name.clone())))
+            .collect()
+    }
+}
+
+impl DecoderVersioned for DescribeTopicPartitionsRequest {
+    fn decode(buf: &mut BytesMut, version: i16) -> Result<Self, io::Error> {
+        if !Self::VERSIONS.contains(version) {
+            return Err(DecodeError::unsupported("unsupported version").into());
+        }
+
+        let topics = CompactArray::<DescribeTopicPartitionsRequestTopic>::decode(buf)?.0;
+        let response_partition_limit = i32::decode(buf)?;
+        let cursor = Decoder::decode(buf)?;
+        let tagged_fields = Decoder::decode(buf)?;
+
+        Ok(Self {
+            topics,
+            response_partition_limit,
+            cursor,
+            tagged_fields,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct DescribeTopicPartitionsRequestTopic {
+    pub name: String,
+    pub tagged_fields: BTreeMap<i32, Bytes>,
+}
+
+impl Decoder for DescribeTopicPartitionsRequestTopic {
+    fn decode(buf: &mut BytesMut) -> Result<Self, io::Error> {
+        let name = CompactString::decode(buf)?.0;
+        let tagged_fields = Decoder::decode(buf)?;
+
+        Ok(Self {
+            name,
+            tagged_fields,
+        })
+    }
+}
+
+/// The first partition to describe, used both to resume a request and to
+/// tell the client where to resume from.
+#[derive(Debug, Clone)]
+pub struct DescribeTopicPartitionsCursor {
+    pub topic_name: String,
+    pub partition_index: i32,
+    pub tagged_fields: BTreeMap<i32, Bytes>,
+}
+
+/// A nullable struct is prefixed with -1 when null and 1 otherwise.
+impl Decoder for Option<DescribeTopicPartitionsCursor> {
+    fn decode(buf: &mut BytesMut) -> Result<Self, io::Error> {
+        if i8::decode(buf)? < 0 {
+            return Ok(None);
+        }
+
+        let topic_name = CompactString::decode(buf)?.0;
+        let partition_index = i32::decode(buf)?;
+        let tagged_fields = Decoder::decode(buf)?;
+
+        Ok(Some(DescribeTopicPartitionsCursor {
+            topic_name,
+            partition_index,
+            tagged_fields,
+        }))
+    }
+}
+
+impl Encoder for Option<DescribeTopicPartitionsCursor> {
+    fn encode(&self, buf: &mut BytesMut) -> Result<(), io::Error> {
+        let Some(cursor) = self else {
+            return (-1i8).encode(buf);
+        };
+
+        1i8.encode(buf)?;
+        CompactString(cursor.topic_name.clone()).encode(buf)?;
+        cursor.partition_index.encode(buf)?;
+        cursor.tagged_fields.encode(buf)?;
+        Ok(())
+    }
+}
+
+pub struct DescribeTopicPartitionsResponse {
+    pub throttle_time_ms: i32,
+    pub topics: Vec<DescribeTopicPartitionsResponseTopic>,
+    /// Where the next request should resume from, or `None` if every
+    /// requested partition was described.
+    pub next_cursor: Option<DescribeTopicPartitionsCursor>,
+    pub tagged_fields: BTreeMap<i32, Bytes>,
+}
+
+impl EncoderVersioned for DescribeTopicPartitionsResponse {
+    fn encode(&self, buf: &mut BytesMut, version: i16) -> Result<(), io::Error> {
+        self.throttle_time_ms.encode(buf)?;
+        CompactArrayRef(&self.topics).encode(buf, version)?;
+        self.next_cursor.encode(buf)?;
+        self.tagged_fields.encode(buf)?;
+        Ok(())
+    }
+
+    fn size_hint(&self, version: i16) -> usize {
+        4 + CompactArrayRef(&self.topics).size_hint(version)
+            + 1
+            + self
+                .next_cursor
+                .as_ref()
+                .map_or(0, |cursor| string_size_hint(&cursor.topic_name) + 4 + 1)
+            + 1
+    }
+}
+
+impl Response for DescribeTopicPartitionsResponse {
+    fn set_throttle_time_ms(&mut self, throttle_time_ms: i32) {
+        self.throttle_time_ms = throttle_time_ms;
+    }
+}
+
+pub struct DescribeTopicPartitionsResponseTopic {
+    pub error_code: ErrorCode,
+    pub name: String,
+    pub topic_id: Uuid,
+    pub is_internal: bool,
+    pub partitions: Vec<DescribeTopicPartitionsResponsePartition>,
+    pub topic_authorized_operations: i32,
+    pub tagged_fields: BTreeMap<i32, Bytes>,
+}
+
+impl DescribeTopicPartitionsResponseTopic {
+    /// A topic with no partitions described yet.
+    pub fn new(name: &str, error_code: ErrorCode) -> Self {
+        Self {
+            error_code,
+            name: name.to_string(),
+            topic_id: Uuid::nil(),
+            is_internal: false,
+            partitions: vec![],
+            topic_authorized_operations: i32::MIN,
+            tagged_fields: Default::default(),
+        }
+    }
+}
+
+impl EncoderVersioned for DescribeTopicPartitionsResponseTopic {
+    fn encode(&self, buf: &mut BytesMut, version: i16) -> Result<(), io::Error> {
+        self.error_code.encode(buf)?;
+        CompactNullableString(self.name.clone()).encode(buf)?;
+        self.topic_id.encode(buf)?;
+        self.is_internal.encode(buf)?;
+        CompactArrayRef(&self.partitions).encode(buf, version)?;
+        self.topic_authorized_operations.encode(buf)?;
+        self.tagged_fields.encode(buf)?;
+        Ok(())
+    }
+
+    fn size_hint(&self, version: i16) -> usize {
+        2 + string_size_hint(&self.name)
+            + 16
+            + 1
+            + CompactArrayRef(&self.partitions).size_hint(version)
+            + 4
+            + 1
+    }
+}
+
+pub struct DescribeTopicPartitionsResponsePartition {
+    pub error_code: ErrorCode,
+    pub partition_index: i32,
+    pub leader_id: i32,
+    pub leader_epoch: i32,
+    pub replica_nodes: Vec<i32>,
+    pub isr_nodes: Vec<i32>,
+    pub eligible_leader_replicas: Option<Vec<i32>>,
+    pub last_known_elr: Option<Vec<i32>>,
+    pub offline_replicas: Vec<i32>,
+    pub tagged_fields: BTreeMap<i32, Bytes>,
+}
+
+impl EncoderVersioned for DescribeTopicPartitionsResponsePartition {
+    fn encode(&self, buf: &mut BytesMut, _version: i16) -> Result<(), io::Error> {
+        self.error_code.encode(buf)?;
+        self.partition_index.encode(buf)?;
+        self.leader_id.encode(buf)?;
+        self.leader_epoch.encode(buf)?;
+        CompactArrayRef(&self.replica_nodes).encode(buf)?;
+        CompactArrayRef(&self.isr_nodes).encode(buf)?;
+        CompactNullableArrayRef(self.eligible_leader_replicas.as_deref()).encode(buf)?;
+        CompactNullableArrayRef(self.last_known_elr.as_deref()).encode(buf)?;
+        CompactArrayRef(&self.offline_replicas).encode(buf)?;
+        self.tagged_fields.encode(buf)?;
+        Ok(())
+    }
+
+    fn size_hint(&self, _version: i16) -> usize {
+        let replicas = self.replica_nodes.len()
+            + self.isr_nodes.len()
+            + self.eligible_leader_replicas.as_ref().map_or(0, Vec::len)
+            + self.last_known_elr.as_ref().map_or(0, Vec::len)
+            + self.offline_replicas.len();
+        2 + 4 + 4 + 4 + 5 + 4 * replicas + 1
+    }
+}
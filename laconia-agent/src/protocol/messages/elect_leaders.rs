@@ -0,0 +1,362 @@
+use std::{collections::BTreeMap, io};
+
+use bytes::{Bytes, BytesMut};
+
+use crate::{
+    Message, VersionRange,
+    authorizer::{Operation, Resource},
+    protocol::{
+        DecodeError, Decoder, DecoderVersioned, Encoder, EncoderVersioned,
+        error::ErrorCode,
+        primitives::{
+            ArrayRef, CompactArray, CompactArrayRef, CompactNullableArray, CompactNullableArrayRef,
+            CompactNullableString, CompactString, NullableArray, NullableArrayRef, NullableString,
+        },
+        request::Request,
+        response::Response,
+    },
+};
+
+/// Elects the preferred replica of a partition as its leader.
+pub const ELECTION_PREFERRED: i8 = 0;
+/// Elects any replica as a partition's leader, even one that's out of sync.
+pub const ELECTION_UNCLEAN: i8 = 1;
+
+/// Asks for leaders to be elected for partitions, as admin tools do to move
+/// leadership back to preferred replicas.
+#[derive(Debug)]
+pub struct ElectLeadersRequest {
+    /// One of the `ELECTION_` types. Version 0 always elects the preferred
+    /// replica.
+    pub election_type: i8,
+    /// The partitions to elect leaders for, or `None` for every partition.
+    pub topic_partitions: Option<Vec<ElectLeadersTopicPartitions>>,
+    pub timeout_ms: i32,
+    pub tagged_fields: BTreeMap<i32, Bytes>,
+}
+
+impl Message for ElectLeadersRequest {
+    const VERSIONS: VersionRange = VersionRange { min: 0, max: 2 };
+    const DEPRECATED_VERSIONS: Option<VersionRange> = None;
+
+    fn header_version(version: i16) -> i16 {
+        if version < 2 { 1 } else { 2 }
+    }
+}
+
+impl Request for ElectLeadersRequest {
+    type Response = ElectLeadersResponse;
+
+    fn error_response(&self, error_code: ErrorCode) -> ElectLeadersResponse {
+        let replica_election_results = self
+            .topic_partitions
+            .iter()
+            .flatten()
+            .map(|topic| ElectLeadersTopicResult {
+                topic: topic.topic.clone(),
+                partition_results: topic
+                    .partitions
+                    .iter()
+                    .map(|&partition_id| ElectLeadersPartitionResult::new(partition_id, error_code))
+                    .collect(),
+                tagged_fields: Default::default(),
+            })
+            .collect();
+
+        ElectLeadersResponse {
+            replica_election_results,
+            ..Self::decode_error_response(error_code)
+        }
+    }
+
+    fn decode_error_response(error_code: ErrorCode) -> ElectLeadersResponse {
+        ElectLeadersResponse {
+            throttle_time_ms: 0,
+            error_code,
+            replica_election_results: vec![],
+            tagged_fields: Default::default(),
+        }
+    }
+
+    fn operations(&self) -> Vec<(Operation, Resource)> {
+        vec![(Operation::Alter, Resource::Cluster)]
+    }
+
+    fn timeout_ms(&self) -> Option<i32> {
+        Some(self.timeout_ms)
+    }
+}
+
+impl DecoderVersioned for ElectLeadersRequest {
+    fn decode(buf: &mut BytesMut, version: i16) -> Result<Self, io::Error> {
+        if !Self::VERSIONS.contains(version) {
+            return Err(DecodeError::unsupported("unsupported version").into());
+        }
+
+        let election_type = if version < 1 {
+            ELECTION_PREFERRED
+        } else {
+            i8::decode(buf)?
+        };
+
+        let topic_partitions = if version < 2 {
+            NullableArray::<ElectLeadersTopicPartitions>::decode(buf, version)?.0
+        } else {
+            CompactNullableArray::<ElectLeadersTopicPartitions>::decode(buf, version)?.0
+        };
+
+        let timeout_ms = i32::decode(buf)?;
+
+        let mut tagged_fields = BTreeMap::new();
+        if version > 1 {
+            tagged_fields = Decoder::decode(buf)?;
+        }
+
+        Ok(Self {
+            election_type,
+            topic_partitions,
+            timeout_ms,
+            tagged_fields,
+        })
+    }
+}
+
+impl EncoderVersioned for ElectLeadersRequest {
+    fn encode(&self, buf: &mut BytesMut, version: i16) -> Result<(), io::Error> {
+        if version > 0 {
+            self.election_type.encode(buf)?;
+        }
+
+        if version < 2 {
+            NullableArrayRef(self.topic_partitions.as_deref()).encode(buf, version)?;
+        } else {
+            CompactNullableArrayRef(self.topic_partitions.as_deref()).encode(buf, version)?;
+        }
+
+        self.timeout_ms.encode(buf)?;
+
+        if version > 1 {
+            self.tagged_fields.encode(buf)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub struct ElectLeadersTopicPartitions {
+    pub topic: String,
+    pub partitions: Vec<i32>,
+    pub tagged_fields: BTreeMap<i32, Bytes>,
+}
+
+impl DecoderVersioned for ElectLeadersTopicPartitions {
+    fn decode(buf: &mut BytesMut, version: i16) -> Result<Self, io::Error> {
+        let (topic, partitions) = if version < 2 {
+            (String::decode(buf)?, Vec::<i32>::decode(buf)?)
+        } else {
+            (
+                CompactString::decode(buf)?.0,
+                CompactArray::<i32>::decode(buf)?.0,
+            )
+        };
+
+        let mut tagged_fields = BTreeMap::new();
+        if version > 1 {
+            tagged_fields = Decoder::decode(buf)?;
+        }
+
+        Ok(Self {
+            topic,
+            partitions,
+            tagged_fields,
+        })
+    }
+}
+
+impl EncoderVersioned for ElectLeadersTopicPartitions {
+    fn encode(&self, buf: &mut BytesMut, version: i16) -> Result<(), io::Error> {
+        if version < 2 {
+            self.topic.encode(buf)?;
+            self.partitions.encode(buf)?;
+        } else {
+            CompactString(self.topic.clone()).encode(buf)?;
+            CompactArrayRef(&self.partitions).encode(buf)?;
+            self.tagged_fields.encode(buf)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub struct ElectLeadersResponse {
+    pub throttle_time_ms: i32,
+    /// An error affecting the whole request. Version 0 only reports errors
+    /// per partition.
+    pub error_code: ErrorCode,
+    pub replica_election_results: Vec<ElectLeadersTopicResult>,
+    pub tagged_fields: BTreeMap<i32, Bytes>,
+}
+
+impl EncoderVersioned for ElectLeadersResponse {
+    fn encode(&self, buf: &mut BytesMut, version: i16) -> Result<(), io::Error> {
+        self.throttle_time_ms.encode(buf)?;
+
+        if version > 0 {
+            self.error_code.encode(buf)?;
+        }
+
+        if version < 2 {
+            ArrayRef(&self.replica_election_results).encode(buf, version)?;
+        } else {
+            CompactArrayRef(&self.replica_election_results).encode(buf, version)?;
+            self.tagged_fields.encode(buf)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl DecoderVersioned for ElectLeadersResponse {
+    fn decode(buf: &mut BytesMut, version: i16) -> Result<Self, io::Error> {
+        let throttle_time_ms = i32::decode(buf)?;
+
+        let error_code = if version > 0 {
+            ErrorCode::decode(buf)?
+        } else {
+            ErrorCode::None
+        };
+
+        let replica_election_results = if version < 2 {
+            Vec::<ElectLeadersTopicResult>::decode(buf, version)?
+        } else {
+            CompactArray::<ElectLeadersTopicResult>::decode(buf, version)?.0
+        };
+
+        let mut tagged_fields = BTreeMap::new();
+        if version > 1 {
+            tagged_fields = Decoder::decode(buf)?;
+        }
+
+        Ok(Self {
+            throttle_time_ms,
+            error_code,
+            replica_election_results,
+            tagged_fields,
+        })
+    }
+}
+
+impl Response for ElectLeadersResponse {
+    fn set_throttle_time_ms(&mut self, throttle_time_ms: i32) {
+        self.throttle_time_ms = throttle_time_ms;
+    }
+}
+
+#[derive(Debug)]
+pub struct ElectLeadersTopicResult {
+    pub topic: String,
+    pub partition_results: Vec<ElectLeadersPartitionResult>,
+    pub tagged_fields: BTreeMap<i32, Bytes>,
+}
+
+impl EncoderVersioned for ElectLeadersTopicResult {
+    fn encode(&self, buf: &mut BytesMut, version: i16) -> Result<(), io::Error> {
+        if version < 2 {
+            self.topic.encode(buf)?;
+            ArrayRef(&self.partition_results).encode(buf, version)?;
+        } else {
+            CompactString(self.topic.clone()).encode(buf)?;
+            CompactArrayRef(&self.partition_results).encode(buf, version)?;
+            self.tagged_fields.encode(buf)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl DecoderVersioned for ElectLeadersTopicResult {
+    fn decode(buf: &mut BytesMut, version: i16) -> Result<Self, io::Error> {
+        let (topic, partition_results) = if version < 2 {
+            (
+                String::decode(buf)?,
+                Vec::<ElectLeadersPartitionResult>::decode(buf, version)?,
+            )
+        } else {
+            (
+                CompactString::decode(buf)?.0,
+                CompactArray::<ElectLeadersPartitionResult>::decode(buf, version)?.0,
+            )
+        };
+
+        let mut tagged_fields = BTreeMap::new();
+        if version > 1 {
+            tagged_fields = Decoder::decode(buf)?;
+        }
+
+        Ok(Self {
+            topic,
+            partition_results,
+            tagged_fields,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct ElectLeadersPartitionResult {
+    pub partition_id: i32,
+    pub error_code: ErrorCode,
+    pub error_message: String,
+    pub tagged_fields: BTreeMap<i32, Bytes>,
+}
+
+impl ElectLeadersPartitionResult {
+    pub fn new(partition_id: i32, error_code: ErrorCode) -> Self {
+        Self {
+            partition_id,
+            error_code,
+            error_message: String::new(),
+            tagged_fields: Default::default(),
+        }
+    }
+}
+
+impl EncoderVersioned for ElectLeadersPartitionResult {
+    fn encode(&self, buf: &mut BytesMut, version: i16) -> Result<(), io::Error> {
+        self.partition_id.encode(buf)?;
+        self.error_code.encode(buf)?;
+
+        if version < 2 {
+            NullableString(self.error_message.clone()).encode(buf)?;
+        } else {
+            CompactNullableString(self.error_message.clone()).encode(buf)?;
+            self.tagged_fields.encode(buf)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl DecoderVersioned for ElectLeadersPartitionResult {
+    fn decode(buf: &mut BytesMut, version: i16) -> Result<Self, io::Error> {
+        let partition_id = i32::decode(buf)?;
+        let error_code = ErrorCode::decode(buf)?;
+
+        let mut tagged_fields = BTreeMap::new();
+        let error_message = if version < 2 {
+            NullableString::decode(buf)?.0
+        } else {
+            let error_message = CompactNullableString::decode(buf)?.0;
+            tagged_fields = Decoder::decode(buf)?;
+            error_message
+        };
+
+        Ok(Self {
+            partition_id,
+            error_code,
+            error_message,
+            tagged_fields,
+        })
+    }
+}
@@ -0,0 +1,114 @@
+use std::{collections::BTreeMap, io};
+
+use bytes::{Bytes, BytesMut};
+
+use crate::{
+    Message, VersionRange,
+    authorizer::{Operation, Resource},
+    protocol::{
+        DecodeError, Decoder, DecoderVersioned, Encoder, EncoderVersioned, error::ErrorCode,
+        primitives::CompactString, request::Request, response::Response,
+    },
+};
+
+#[derive(Debug)]
+pub struct EndTxnRequest {
+    pub transactional_id: String,
+    pub producer_id: i64,
+    pub producer_epoch: i16,
+    /// Whether the transaction is committed, rather than aborted.
+    pub committed: bool,
+    pub tagged_fields: BTreeMap<i32, Bytes>,
+}
+
+impl Message for EndTxnRequest {
+    const VERSIONS: VersionRange = VersionRange { min: 0, max: 3 };
+    const DEPRECATED_VERSIONS: Option<VersionRange> = None;
+
+    fn header_version(version: i16) -> i16 {
+        if version < 3 { 1 } else { 2 }
+    }
+}
+
+impl Request for EndTxnRequest {
+    type Response = EndTxnResponse;
+
+    fn error_response(&self, error_code: ErrorCode) -> EndTxnResponse {
+        Self::decode_error_response(error_code)
+    }
+
+    fn decode_error_response(error_code: ErrorCode) -> EndTxnResponse {
+        EndTxnResponse {
+            throttle_time_ms: 0,
+            error_code,
+            tagged_fields: Default::default(),
+        }
+    }
+
+    fn operations(&self) -> Vec<(Operation, Resource)> {
+        vec![(
+            Operation::Write,
+            Resource::TransactionalId(self.transactional_id.clone()),
+        )]
+    }
+}
+
+impl DecoderVersioned for EndTxnRequest {
+    fn decode(buf: &mut BytesMut, version: i16) -> Result<Self, io::Error> {
+        if !Self::VERSIONS.contains(version) {
+            return Err(DecodeError::unsupported("unsupported version").into());
+        }
+
+        let transactional_id = if version < 3 {
+            String::decode(buf)?
+        } else {
+            CompactString::decode(buf)?.0
+        };
+
+        let producer_id = i64::decode(buf)?;
+        let producer_epoch = i16::decode(buf)?;
+        let committed = bool::decode(buf)?;
+
+        let mut tagged_fields = BTreeMap::new();
+        if version > 2 {
+            tagged_fields = Decoder::decode(buf)?;
+        }
+
+        Ok(Self {
+            transactional_id,
+            producer_id,
+            producer_epoch,
+            committed,
+            tagged_fields,
+        })
+    }
+}
+
+pub struct EndTxnResponse {
+    pub throttle_time_ms: i32,
+    pub error_code: ErrorCode,
+    pub tagged_fields: BTreeMap<i32, Bytes>,
+}
+
+impl EncoderVersioned for EndTxnResponse {
+    fn encode(&self, buf: &mut BytesMut, version: i16) -> Result<(), io::Error> {
+        self.throttle_time_ms.encode(buf)?;
+        self.error_code.encode(buf)?;
+
+        if version > 2 {
+            self.tagged_fields.encode(buf)?;
+        }
+
+        Ok(())
+    }
+
+    fn size_hint(&self, _version: i16) -> usize {
+        4 + 2 + 1
+    }
+}
+
+impl Response for EndTxnResponse {
+    fn set_throttle_time_ms(&mut self, throttle_time_ms: i32) {
+        self.throttle_time_ms = throttle_time_ms;
+    }
+}
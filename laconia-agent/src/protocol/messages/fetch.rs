@@ -0,0 +1,401 @@
+use std::{collections::BTreeMap, io};
+
+use bytes::{Bytes, BytesMut};
+
+use crate::{
+    Message, VersionRange,
+    authorizer::{Operation, Resource},
+    protocol::{
+        DecodeError, Decoder, DecoderVersioned, Encoder, EncoderVersioned,
+        error::ErrorCode,
+        primitives::{
+            ArrayRef, CompactArray, CompactArrayRef, CompactBytes, CompactNullableArrayRef,
+            CompactString, NullableArrayRef, string_size_hint,
+        },
+        request::Request,
+        response::Response,
+    },
+};
+
+/// A consumer or follower fetching records. Versions before 4 predate record
+/// batches, and versions 13 and up identify topics by id, so neither is
+/// supported.
+#[derive(Debug)]
+pub struct FetchRequest {
+    pub replica_id: i32,
+    pub max_wait_ms: i32,
+    pub min_bytes: i32,
+    pub max_bytes: i32,
+    pub isolation_level: i8,
+    /// The fetch session, or 0 when not using one.
+    pub session_id: i32,
+    /// The epoch of the fetch session. -1 closes the session, if any, and
+    /// fetches without one, while 0 starts a new session.
+    pub session_epoch: i32,
+    pub topics: Vec<FetchRequestTopic>,
+    /// Partitions to remove from an incremental fetch session.
+    pub forgotten_topics_data: Vec<FetchForgottenTopic>,
+    pub rack_id: String,
+    pub tagged_fields: BTreeMap<i32, Bytes>,
+}
+
+impl Message for FetchRequest {
+    const VERSIONS: VersionRange = VersionRange { min: 4, max: 12 };
+    const DEPRECATED_VERSIONS: Option<VersionRange> = None;
+
+    fn header_version(version: i16) -> i16 {
+        if version < 12 { 1 } else { 2 }
+    }
+}
+
+impl Request for FetchRequest {
+    type Response = FetchResponse;
+
+    fn error_response(&self, error_code: ErrorCode) -> FetchResponse {
+        Self::decode_error_response(error_code)
+    }
+
+    fn decode_error_response(error_code: ErrorCode) -> FetchResponse {
+        FetchResponse {
+            throttle_time_ms: 0,
+            error_code,
+            session_id: 0,
+            responses: vec![],
+            tagged_fields: Default::default(),
+        }
+    }
+
+    /// Followers replicate every partition, which is a cluster action.
+    fn operations(&self) -> Vec<(Operation, Resource)> {
+        if self.replica_id >= 0 {
+            return vec![(Operation::ClusterAction, Resource::Cluster)];
+        }
+
+        self.topics
+            .iter()
+            .map(|topic| (Operation::Read, Resource::Topic(topic.topic.clone())))
+            .collect()
+    }
+
+    fn timeout_ms(&self) -> Option<i32> {
+        Some(self.max_wait_ms)
+    }
+}
+
+impl DecoderVersioned for FetchRequest {
+    fn decode(buf: &mut BytesMut, version: i16) -> Result<Self, io::Error> {
+        if !Self::VERSIONS.contains(version) {
+            return Err(DecodeError::unsupported("unsupported version").into());
+        }
+
+        let replica_id = i32::decode(buf)?;
+        let max_wait_ms = i32::decode(buf)?;
+        let min_bytes = i32::decode(buf)?;
+        let max_bytes = i32::decode(buf)?;
+        let isolation_level = i8::decode(buf)?;
+
+        let (session_id, session_epoch) = if version < 7 {
+            (0, -1)
+        } else {
+            (i32::decode(buf)?, i32::decode(buf)?)
+        };
+
+        let topics = if version < 12 {
+            Vec::<FetchRequestTopic>::decode(buf, version)?
+        } else {
+            CompactArray::<FetchRequestTopic>::decode(buf, version)?.0
+        };
+
+        let forgotten_topics_data = if version < 7 {
+            vec![]
+        } else if version < 12 {
+            Vec::<FetchForgottenTopic>::decode(buf, version)?
+        } else {
+            CompactArray::<FetchForgottenTopic>::decode(buf, version)?.0
+        };
+
+        let rack_id = if version < 11 {
+            String::new()
+        } else if version < 12 {
+            String::decode(buf)?
+        } else {
+            CompactString::decode(buf)?.0
+        };
+
+        let mut tagged_fields = BTreeMap::new();
+        if version > 11 {
+            tagged_fields = Decoder::decode(buf)?;
+        }
+
+        Ok(Self {
+            replica_id,
+            max_wait_ms,
+            min_bytes,
+            max_bytes,
+            isolation_level,
+            session_id,
+            session_epoch,
+            topics,
+            forgotten_topics_data,
+            rack_id,
+            tagged_fields,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct FetchRequestTopic {
+    pub topic: String,
+    pub partitions: Vec<FetchRequestPartition>,
+    pub tagged_fields: BTreeMap<i32, Bytes>,
+}
+
+impl DecoderVersioned for FetchRequestTopic {
+    fn decode(buf: &mut BytesMut, version: i16) -> Result<Self, io::Error> {
+        let (topic, partitions) = if version < 12 {
+            (
+                String::decode(buf)?,
+                Vec::<FetchRequestPartition>::decode(buf, version)?,
+            )
+        } else {
+            (
+                CompactString::decode(buf)?.0,
+                CompactArray::<FetchRequestPartition>::decode(buf, version)?.0,
+            )
+        };
+
+        let mut tagged_fields = BTreeMap::new();
+        if version > 11 {
+            tagged_fields = Decoder::decode(buf)?;
+        }
+
+        Ok(Self {
+            topic,
+            partitions,
+            tagged_fields,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct FetchRequestPartition {
+    pub partition: i32,
+    pub current_leader_epoch: i32,
+    pub fetch_offset: i64,
+    pub last_fetched_epoch: i32,
+    pub log_start_offset: i64,
+    pub partition_max_bytes: i32,
+    pub tagged_fields: BTreeMap<i32, Bytes>,
+}
+
+impl DecoderVersioned for FetchRequestPartition {
+    fn decode(buf: &mut BytesMut, version: i16) -> Result<Self, io::Error> {
+        let partition = i32::decode(buf)?;
+        let current_leader_epoch = if version < 9 { -1 } else { i32::decode(buf)? };
+        let fetch_offset = i64::decode(buf)?;
+        let last_fetched_epoch = if version < 12 { -1 } else { i32::decode(buf)? };
+        let log_start_offset = if version < 5 { -1 } else { i64::decode(buf)? };
+        let partition_max_bytes = i32::decode(buf)?;
+
+        let mut tagged_fields = BTreeMap::new();
+        if version > 11 {
+            tagged_fields = Decoder::decode(buf)?;
+        }
+
+        Ok(Self {
+            partition,
+            current_leader_epoch,
+            fetch_offset,
+            last_fetched_epoch,
+            log_start_offset,
+            partition_max_bytes,
+            tagged_fields,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct FetchForgottenTopic {
+    pub topic: String,
+    pub partitions: Vec<i32>,
+    pub tagged_fields: BTreeMap<i32, Bytes>,
+}
+
+impl DecoderVersioned for FetchForgottenTopic {
+    fn decode(buf: &mut BytesMut, version: i16) -> Result<Self, io::Error> {
+        let (topic, partitions) = if version < 12 {
+            (String::decode(buf)?, Vec::<i32>::decode(buf)?)
+        } else {
+            (
+                CompactString::decode(buf)?.0,
+                CompactArray::<i32>::decode(buf)?.0,
+            )
+        };
+
+        let mut tagged_fields = BTreeMap::new();
+        if version > 11 {
+            tagged_fields = Decoder::decode(buf)?;
+        }
+
+        Ok(Self {
+            topic,
+            partitions,
+            tagged_fields,
+        })
+    }
+}
+
+pub struct FetchResponse {
+    pub throttle_time_ms: i32,
+    pub error_code: ErrorCode,
+    pub session_id: i32,
+    pub responses: Vec<FetchResponseTopic>,
+    pub tagged_fields: BTreeMap<i32, Bytes>,
+}
+
+impl EncoderVersioned for FetchResponse {
+    fn encode(&self, buf: &mut BytesMut, version: i16) -> Result<(), io::Error> {
+        self.throttle_time_ms.encode(buf)?;
+
+        if version > 6 {
+            self.error_code.encode(buf)?;
+            self.session_id.encode(buf)?;
+        }
+
+        if version < 12 {
+            ArrayRef(&self.responses).encode(buf, version)?;
+        } else {
+            CompactArrayRef(&self.responses).encode(buf, version)?;
+            self.tagged_fields.encode(buf)?;
+        }
+
+        Ok(())
+    }
+
+    fn size_hint(&self, version: i16) -> usize {
+        4 + 2 + 4 + ArrayRef(&self.responses).size_hint(version) + 1
+    }
+}
+
+impl Response for FetchResponse {
+    fn set_throttle_time_ms(&mut self, throttle_time_ms: i32) {
+        self.throttle_time_ms = throttle_time_ms;
+    }
+}
+
+pub struct FetchResponseTopic {
+    pub topic: String,
+    pub partitions: Vec<FetchResponsePartition>,
+    pub tagged_fields: BTreeMap<i32, Bytes>,
+}
+
+impl EncoderVersioned for FetchResponseTopic {
+    fn encode(&self, buf: &mut BytesMut, version: i16) -> Result<(), io::Error> {
+        if version < 12 {
+            self.topic.encode(buf)?;
+            ArrayRef(&self.partitions).encode(buf, version)?;
+        } else {
+            CompactString(self.topic.clone()).encode(buf)?;
+            CompactArrayRef(&self.partitions).encode(buf, version)?;
+            self.tagged_fields.encode(buf)?;
+        }
+
+        Ok(())
+    }
+
+    fn size_hint(&self, version: i16) -> usize {
+        string_size_hint(&self.topic) + ArrayRef(&self.partitions).size_hint(version) + 1
+    }
+}
+
+pub struct FetchResponsePartition {
+    pub partition_index: i32,
+    pub error_code: ErrorCode,
+    pub high_watermark: i64,
+    pub last_stable_offset: i64,
+    pub log_start_offset: i64,
+    /// The aborted transactions in the returned records, or `None` when the
+    /// fetch isn't read committed.
+    pub aborted_transactions: Option<Vec<FetchAbortedTransaction>>,
+    pub preferred_read_replica: i32,
+    /// The record batches, back to back.
+    pub records: Bytes,
+    pub tagged_fields: BTreeMap<i32, Bytes>,
+}
+
+impl FetchResponsePartition {
+    /// A partition with no records, failed with `error_code`.
+    pub fn error(partition_index: i32, error_code: ErrorCode) -> Self {
+        Self {
+            partition_index,
+            error_code,
+            high_watermark: -1,
+            last_stable_offset: -1,
+            log_start_offset: -1,
+            aborted_transactions: None,
+            preferred_read_replica: -1,
+            records: Bytes::new(),
+            tagged_fields: Default::default(),
+        }
+    }
+}
+
+impl EncoderVersioned for FetchResponsePartition {
+    fn encode(&self, buf: &mut BytesMut, version: i16) -> Result<(), io::Error> {
+        self.partition_index.encode(buf)?;
+        self.error_code.encode(buf)?;
+        self.high_watermark.encode(buf)?;
+        self.last_stable_offset.encode(buf)?;
+
+        if version > 4 {
+            self.log_start_offset.encode(buf)?;
+        }
+
+        if version < 12 {
+            NullableArrayRef(self.aborted_transactions.as_deref()).encode(buf, version)?;
+        } else {
+            CompactNullableArrayRef(self.aborted_transactions.as_deref()).encode(buf, version)?;
+        }
+
+        if version > 10 {
+            self.preferred_read_replica.encode(buf)?;
+        }
+
+        if version < 12 {
+            self.records.encode(buf)?;
+        } else {
+            CompactBytes(self.records.clone()).encode(buf)?;
+            self.tagged_fields.encode(buf)?;
+        }
+
+        Ok(())
+    }
+
+    fn size_hint(&self, _version: i16) -> usize {
+        let aborted_transactions = self.aborted_transactions.as_ref().map_or(0, Vec::len);
+        4 + 2 + 8 + 8 + 8 + 4 + 16 * aborted_transactions + 4 + 4 + self.records.len() + 1
+    }
+}
+
+pub struct FetchAbortedTransaction {
+    pub producer_id: i64,
+    pub first_offset: i64,
+    pub tagged_fields: BTreeMap<i32, Bytes>,
+}
+
+impl EncoderVersioned for FetchAbortedTransaction {
+    fn encode(&self, buf: &mut BytesMut, version: i16) -> Result<(), io::Error> {
+        self.producer_id.encode(buf)?;
+        self.first_offset.encode(buf)?;
+
+        if version > 11 {
+            self.tagged_fields.encode(buf)?;
+        }
+
+        Ok(())
+    }
+
+    fn size_hint(&self, _version: i16) -> usize {
+        8 + 8 + 1
+    }
+}
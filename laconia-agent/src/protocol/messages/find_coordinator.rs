@@ -1,13 +1,36 @@
-use std::io;
+use std::{collections::BTreeMap, io};
 
-use bytes::BytesMut;
+use bytes::{Bytes, BytesMut};
 
 use crate::{
     Message, VersionRange,
-    protocol::{DecoderVersioned, EncoderVersioned, request::Request, response::Response},
+    authorizer::{Operation, Resource},
+    protocol::{
+        DecodeError, Decoder, DecoderVersioned, Encoder, EncoderVersioned,
+        error::ErrorCode,
+        primitives::{
+            CompactArray, CompactArrayRef, CompactNullableString, CompactString, NullableString,
+        },
+        request::Request,
+        response::Response,
+    },
 };
 
-pub struct FindCoordinatorRequest {}
+/// The key type of a consumer group coordinator.
+pub const COORDINATOR_GROUP: i8 = 0;
+/// The key type of a transaction coordinator.
+pub const COORDINATOR_TRANSACTION: i8 = 1;
+
+/// A client looking up the coordinator of consumer groups or transactional
+/// ids. Versions 4 and up look up several keys at once.
+#[derive(Debug)]
+pub struct FindCoordinatorRequest {
+    pub key_type: i8,
+    /// The group or transactional ids to look up. Versions before 4 carry
+    /// exactly one.
+    pub coordinator_keys: Vec<String>,
+    pub tagged_fields: BTreeMap<i32, Bytes>,
+}
 
 impl Message for FindCoordinatorRequest {
     const VERSIONS: VersionRange = VersionRange { min: 0, max: 6 };
@@ -20,20 +43,164 @@ impl Message for FindCoordinatorRequest {
 
 impl Request for FindCoordinatorRequest {
     type Response = FindCoordinatorResponse;
+
+    /// Versions 4 and up report errors per key, so the keys are echoed back.
+    fn error_response(&self, error_code: ErrorCode) -> FindCoordinatorResponse {
+        let coordinators = self
+            .coordinator_keys
+            .iter()
+            .map(|key| FindCoordinatorResponseCoordinator {
+                key: key.clone(),
+                node_id: -1,
+                host: String::new(),
+                port: -1,
+                error_code,
+                error_message: String::new(),
+                tagged_fields: Default::default(),
+            })
+            .collect();
+
+        FindCoordinatorResponse {
+            coordinators,
+            ..Self::decode_error_response(error_code)
+        }
+    }
+
+    fn decode_error_response(error_code: ErrorCode) -> FindCoordinatorResponse {
+        FindCoordinatorResponse {
+            throttle_time_ms: 0,
+            error_code,
+            error_message: String::new(),
+            node_id: -1,
+            host: String::new(),
+            port: -1,
+            coordinators: vec![],
+            tagged_fields: Default::default(),
+        }
+    }
+
+    fn operations(&self) -> Vec<(Operation, Resource)> {
+        self.coordinator_keys
+            .iter()
+            .filter_map(|key| match self.key_type {
+                COORDINATOR_GROUP => Some((Operation::Describe, Resource::Group(key.clone()))),
+                COORDINATOR_TRANSACTION => {
+                    Some((Operation::Describe, Resource::TransactionalId(key.clone())))
+                }
+                _ => None,
+            })
+            .collect()
+    }
 }
 
 impl DecoderVersioned for FindCoordinatorRequest {
     fn decode(buf: &mut BytesMut, version: i16) -> Result<Self, io::Error> {
-        todo!()
+        if !Self::VERSIONS.contains(version) {
+            return Err(DecodeError::unsupported("unsupported version").into());
+        }
+
+        let key = if version < 3 {
+            Some(String::decode(buf)?)
+        } else if version < 4 {
+            Some(CompactString::decode(buf)?.0)
+        } else {
+            None
+        };
+
+        let key_type = if version < 1 {
+            COORDINATOR_GROUP
+        } else {
+            i8::decode(buf)?
+        };
+
+        let coordinator_keys = match key {
+            Some(key) => vec![key],
+            None => CompactArray::<CompactString>::decode(buf)?
+                .0
+                .into_iter()
+                .map(|key| key.0)
+                .collect(),
+        };
+
+        let mut tagged_fields = BTreeMap::new();
+        if version > 2 {
+            tagged_fields = Decoder::decode(buf)?;
+        }
+
+        Ok(Self {
+            key_type,
+            coordinator_keys,
+            tagged_fields,
+        })
     }
 }
 
-pub struct FindCoordinatorResponse {}
+pub struct FindCoordinatorResponse {
+    pub throttle_time_ms: i32,
+    pub error_code: ErrorCode,
+    pub error_message: String,
+    pub node_id: i32,
+    pub host: String,
+    pub port: i32,
+    pub coordinators: Vec<FindCoordinatorResponseCoordinator>,
+    pub tagged_fields: BTreeMap<i32, Bytes>,
+}
 
-impl Response for FindCoordinatorResponse {}
+impl Response for FindCoordinatorResponse {
+    fn set_throttle_time_ms(&mut self, throttle_time_ms: i32) {
+        self.throttle_time_ms = throttle_time_ms;
+    }
+}
 
 impl EncoderVersioned for FindCoordinatorResponse {
     fn encode(&self, buf: &mut BytesMut, version: i16) -> Result<(), io::Error> {
-        todo!()
+        if version > 0 {
+            self.throttle_time_ms.encode(buf)?;
+        }
+
+        if version < 3 {
+            self.error_code.encode(buf)?;
+            if version > 0 {
+                NullableString(self.error_message.clone()).encode(buf)?;
+            }
+            self.node_id.encode(buf)?;
+            self.host.encode(buf)?;
+            self.port.encode(buf)?;
+        } else if version < 4 {
+            self.error_code.encode(buf)?;
+            CompactNullableString(self.error_message.clone()).encode(buf)?;
+            self.node_id.encode(buf)?;
+            CompactString(self.host.clone()).encode(buf)?;
+            self.port.encode(buf)?;
+            self.tagged_fields.encode(buf)?;
+        } else {
+            CompactArrayRef(&self.coordinators).encode(buf, version)?;
+            self.tagged_fields.encode(buf)?;
+        }
+
+        Ok(())
+    }
+}
+
+pub struct FindCoordinatorResponseCoordinator {
+    pub key: String,
+    pub node_id: i32,
+    pub host: String,
+    pub port: i32,
+    pub error_code: ErrorCode,
+    pub error_message: String,
+    pub tagged_fields: BTreeMap<i32, Bytes>,
+}
+
+impl EncoderVersioned for FindCoordinatorResponseCoordinator {
+    fn encode(&self, buf: &mut BytesMut, _version: i16) -> Result<(), io::Error> {
+        CompactString(self.key.clone()).encode(buf)?;
+        self.node_id.encode(buf)?;
+        CompactString(self.host.clone()).encode(buf)?;
+        self.port.encode(buf)?;
+        self.error_code.encode(buf)?;
+        CompactNullableString(self.error_message.clone()).encode(buf)?;
+        self.tagged_fields.encode(buf)?;
+        Ok(())
     }
 }
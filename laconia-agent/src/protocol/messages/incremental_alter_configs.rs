@@ -0,0 +1,239 @@
+use std::{collections::BTreeMap, io};
+
+use bytes::{Bytes, BytesMut};
+
+use crate::{
+    Message, VersionRange,
+    authorizer::{Operation, Resource},
+    configs::ResourceType,
+    protocol::{
+        DecodeError, Decoder, DecoderVersioned, Encoder, EncoderVersioned,
+        error::ErrorCode,
+        primitives::{
+            ArrayRef, CompactArray, CompactArrayRef, CompactNullableString, CompactString,
+            NullableString,
+        },
+        request::Request,
+        response::Response,
+    },
+};
+
+#[derive(Debug)]
+pub struct IncrementalAlterConfigsRequest {
+    pub resources: Vec<IncrementalAlterConfigsResource>,
+    pub validate_only: bool,
+    pub tagged_fields: BTreeMap<i32, Bytes>,
+}
+
+impl Message for IncrementalAlterConfigsRequest {
+    const VERSIONS: VersionRange = VersionRange { min: 0, max: 1 };
+    const DEPRECATED_VERSIONS: Option<VersionRange> = None;
+
+    fn header_version(version: i16) -> i16 {
+        if version < 1 { 1 } else { 2 }
+    }
+}
+
+impl Request for IncrementalAlterConfigsRequest {
+    type Response = IncrementalAlterConfigsResponse;
+
+    fn error_response(&self, error_code: ErrorCode) -> IncrementalAlterConfigsResponse {
+        let responses = self
+            .resources
+            .iter()
+            .map(|resource| IncrementalAlterConfigsResult {
+                error_code,
+                error_message: String::new(),
+                resource_type: resource.resource_type,
+                resource_name: resource.resource_name.clone(),
+                tagged_fields: Default::default(),
+            })
+            .collect();
+
+        IncrementalAlterConfigsResponse {
+            responses,
+            ..Self::decode_error_response(error_code)
+        }
+    }
+
+    fn decode_error_response(_error_code: ErrorCode) -> IncrementalAlterConfigsResponse {
+        IncrementalAlterConfigsResponse {
+            throttle_time_ms: 0,
+            responses: vec![],
+            tagged_fields: Default::default(),
+        }
+    }
+
+    /// Broker configs belong to the cluster. Resources of unknown types are
+    /// left for the handler to reject.
+    fn operations(&self) -> Vec<(Operation, Resource)> {
+        self.resources
+            .iter()
+            .filter_map(
+                |resource| match ResourceType::from_i8(resource.resource_type)? {
+                    ResourceType::Topic => Some(Resource::Topic(resource.resource_name.clone())),
+                    ResourceType::Broker => Some(Resource::Cluster),
+                },
+            )
+            .map(|resource| (Operation::AlterConfigs, resource))
+            .collect()
+    }
+}
+
+impl DecoderVersioned for IncrementalAlterConfigsRequest {
+    fn decode(buf: &mut BytesMut, version: i16) -> Result<Self, io::Error> {
+        if !Self::VERSIONS.contains(version) {
+            return Err(DecodeError::unsupported("unsupported version").into());
+        }
+
+        let resources = if version < 1 {
+            Vec::<IncrementalAlterConfigsResource>::decode(buf, version)?
+        } else {
+            CompactArray::<IncrementalAlterConfigsResource>::decode(buf, version)?.0
+        };
+
+        let validate_only = bool::decode(buf)?;
+
+        let mut tagged_fields = BTreeMap::new();
+        if version > 0 {
+            tagged_fields = Decoder::decode(buf)?;
+        }
+
+        Ok(Self {
+            resources,
+            validate_only,
+            tagged_fields,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct IncrementalAlterConfigsResource {
+    pub resource_type: i8,
+    pub resource_name: String,
+    pub configs: Vec<IncrementalAlterConfigsConfig>,
+    pub tagged_fields: BTreeMap<i32, Bytes>,
+}
+
+impl DecoderVersioned for IncrementalAlterConfigsResource {
+    fn decode(buf: &mut BytesMut, version: i16) -> Result<Self, io::Error> {
+        let resource_type = i8::decode(buf)?;
+
+        let resource_name = if version < 1 {
+            String::decode(buf)?
+        } else {
+            CompactString::decode(buf)?.0
+        };
+
+        let configs = if version < 1 {
+            Vec::<IncrementalAlterConfigsConfig>::decode(buf, version)?
+        } else {
+            CompactArray::<IncrementalAlterConfigsConfig>::decode(buf, version)?.0
+        };
+
+        let mut tagged_fields = BTreeMap::new();
+        if version > 0 {
+            tagged_fields = Decoder::decode(buf)?;
+        }
+
+        Ok(Self {
+            resource_type,
+            resource_name,
+            configs,
+            tagged_fields,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct IncrementalAlterConfigsConfig {
+    pub name: String,
+    /// 0 for SET, 1 for DELETE, 2 for APPEND and 3 for SUBTRACT.
+    pub config_operation: i8,
+    pub value: String,
+    pub tagged_fields: BTreeMap<i32, Bytes>,
+}
+
+impl DecoderVersioned for IncrementalAlterConfigsConfig {
+    fn decode(buf: &mut BytesMut, version: i16) -> Result<Self, io::Error> {
+        let name = if version < 1 {
+            String::decode(buf)?
+        } else {
+            CompactString::decode(buf)?.0
+        };
+
+        let config_operation = i8::decode(buf)?;
+
+        let value = if version < 1 {
+            NullableString::decode(buf)?.0
+        } else {
+            CompactNullableString::decode(buf)?.0
+        };
+
+        let mut tagged_fields = BTreeMap::new();
+        if version > 0 {
+            tagged_fields = Decoder::decode(buf)?;
+        }
+
+        Ok(Self {
+            name,
+            config_operation,
+            value,
+            tagged_fields,
+        })
+    }
+}
+
+pub struct IncrementalAlterConfigsResponse {
+    pub throttle_time_ms: i32,
+    pub responses: Vec<IncrementalAlterConfigsResult>,
+    pub tagged_fields: BTreeMap<i32, Bytes>,
+}
+
+impl EncoderVersioned for IncrementalAlterConfigsResponse {
+    fn encode(&self, buf: &mut BytesMut, version: i16) -> Result<(), io::Error> {
+        self.throttle_time_ms.encode(buf)?;
+
+        if version < 1 {
+            ArrayRef(&self.responses).encode(buf, version)?;
+        } else {
+            CompactArrayRef(&self.responses).encode(buf, version)?;
+            self.tagged_fields.encode(buf)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Response for IncrementalAlterConfigsResponse {
+    fn set_throttle_time_ms(&mut self, throttle_time_ms: i32) {
+        self.throttle_time_ms = throttle_time_ms;
+    }
+}
+
+pub struct IncrementalAlterConfigsResult {
+    pub error_code: ErrorCode,
+    pub error_message: String,
+    pub resource_type: i8,
+    pub resource_name: String,
+    pub tagged_fields: BTreeMap<i32, Bytes>,
+}
+
+impl EncoderVersioned for IncrementalAlterConfigsResult {
+    fn encode(&self, buf: &mut BytesMut, version: i16) -> Result<(), io::Error> {
+        self.error_code.encode(buf)?;
+
+        if version < 1 {
+            NullableString(self.error_message.clone()).encode(buf)?;
+            self.resource_type.encode(buf)?;
+            self.resource_name.encode(buf)?;
+        } else {
+            CompactNullableString(self.error_message.clone()).encode(buf)?;
+            self.resource_type.encode(buf)?;
+            CompactString(self.resource_name.clone()).encode(buf)?;
+            self.tagged_fields.encode(buf)?;
+        }
+
+        Ok(())
+    }
+}
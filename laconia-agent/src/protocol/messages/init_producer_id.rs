@@ -0,0 +1,134 @@
+use std::{collections::BTreeMap, io};
+
+use bytes::{Bytes, BytesMut};
+
+use crate::{
+    Message, VersionRange,
+    authorizer::{Operation, Resource},
+    protocol::{
+        DecodeError, Decoder, DecoderVersioned, Encoder, EncoderVersioned,
+        error::ErrorCode,
+        primitives::{CompactNullableString, NullableString},
+        request::Request,
+        response::Response,
+    },
+};
+
+#[derive(Debug)]
+pub struct InitProducerIdRequest {
+    /// The transactional id, or empty for an idempotent producer.
+    pub transactional_id: String,
+    pub transaction_timeout_ms: i32,
+    /// The producer's current id and epoch, or -1 if it has none.
+    pub producer_id: i64,
+    pub producer_epoch: i16,
+    pub tagged_fields: BTreeMap<i32, Bytes>,
+}
+
+impl Message for InitProducerIdRequest {
+    const VERSIONS: VersionRange = VersionRange { min: 0, max: 5 };
+    const DEPRECATED_VERSIONS: Option<VersionRange> = None;
+
+    fn header_version(version: i16) -> i16 {
+        if version < 2 { 1 } else { 2 }
+    }
+}
+
+impl Request for InitProducerIdRequest {
+    type Response = InitProducerIdResponse;
+
+    fn error_response(&self, error_code: ErrorCode) -> InitProducerIdResponse {
+        Self::decode_error_response(error_code)
+    }
+
+    fn decode_error_response(error_code: ErrorCode) -> InitProducerIdResponse {
+        InitProducerIdResponse {
+            throttle_time_ms: 0,
+            error_code,
+            producer_id: -1,
+            producer_epoch: -1,
+            tagged_fields: Default::default(),
+        }
+    }
+
+    /// Idempotent producers, without a transactional id, write to the
+    /// cluster.
+    fn operations(&self) -> Vec<(Operation, Resource)> {
+        if self.transactional_id.is_empty() {
+            return vec![(Operation::IdempotentWrite, Resource::Cluster)];
+        }
+
+        vec![(
+            Operation::Write,
+            Resource::TransactionalId(self.transactional_id.clone()),
+        )]
+    }
+}
+
+impl DecoderVersioned for InitProducerIdRequest {
+    fn decode(buf: &mut BytesMut, version: i16) -> Result<Self, io::Error> {
+        if !Self::VERSIONS.contains(version) {
+            return Err(DecodeError::unsupported("unsupported version").into());
+        }
+
+        let transactional_id = if version < 2 {
+            NullableString::decode(buf)?.0
+        } else {
+            CompactNullableString::decode(buf)?.0
+        };
+
+        let transaction_timeout_ms = i32::decode(buf)?;
+
+        let (producer_id, producer_epoch) = if version < 3 {
+            (-1, -1)
+        } else {
+            (i64::decode(buf)?, i16::decode(buf)?)
+        };
+
+        let mut tagged_fields = BTreeMap::new();
+        if version > 1 {
+            tagged_fields = Decoder::decode(buf)?;
+        }
+
+        Ok(Self {
+            transactional_id,
+            transaction_timeout_ms,
+            producer_id,
+            producer_epoch,
+            tagged_fields,
+        })
+    }
+}
+
+pub struct InitProducerIdResponse {
+    pub throttle_time_ms: i32,
+    pub error_code: ErrorCode,
+    pub producer_id: i64,
+    pub producer_epoch: i16,
+    pub tagged_fields: BTreeMap<i32, Bytes>,
+}
+
+impl EncoderVersioned for InitProducerIdResponse {
+    fn encode(&self, buf: &mut BytesMut, version: i16) -> Result<(), io::Error> {
+        self.throttle_time_ms.encode(buf)?;
+        self.error_code.encode(buf)?;
+        self.producer_id.encode(buf)?;
+        self.producer_epoch.encode(buf)?;
+
+        if version > 1 {
+            self.tagged_fields.encode(buf)?;
+        }
+
+        Ok(())
+    }
+
+    fn size_hint(&self, _version: i16) -> usize {
+        4 + 2 + 8 + 2 + 1
+    }
+}
+
+impl Response for InitProducerIdResponse {
+    fn set_throttle_time_ms(&mut self, throttle_time_ms: i32) {
+        self.throttle_time_ms = throttle_time_ms;
+    }
+}
@@ -5,9 +5,16 @@ use uuid::Uuid;
 
 use crate::{
     Message, VersionRange,
+    authorizer::{Operation, Resource},
+    log::PartitionLog,
     protocol::{
-        Decoder, DecoderVersioned, Encoder, EncoderVersioned,
-        primitives::{CompactArray, CompactArrayRef, CompactNullableString, CompactString},
+        DecodeError, Decoder, DecoderVersioned, Encoder, EncoderVersioned,
+        error::ErrorCode,
+        primitives::{
+            ArrayRef, CompactArrayRef, CompactNullableArray, CompactNullableArrayRef,
+            CompactNullableString, CompactString, NullableArray, NullableArrayRef,
+            decode_tagged_if_flexible, string_size_hint,
+        },
         request::Request,
         response::Response,
     },
@@ -15,7 +22,8 @@ use crate::{
 
 #[derive(Debug)]
 pub struct MetadataRequest {
-    pub topics: Vec<MetadataRequestTopic>,
+    /// The topics to fetch metadata for, or `None` for all topics.
+    pub topics: Option<Vec<MetadataRequestTopic>>,
     pub allow_auto_topic_creation: bool,
     pub include_cluster_authorized_operations: bool,
     pub include_topic_authorized_operations: bool,
@@ -33,21 +41,65 @@ impl Message for MetadataRequest {
 
 impl Request for MetadataRequest {
     type Response = MetadataResponse;
+
+    fn error_response(&self, error_code: ErrorCode) -> MetadataResponse {
+        let topics = self
+            .topics
+            .iter()
+            .flatten()
+            .map(|topic| MetadataResponseTopic {
+                error_code,
+                name: topic.name.clone(),
+                topic_id: topic.topic_id,
+                is_internal: false,
+                partitions: vec![],
+                topic_authorized_operations: i32::MIN,
+                tagged_fields: Default::default(),
+            })
+            .collect();
+
+        MetadataResponse {
+            topics,
+            ..Self::decode_error_response(error_code)
+        }
+    }
+
+    fn decode_error_response(_error_code: ErrorCode) -> MetadataResponse {
+        MetadataResponse {
+            throttle_time_ms: 0,
+            brokers: vec![],
+            cluster_id: String::new(),
+            controller_id: -1,
+            topics: vec![],
+            tagged_fields: Default::default(),
+        }
+    }
+
+    /// Listing every topic describes the cluster.
+    fn operations(&self) -> Vec<(Operation, Resource)> {
+        match &self.topics {
+            Some(topics) => topics
+                .iter()
+                .map(|topic| (Operation::Describe, Resource::Topic(topic.name.clone())))
+                .collect(),
+            None => vec![(Operation::Describe, Resource::Cluster)],
+        }
+    }
 }
 
 impl DecoderVersioned for MetadataRequest {
     fn decode(buf: &mut BytesMut, version: i16) -> Result<Self, io::Error> {
         if !Self::VERSIONS.contains(version) {
-            return Err(io::Error::new(
-                io::ErrorKind::InvalidData,
-                "invalid version",
-            ));
+            return Err(DecodeError::unsupported("unsupported version").into());
         }
 
-        let topics = if version < 9 {
-            Vec::<MetadataRequestTopic>::decode(buf, version)?
+        // Version 0 has no null array; an empty one means all topics.
+        let topics = if version < 1 {
+            Some(Vec::<MetadataRequestTopic>::decode(buf, version)?).filter(|t| !t.is_empty())
+        } else if version < 9 {
+            NullableArray::<MetadataRequestTopic>::decode(buf, version)?.0
         } else {
-            CompactArray::<MetadataRequestTopic>::decode(buf, version)?.0
+            CompactNullableArray::<MetadataRequestTopic>::decode(buf, version)?.0
         };
 
         let allow_auto_topic_creation = if version < 4 {
@@ -70,10 +122,7 @@ impl DecoderVersioned for MetadataRequest {
             bool::decode(buf)?
         };
 
-        let mut tagged_fields = BTreeMap::new();
-        if version > 8 {
-            tagged_fields = Decoder::decode(buf)?;
-        }
+        let tagged_fields = decode_tagged_if_flexible(buf, version, 9)?;
 
         Ok(Self {
             topics,
@@ -85,6 +134,36 @@ impl DecoderVersioned for MetadataRequest {
     }
 }
 
+impl EncoderVersioned for MetadataRequest {
+    fn encode(&self, buf: &mut BytesMut, version: i16) -> Result<(), io::Error> {
+        if version < 1 {
+            ArrayRef(self.topics.as_deref().unwrap_or_default()).encode(buf, version)?;
+        } else if version < 9 {
+            NullableArrayRef(self.topics.as_deref()).encode(buf, version)?;
+        } else {
+            CompactNullableArrayRef(self.topics.as_deref()).encode(buf, version)?;
+        }
+
+        if version > 3 {
+            self.allow_auto_topic_creation.encode(buf)?;
+        }
+
+        if (8..11).contains(&version) {
+            self.include_cluster_authorized_operations.encode(buf)?;
+        }
+
+        if version > 7 {
+            self.include_topic_authorized_operations.encode(buf)?;
+        }
+
+        if version > 8 {
+            self.tagged_fields.encode(buf)?;
+        }
+
+        Ok(())
+    }
+}
+
 #[derive(Debug)]
 pub struct MetadataRequestTopic {
     pub topic_id: Uuid,
@@ -108,10 +187,7 @@ impl DecoderVersioned for MetadataRequestTopic {
             CompactNullableString::decode(buf)?.0
         };
 
-        let mut tagged_fields = BTreeMap::new();
-        if version > 8 {
-            tagged_fields = Decoder::decode(buf)?;
-        };
+        let tagged_fields = decode_tagged_if_flexible(buf, version, 9)?;
 
         Ok(Self {
             topic_id,
@@ -121,6 +197,28 @@ impl DecoderVersioned for MetadataRequestTopic {
     }
 }
 
+impl EncoderVersioned for MetadataRequestTopic {
+    fn encode(&self, buf: &mut BytesMut, version: i16) -> Result<(), io::Error> {
+        if version > 9 {
+            self.topic_id.encode(buf)?;
+        }
+
+        if version < 9 {
+            self.name.encode(buf)?;
+        } else if version < 10 {
+            CompactString(self.name.clone()).encode(buf)?;
+        } else {
+            CompactNullableString(self.name.clone()).encode(buf)?;
+        }
+
+        if version > 8 {
+            self.tagged_fields.encode(buf)?;
+        }
+
+        Ok(())
+    }
+}
+
 pub struct MetadataResponse {
     pub throttle_time_ms: i32,
     pub brokers: Vec<MetadataResponseBrokers>,
@@ -140,9 +238,21 @@ impl EncoderVersioned for MetadataResponse {
         self.tagged_fields.encode(buf)?;
         Ok(())
     }
+
+    fn size_hint(&self, version: i16) -> usize {
+        4 + CompactArrayRef(&self.brokers).size_hint(version)
+            + string_size_hint(&self.cluster_id)
+            + 4
+            + CompactArrayRef(&self.topics).size_hint(version)
+            + 1
+    }
 }
 
-impl Response for MetadataResponse {}
+impl Response for MetadataResponse {
+    fn set_throttle_time_ms(&mut self, throttle_time_ms: i32) {
+        self.throttle_time_ms = throttle_time_ms;
+    }
+}
 
 #[derive(Clone)]
 pub struct MetadataResponseBrokers {
@@ -162,11 +272,15 @@ impl EncoderVersioned for MetadataResponseBrokers {
         self.tagged_fields.encode(buf)?;
         Ok(())
     }
+
+    fn size_hint(&self, _version: i16) -> usize {
+        4 + string_size_hint(&self.host) + 4 + string_size_hint(&self.rack) + 1
+    }
 }
 
 #[derive(Clone)]
 pub struct MetadataResponseTopic {
-    pub error_code: i16,
+    pub error_code: ErrorCode,
     pub name: String,
     pub topic_id: Uuid,
     pub is_internal: bool,
@@ -185,11 +299,19 @@ impl EncoderVersioned for MetadataResponseTopic {
         self.tagged_fields.encode(buf)?;
         Ok(())
     }
+
+    fn size_hint(&self, version: i16) -> usize {
+        2 + string_size_hint(&self.name)
+            + 1
+            + CompactArrayRef(&self.partitions).size_hint(version)
+            + 4
+            + 1
+    }
 }
 
 #[derive(Clone)]
 pub struct MetadataResponseTopicPartition {
-    pub error_code: i16,
+    pub error_code: ErrorCode,
     pub partition_index: i32,
     pub leader_id: i32,
     pub leader_epoch: i32,
@@ -199,9 +321,45 @@ pub struct MetadataResponseTopicPartition {
     pub tagged_fields: BTreeMap<i32, Bytes>,
 }
 
+impl MetadataResponseTopicPartition {
+    /// Describes a partition with a single replica: led by `broker_id`,
+    /// which is its only replica and in sync, at the log's leader epoch. A
+    /// partition without a `log` doesn't exist, and is described with an
+    /// error and no leader.
+    pub fn single_broker(
+        partition_index: i32,
+        log: Option<&dyn PartitionLog>,
+        broker_id: i32,
+    ) -> Self {
+        let Some(log) = log else {
+            return Self {
+                error_code: ErrorCode::UnknownTopicOrPartition,
+                partition_index,
+                leader_id: -1,
+                leader_epoch: -1,
+                replica_nodes: vec![],
+                isr_nodes: vec![],
+                offline_replicas: vec![],
+                tagged_fields: Default::default(),
+            };
+        };
+
+        Self {
+            error_code: ErrorCode::None,
+            partition_index,
+            leader_id: broker_id,
+            leader_epoch: log.leader_epoch(),
+            replica_nodes: vec![broker_id],
+            isr_nodes: vec![broker_id],
+            offline_replicas: vec![],
+            tagged_fields: Default::default(),
+        }
+    }
+}
+
 impl EncoderVersioned for MetadataResponseTopicPartition {
     fn encode(&self, buf: &mut BytesMut, version: i16) -> Result<(), io::Error> {
-        buf.put_i16(self.error_code);
+        self.error_code.encode(buf)?;
         buf.put_i32(self.partition_index);
         buf.put_i32(self.leader_id);
         buf.put_i32(self.leader_epoch);
@@ -211,4 +369,10 @@ impl EncoderVersioned for MetadataResponseTopicPartition {
         self.tagged_fields.encode(buf)?;
         Ok(())
     }
+
+    fn size_hint(&self, _version: i16) -> usize {
+        let replicas =
+            self.replica_nodes.len() + self.isr_nodes.len() + self.offline_replicas.len();
+        2 + 4 + 4 + 4 + 3 + 4 * replicas + 1
+    }
 }
@@ -0,0 +1,194 @@
+use std::io;
+
+use bytes::BytesMut;
+
+use crate::{
+    Message, VersionRange,
+    authorizer::{Operation, Resource},
+    protocol::{
+        DecodeError, Decoder, DecoderVersioned, Encoder, EncoderVersioned, error::ErrorCode,
+        primitives::ArrayRef, request::Request, response::Response,
+    },
+};
+
+/// Deletes offsets a consumer group has committed, as
+/// `kafka-consumer-groups.sh --delete-offsets` does. No version is flexible.
+#[derive(Debug)]
+pub struct OffsetDeleteRequest {
+    pub group_id: String,
+    pub topics: Vec<OffsetDeleteRequestTopic>,
+}
+
+impl Message for OffsetDeleteRequest {
+    const VERSIONS: VersionRange = VersionRange { min: 0, max: 1 };
+    const DEPRECATED_VERSIONS: Option<VersionRange> = None;
+
+    fn header_version(_version: i16) -> i16 {
+        1
+    }
+}
+
+impl Request for OffsetDeleteRequest {
+    type Response = OffsetDeleteResponse;
+
+    fn error_response(&self, error_code: ErrorCode) -> OffsetDeleteResponse {
+        Self::decode_error_response(error_code)
+    }
+
+    fn decode_error_response(error_code: ErrorCode) -> OffsetDeleteResponse {
+        OffsetDeleteResponse {
+            error_code,
+            throttle_time_ms: 0,
+            topics: vec![],
+        }
+    }
+
+    fn operations(&self) -> Vec<(Operation, Resource)> {
+        [(Operation::Delete, Resource::Group(self.group_id.clone()))]
+            .into_iter()
+            .chain(
+                self.topics
+                    .iter()
+                    .map(|topic| (Operation::Read, Resource::Topic(topic.name.clone()))),
+            )
+            .collect()
+    }
+}
+
+impl DecoderVersioned for OffsetDeleteRequest {
+    fn decode(buf: &mut BytesMut, version: i16) -> Result<Self, io::Error> {
+        if !Self::VERSIONS.contains(version) {
+            return Err(DecodeError::unsupported("unsupported version").into());
+        }
+
+        Ok(Self {
+            group_id: String::decode(buf)?,
+            topics: Vec::<OffsetDeleteRequestTopic>::decode(buf, version)?,
+        })
+    }
+}
+
+impl EncoderVersioned for OffsetDeleteRequest {
+    fn encode(&self, buf: &mut BytesMut, version: i16) -> Result<(), io::Error> {
+        self.group_id.encode(buf)?;
+        ArrayRef(&self.topics).encode(buf, version)
+    }
+}
+
+#[derive(Debug)]
+pub struct OffsetDeleteRequestTopic {
+    pub name: String,
+    pub partitions: Vec<OffsetDeleteRequestPartition>,
+}
+
+impl DecoderVersioned for OffsetDeleteRequestTopic {
+    fn decode(buf: &mut BytesMut, version: i16) -> Result<Self, io::Error> {
+        Ok(Self {
+            name: String::decode(buf)?,
+            partitions: Vec::<OffsetDeleteRequestPartition>::decode(buf, version)?,
+        })
+    }
+}
+
+impl EncoderVersioned for OffsetDeleteRequestTopic {
+    fn encode(&self, buf: &mut BytesMut, version: i16) -> Result<(), io::Error> {
+        self.name.encode(buf)?;
+        ArrayRef(&self.partitions).encode(buf, version)
+    }
+}
+
+#[derive(Debug)]
+pub struct OffsetDeleteRequestPartition {
+    pub partition_index: i32,
+}
+
+impl DecoderVersioned for OffsetDeleteRequestPartition {
+    fn decode(buf: &mut BytesMut, _version: i16) -> Result<Self, io::Error> {
+        Ok(Self {
+            partition_index: i32::decode(buf)?,
+        })
+    }
+}
+
+impl EncoderVersioned for OffsetDeleteRequestPartition {
+    fn encode(&self, buf: &mut BytesMut, _version: i16) -> Result<(), io::Error> {
+        self.partition_index.encode(buf)
+    }
+}
+
+#[derive(Debug)]
+pub struct OffsetDeleteResponse {
+    /// An error affecting the whole group, in which case there are no topic
+    /// results.
+    pub error_code: ErrorCode,
+    pub throttle_time_ms: i32,
+    pub topics: Vec<OffsetDeleteResponseTopic>,
+}
+
+impl EncoderVersioned for OffsetDeleteResponse {
+    fn encode(&self, buf: &mut BytesMut, version: i16) -> Result<(), io::Error> {
+        self.error_code.encode(buf)?;
+        self.throttle_time_ms.encode(buf)?;
+        ArrayRef(&self.topics).encode(buf, version)
+    }
+}
+
+impl DecoderVersioned for OffsetDeleteResponse {
+    fn decode(buf: &mut BytesMut, version: i16) -> Result<Self, io::Error> {
+        Ok(Self {
+            error_code: ErrorCode::decode(buf)?,
+            throttle_time_ms: i32::decode(buf)?,
+            topics: Vec::<OffsetDeleteResponseTopic>::decode(buf, version)?,
+        })
+    }
+}
+
+impl Response for OffsetDeleteResponse {
+    fn set_throttle_time_ms(&mut self, throttle_time_ms: i32) {
+        self.throttle_time_ms = throttle_time_ms;
+    }
+}
+
+#[derive(Debug)]
+pub struct OffsetDeleteResponseTopic {
+    pub name: String,
+    pub partitions: Vec<OffsetDeleteResponsePartition>,
+}
+
+impl EncoderVersioned for OffsetDeleteResponseTopic {
+    fn encode(&self, buf: &mut BytesMut, version: i16) -> Result<(), io::Error> {
+        self.name.encode(buf)?;
+        ArrayRef(&self.partitions).encode(buf, version)
+    }
+}
+
+impl DecoderVersioned for OffsetDeleteResponseTopic {
+    fn decode(buf: &mut BytesMut, version: i16) -> Result<Self, io::Error> {
+        Ok(Self {
+            name: String::decode(buf)?,
+            partitions: Vec::<OffsetDeleteResponsePartition>::decode(buf, version)?,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct OffsetDeleteResponsePartition {
+    pub partition_index: i32,
+    pub error_code: ErrorCode,
+}
+
+impl EncoderVersioned for OffsetDeleteResponsePartition {
+    fn encode(&self, buf: &mut BytesMut, _version: i16) -> Result<(), io::Error> {
+        self.partition_index.encode(buf)?;
+        self.error_code.encode(buf)
+    }
+}
+
+impl DecoderVersioned for OffsetDeleteResponsePartition {
+    fn decode(buf: &mut BytesMut, _version: i16) -> Result<Self, io::Error> {
+        Ok(Self {
+            partition_index: i32::decode(buf)?,
+            error_code: ErrorCode::decode(buf)?,
+        })
+    }
+}
@@ -0,0 +1,274 @@
+use std::{collections::BTreeMap, io};
+
+use bytes::{Bytes, BytesMut};
+
+use crate::{
+    Message, VersionRange,
+    authorizer::{Operation, Resource},
+    protocol::{
+        DecodeError, Decoder, DecoderVersioned, Encoder, EncoderVersioned,
+        error::ErrorCode,
+        primitives::{ArrayRef, CompactArray, CompactArrayRef, CompactString, string_size_hint},
+        request::Request,
+        response::Response,
+    },
+};
+
+/// Asks for the end offset of a leader epoch in each partition, which
+/// consumers and followers use to detect that a log was truncated.
+#[derive(Debug)]
+pub struct OffsetForLeaderEpochRequest {
+    /// The broker id of a follower, or -1 for consumers.
+    pub replica_id: i32,
+    pub topics: Vec<OffsetForLeaderTopic>,
+    pub tagged_fields: BTreeMap<i32, Bytes>,
+}
+
+impl Message for OffsetForLeaderEpochRequest {
+    const VERSIONS: VersionRange = VersionRange { min: 0, max: 4 };
+    const DEPRECATED_VERSIONS: Option<VersionRange> = None;
+
+    fn header_version(version: i16) -> i16 {
+        if version < 4 { 1 } else { 2 }
+    }
+}
+
+impl Request for OffsetForLeaderEpochRequest {
+    type Response = OffsetForLeaderEpochResponse;
+
+    fn error_response(&self, error_code: ErrorCode) -> OffsetForLeaderEpochResponse {
+        let topics = self
+            .topics
+            .iter()
+            .map(|topic| OffsetForLeaderTopicResult {
+                topic: topic.topic.clone(),
+                partitions: topic
+                    .partitions
+                    .iter()
+                    .map(|partition| EpochEndOffset::error(partition.partition, error_code))
+                    .collect(),
+                tagged_fields: Default::default(),
+            })
+            .collect();
+
+        OffsetForLeaderEpochResponse {
+            topics,
+            ..Self::decode_error_response(error_code)
+        }
+    }
+
+    fn decode_error_response(_error_code: ErrorCode) -> OffsetForLeaderEpochResponse {
+        OffsetForLeaderEpochResponse {
+            throttle_time_ms: 0,
+            topics: vec![],
+            tagged_fields: Default::default(),
+        }
+    }
+
+    /// Followers ask on behalf of replication, which is a cluster action.
+    fn operations(&self) -> Vec<(Operation, Resource)> {
+        if self.replica_id >= 0 {
+            return vec![(Operation::ClusterAction, Resource::Cluster)];
+        }
+
+        self.topics
+            .iter()
+            .map(|topic| (Operation::Describe, Resource::Topic(topic.topic.clone())))
+            .collect()
+    }
+}
+
+impl DecoderVersioned for OffsetForLeaderEpochRequest {
+    fn decode(buf: &mut BytesMut, version: i16) -> Result<Self, io::Error> {
+        if !Self::VERSIONS.contains(version) {
+            return Err(DecodeError::unsupported("unsupported version").into());
+        }
+
+        let replica_id = if version < 3 { -1 } else { i32::decode(buf)? };
+
+        let topics = if version < 4 {
+            Vec::<OffsetForLeaderTopic>::decode(buf, version)?
+        } else {
+            CompactArray::<OffsetForLeaderTopic>::decode(buf, version)?.0
+        };
+
+        let mut tagged_fields = BTreeMap::new();
+        if version > 3 {
+            tagged_fields = Decoder::decode(buf)?;
+        }
+
+        Ok(Self {
+            replica_id,
+            topics,
+            tagged_fields,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct OffsetForLeaderTopic {
+    pub topic: String,
+    pub partitions: Vec<OffsetForLeaderPartition>,
+    pub tagged_fields: BTreeMap<i32, Bytes>,
+}
+
+impl DecoderVersioned for OffsetForLeaderTopic {
+    fn decode(buf: &mut BytesMut, version: i16) -> Result<Self, io::Error> {
+        let (topic, partitions) = if version < 4 {
+            (
+                String::decode(buf)?,
+                Vec::<OffsetForLeaderPartition>::decode(buf, version)?,
+            )
+        } else {
+            (
+                CompactString::decode(buf)?.0,
+                CompactArray::<OffsetForLeaderPartition>::decode(buf, version)?.0,
+            )
+        };
+
+        let mut tagged_fields = BTreeMap::new();
+        if version > 3 {
+            tagged_fields = Decoder::decode(buf)?;
+        }
+
+        Ok(Self {
+            topic,
+            partitions,
+            tagged_fields,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct OffsetForLeaderPartition {
+    pub partition: i32,
+    /// The leader epoch the client knows of, or -1 to skip fencing.
+    pub current_leader_epoch: i32,
+    /// The epoch to look up the end offset of.
+    pub leader_epoch: i32,
+    pub tagged_fields: BTreeMap<i32, Bytes>,
+}
+
+impl DecoderVersioned for OffsetForLeaderPartition {
+    fn decode(buf: &mut BytesMut, version: i16) -> Result<Self, io::Error> {
+        let partition = i32::decode(buf)?;
+        let current_leader_epoch = if version < 2 { -1 } else { i32::decode(buf)? };
+        let leader_epoch = i32::decode(buf)?;
+
+        let mut tagged_fields = BTreeMap::new();
+        if version > 3 {
+            tagged_fields = Decoder::decode(buf)?;
+        }
+
+        Ok(Self {
+            partition,
+            current_leader_epoch,
+            leader_epoch,
+            tagged_fields,
+        })
+    }
+}
+
+pub struct OffsetForLeaderEpochResponse {
+    pub throttle_time_ms: i32,
+    pub topics: Vec<OffsetForLeaderTopicResult>,
+    pub tagged_fields: BTreeMap<i32, Bytes>,
+}
+
+impl EncoderVersioned for OffsetForLeaderEpochResponse {
+    fn encode(&self, buf: &mut BytesMut, version: i16) -> Result<(), io::Error> {
+        if version > 1 {
+            self.throttle_time_ms.encode(buf)?;
+        }
+
+        if version < 4 {
+            ArrayRef(&self.topics).encode(buf, version)?;
+        } else {
+            CompactArrayRef(&self.topics).encode(buf, version)?;
+            self.tagged_fields.encode(buf)?;
+        }
+
+        Ok(())
+    }
+
+    fn size_hint(&self, version: i16) -> usize {
+        4 + ArrayRef(&self.topics).size_hint(version) + 1
+    }
+}
+
+impl Response for OffsetForLeaderEpochResponse {
+    fn set_throttle_time_ms(&mut self, throttle_time_ms: i32) {
+        self.throttle_time_ms = throttle_time_ms;
+    }
+}
+
+pub struct OffsetForLeaderTopicResult {
+    pub topic: String,
+    pub partitions: Vec<EpochEndOffset>,
+    pub tagged_fields: BTreeMap<i32, Bytes>,
+}
+
+impl EncoderVersioned for OffsetForLeaderTopicResult {
+    fn encode(&self, buf: &mut BytesMut, version: i16) -> Result<(), io::Error> {
+        if version < 4 {
+            self.topic.encode(buf)?;
+            ArrayRef(&self.partitions).encode(buf, version)?;
+        } else {
+            CompactString(self.topic.clone()).encode(buf)?;
+            CompactArrayRef(&self.partitions).encode(buf, version)?;
+            self.tagged_fields.encode(buf)?;
+        }
+
+        Ok(())
+    }
+
+    fn size_hint(&self, version: i16) -> usize {
+        string_size_hint(&self.topic) + ArrayRef(&self.partitions).size_hint(version) + 1
+    }
+}
+
+pub struct EpochEndOffset {
+    pub error_code: ErrorCode,
+    pub partition: i32,
+    /// The largest epoch no larger than the requested one, or -1 if there's
+    /// none.
+    pub leader_epoch: i32,
+    /// The offset the epoch ends at, exclusive, or -1 if there's none.
+    pub end_offset: i64,
+    pub tagged_fields: BTreeMap<i32, Bytes>,
+}
+
+impl EpochEndOffset {
+    pub fn error(partition: i32, error_code: ErrorCode) -> Self {
+        Self {
+            error_code,
+            partition,
+            leader_epoch: -1,
+            end_offset: -1,
+            tagged_fields: Default::default(),
+        }
+    }
+}
+
+impl EncoderVersioned for EpochEndOffset {
+    fn encode(&self, buf: &mut BytesMut, version: i16) -> Result<(), io::Error> {
+        self.error_code.encode(buf)?;
+        self.partition.encode(buf)?;
+
+        if version > 0 {
+            self.leader_epoch.encode(buf)?;
+        }
+
+        self.end_offset.encode(buf)?;
+
+        if version > 3 {
+            self.tagged_fields.encode(buf)?;
+        }
+
+        Ok(())
+    }
+
+    fn size_hint(&self, _version: i16) -> usize {
+        2 + 4 + 4 + 8 + 1
+    }
+}
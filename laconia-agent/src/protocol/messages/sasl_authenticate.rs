@@ -0,0 +1,115 @@
+use std::{collections::BTreeMap, io};
+
+use bytes::{Bytes, BytesMut};
+
+use crate::{
+    Message, VersionRange,
+    protocol::{
+        DecodeError, Decoder, DecoderVersioned, Encoder, EncoderVersioned,
+        error::ErrorCode,
+        primitives::{CompactBytes, CompactNullableString, NullableString, string_size_hint},
+        request::Request,
+        response::Response,
+    },
+};
+
+#[derive(Debug)]
+pub struct SaslAuthenticateRequest {
+    /// The SASL authentication bytes from the client, which may hold
+    /// credentials.
+    pub auth_bytes: Bytes,
+    pub tagged_fields: BTreeMap<i32, Bytes>,
+}
+
+impl Message for SaslAuthenticateRequest {
+    const VERSIONS: VersionRange = VersionRange { min: 0, max: 2 };
+    const DEPRECATED_VERSIONS: Option<VersionRange> = None;
+
+    fn header_version(version: i16) -> i16 {
+        if version < 2 { 1 } else { 2 }
+    }
+}
+
+impl Request for SaslAuthenticateRequest {
+    type Response = SaslAuthenticateResponse;
+
+    fn error_response(&self, error_code: ErrorCode) -> SaslAuthenticateResponse {
+        Self::decode_error_response(error_code)
+    }
+
+    fn decode_error_response(error_code: ErrorCode) -> SaslAuthenticateResponse {
+        SaslAuthenticateResponse {
+            error_code,
+            error_message: String::new(),
+            auth_bytes: Bytes::new(),
+            session_lifetime_ms: 0,
+            tagged_fields: Default::default(),
+        }
+    }
+
+    fn is_sensitive() -> bool {
+        true
+    }
+}
+
+impl DecoderVersioned for SaslAuthenticateRequest {
+    fn decode(buf: &mut BytesMut, version: i16) -> Result<Self, io::Error> {
+        if !Self::VERSIONS.contains(version) {
+            return Err(DecodeError::unsupported("unsupported version").into());
+        }
+
+        let auth_bytes = if version < 2 {
+            Bytes::decode(buf)?
+        } else {
+            CompactBytes::decode(buf)?.0
+        };
+
+        let mut tagged_fields = BTreeMap::new();
+        if version > 1 {
+            tagged_fields = Decoder::decode(buf)?;
+        }
+
+        Ok(Self {
+            auth_bytes,
+            tagged_fields,
+        })
+    }
+}
+
+pub struct SaslAuthenticateResponse {
+    pub error_code: ErrorCode,
+    pub error_message: String,
+    pub auth_bytes: Bytes,
+    pub session_lifetime_ms: i64,
+    pub tagged_fields: BTreeMap<i32, Bytes>,
+}
+
+impl EncoderVersioned for SaslAuthenticateResponse {
+    fn encode(&self, buf: &mut BytesMut, version: i16) -> Result<(), io::Error> {
+        self.error_code.encode(buf)?;
+
+        if version < 2 {
+            NullableString(self.error_message.clone()).encode(buf)?;
+            self.auth_bytes.encode(buf)?;
+        } else {
+            CompactNullableString(self.error_message.clone()).encode(buf)?;
+            CompactBytes(self.auth_bytes.clone()).encode(buf)?;
+        }
+
+        if version > 0 {
+            self.session_lifetime_ms.encode(buf)?;
+        }
+
+        if version > 1 {
+            self.tagged_fields.encode(buf)?;
+        }
+
+        Ok(())
+    }
+
+    fn size_hint(&self, _version: i16) -> usize {
+        2 + string_size_hint(&self.error_message) + 4 + self.auth_bytes.len() + 8 + 1
+    }
+}
+
+impl Response for SaslAuthenticateResponse {}
@@ -0,0 +1,299 @@
+use std::{collections::BTreeMap, io};
+
+use bytes::{Bytes, BytesMut};
+
+use crate::{
+    Message, VersionRange,
+    authorizer::{Operation, Resource},
+    protocol::{
+        DecodeError, Decoder, DecoderVersioned, Encoder, EncoderVersioned,
+        error::ErrorCode,
+        primitives::{
+            ArrayRef, CompactArray, CompactArrayRef, CompactNullableString, CompactString,
+            NullableString, string_size_hint,
+        },
+        request::Request,
+        response::Response,
+    },
+};
+
+/// A transactional producer committing a consumer group's offsets as part of
+/// its transaction. The offsets only become visible if the transaction
+/// commits.
+#[derive(Debug)]
+pub struct TxnOffsetCommitRequest {
+    pub transactional_id: String,
+    pub group_id: String,
+    pub producer_id: i64,
+    pub producer_epoch: i16,
+    /// The generation of the consumer group, or -1 before v3.
+    pub generation_id: i32,
+    /// The member id of the consumer, or empty before v3.
+    pub member_id: String,
+    /// The static member id of the consumer, or empty if it has none.
+    pub group_instance_id: String,
+    pub topics: Vec<TxnOffsetCommitTopic>,
+    pub tagged_fields: BTreeMap<i32, Bytes>,
+}
+
+impl Message for TxnOffsetCommitRequest {
+    const VERSIONS: VersionRange = VersionRange { min: 0, max: 3 };
+    const DEPRECATED_VERSIONS: Option<VersionRange> = None;
+
+    fn header_version(version: i16) -> i16 {
+        if version < 3 { 1 } else { 2 }
+    }
+}
+
+impl Request for TxnOffsetCommitRequest {
+    type Response = TxnOffsetCommitResponse;
+
+    fn error_response(&self, error_code: ErrorCode) -> TxnOffsetCommitResponse {
+        let topics = self
+            .topics
+            .iter()
+            .map(|topic| TxnOffsetCommitTopicResult {
+                name: topic.name.clone(),
+                partitions: topic
+                    .partitions
+                    .iter()
+                    .map(|partition| TxnOffsetCommitPartitionResult {
+                        partition_index: partition.partition_index,
+                        error_code,
+                        tagged_fields: Default::default(),
+                    })
+                    .collect(),
+                tagged_fields: Default::default(),
+            })
+            .collect();
+
+        TxnOffsetCommitResponse {
+            topics,
+            ..Self::decode_error_response(error_code)
+        }
+    }
+
+    fn decode_error_response(_error_code: ErrorCode) -> TxnOffsetCommitResponse {
+        TxnOffsetCommitResponse {
+            throttle_time_ms: 0,
+            topics: vec![],
+            tagged_fields: Default::default(),
+        }
+    }
+
+    fn operations(&self) -> Vec<(Operation, Resource)> {
+        [
+            (
+                Operation::Write,
+                Resource::TransactionalId(self.transactional_id.clone()),
+            ),
+            (Operation::Read, Resource::Group(self.group_id.clone())),
+        ]
+        .into_iter()
+        .chain(
+            self.topics
+                .iter()
+                .map(|topic| (Operation::Read, Resource::Topic(topic.name.clone()))),
+        )
+        .collect()
+    }
+}
+
+impl DecoderVersioned for TxnOffsetCommitRequest {
+    fn decode(buf: &mut BytesMut, version: i16) -> Result<Self, io::Error> {
+        if !Self::VERSIONS.contains(version) {
+            return Err(DecodeError::unsupported("unsupported version").into());
+        }
+
+        let (transactional_id, group_id) = if version < 3 {
+            (String::decode(buf)?, String::decode(buf)?)
+        } else {
+            (CompactString::decode(buf)?.0, CompactString::decode(buf)?.0)
+        };
+
+        let producer_id = i64::decode(buf)?;
+        let producer_epoch = i16::decode(buf)?;
+
+        let (generation_id, member_id, group_instance_id) = if version < 3 {
+            (-1, String::new(), String::new())
+        } else {
+            (
+                i32::decode(buf)?,
+                CompactString::decode(buf)?.0,
+                CompactNullableString::decode(buf)?.0,
+            )
+        };
+
+        let topics = if version < 3 {
+            Vec::<TxnOffsetCommitTopic>::decode(buf, version)?
+        } else {
+            CompactArray::<TxnOffsetCommitTopic>::decode(buf, version)?.0
+        };
+
+        let mut tagged_fields = BTreeMap::new();
+        if version > 2 {
+            tagged_fields = Decoder::decode(buf)?;
+        }
+
+        Ok(Self {
+            transactional_id,
+            group_id,
+            producer_id,
+            producer_epoch,
+            generation_id,
+            member_id,
+            group_instance_id,
+            topics,
+            tagged_fields,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct TxnOffsetCommitTopic {
+    pub name: String,
+    pub partitions: Vec<TxnOffsetCommitPartition>,
+    pub tagged_fields: BTreeMap<i32, Bytes>,
+}
+
+impl DecoderVersioned for TxnOffsetCommitTopic {
+    fn decode(buf: &mut BytesMut, version: i16) -> Result<Self, io::Error> {
+        let (name, partitions) = if version < 3 {
+            (
+                String::decode(buf)?,
+                Vec::<TxnOffsetCommitPartition>::decode(buf, version)?,
+            )
+        } else {
+            (
+                CompactString::decode(buf)?.0,
+                CompactArray::<TxnOffsetCommitPartition>::decode(buf, version)?.0,
+            )
+        };
+
+        let mut tagged_fields = BTreeMap::new();
+        if version > 2 {
+            tagged_fields = Decoder::decode(buf)?;
+        }
+
+        Ok(Self {
+            name,
+            partitions,
+            tagged_fields,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct TxnOffsetCommitPartition {
+    pub partition_index: i32,
+    pub committed_offset: i64,
+    /// The leader epoch of the last consumed record, or -1 if unknown.
+    pub committed_leader_epoch: i32,
+    pub committed_metadata: String,
+    pub tagged_fields: BTreeMap<i32, Bytes>,
+}
+
+impl DecoderVersioned for TxnOffsetCommitPartition {
+    fn decode(buf: &mut BytesMut, version: i16) -> Result<Self, io::Error> {
+        let partition_index = i32::decode(buf)?;
+        let committed_offset = i64::decode(buf)?;
+        let committed_leader_epoch = if version < 2 { -1 } else { i32::decode(buf)? };
+        let committed_metadata = if version < 3 {
+            NullableString::decode(buf)?.0
+        } else {
+            CompactNullableString::decode(buf)?.0
+        };
+
+        let mut tagged_fields = BTreeMap::new();
+        if version > 2 {
+            tagged_fields = Decoder::decode(buf)?;
+        }
+
+        Ok(Self {
+            partition_index,
+            committed_offset,
+            committed_leader_epoch,
+            committed_metadata,
+            tagged_fields,
+        })
+    }
+}
+
+pub struct TxnOffsetCommitResponse {
+    pub throttle_time_ms: i32,
+    pub topics: Vec<TxnOffsetCommitTopicResult>,
+    pub tagged_fields: BTreeMap<i32, Bytes>,
+}
+
+impl EncoderVersioned for TxnOffsetCommitResponse {
+    fn encode(&self, buf: &mut BytesMut, version: i16) -> Result<(), io::Error> {
+        self.throttle_time_ms.encode(buf)?;
+
+        if version < 3 {
+            ArrayRef(&self.topics).encode(buf, version)?;
+        } else {
+            CompactArrayRef(&self.topics).encode(buf, version)?;
+            self.tagged_fields.encode(buf)?;
+        }
+
+        Ok(())
+    }
+
+    fn size_hint(&self, version: i16) -> usize {
+        4 + ArrayRef(&self.topics).size_hint(version) + 1
+    }
+}
+
+impl Response for TxnOffsetCommitResponse {
+    fn set_throttle_time_ms(&mut self, throttle_time_ms: i32) {
+        self.throttle_time_ms = throttle_time_ms;
+    }
+}
+
+pub struct TxnOffsetCommitTopicResult {
+    pub name: String,
+    pub partitions: Vec<TxnOffsetCommitPartitionResult>,
+    pub tagged_fields: BTreeMap<i32, Bytes>,
+}
+
+impl EncoderVersioned for TxnOffsetCommitTopicResult {
+    fn encode(&self, buf: &mut BytesMut, version: i16) -> Result<(), io::Error> {
+        if version < 3 {
+            self.name.encode(buf)?;
+            ArrayRef(&self.partitions).encode(buf, version)?;
+        } else {
+            CompactString(self.name.clone()).encode(buf)?;
+            CompactArrayRef(&self.partitions).encode(buf, version)?;
+            self.tagged_fields.encode(buf)?;
+        }
+
+        Ok(())
+    }
+
+    fn size_hint(&self, version: i16) -> usize {
+        string_size_hint(&self.name) + ArrayRef(&self.partitions).size_hint(version) + 1
+    }
+}
+
+pub struct TxnOffsetCommitPartitionResult {
+    pub partition_index: i32,
+    pub error_code: ErrorCode,
+    pub tagged_fields: BTreeMap<i32, Bytes>,
+}
+
+impl EncoderVersioned for TxnOffsetCommitPartitionResult {
+    fn encode(&self, buf: &mut BytesMut, version: i16) -> Result<(), io::Error> {
+        self.partition_index.encode(buf)?;
+        self.error_code.encode(buf)?;
+
+        if version > 2 {
+            self.tagged_fields.encode(buf)?;
+        }
+
+        Ok(())
+    }
+
+    fn size_hint(&self, _version: i16) -> usize {
+        4 + 2 + 1
+    }
+}
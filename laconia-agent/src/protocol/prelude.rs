@@ -0,0 +1,58 @@
+//! Everything needed to handle requests from outside the crate: every request
+//! and response message, the traits they and their handlers implement, and
+//! the registry handlers are registered with.
+//!
+//! ```
+//! use std::{io, sync::Arc};
+//!
+//! use laconia_agent::protocol::prelude::*;
+//!
+//! /// Refuses to describe the cluster.
+//! struct NoClusterHandler;
+//!
+//! impl RequestHandler<DescribeClusterRequest> for NoClusterHandler {
+//!     async fn handle(
+//!         &self,
+//!         _header: &RequestHeader,
+//!         request: &DescribeClusterRequest,
+//!         _state: &mut ConnectionState,
+//!     ) -> Result<DescribeClusterResponse, io::Error> {
+//!         Ok(request.error_response(ErrorCode::ClusterAuthorizationFailed))
+//!     }
+//! }
+//!
+//! let mut registry = MessageRegistry::new();
+//! registry.register(ApiKey::DescribeCluster, NoClusterHandler).unwrap();
+//!
+//! # let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+//! # runtime.block_on(async {
+//! let header = RequestHeader {
+//!     api_key: ApiKey::DescribeCluster.as_i16(),
+//!     version: 0,
+//!     correlation_id: 1,
+//!     client_id: "docs".to_string(),
+//!     tagged_fields: Default::default(),
+//! };
+//! let request = DescribeClusterRequest {
+//!     include_cluster_authorized_operations: false,
+//!     endpoint_type: 1,
+//!     include_fenced_brokers: false,
+//!     tagged_fields: Default::default(),
+//! };
+//! let mut state = ConnectionState::new(Arc::new(registry));
+//! let response = NoClusterHandler
+//!     .handle(&header, &request, &mut state)
+//!     .await
+//!     .unwrap();
+//! assert_eq!(response.error_code, ErrorCode::ClusterAuthorizationFailed);
+//! # });
+//! ```
+
+pub use crate::{
+    ConnectionState, Message, RequestHeader, VersionRange,
+    protocol::{
+        Decoder, DecoderVersioned, Encoder, EncoderVersioned, api_keys::ApiKey, error::ErrorCode,
+        handlers::RequestHandler, messages::*, registry::MessageRegistry, request::Request,
+        response::Response,
+    },
+};
@@ -1,22 +1,123 @@
-use std::{collections::BTreeMap, io};
+use std::{collections::BTreeMap, io, ops::Deref};
 
 use bytes::{Buf, BufMut, Bytes, BytesMut};
 use integer_encoding::{VarIntReader, VarIntWriter};
 use uuid::Uuid;
 
-use crate::protocol::{Decoder, DecoderVersioned, Encoder, EncoderVersioned};
+use crate::protocol::{DecodeError, Decoder, DecoderVersioned, Encoder, EncoderVersioned};
+
+/// Bounds-checked reads from a buffer.
+///
+/// The `get_*` methods on [`Buf`] panic when the buffer is too short, which is
+/// reachable from the network. These return [`DecodeError::Incomplete`]
+/// instead. They are named `checked_*` to avoid clashing with [`Buf`]'s own
+/// `try_get_*`.
+pub trait CheckedGet {
+    fn checked_get_i8(&mut self) -> Result<i8, DecodeError>;
+    fn checked_get_u8(&mut self) -> Result<u8, DecodeError>;
+    fn checked_get_i16(&mut self) -> Result<i16, DecodeError>;
+    fn checked_get_i32(&mut self) -> Result<i32, DecodeError>;
+    fn checked_get_u32(&mut self) -> Result<u32, DecodeError>;
+    fn checked_get_i64(&mut self) -> Result<i64, DecodeError>;
+    fn checked_get_uvarint(&mut self) -> Result<u32, DecodeError>;
+    fn checked_split_to(&mut self, len: usize) -> Result<BytesMut, DecodeError>;
+}
+
+impl CheckedGet for BytesMut {
+    fn checked_get_i8(&mut self) -> Result<i8, DecodeError> {
+        Ok(self.try_get_i8()?)
+    }
+
+    fn checked_get_u8(&mut self) -> Result<u8, DecodeError> {
+        Ok(self.try_get_u8()?)
+    }
+
+    fn checked_get_i16(&mut self) -> Result<i16, DecodeError> {
+        Ok(self.try_get_i16()?)
+    }
+
+    fn checked_get_i32(&mut self) -> Result<i32, DecodeError> {
+        Ok(self.try_get_i32()?)
+    }
+
+    fn checked_get_u32(&mut self) -> Result<u32, DecodeError> {
+        Ok(self.try_get_u32()?)
+    }
+
+    fn checked_get_i64(&mut self) -> Result<i64, DecodeError> {
+        Ok(self.try_get_i64()?)
+    }
+
+    fn checked_get_uvarint(&mut self) -> Result<u32, DecodeError> {
+        self.reader()
+            .read_varint::<u32>()
+            .map_err(|err| match err.kind() {
+                io::ErrorKind::UnexpectedEof => DecodeError::Incomplete { needed: 1 },
+                _ => DecodeError::invalid(err),
+            })
+    }
+
+    fn checked_split_to(&mut self, len: usize) -> Result<BytesMut, DecodeError> {
+        if self.len() < len {
+            return Err(DecodeError::Incomplete {
+                needed: len - self.len(),
+            });
+        }
+
+        Ok(self.split_to(len))
+    }
+}
+
+/// Encodes a body with `encode`, preceded by its size as an `i32`, like
+/// request frames and the batch length of record batches. The size isn't
+/// known until the body is encoded, so a placeholder is written first and
+/// backfilled afterwards.
+pub fn with_size_prefix(
+    buf: &mut BytesMut,
+    encode: impl FnOnce(&mut BytesMut) -> Result<(), io::Error>,
+) -> Result<(), io::Error> {
+    let start = buf.len();
+    buf.put_i32(0);
+    encode(buf)?;
+
+    let size = buf.len() - start - 4;
+    let size = i32::try_from(size).map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{} bytes is too large for a size prefix", size),
+        )
+    })?;
+    buf[start..start + 4].copy_from_slice(&size.to_be_bytes());
+    Ok(())
+}
+
+/// The number of bytes `value` takes up as an unsigned varint.
+pub fn varint_size(value: usize) -> usize {
+    (usize::BITS - value.leading_zeros()).div_ceil(7).max(1) as usize
+}
+
+/// Estimates the encoded size of a string, including its length prefix.
+pub fn string_size_hint(value: &str) -> usize {
+    varint_size(value.len() + 1) + value.len()
+}
+
+/// Bounds the capacity to preallocate for an array of `length` elements.
+///
+/// The length comes off the wire, so it can't be trusted. Every element takes
+/// at least one byte, so an array can't hold more elements than there are
+/// bytes left in the buffer.
+fn array_capacity(length: usize, buf: &BytesMut) -> usize {
+    length.min(buf.len())
+}
 
 impl Decoder for bool {
     fn decode(buf: &mut BytesMut) -> Result<bool, io::Error> {
-        let value = buf.get_u8();
+        let value = buf.checked_get_u8()?;
         Ok(match value {
             0 => false,
             1 => true,
             _ => {
-                return Err(io::Error::new(
-                    io::ErrorKind::InvalidData,
-                    "invalid bool value",
-                ));
+                return Err(DecodeError::invalid("invalid bool value").into());
             }
         })
     }
@@ -33,16 +134,22 @@ impl Encoder for bool {
     }
 }
 
+impl Decoder for i8 {
+    fn decode(buf: &mut BytesMut) -> Result<i8, io::Error> {
+        Ok(buf.checked_get_i8()?)
+    }
+}
+
+impl Encoder for i8 {
+    fn encode(&self, buf: &mut BytesMut) -> Result<(), io::Error> {
+        buf.put_i8(*self);
+        Ok(())
+    }
+}
+
 impl Decoder for i16 {
     fn decode(buf: &mut BytesMut) -> Result<i16, io::Error> {
-        if buf.len() < 2 {
-            return Err(io::Error::new(
-                io::ErrorKind::InvalidData,
-                "not enough data",
-            ));
-        }
-
-        Ok(buf.get_i16())
+        Ok(buf.checked_get_i16()?)
     }
 }
 
@@ -53,6 +160,12 @@ impl Encoder for i16 {
     }
 }
 
+impl Decoder for i32 {
+    fn decode(buf: &mut BytesMut) -> Result<i32, io::Error> {
+        Ok(buf.checked_get_i32()?)
+    }
+}
+
 impl Encoder for i32 {
     fn encode(&self, buf: &mut BytesMut) -> Result<(), io::Error> {
         buf.put_i32(*self);
@@ -60,44 +173,46 @@ impl Encoder for i32 {
     }
 }
 
+impl Decoder for i64 {
+    fn decode(buf: &mut BytesMut) -> Result<i64, io::Error> {
+        Ok(buf.checked_get_i64()?)
+    }
+}
+
+impl Encoder for i64 {
+    fn encode(&self, buf: &mut BytesMut) -> Result<(), io::Error> {
+        buf.put_i64(*self);
+        Ok(())
+    }
+}
+
 impl Decoder for Uuid {
     fn decode(buf: &mut BytesMut) -> Result<Uuid, io::Error> {
-        if buf.len() < 16 {
-            return Err(io::Error::new(
-                io::ErrorKind::InvalidData,
-                "not enough data",
-            ));
-        }
+        let bytes = buf.checked_split_to(16)?;
+        Ok(Uuid::from_slice(&bytes).expect("split 16 bytes"))
+    }
+}
 
-        let mut bytes = [0u8; 16];
-        buf.copy_to_slice(&mut bytes);
-        Ok(Uuid::from_bytes(bytes))
+impl Encoder for Uuid {
+    fn encode(&self, buf: &mut BytesMut) -> Result<(), io::Error> {
+        buf.put_slice(self.as_bytes());
+        Ok(())
     }
 }
 
 impl Decoder for String {
     fn decode(buf: &mut BytesMut) -> Result<String, io::Error> {
-        if buf.len() < 4 {
-            return Err(io::Error::new(
-                io::ErrorKind::InvalidData,
-                "not enough data for string length",
-            ));
-        }
-
-        let len = i16::decode(buf)? as usize;
+        let len = i16::decode(buf)?;
 
-        if buf.len() < len {
-            return Err(io::Error::new(
-                io::ErrorKind::InvalidData,
-                "not enough bytes for string data",
-            ));
+        if len < 0 {
+            return Err(DecodeError::invalid("invalid string length").into());
         }
 
-        let str_bytes = buf.split_to(len);
+        let str_bytes = buf.checked_split_to(len as usize)?;
         let str = match String::from_utf8(str_bytes.to_vec()) {
             Ok(str) => str,
             Err(err) => {
-                return Err(io::Error::new(io::ErrorKind::InvalidData, err));
+                return Err(DecodeError::invalid(err).into());
             }
         };
 
@@ -105,35 +220,87 @@ impl Decoder for String {
     }
 }
 
+impl Encoder for String {
+    fn encode(&self, buf: &mut BytesMut) -> Result<(), io::Error> {
+        let bytes = self.as_bytes();
+        buf.put_i16(string_length(bytes)?);
+        buf.put_slice(bytes);
+        Ok(())
+    }
+}
+
+/// The i16 length prefix of a string, which can't hold lengths past
+/// `i16::MAX`.
+fn string_length(bytes: &[u8]) -> Result<i16, io::Error> {
+    i16::try_from(bytes.len()).map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("string of {} bytes is too long to encode", bytes.len()),
+        )
+    })
+}
+
+/// A UTF-8 string that shares the buffer it was decoded from instead of
+/// copying it out. Prefer it over [`String`] on hot decode paths.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Str(Bytes);
+
+impl Str {
+    fn from_utf8(bytes: Bytes) -> Result<Self, io::Error> {
+        match std::str::from_utf8(&bytes) {
+            Ok(_) => Ok(Self(bytes)),
+            Err(err) => Err(DecodeError::invalid(err).into()),
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        // SAFETY: the bytes were validated as UTF-8 when `self` was created.
+        unsafe { std::str::from_utf8_unchecked(&self.0) }
+    }
+}
+
+impl Deref for Str {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl Decoder for Str {
+    fn decode(buf: &mut BytesMut) -> Result<Str, io::Error> {
+        let len = i16::decode(buf)?;
+
+        if len < 0 {
+            return Err(DecodeError::invalid("invalid string length").into());
+        }
+
+        Str::from_utf8(buf.checked_split_to(len as usize)?.freeze())
+    }
+}
+
+/// A string that may be null. Null is decoded as, and the empty string is
+/// encoded as, null. Nothing in the protocol tells the two apart, so an
+/// empty string never goes over the wire.
 pub struct NullableString(pub String);
 
 impl Decoder for NullableString {
     fn decode(buf: &mut BytesMut) -> Result<NullableString, io::Error> {
-        if buf.len() < 2 {
-            return Err(io::Error::new(
-                io::ErrorKind::InvalidData,
-                "not enough data for nullable string length",
-            ));
-        }
-
-        let len = buf.get_i16();
+        let len = buf.checked_get_i16()?;
 
         if len == -1 {
             return Ok(NullableString(String::new()));
         }
 
-        if buf.len() < len as usize {
-            return Err(io::Error::new(
-                io::ErrorKind::InvalidData,
-                "not enough data for nullable string data",
-            ));
+        if len < -1 {
+            return Err(DecodeError::invalid("invalid nullable string length").into());
         }
 
-        let str_bytes = buf.split_to(len as usize);
+        let str_bytes = buf.checked_split_to(len as usize)?;
         let str = match String::from_utf8(str_bytes.to_vec()) {
             Ok(str) => str,
             Err(err) => {
-                return Err(io::Error::new(io::ErrorKind::InvalidData, err));
+                return Err(DecodeError::invalid(err).into());
             }
         };
 
@@ -141,33 +308,56 @@ impl Decoder for NullableString {
     }
 }
 
-pub struct CompactString(pub String);
+impl Encoder for NullableString {
+    fn encode(&self, buf: &mut BytesMut) -> Result<(), io::Error> {
+        let bytes = self.0.as_bytes();
+        if bytes.is_empty() {
+            buf.put_i16(-1);
+        } else {
+            buf.put_i16(string_length(bytes)?);
+            buf.put_slice(bytes);
+        }
+        Ok(())
+    }
+}
 
-impl Decoder for CompactString {
-    fn decode(buf: &mut BytesMut) -> Result<CompactString, io::Error> {
-        let length = buf.reader().read_varint::<u32>()? as usize;
+pub struct CompactString(pub String);
 
-        if length == 0 {
-            return Err(io::Error::new(
-                io::ErrorKind::InvalidData,
-                "zero-length compact string",
-            ));
-        }
+/// The longest string the protocol allows. Non-compact strings have an `i16`
+/// length, and compact strings are held to the same limit.
+pub const MAX_STRING_LEN: usize = i16::MAX as usize;
+
+/// Reads the length prefix of a compact string, returning `None` for null.
+///
+/// A varint can claim up to 4GB, which would otherwise look like a string
+/// that hasn't fully arrived yet, so lengths no string can have are rejected
+/// before anything is split off the buffer.
+fn decode_compact_string_len(buf: &mut BytesMut) -> Result<Option<usize>, DecodeError> {
+    let Some(length) = (buf.checked_get_uvarint()? as usize).checked_sub(1) else {
+        return Ok(None);
+    };
+
+    if length > MAX_STRING_LEN {
+        return Err(DecodeError::invalid(format!(
+            "compact string of {} bytes is longer than the maximum of {}",
+            length, MAX_STRING_LEN
+        )));
+    }
 
-        let length = length - 1;
+    Ok(Some(length))
+}
 
-        if buf.len() < length {
-            return Err(io::Error::new(
-                io::ErrorKind::InvalidData,
-                "not enough bytes for compact string data",
-            ));
-        }
+impl Decoder for CompactString {
+    fn decode(buf: &mut BytesMut) -> Result<CompactString, io::Error> {
+        let Some(length) = decode_compact_string_len(buf)? else {
+            return Err(DecodeError::invalid("zero-length compact string").into());
+        };
 
-        let str_bytes = buf.split_to(length);
+        let str_bytes = buf.checked_split_to(length)?;
         let str = match String::from_utf8(str_bytes.to_vec()) {
             Ok(str) => str,
             Err(err) => {
-                return Err(io::Error::new(io::ErrorKind::InvalidData, err));
+                return Err(DecodeError::invalid(err).into());
             }
         };
 
@@ -184,30 +374,36 @@ impl Encoder for CompactString {
     }
 }
 
+/// The compact encoding of [`Str`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CompactStr(pub Str);
+
+impl Decoder for CompactStr {
+    fn decode(buf: &mut BytesMut) -> Result<CompactStr, io::Error> {
+        let Some(length) = decode_compact_string_len(buf)? else {
+            return Err(DecodeError::invalid("zero-length compact string").into());
+        };
+
+        let str_bytes = buf.checked_split_to(length)?.freeze();
+        Ok(Self(Str::from_utf8(str_bytes)?))
+    }
+}
+
+/// The compact form of [`NullableString`], with null and the empty string
+/// likewise treated as one.
 pub struct CompactNullableString(pub String);
 
 impl Decoder for CompactNullableString {
     fn decode(buf: &mut BytesMut) -> Result<CompactNullableString, io::Error> {
-        let length = buf.reader().read_varint::<u32>()? as usize;
-
-        if length == 0 {
+        let Some(length) = decode_compact_string_len(buf)? else {
             return Ok(Self(String::new()));
-        }
-
-        let length = length - 1;
-
-        if buf.len() < length {
-            return Err(io::Error::new(
-                io::ErrorKind::InvalidData,
-                "not enough bytes for string data",
-            ));
-        }
+        };
 
-        let str_bytes = buf.split_to(length);
+        let str_bytes = buf.checked_split_to(length)?;
         let str = match String::from_utf8(str_bytes.to_vec()) {
             Ok(str) => str,
             Err(err) => {
-                return Err(io::Error::new(io::ErrorKind::InvalidData, err));
+                return Err(DecodeError::invalid(err).into());
             }
         };
 
@@ -228,21 +424,61 @@ impl Encoder for CompactNullableString {
     }
 }
 
+impl Decoder for Bytes {
+    fn decode(buf: &mut BytesMut) -> Result<Bytes, io::Error> {
+        let len = buf.checked_get_i32()?;
+
+        if len < 0 {
+            return Err(DecodeError::invalid("invalid bytes length").into());
+        }
+
+        Ok(buf.checked_split_to(len as usize)?.freeze())
+    }
+}
+
+impl Encoder for Bytes {
+    fn encode(&self, buf: &mut BytesMut) -> Result<(), io::Error> {
+        buf.put_i32(self.len() as i32);
+        buf.put_slice(self);
+        Ok(())
+    }
+}
+
+pub struct CompactBytes(pub Bytes);
+
+impl Decoder for CompactBytes {
+    fn decode(buf: &mut BytesMut) -> Result<CompactBytes, io::Error> {
+        let length = buf.checked_get_uvarint()? as usize;
+
+        if length == 0 {
+            return Err(DecodeError::invalid("zero-length compact bytes").into());
+        }
+
+        Ok(Self(buf.checked_split_to(length - 1)?.freeze()))
+    }
+}
+
+impl Encoder for CompactBytes {
+    fn encode(&self, buf: &mut BytesMut) -> Result<(), io::Error> {
+        buf.writer().write_varint(self.0.len() as u32 + 1)?;
+        buf.put_slice(&self.0);
+        Ok(())
+    }
+}
+
 impl<T> Decoder for Vec<T>
 where
     T: Decoder,
 {
     fn decode(buf: &mut BytesMut) -> Result<Self, io::Error> {
-        if buf.len() < 4 {
-            return Err(io::Error::new(
-                io::ErrorKind::InvalidData,
-                "not enough data for array length",
-            ));
-        }
+        let length = buf.checked_get_i32()?;
 
-        let length = buf.get_u32() as usize;
+        if length < 0 {
+            return Err(DecodeError::invalid("invalid array length").into());
+        }
 
-        let mut array = Vec::with_capacity(length);
+        let length = length as usize;
+        let mut array = Vec::with_capacity(array_capacity(length, buf));
         for _ in 0..length {
             array.push(T::decode(buf)?);
         }
@@ -256,16 +492,14 @@ where
     T: DecoderVersioned,
 {
     fn decode(buf: &mut BytesMut, version: i16) -> Result<Self, io::Error> {
-        if buf.len() < 4 {
-            return Err(io::Error::new(
-                io::ErrorKind::InvalidData,
-                "not enough data for array length",
-            ));
-        }
+        let length = buf.checked_get_i32()?;
 
-        let length = buf.get_u32() as usize;
+        if length < 0 {
+            return Err(DecodeError::invalid("invalid array length").into());
+        }
 
-        let mut array = Vec::with_capacity(length);
+        let length = length as usize;
+        let mut array = Vec::with_capacity(array_capacity(length, buf));
         for _ in 0..length {
             array.push(T::decode(buf, version)?);
         }
@@ -287,6 +521,92 @@ where
     }
 }
 
+pub struct ArrayRef<'a, T>(pub &'a [T]);
+
+impl<'a, T> EncoderVersioned for ArrayRef<'a, T>
+where
+    T: EncoderVersioned,
+{
+    fn encode(&self, buf: &mut BytesMut, version: i16) -> Result<(), io::Error> {
+        buf.put_i32(self.0.len() as i32);
+        for element in self.0 {
+            element.encode(buf, version)?;
+        }
+        Ok(())
+    }
+
+    fn size_hint(&self, version: i16) -> usize {
+        4 + self.0.iter().map(|e| e.size_hint(version)).sum::<usize>()
+    }
+}
+
+pub struct NullableArray<T>(pub Option<Vec<T>>);
+
+impl<T> Decoder for NullableArray<T>
+where
+    T: Decoder,
+{
+    fn decode(buf: &mut BytesMut) -> Result<NullableArray<T>, io::Error> {
+        let length = buf.checked_get_i32()?;
+
+        if length == -1 {
+            return Ok(Self(None));
+        }
+
+        if length < -1 {
+            return Err(DecodeError::invalid("invalid nullable array length").into());
+        }
+
+        let mut array = Vec::with_capacity(array_capacity(length as usize, buf));
+        for _ in 0..length {
+            array.push(T::decode(buf)?);
+        }
+
+        Ok(Self(Some(array)))
+    }
+}
+
+impl<T> DecoderVersioned for NullableArray<T>
+where
+    T: DecoderVersioned,
+{
+    fn decode(buf: &mut BytesMut, version: i16) -> Result<NullableArray<T>, io::Error> {
+        let length = buf.checked_get_i32()?;
+
+        if length == -1 {
+            return Ok(Self(None));
+        }
+
+        if length < -1 {
+            return Err(DecodeError::invalid("invalid nullable array length").into());
+        }
+
+        let mut array = Vec::with_capacity(array_capacity(length as usize, buf));
+        for _ in 0..length {
+            array.push(T::decode(buf, version)?);
+        }
+
+        Ok(Self(Some(array)))
+    }
+}
+
+pub struct NullableArrayRef<'a, T>(pub Option<&'a [T]>);
+
+impl<'a, T> EncoderVersioned for NullableArrayRef<'a, T>
+where
+    T: EncoderVersioned,
+{
+    fn encode(&self, buf: &mut BytesMut, version: i16) -> Result<(), io::Error> {
+        match self.0 {
+            Some(array) => ArrayRef(array).encode(buf, version),
+            None => {
+                buf.put_i32(-1);
+                Ok(())
+            }
+        }
+    }
+}
+
 pub struct CompactArray<T>(pub Vec<T>);
 
 impl<T> Decoder for CompactArray<T>
@@ -294,17 +614,14 @@ where
     T: Decoder,
 {
     fn decode(buf: &mut BytesMut) -> Result<CompactArray<T>, io::Error> {
-        let length = buf.reader().read_varint::<u32>()? as usize;
+        let length = buf.checked_get_uvarint()? as usize;
 
         if length == 0 {
-            return Err(io::Error::new(
-                io::ErrorKind::InvalidData,
-                "compact array length is 0",
-            ));
+            return Err(DecodeError::invalid("compact array length is 0").into());
         }
 
         let length = length - 1;
-        let mut array = Vec::with_capacity(length);
+        let mut array = Vec::with_capacity(array_capacity(length, buf));
         for _ in 0..length {
             array.push(T::decode(buf)?);
         }
@@ -318,17 +635,14 @@ where
     T: DecoderVersioned,
 {
     fn decode(buf: &mut BytesMut, version: i16) -> Result<Self, io::Error> {
-        let length = buf.reader().read_varint::<u32>()? as usize;
+        let length = buf.checked_get_uvarint()? as usize;
 
         if length == 0 {
-            return Err(io::Error::new(
-                io::ErrorKind::InvalidData,
-                "compact array length is 0",
-            ));
+            return Err(DecodeError::invalid("compact array length is 0").into());
         }
 
         let length = length - 1;
-        let mut array = Vec::with_capacity(length);
+        let mut array = Vec::with_capacity(array_capacity(length, buf));
         for _ in 0..length {
             array.push(T::decode(buf, version)?);
         }
@@ -337,6 +651,82 @@ where
     }
 }
 
+pub struct CompactNullableArray<T>(pub Option<Vec<T>>);
+
+impl<T> Decoder for CompactNullableArray<T>
+where
+    T: Decoder,
+{
+    fn decode(buf: &mut BytesMut) -> Result<CompactNullableArray<T>, io::Error> {
+        let length = buf.checked_get_uvarint()? as usize;
+
+        if length == 0 {
+            return Ok(Self(None));
+        }
+
+        let length = length - 1;
+        let mut array = Vec::with_capacity(array_capacity(length, buf));
+        for _ in 0..length {
+            array.push(T::decode(buf)?);
+        }
+
+        Ok(Self(Some(array)))
+    }
+}
+
+impl<T> DecoderVersioned for CompactNullableArray<T>
+where
+    T: DecoderVersioned,
+{
+    fn decode(buf: &mut BytesMut, version: i16) -> Result<CompactNullableArray<T>, io::Error> {
+        let length = buf.checked_get_uvarint()? as usize;
+
+        if length == 0 {
+            return Ok(Self(None));
+        }
+
+        let length = length - 1;
+        let mut array = Vec::with_capacity(array_capacity(length, buf));
+        for _ in 0..length {
+            array.push(T::decode(buf, version)?);
+        }
+
+        Ok(Self(Some(array)))
+    }
+}
+
+pub struct CompactNullableArrayRef<'a, T>(pub Option<&'a [T]>);
+
+impl<'a, T> Encoder for CompactNullableArrayRef<'a, T>
+where
+    T: Encoder,
+{
+    fn encode(&self, buf: &mut BytesMut) -> Result<(), io::Error> {
+        match self.0 {
+            Some(array) => CompactArrayRef(array).encode(buf),
+            None => {
+                buf.writer().write_varint(0u32)?;
+                Ok(())
+            }
+        }
+    }
+}
+
+impl<'a, T> EncoderVersioned for CompactNullableArrayRef<'a, T>
+where
+    T: EncoderVersioned,
+{
+    fn encode(&self, buf: &mut BytesMut, version: i16) -> Result<(), io::Error> {
+        match self.0 {
+            Some(array) => CompactArrayRef(array).encode(buf, version),
+            None => {
+                buf.writer().write_varint(0u32)?;
+                Ok(())
+            }
+        }
+    }
+}
+
 pub struct CompactArrayRef<'a, T>(pub &'a [T]);
 
 impl<'a, T> Encoder for CompactArrayRef<'a, T>
@@ -363,30 +753,118 @@ where
         }
         Ok(())
     }
+
+    fn size_hint(&self, version: i16) -> usize {
+        varint_size(self.0.len() + 1) + self.0.iter().map(|e| e.size_hint(version)).sum::<usize>()
+    }
+}
+
+/// Typed access to tagged fields, which are kept as raw bytes when decoded.
+pub trait TaggedFields {
+    /// Decodes the field with the given tag, if present. The whole field must
+    /// be consumed by the decoder.
+    fn decode_tag<T: Decoder>(&self, tag: i32) -> Option<Result<T, io::Error>>;
+
+    /// Encodes `value` as the field with the given tag, replacing any field
+    /// already there. It's written out in the tagged fields section, in tag
+    /// order, when the fields are encoded.
+    fn encode_tag<T: Encoder>(&mut self, tag: i32, value: &T) -> Result<(), io::Error>;
+}
+
+impl TaggedFields for BTreeMap<i32, Bytes> {
+    fn encode_tag<T: Encoder>(&mut self, tag: i32, value: &T) -> Result<(), io::Error> {
+        let mut buf = BytesMut::new();
+        value.encode(&mut buf)?;
+        self.insert(tag, buf.freeze());
+        Ok(())
+    }
+
+    fn decode_tag<T: Decoder>(&self, tag: i32) -> Option<Result<T, io::Error>> {
+        let mut buf = BytesMut::from(self.get(&tag)?.as_ref());
+
+        Some(T::decode(&mut buf).and_then(|value| {
+            if buf.is_empty() {
+                Ok(value)
+            } else {
+                Err(DecodeError::invalid(format!(
+                    "{} trailing bytes in tagged field {}",
+                    buf.len(),
+                    tag
+                ))
+                .into())
+            }
+        }))
+    }
 }
 
 impl Decoder for BTreeMap<i32, Bytes> {
     fn decode(buf: &mut BytesMut) -> Result<Self, io::Error> {
         let mut tagged_fields = BTreeMap::new();
-        let num_tagged_fields = buf.reader().read_varint::<u32>()? as usize;
+        let num_tagged_fields = buf.checked_get_uvarint()? as usize;
+
+        // Every field takes at least two bytes for its tag and size, so a
+        // count the buffer can't hold is bogus rather than truncated.
+        if num_tagged_fields > buf.len() / 2 {
+            return Err(DecodeError::invalid(format!(
+                "{} tagged fields don't fit in the remaining {} bytes",
+                num_tagged_fields,
+                buf.len()
+            ))
+            .into());
+        }
+
         for _ in 0..num_tagged_fields {
-            let tag = buf.reader().read_varint::<u32>()?;
-            let size = buf.reader().read_varint::<u32>()? as usize;
-            let unknown_value = buf.split_to(size);
+            let tag = buf.checked_get_uvarint()?;
+            let size = buf.checked_get_uvarint()? as usize;
+
+            // Flexible request headers end in tagged fields, so this is the
+            // first thing to see a bogus size, before any handler runs. The
+            // frame has fully arrived by then, so it's invalid, not truncated.
+            if size > buf.len() {
+                return Err(DecodeError::invalid(format!(
+                    "tagged field {} of {} bytes exceeds the remaining {} bytes",
+                    tag,
+                    size,
+                    buf.len()
+                ))
+                .into());
+            }
+
+            let unknown_value = buf.checked_split_to(size)?;
             tagged_fields.insert(tag as i32, unknown_value.freeze());
         }
         Ok(tagged_fields)
     }
 }
 
+/// Decodes the tagged fields ending a message at `version`, if it's at least
+/// `flexible_since`, the first flexible version. Older versions have no
+/// tagged fields, so nothing is read and the map is empty.
+pub fn decode_tagged_if_flexible(
+    buf: &mut BytesMut,
+    version: i16,
+    flexible_since: i16,
+) -> Result<BTreeMap<i32, Bytes>, io::Error> {
+    if version >= flexible_since {
+        Decoder::decode(buf)
+    } else {
+        Ok(BTreeMap::new())
+    }
+}
+
 impl Encoder for BTreeMap<i32, Bytes> {
     fn encode(&self, buf: &mut BytesMut) -> Result<(), io::Error> {
-        if !self.is_empty() {
-            panic!("cannot send non-empty tagged fields")
+        let mut writer = buf.writer();
+        writer.write_varint(self.len() as u32)?;
+
+        // Fields are written in ascending tag order, which the map iterates
+        // in.
+        for (&tag, value) in self {
+            writer.write_varint(tag as u32)?;
+            writer.write_varint(value.len() as u32)?;
+            writer.get_mut().extend_from_slice(value);
         }
 
-        buf.writer().write_varint(0u32)?;
-        // TODO(herbstein): actually write fields
         Ok(())
     }
 }
@@ -5,12 +5,26 @@ use bytes::BytesMut;
 use crate::{
     ConnectionState, RequestHeader, VersionRange,
     protocol::{
-        handlers::{AnyRequestHandler, RequestHandler, TypedRequestHandler},
+        api_keys::{self, ApiKey},
+        error::ErrorCode,
+        handlers::{
+            AddPartitionsToTxnHandler, AnyRequestHandler, ApiVersionsHandler,
+            ConsumerGroupHeartbeatHandler, ControlledShutdownHandler, CreateAclsHandler,
+            DeleteAclsHandler, DeleteRecordsHandler, DescribeAclsHandler, DescribeClusterHandler,
+            DescribeConfigsHandler, DescribeProducersHandler, DescribeTopicPartitionsHandler,
+            ElectLeadersHandler, EndTxnHandler, FetchHandler, FindCoordinatorHandler,
+            IncrementalAlterConfigsHandler, InitProducerIdHandler, MetadataHandler,
+            OffsetDeleteHandler, OffsetForLeaderEpochHandler, RequestHandler,
+            SaslAuthenticateHandler, TxnOffsetCommitHandler, TypedRequestHandler,
+        },
         request::Request,
-        response::AnyResponse,
+        response::{AnyResponse, UnimplementedResponse},
     },
+    server::BrokerState,
 };
 
+pub const API_VERSIONS_KEY: i16 = ApiKey::ApiVersions.as_i16();
+
 pub struct MessageRegistry {
     handlers: BTreeMap<i16, Box<dyn AnyRequestHandler>>,
 }
@@ -22,13 +36,135 @@ impl MessageRegistry {
         }
     }
 
-    pub fn register<Req, H>(&mut self, key: i16, handler: H)
+    /// Creates a registry with a handler for every api key the agent
+    /// supports.
+    pub fn with_default_handlers(state: &BrokerState) -> Self {
+        let mut registry = Self::new();
+        registry
+            .register_defaults(state)
+            .expect("default handlers have distinct api keys");
+        registry
+    }
+
+    fn register_defaults(&mut self, state: &BrokerState) -> Result<(), io::Error> {
+        self.register(
+            ApiKey::Fetch,
+            FetchHandler::new(state.logs.clone(), state.fetch_sessions.clone()),
+        )?;
+        self.register(
+            ApiKey::Metadata,
+            MetadataHandler::new(
+                state.logs.clone(),
+                state.cluster.clone(),
+                state.configs.clone(),
+            ),
+        )?;
+        self.register(
+            ApiKey::ControlledShutdown,
+            ControlledShutdownHandler::new(state.cluster.clone(), state.shutting_down.clone()),
+        )?;
+        self.register(
+            ApiKey::FindCoordinator,
+            FindCoordinatorHandler::new(state.cluster.clone()),
+        )?;
+        self.register(ApiKey::ApiVersions, ApiVersionsHandler)?;
+        self.register(
+            ApiKey::DeleteRecords,
+            DeleteRecordsHandler::new(state.logs.clone()),
+        )?;
+        self.register(
+            ApiKey::InitProducerId,
+            InitProducerIdHandler::new(state.transactions.clone()),
+        )?;
+        self.register(
+            ApiKey::OffsetForLeaderEpoch,
+            OffsetForLeaderEpochHandler::new(state.logs.clone()),
+        )?;
+        self.register(
+            ApiKey::AddPartitionsToTxn,
+            AddPartitionsToTxnHandler::new(state.transactions.clone()),
+        )?;
+        self.register(
+            ApiKey::EndTxn,
+            EndTxnHandler::new(state.transactions.clone()),
+        )?;
+        self.register(
+            ApiKey::TxnOffsetCommit,
+            TxnOffsetCommitHandler::new(state.transactions.clone()),
+        )?;
+        self.register(
+            ApiKey::DescribeAcls,
+            DescribeAclsHandler::new(state.acls.clone()),
+        )?;
+        self.register(
+            ApiKey::CreateAcls,
+            CreateAclsHandler::new(state.acls.clone()),
+        )?;
+        self.register(
+            ApiKey::DeleteAcls,
+            DeleteAclsHandler::new(state.acls.clone()),
+        )?;
+        self.register(
+            ApiKey::DescribeConfigs,
+            DescribeConfigsHandler::new(state.configs.clone(), state.logs.clone()),
+        )?;
+        self.register(ApiKey::SaslAuthenticate, SaslAuthenticateHandler)?;
+        self.register(
+            ApiKey::ElectLeaders,
+            ElectLeadersHandler::new(state.logs.clone()),
+        )?;
+        self.register(
+            ApiKey::IncrementalAlterConfigs,
+            IncrementalAlterConfigsHandler::new(state.configs.clone()),
+        )?;
+        self.register(
+            ApiKey::OffsetDelete,
+            OffsetDeleteHandler::new(state.offsets.clone()),
+        )?;
+        self.register(
+            ApiKey::DescribeProducers,
+            DescribeProducersHandler::new(state.logs.clone(), state.transactions.clone()),
+        )?;
+        self.register(
+            ApiKey::ConsumerGroupHeartbeat,
+            ConsumerGroupHeartbeatHandler::new(state.groups.clone(), state.logs.clone()),
+        )?;
+        self.register(
+            ApiKey::DescribeCluster,
+            DescribeClusterHandler::new(state.cluster.clone()),
+        )?;
+        self.register(
+            ApiKey::DescribeTopicPartitions,
+            DescribeTopicPartitionsHandler::new(state.logs.clone(), state.cluster.clone()),
+        )?;
+        Ok(())
+    }
+
+    /// Registers `handler` for `key`, failing if `key` already has one.
+    pub fn register<Req, H>(&mut self, key: ApiKey, handler: H) -> Result<(), io::Error>
+    where
+        Req: Request + Send + Sync + 'static,
+        H: RequestHandler<Req> + Send + Sync + 'static,
+    {
+        if self.handlers.contains_key(&key.as_i16()) {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("api key {} already has a handler", key),
+            ));
+        }
+
+        self.register_override(key, handler);
+        Ok(())
+    }
+
+    /// Registers `handler` for `key`, replacing any handler it already has.
+    pub fn register_override<Req, H>(&mut self, key: ApiKey, handler: H)
     where
         Req: Request + Send + Sync + 'static,
         H: RequestHandler<Req> + Send + Sync + 'static,
     {
         self.handlers
-            .insert(key, Box::new(TypedRequestHandler::new(handler)));
+            .insert(key.as_i16(), Box::new(TypedRequestHandler::new(handler)));
     }
 
     pub async fn handle_request(
@@ -37,8 +173,20 @@ impl MessageRegistry {
         header: &RequestHeader,
         state: &mut ConnectionState,
     ) -> Result<Box<dyn AnyResponse>, io::Error> {
-        match self.handlers.get(&header.api_key) {
-            Some(handler) => handler.handle(buf, header, state).await,
+        if let Some(handler) = self.handlers.get(&header.api_key) {
+            return handler.handle(buf, header, state).await;
+        }
+
+        // Keys the protocol defines get a minimal error response, rather than
+        // tearing down the connection.
+        match api_keys::lookup(header.api_key) {
+            Some(api_key) => {
+                tracing::debug!(api_key = api_key.name, "Api key is not implemented");
+                Ok(Box::new(UnimplementedResponse {
+                    api_key: *api_key,
+                    error_code: ErrorCode::UnsupportedVersion,
+                }))
+            }
             None => Err(io::Error::other(format!(
                 "Unsupported api key: {}",
                 header.api_key
@@ -46,9 +194,38 @@ impl MessageRegistry {
         }
     }
 
-    pub fn header_version(&self, api_key: i16, version: i16) -> Result<i16, io::Error> {
-        match self.handlers.get(&api_key) {
-            Some(handler) => Ok(handler.header_version(version)),
+    /// Decodes a raw request frame, without its length prefix, into a
+    /// human-readable description of the header and body. Useful for
+    /// diagnosing captured traffic.
+    pub fn decode_request_debug(&self, buf: &mut BytesMut) -> Result<String, io::Error> {
+        let header = RequestHeader::decode(buf, self, usize::MAX)?;
+
+        let body = match self.handlers.get(&header.api_key) {
+            Some(handler) => handler.decode_debug(buf, header.version)?,
+            None => {
+                return Err(io::Error::other(format!(
+                    "unsupported api key: {}",
+                    header.api_key
+                )));
+            }
+        };
+
+        Ok(format!(
+            "api key {} version {}\n{:#?}\n{}",
+            header.api_key, header.version, header, body
+        ))
+    }
+
+    /// The version of the header of requests for `api_key` at `version`.
+    /// Keys without a handler fall back to the protocol's api key table.
+    pub fn header_version(&self, api_key: impl Into<i16>, version: i16) -> Result<i16, io::Error> {
+        let api_key = api_key.into();
+        if let Some(handler) = self.handlers.get(&api_key) {
+            return Ok(handler.header_version(version));
+        }
+
+        match api_keys::lookup(api_key) {
+            Some(api_key) => Ok(api_key.request_header_version(version)),
             None => Err(io::Error::other(format!(
                 "unsupported api key: {}",
                 api_key
@@ -56,7 +233,8 @@ impl MessageRegistry {
         }
     }
 
-    pub fn versions(&self, api_key: i16) -> Result<VersionRange, io::Error> {
+    pub fn versions(&self, api_key: impl Into<i16>) -> Result<VersionRange, io::Error> {
+        let api_key = api_key.into();
         match self.handlers.get(&api_key) {
             Some(handler) => Ok(handler.versions()),
             None => Err(io::Error::other(format!(
@@ -66,6 +244,37 @@ impl MessageRegistry {
         }
     }
 
+    /// Caps the versions of `api_key` that are advertised and accepted at
+    /// `max_version`, which must be within the versions its handler
+    /// supports.
+    pub fn cap_max_version(
+        &mut self,
+        api_key: impl Into<i16>,
+        max_version: i16,
+    ) -> Result<(), io::Error> {
+        let api_key = api_key.into();
+        let Some(handler) = self.handlers.get_mut(&api_key) else {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("unsupported api key: {}", api_key),
+            ));
+        };
+
+        let versions = handler.versions();
+        if !versions.contains(max_version) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "max version {} of api key {} is outside of its supported versions [{}, {}]",
+                    max_version, api_key, versions.min, versions.max
+                ),
+            ));
+        }
+
+        handler.cap_max_version(max_version);
+        Ok(())
+    }
+
     pub fn all_api_keys(&self) -> impl Iterator<Item = i16> {
         self.handlers.keys().copied()
     }
@@ -1,8 +1,53 @@
+use std::{any, fmt::Debug};
+
 use crate::{
     Message,
-    protocol::{DecoderVersioned, response::Response},
+    authorizer::{Operation, Resource},
+    protocol::{DecoderVersioned, error::ErrorCode, response::Response},
 };
 
-pub trait Request: Message + DecoderVersioned + Send + Sync {
+pub trait Request: Message + DecoderVersioned + Debug + Send + Sync {
     type Response: Response;
+
+    /// Builds the response sent in place of the handler's when handling the
+    /// request fails with `error_code`.
+    fn error_response(&self, error_code: ErrorCode) -> Self::Response;
+
+    /// Builds the response sent when the request body couldn't be decoded,
+    /// so there is no request to take details from.
+    fn decode_error_response(error_code: ErrorCode) -> Self::Response;
+
+    /// Whether the request carries secrets, such as credentials, that must
+    /// never end up in logs or request dumps.
+    fn is_sensitive() -> bool {
+        false
+    }
+
+    /// The operations the request performs, each of which the client must be
+    /// authorized for before the request is handled. Requests any client may
+    /// make, such as ApiVersions, perform none.
+    fn operations(&self) -> Vec<(Operation, Resource)> {
+        vec![]
+    }
+
+    /// How long the client is willing to wait for the response, for requests
+    /// that carry a timeout. Handlers find the resulting deadline on the
+    /// [connection state](crate::ConnectionState::deadline).
+    fn timeout_ms(&self) -> Option<i32> {
+        None
+    }
+}
+
+/// Describes `request` for logs and request dumps. The body of a
+/// [sensitive](Request::is_sensitive) request is replaced with `<redacted>`.
+pub fn describe<Req: Request>(request: &Req) -> String {
+    if Req::is_sensitive() {
+        let name = any::type_name::<Req>()
+            .rsplit("::")
+            .next()
+            .unwrap_or_default();
+        return format!("{} {{ <redacted> }}", name);
+    }
+
+    format!("{:#?}", request)
 }
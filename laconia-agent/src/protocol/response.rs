@@ -1,17 +1,63 @@
-use std::io;
+use std::{collections::BTreeMap, io};
 
-use bytes::BytesMut;
+use bytes::{Bytes, BytesMut};
 
-use crate::protocol::EncoderVersioned;
+use crate::protocol::{Encoder, EncoderVersioned, api_keys::ApiKeyInfo, error::ErrorCode};
 
-pub trait Response: EncoderVersioned + Send {}
+pub trait Response: EncoderVersioned + Send {
+    /// Sets the response's `throttle_time_ms` field. Responses without one
+    /// ignore it.
+    fn set_throttle_time_ms(&mut self, _throttle_time_ms: i32) {}
+}
 
 pub trait AnyResponse: Send {
     fn encode_any(&self, buf: &mut BytesMut, version: i16) -> Result<(), io::Error>;
+
+    fn size_hint_any(&self, version: i16) -> usize;
+
+    fn set_throttle_time_ms(&mut self, throttle_time_ms: i32);
 }
 
 impl<T: Response> AnyResponse for T {
     fn encode_any(&self, buf: &mut BytesMut, version: i16) -> Result<(), io::Error> {
         self.encode(buf, version)
     }
+
+    fn size_hint_any(&self, version: i16) -> usize {
+        self.size_hint(version)
+    }
+
+    fn set_throttle_time_ms(&mut self, throttle_time_ms: i32) {
+        Response::set_throttle_time_ms(self, throttle_time_ms)
+    }
+}
+
+/// The response to a request for an api key the protocol defines but the
+/// agent doesn't implement: just an error code, followed by empty tagged
+/// fields in flexible versions.
+///
+/// Most responses don't start with their error code, so clients can't read
+/// it as the api's own response. It's framed and correlated correctly
+/// though, so they fail the one request rather than the whole connection.
+pub struct UnimplementedResponse {
+    pub api_key: ApiKeyInfo,
+    pub error_code: ErrorCode,
 }
+
+impl EncoderVersioned for UnimplementedResponse {
+    fn encode(&self, buf: &mut BytesMut, version: i16) -> Result<(), io::Error> {
+        self.error_code.encode(buf)?;
+
+        if self.api_key.is_flexible(version) {
+            BTreeMap::<i32, Bytes>::new().encode(buf)?;
+        }
+
+        Ok(())
+    }
+
+    fn size_hint(&self, _version: i16) -> usize {
+        2 + 1
+    }
+}
+
+impl Response for UnimplementedResponse {}
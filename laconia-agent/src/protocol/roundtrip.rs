@@ -0,0 +1,42 @@
+//! Helpers for checking that a message's encoder and decoder agree.
+
+use std::fmt::Debug;
+
+use bytes::BytesMut;
+
+use crate::protocol::{DecoderVersioned, EncoderVersioned};
+
+/// Encodes `value` at `version`, decodes the result, and re-encodes the
+/// decoded value. Panics unless the decoder consumed the whole buffer and both
+/// encodings are byte-for-byte identical.
+pub fn assert_roundtrip<T>(value: &T, version: i16)
+where
+    T: EncoderVersioned + DecoderVersioned + Debug,
+{
+    let mut encoded = BytesMut::new();
+    value
+        .encode(&mut encoded, version)
+        .unwrap_or_else(|err| panic!("failed to encode {:?} at v{}: {}", value, version, err));
+
+    let mut buf = encoded.clone();
+    let decoded = T::decode(&mut buf, version)
+        .unwrap_or_else(|err| panic!("failed to decode {:?} at v{}: {}", value, version, err));
+    assert!(
+        buf.is_empty(),
+        "decoding {:?} at v{} left {} trailing bytes",
+        value,
+        version,
+        buf.len()
+    );
+
+    let mut reencoded = BytesMut::new();
+    decoded
+        .encode(&mut reencoded, version)
+        .unwrap_or_else(|err| panic!("failed to re-encode {:?} at v{}: {}", decoded, version, err));
+
+    assert_eq!(
+        encoded, reencoded,
+        "{:?} did not round-trip at v{}",
+        value, version
+    );
+}
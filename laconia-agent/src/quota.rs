@@ -0,0 +1,92 @@
+use std::{
+    collections::HashMap,
+    io,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use crate::clock::{Clock, SystemClock};
+
+struct TokenBucket {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+/// Per-client-id request rate limiting using a token bucket.
+///
+/// Each client may burst up to one second's worth of requests. Once the
+/// bucket runs dry the client is allowed to go into debt, and the debt is
+/// reported back as the time the client should be throttled for.
+pub struct QuotaManager {
+    requests_per_sec: Option<f64>,
+    buckets: Mutex<HashMap<String, TokenBucket>>,
+    clock: Arc<dyn Clock>,
+}
+
+impl QuotaManager {
+    /// Fails if `requests_per_sec` isn't a positive, finite rate, which the
+    /// buckets couldn't refill at.
+    pub fn new(requests_per_sec: Option<f64>) -> Result<Self, io::Error> {
+        if let Some(rate) = requests_per_sec
+            && !(rate.is_finite() && rate > 0.0)
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("quota rate must be positive and finite, got {}", rate),
+            ));
+        }
+
+        Ok(Self {
+            requests_per_sec,
+            buckets: Mutex::new(HashMap::new()),
+            clock: Arc::new(SystemClock),
+        })
+    }
+
+    /// Measures the time buckets take to refill with `clock` rather than the
+    /// system clock.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Records a request from `client_id` and returns how long the client
+    /// should be throttled for. Returns [`Duration::ZERO`] when the client is
+    /// within its quota or no quota is configured.
+    pub fn record(&self, client_id: &str) -> Duration {
+        let Some(rate) = self.requests_per_sec else {
+            return Duration::ZERO;
+        };
+
+        let now = self.clock.now();
+        let mut buckets = self.buckets.lock().unwrap();
+        let bucket = buckets
+            .entry(client_id.to_string())
+            .or_insert_with(|| TokenBucket {
+                tokens: rate,
+                last_refill: now,
+            });
+
+        let elapsed = now.duration_since(bucket.last_refill).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * rate).min(rate);
+        bucket.last_refill = now;
+        bucket.tokens -= 1.0;
+
+        if bucket.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-bucket.tokens / rate)
+        }
+    }
+}
+
+impl Default for QuotaManager {
+    /// A manager with no quota configured, which never throttles.
+    fn default() -> Self {
+        Self {
+            requests_per_sec: None,
+            buckets: Mutex::new(HashMap::new()),
+            clock: Arc::new(SystemClock),
+        }
+    }
+}
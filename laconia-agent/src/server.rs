@@ -0,0 +1,676 @@
+use std::{
+    collections::BTreeMap,
+    fmt, io,
+    net::{Ipv4Addr, SocketAddr},
+    sync::{Arc, atomic::AtomicBool},
+    time::Duration,
+};
+
+use futures::{SinkExt, StreamExt, future};
+use tokio::{
+    io::{AsyncRead, AsyncWrite, split},
+    net::{TcpListener, TcpStream},
+    sync::{OwnedSemaphorePermit, Semaphore, mpsc},
+    time,
+};
+use tokio_util::codec::{FramedRead, FramedWrite};
+use tracing::Instrument;
+
+use crate::{
+    ConnectionState, DEFAULT_MAX_CLIENT_ID_LEN, DEFAULT_MAX_REQUEST_SIZE, KafkaMessageCodec,
+    KafkaRequest, KafkaRequestCodec, KafkaResponse,
+    authorizer::{AclStore, AllowAll, Authorizer},
+    cluster::ClusterInfo,
+    configs::ConfigStore,
+    connections::ConnectionRegistry,
+    features::Features,
+    fetch_session::{DEFAULT_MAX_FETCH_SESSIONS, FetchSessionCache},
+    groups::GroupCoordinator,
+    log::LogStore,
+    offsets::OffsetStore,
+    protocol::{
+        api_keys::ApiKey, handlers::RequestHandler, registry::MessageRegistry, request::Request,
+    },
+    quota::QuotaManager,
+    telemetry,
+    transactions::TransactionManager,
+};
+
+/// The state shared by the default request handlers.
+#[derive(Clone)]
+pub struct BrokerState {
+    pub cluster: Arc<ClusterInfo>,
+    pub configs: Arc<ConfigStore>,
+    pub logs: Arc<LogStore>,
+    /// The offsets committed by consumer groups.
+    pub offsets: Arc<OffsetStore>,
+    /// The consumer groups using the consumer rebalance protocol.
+    pub groups: Arc<GroupCoordinator>,
+    pub transactions: Arc<TransactionManager>,
+    pub fetch_sessions: Arc<FetchSessionCache>,
+    /// Set once the broker has been asked to shut down by a
+    /// ControlledShutdown request.
+    pub shutting_down: Arc<AtomicBool>,
+    /// The ACLs managed through the ACL admin APIs, or `None` if ACLs aren't
+    /// enforced and the APIs are disabled.
+    pub acls: Option<Arc<AclStore>>,
+}
+
+impl BrokerState {
+    /// The state of a broker in `cluster`, serving the partitions in `logs`,
+    /// with nothing else going on yet and ACLs disabled.
+    pub fn new(cluster: Arc<ClusterInfo>, configs: Arc<ConfigStore>, logs: Arc<LogStore>) -> Self {
+        let offsets = Arc::new(OffsetStore::new());
+        let groups = Arc::new(GroupCoordinator::new(logs.clone()));
+        let transactions = Arc::new(TransactionManager::new(logs.clone(), offsets.clone()));
+        Self {
+            cluster,
+            configs,
+            logs,
+            offsets,
+            groups,
+            transactions,
+            fetch_sessions: Arc::new(FetchSessionCache::new(DEFAULT_MAX_FETCH_SESSIONS)),
+            shutting_down: Arc::new(AtomicBool::new(false)),
+            acls: None,
+        }
+    }
+}
+
+/// The security protocol of a listener, named as in Kafka's
+/// `listener.security.protocol.map`.
+///
+/// Connections are only tagged with it, so handlers can tell listeners
+/// apart. TLS and SASL themselves aren't implemented, so every listener
+/// speaks plaintext.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SecurityProtocol {
+    Plaintext,
+    Ssl,
+    SaslPlaintext,
+    SaslSsl,
+}
+
+impl SecurityProtocol {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Plaintext => "PLAINTEXT",
+            Self::Ssl => "SSL",
+            Self::SaslPlaintext => "SASL_PLAINTEXT",
+            Self::SaslSsl => "SASL_SSL",
+        }
+    }
+}
+
+impl fmt::Display for SecurityProtocol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// An address to accept connections on, and the security protocol of the
+/// connections accepted there.
+#[derive(Debug, Clone, Copy)]
+pub struct ListenerConfig {
+    pub addr: SocketAddr,
+    pub security_protocol: SecurityProtocol,
+}
+
+impl ListenerConfig {
+    pub fn new(addr: SocketAddr, security_protocol: SecurityProtocol) -> Self {
+        Self {
+            addr,
+            security_protocol,
+        }
+    }
+
+    pub fn plaintext(addr: SocketAddr) -> Self {
+        Self::new(addr, SecurityProtocol::Plaintext)
+    }
+}
+
+struct Listener {
+    listener: TcpListener,
+    security_protocol: SecurityProtocol,
+}
+
+/// How long a connection may go without sending a request, unless
+/// configured otherwise.
+const DEFAULT_IDLE_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// How many responses a connection may have waiting to be written before no
+/// more of its requests are read, unless configured otherwise.
+const DEFAULT_MAX_QUEUED_RESPONSES: usize = 16;
+
+/// The settings every connection of a server is served with.
+#[derive(Clone)]
+struct ConnectionSettings {
+    registry: Arc<MessageRegistry>,
+    idle_timeout: Duration,
+    max_client_id_len: usize,
+    max_request_size: usize,
+    max_queued_responses: usize,
+    strict_decoding: bool,
+    authorizer: Arc<dyn Authorizer>,
+    quotas: Arc<QuotaManager>,
+    features: Arc<Features>,
+    connections: Arc<ConnectionRegistry>,
+}
+
+impl ConnectionSettings {
+    fn new(registry: Arc<MessageRegistry>) -> Self {
+        Self {
+            registry,
+            idle_timeout: DEFAULT_IDLE_TIMEOUT,
+            max_client_id_len: DEFAULT_MAX_CLIENT_ID_LEN,
+            max_request_size: DEFAULT_MAX_REQUEST_SIZE,
+            max_queued_responses: DEFAULT_MAX_QUEUED_RESPONSES,
+            strict_decoding: false,
+            authorizer: Arc::new(AllowAll),
+            quotas: Arc::new(QuotaManager::default()),
+            features: Arc::new(Features::new()),
+            connections: Arc::new(ConnectionRegistry::new()),
+        }
+    }
+}
+
+/// A Kafka protocol server, answering requests with the handlers in its
+/// registry.
+pub struct KafkaServer {
+    /// Never empty.
+    listeners: Vec<Listener>,
+    state: Option<BrokerState>,
+    connections: ConnectionSettings,
+    connection_permits: Arc<Semaphore>,
+}
+
+impl KafkaServer {
+    pub fn builder() -> KafkaServerBuilder {
+        KafkaServerBuilder::new()
+    }
+
+    /// The address of the server's first listener.
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listeners[0].listener.local_addr()
+    }
+
+    /// The addresses of every listener, in the order they were added.
+    pub fn local_addrs(&self) -> io::Result<Vec<SocketAddr>> {
+        self.listeners
+            .iter()
+            .map(|listener| listener.listener.local_addr())
+            .collect()
+    }
+
+    /// The connections the server has open.
+    pub fn connections(&self) -> &Arc<ConnectionRegistry> {
+        &self.connections.connections
+    }
+
+    /// The state the server was built with, if any.
+    pub fn state(&self) -> Option<&BrokerState> {
+        self.state.as_ref()
+    }
+
+    /// Waits for the next connection on any listener and spawns a task
+    /// serving it. Once the connection limit is reached, this waits for a
+    /// connection to close first.
+    pub async fn accept(&self) -> io::Result<()> {
+        let permit = self.acquire_connection_permit().await?;
+        let (stream, peer, security_protocol) = self.accept_any().await?;
+        self.spawn_connection(stream, peer, security_protocol, permit);
+        Ok(())
+    }
+
+    /// Spawns a task serving `stream` as if it had been accepted on a
+    /// plaintext listener, so the server can be driven in-process, such as
+    /// over one end of a [`tokio::io::duplex`] pipe. Counts towards the
+    /// connection limit like any other connection.
+    pub async fn serve<S>(&self, stream: S) -> io::Result<()>
+    where
+        S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+    {
+        let permit = self.acquire_connection_permit().await?;
+        self.spawn_connection(stream, "in-process", SecurityProtocol::Plaintext, permit);
+        Ok(())
+    }
+
+    async fn acquire_connection_permit(&self) -> io::Result<OwnedSemaphorePermit> {
+        match self.connection_permits.clone().try_acquire_owned() {
+            Ok(permit) => Ok(permit),
+            Err(_) => {
+                tracing::warn!("Connection limit reached, waiting for a connection to close");
+                self.connection_permits
+                    .clone()
+                    .acquire_owned()
+                    .await
+                    .map_err(io::Error::other)
+            }
+        }
+    }
+
+    fn spawn_connection<S>(
+        &self,
+        stream: S,
+        peer: impl fmt::Display,
+        security_protocol: SecurityProtocol,
+        permit: OwnedSemaphorePermit,
+    ) where
+        S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+    {
+        let connection = serve_connection(
+            stream,
+            self.connections.clone(),
+            peer.to_string(),
+            security_protocol,
+        );
+
+        tokio::spawn(
+            async move {
+                connection.await;
+                drop(permit);
+            }
+            .instrument(tracing::info_span!(
+                "connection",
+                %peer,
+                listener = %security_protocol
+            )),
+        );
+    }
+
+    /// Waits for a connection on any of the listeners. Accepting is cancel
+    /// safe, so the listeners that lose the race don't drop a connection.
+    async fn accept_any(&self) -> io::Result<(TcpStream, SocketAddr, SecurityProtocol)> {
+        let accepts = self.listeners.iter().map(|listener| {
+            Box::pin(async move {
+                let (stream, peer) = listener.listener.accept().await?;
+                Ok((stream, peer, listener.security_protocol))
+            })
+        });
+
+        let (result, _, _) = future::select_all(accepts).await;
+        result
+    }
+}
+
+/// Serves requests on `stream` with the handlers in `registry` until the
+/// client disconnects, as a plaintext connection with the server's default
+/// settings. Unlike [`KafkaServer::serve`], this runs the connection to
+/// completion rather than spawning it, and needs no listener.
+pub async fn handle_connection<S>(stream: S, registry: Arc<MessageRegistry>)
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    serve_connection(
+        stream,
+        ConnectionSettings::new(registry),
+        "in-process".to_string(),
+        SecurityProtocol::Plaintext,
+    )
+    .await
+}
+
+async fn serve_connection<S>(
+    stream: S,
+    settings: ConnectionSettings,
+    peer: String,
+    security_protocol: SecurityProtocol,
+) where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let ConnectionSettings {
+        registry,
+        idle_timeout,
+        max_client_id_len,
+        max_request_size,
+        max_queued_responses,
+        strict_decoding,
+        authorizer,
+        quotas,
+        features,
+        connections,
+    } = settings;
+
+    let mut connection_state = ConnectionState::new(registry.clone())
+        .with_security_protocol(security_protocol)
+        .with_max_client_id_len(max_client_id_len)
+        .with_strict_decoding(strict_decoding)
+        .with_authorizer(authorizer)
+        .with_features(features);
+    let connection = connections.register(peer, &connection_state);
+
+    let (reader, writer) = split(stream);
+    let mut requests = FramedRead::new(
+        reader,
+        KafkaRequestCodec::new(registry.clone())
+            .with_max_client_id_len(max_client_id_len)
+            .with_max_request_size(max_request_size),
+    );
+    let mut responses = FramedWrite::new(writer, KafkaMessageCodec::new());
+
+    // Responses are written as they're queued, while the next request is
+    // read and handled. Once the queue is full, no more requests are read
+    // until the client reads some responses, so a client that stops reading
+    // can't make the server buffer without limit.
+    let (queue, mut queued) = mpsc::channel::<KafkaResponse>(max_queued_responses);
+
+    telemetry::connection_opened();
+
+    let read = async {
+        let queue = queue;
+        loop {
+            let Ok(slot) = queue.reserve().await else {
+                // The writer has stopped, so the client is gone.
+                break;
+            };
+
+            // A frame whose header is unreadable is an error here, as there
+            // is no correlation id to respond with. Body decode failures are
+            // answered by the handler.
+            let (header, mut body) = match time::timeout(idle_timeout, requests.next()).await {
+                Ok(Some(Ok(request))) => request,
+                Ok(Some(Err(err))) => {
+                    tracing::warn!("Kafka protocol error: {}", err);
+                    break;
+                }
+                Ok(None) => break,
+                Err(_) => {
+                    tracing::debug!("Closing connection idle for {:?}", idle_timeout);
+                    break;
+                }
+            };
+            connection_state.record_request(requests.decoder().last_frame_len());
+
+            let mut request =
+                match KafkaRequest::handle(header, &mut body, &registry, &mut connection_state)
+                    .await
+                {
+                    Ok(request) => request,
+                    Err(err) => {
+                        tracing::warn!("Failed to handle request: {}", err);
+                        break;
+                    }
+                };
+
+            let throttle = quotas.record(&request.header.client_id);
+            if !throttle.is_zero() {
+                request
+                    .response
+                    .set_throttle_time_ms(throttle.as_millis() as i32);
+                time::sleep(throttle).await;
+            }
+
+            let response =
+                KafkaResponse::new(&request.header, request.response_version, request.response);
+
+            connection.update(&connection_state);
+            slot.send(response);
+            telemetry::response_queued();
+        }
+    };
+
+    // Drains the queue until the reader stops and drops its end, so every
+    // response handled is still written.
+    let write = async {
+        while let Some(response) = queued.recv().await {
+            telemetry::response_dequeued();
+            if let Err(err) = responses.send(response).await {
+                tracing::debug!("Failed to write response: {}", err);
+                break;
+            }
+        }
+
+        // Responses left behind when the client stops reading are dropped.
+        queued.close();
+        while queued.recv().await.is_some() {
+            telemetry::response_dequeued();
+        }
+    };
+
+    future::join(read, write).await;
+
+    // The connection span, if any, carries the peer address.
+    tracing::debug!(
+        requests = connection_state.request_count(),
+        bytes = connection_state.bytes_read(),
+        duration_ms = connection_state.duration().as_millis() as u64,
+        "Connection closed"
+    );
+    telemetry::connection_closed();
+}
+
+/// Assembles a [`KafkaServer`] from the handlers it should serve.
+///
+/// A builder from [`KafkaServerBuilder::new`] starts with no handlers at all,
+/// so embedders can pick exactly the api keys their broker answers, while
+/// [`KafkaServerBuilder::with_default_handlers`] starts with the agent's own.
+pub struct KafkaServerBuilder {
+    registry: MessageRegistry,
+    /// The first failed registration, reported by [`build`](Self::build).
+    register_error: Option<io::Error>,
+    max_versions: BTreeMap<ApiKey, i16>,
+    listeners: Vec<ListenerConfig>,
+    state: Option<BrokerState>,
+    idle_timeout: Duration,
+    max_client_id_len: usize,
+    max_request_size: usize,
+    max_queued_responses: usize,
+    strict_decoding: bool,
+    authorizer: Arc<dyn Authorizer>,
+    max_connections: usize,
+    quotas: QuotaManager,
+    features: Features,
+}
+
+impl KafkaServerBuilder {
+    /// A builder with no handlers. Unless a listener is added, the server
+    /// listens on an ephemeral localhost port.
+    pub fn new() -> Self {
+        Self {
+            registry: MessageRegistry::new(),
+            register_error: None,
+            max_versions: BTreeMap::new(),
+            listeners: vec![],
+            state: None,
+            idle_timeout: DEFAULT_IDLE_TIMEOUT,
+            max_client_id_len: DEFAULT_MAX_CLIENT_ID_LEN,
+            max_request_size: DEFAULT_MAX_REQUEST_SIZE,
+            max_queued_responses: DEFAULT_MAX_QUEUED_RESPONSES,
+            strict_decoding: false,
+            authorizer: Arc::new(AllowAll),
+            max_connections: 1024,
+            quotas: QuotaManager::default(),
+            features: Features::new(),
+        }
+    }
+
+    /// A builder with a handler for every api key the agent supports, backed
+    /// by `state`.
+    pub fn with_default_handlers(state: BrokerState) -> Self {
+        Self {
+            registry: MessageRegistry::with_default_handlers(&state),
+            ..Self::new()
+        }
+        .with_state(state)
+    }
+
+    /// Registers `handler` for `key`. [`build`](Self::build) fails if `key`
+    /// already has a handler.
+    pub fn register<Req, H>(mut self, key: ApiKey, handler: H) -> Self
+    where
+        Req: Request + Send + Sync + 'static,
+        H: RequestHandler<Req> + Send + Sync + 'static,
+    {
+        if let Err(err) = self.registry.register(key, handler) {
+            self.register_error.get_or_insert(err);
+        }
+        self
+    }
+
+    /// Registers `handler` for `key`, replacing any handler it already has,
+    /// such as one of the default handlers.
+    pub fn register_override<Req, H>(mut self, key: ApiKey, handler: H) -> Self
+    where
+        Req: Request + Send + Sync + 'static,
+        H: RequestHandler<Req> + Send + Sync + 'static,
+    {
+        self.registry.register_override(key, handler);
+        self
+    }
+
+    /// Caps the versions of `api_key` the server advertises and accepts at
+    /// `max_version`, to work around clients that mishandle newer versions.
+    /// [`build`](Self::build) fails unless the handler of `api_key` supports
+    /// `max_version`.
+    pub fn max_version(mut self, api_key: ApiKey, max_version: i16) -> Self {
+        self.max_versions.insert(api_key, max_version);
+        self
+    }
+
+    /// Adds a plaintext listener on `addr`.
+    pub fn bind(self, addr: SocketAddr) -> Self {
+        self.listener(ListenerConfig::plaintext(addr))
+    }
+
+    pub fn listener(mut self, listener: ListenerConfig) -> Self {
+        self.listeners.push(listener);
+        self
+    }
+
+    pub fn with_state(mut self, state: BrokerState) -> Self {
+        self.state = Some(state);
+        self
+    }
+
+    /// How long a connection may go without sending a request before it's
+    /// closed.
+    pub fn idle_timeout(mut self, idle_timeout: Duration) -> Self {
+        self.idle_timeout = idle_timeout;
+        self
+    }
+
+    /// The longest client id accepted in request headers. Connections
+    /// sending a longer one are closed.
+    pub fn max_client_id_len(mut self, max_client_id_len: usize) -> Self {
+        self.max_client_id_len = max_client_id_len;
+        self
+    }
+
+    /// The largest request accepted, length prefix excluded. Connections
+    /// announcing a larger one are closed before it's read.
+    pub fn max_request_size(mut self, max_request_size: usize) -> Self {
+        self.max_request_size = max_request_size;
+        self
+    }
+
+    /// How many responses a connection may have waiting to be written to a
+    /// client that's slow to read them. Once that many are waiting, no more
+    /// of its requests are read until it catches up. [`build`](Self::build)
+    /// fails if it's zero.
+    pub fn max_queued_responses(mut self, max_queued_responses: usize) -> Self {
+        self.max_queued_responses = max_queued_responses;
+        self
+    }
+
+    /// Whether to reject requests whose body isn't fully consumed by its
+    /// decoder, which surfaces decoder bugs and version mismatches. Off by
+    /// default, so the leftover bytes are ignored.
+    pub fn strict_decoding(mut self, strict_decoding: bool) -> Self {
+        self.strict_decoding = strict_decoding;
+        self
+    }
+
+    /// The authorizer every request is checked against before it's handled.
+    /// Defaults to allowing everything.
+    pub fn authorizer(mut self, authorizer: Arc<dyn Authorizer>) -> Self {
+        self.authorizer = authorizer;
+        self
+    }
+
+    /// How many connections may be open at once. Once that many are open,
+    /// accepting waits for one to close. [`build`](Self::build) fails if
+    /// it's zero.
+    pub fn max_connections(mut self, max_connections: usize) -> Self {
+        self.max_connections = max_connections;
+        self
+    }
+
+    pub fn quotas(mut self, quotas: QuotaManager) -> Self {
+        self.quotas = quotas;
+        self
+    }
+
+    /// The features advertised to clients in ApiVersions, which handlers
+    /// can check the negotiated level of through
+    /// [`ConnectionState::feature_level`].
+    pub fn features(mut self, features: Features) -> Self {
+        self.features = features;
+        self
+    }
+
+    /// Binds the listeners, after which the server is ready to
+    /// [`accept`](KafkaServer::accept) connections.
+    pub async fn build(mut self) -> io::Result<KafkaServer> {
+        if let Some(err) = self.register_error {
+            return Err(err);
+        }
+
+        if self.max_connections == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "max_connections must be at least 1",
+            ));
+        }
+
+        if self.max_queued_responses == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "max_queued_responses must be at least 1",
+            ));
+        }
+
+        for (&api_key, &max_version) in &self.max_versions {
+            self.registry.cap_max_version(api_key, max_version)?;
+        }
+
+        if self.listeners.is_empty() {
+            self.listeners
+                .push(ListenerConfig::plaintext(SocketAddr::from((
+                    Ipv4Addr::LOCALHOST,
+                    0,
+                ))));
+        }
+
+        let mut listeners = Vec::with_capacity(self.listeners.len());
+        for config in &self.listeners {
+            listeners.push(Listener {
+                listener: TcpListener::bind(config.addr).await?,
+                security_protocol: config.security_protocol,
+            });
+        }
+
+        Ok(KafkaServer {
+            listeners,
+            state: self.state,
+            connections: ConnectionSettings {
+                registry: Arc::new(self.registry),
+                idle_timeout: self.idle_timeout,
+                max_client_id_len: self.max_client_id_len,
+                max_request_size: self.max_request_size,
+                max_queued_responses: self.max_queued_responses,
+                strict_decoding: self.strict_decoding,
+                authorizer: self.authorizer,
+                quotas: Arc::new(self.quotas),
+                features: Arc::new(self.features),
+                connections: Arc::new(ConnectionRegistry::new()),
+            },
+            connection_permits: Arc::new(Semaphore::new(self.max_connections)),
+        })
+    }
+}
+
+impl Default for KafkaServerBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,55 @@
+use std::{io, net::SocketAddr, time::Duration};
+
+use metrics::{counter, gauge, histogram};
+use metrics_exporter_prometheus::PrometheusBuilder;
+
+use crate::protocol::error::ErrorCode;
+
+/// Installs the global metrics recorder and serves its metrics in the
+/// Prometheus text format on `addr`.
+pub fn install(addr: SocketAddr) -> Result<(), io::Error> {
+    PrometheusBuilder::new()
+        .with_http_listener(addr)
+        .install()
+        .map_err(io::Error::other)
+}
+
+/// Records a handled request and how long handling it took.
+pub fn record_request(api_key: i16, elapsed: Duration) {
+    let api_key = api_key.to_string();
+    counter!("requests_total", "api_key" => api_key.clone()).increment(1);
+    histogram!("request_duration_seconds", "api_key" => api_key).record(elapsed);
+}
+
+/// Records the size of a request frame, excluding its length prefix.
+pub fn record_request_size(api_key: i16, bytes: usize) {
+    histogram!("request_bytes", "api_key" => api_key.to_string()).record(bytes as f64);
+}
+
+/// Records the size of an encoded response, excluding its length prefix.
+pub fn record_response_size(api_key: i16, bytes: usize) {
+    histogram!("response_bytes", "api_key" => api_key.to_string()).record(bytes as f64);
+}
+
+/// Records a request that was answered with `error_code`.
+pub fn record_error(error_code: ErrorCode) {
+    counter!("errors_total", "error_code" => error_code.name()).increment(1);
+}
+
+pub fn connection_opened() {
+    gauge!("active_connections").increment(1);
+}
+
+pub fn connection_closed() {
+    gauge!("active_connections").decrement(1);
+}
+
+/// Records a response queued to be written to its connection. Summed over
+/// every connection, so a growing gauge points at clients reading slowly.
+pub fn response_queued() {
+    gauge!("queued_responses").increment(1);
+}
+
+pub fn response_dequeued() {
+    gauge!("queued_responses").decrement(1);
+}
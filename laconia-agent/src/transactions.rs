@@ -0,0 +1,282 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    sync::{Arc, Mutex},
+};
+
+use crate::{
+    log::LogStore,
+    offsets::{CommittedOffset, OffsetStore},
+    protocol::error::ErrorCode,
+};
+
+/// The highest epoch a producer id is bumped to. Clients treat `i16::MAX` as
+/// exhausted, so once an epoch reaches this the producer id is replaced.
+const MAX_PRODUCER_EPOCH: i16 = i16::MAX - 1;
+
+/// Where a transactional producer is in its current transaction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransactionStatus {
+    /// No partitions have been added since the last transaction ended.
+    Empty,
+    /// Partitions or offsets have been added and the transaction hasn't ended
+    /// yet.
+    Ongoing,
+}
+
+struct Transaction {
+    producer_id: i64,
+    producer_epoch: i16,
+    status: TransactionStatus,
+    partitions: BTreeSet<(String, i32)>,
+    /// Offsets committed in the transaction, keyed by group, topic and
+    /// partition.
+    offsets: BTreeMap<(String, String, i32), CommittedOffset>,
+}
+
+impl Transaction {
+    /// Forgets the partitions and offsets of the current transaction.
+    fn clear(&mut self) {
+        self.status = TransactionStatus::Empty;
+        self.partitions.clear();
+        self.offsets.clear();
+    }
+}
+
+/// The transaction coordinator state for a single broker, kept in memory.
+///
+/// Producer ids and epochs are tracked per transactional id, along with the
+/// partitions in each ongoing transaction. Ending a transaction writes a
+/// commit or abort marker to each of its partitions, which lets consumers
+/// reading committed records only see past it. Offsets committed in a
+/// transaction are staged until it commits, and only then written to the
+/// offset store.
+pub struct TransactionManager {
+    inner: Mutex<TransactionManagerInner>,
+    logs: Arc<LogStore>,
+    offsets: Arc<OffsetStore>,
+}
+
+struct TransactionManagerInner {
+    next_producer_id: i64,
+    transactions: HashMap<String, Transaction>,
+}
+
+impl TransactionManager {
+    pub fn new(logs: Arc<LogStore>, offsets: Arc<OffsetStore>) -> Self {
+        Self {
+            inner: Mutex::new(TransactionManagerInner {
+                next_producer_id: 0,
+                transactions: HashMap::new(),
+            }),
+            logs,
+            offsets,
+        }
+    }
+
+    /// Assigns a producer id and epoch to a producer. Idempotent producers,
+    /// with no `transactional_id`, get a fresh producer id every time.
+    ///
+    /// A transactional producer keeps the producer id of its transactional
+    /// id, with the epoch bumped to fence any older instance of the producer
+    /// still using it. Any transaction it had ongoing is aborted. Once the
+    /// epoch can't be bumped any further, the producer id is replaced
+    /// instead, starting over at epoch 0.
+    pub fn init_producer_id(&self, transactional_id: Option<&str>) -> (i64, i16) {
+        let mut inner = self.inner.lock().unwrap();
+
+        let Some(transactional_id) = transactional_id else {
+            return (inner.allocate_producer_id(), 0);
+        };
+
+        let inner = &mut *inner;
+        if let Some(transaction) = inner.transactions.get_mut(transactional_id) {
+            if let Err(error_code) = self.write_markers(transaction, false) {
+                tracing::warn!(transactional_id, %error_code, "Failed to abort transaction");
+            }
+            transaction.clear();
+
+            if transaction.producer_epoch < MAX_PRODUCER_EPOCH {
+                transaction.producer_epoch += 1;
+            } else {
+                transaction.producer_id = inner.next_producer_id;
+                inner.next_producer_id += 1;
+                transaction.producer_epoch = 0;
+            }
+
+            tracing::debug!(
+                transactional_id,
+                producer_id = transaction.producer_id,
+                producer_epoch = transaction.producer_epoch,
+                "Fenced previous producer"
+            );
+            return (transaction.producer_id, transaction.producer_epoch);
+        }
+
+        let producer_id = inner.allocate_producer_id();
+        inner.transactions.insert(
+            transactional_id.to_string(),
+            Transaction {
+                producer_id,
+                producer_epoch: 0,
+                status: TransactionStatus::Empty,
+                partitions: BTreeSet::new(),
+                offsets: BTreeMap::new(),
+            },
+        );
+
+        (producer_id, 0)
+    }
+
+    /// Adds `partitions` to the producer's transaction, starting one if
+    /// there's none ongoing.
+    pub fn add_partitions(
+        &self,
+        transactional_id: &str,
+        producer_id: i64,
+        producer_epoch: i16,
+        partitions: impl IntoIterator<Item = (String, i32)>,
+    ) -> Result<(), ErrorCode> {
+        let mut inner = self.inner.lock().unwrap();
+        let transaction = inner.transaction(transactional_id, producer_id, producer_epoch)?;
+
+        transaction.status = TransactionStatus::Ongoing;
+        transaction.partitions.extend(partitions);
+        Ok(())
+    }
+
+    /// Stages `offsets`, committed by `group_id`, in the producer's
+    /// transaction, starting one if there's none ongoing. They're written to
+    /// the offset store if the transaction commits, and discarded if it
+    /// aborts.
+    pub fn add_offsets(
+        &self,
+        transactional_id: &str,
+        producer_id: i64,
+        producer_epoch: i16,
+        group_id: &str,
+        offsets: impl IntoIterator<Item = (String, i32, CommittedOffset)>,
+    ) -> Result<(), ErrorCode> {
+        let mut inner = self.inner.lock().unwrap();
+        let transaction = inner.transaction(transactional_id, producer_id, producer_epoch)?;
+
+        transaction.status = TransactionStatus::Ongoing;
+        transaction.offsets.extend(
+            offsets.into_iter().map(|(topic, partition, offset)| {
+                ((group_id.to_string(), topic, partition), offset)
+            }),
+        );
+        Ok(())
+    }
+
+    /// Commits or aborts the producer's ongoing transaction, writing its
+    /// staged offsets to the offset store if it commits. Ending a
+    /// transaction nothing was added to is a no-op.
+    pub fn end_transaction(
+        &self,
+        transactional_id: &str,
+        producer_id: i64,
+        producer_epoch: i16,
+        committed: bool,
+    ) -> Result<(), ErrorCode> {
+        let mut inner = self.inner.lock().unwrap();
+        let transaction = inner.transaction(transactional_id, producer_id, producer_epoch)?;
+
+        tracing::debug!(
+            transactional_id,
+            committed,
+            "Ending transaction over {} partitions and {} offsets",
+            transaction.partitions.len(),
+            transaction.offsets.len()
+        );
+
+        self.write_markers(transaction, committed)?;
+
+        if committed {
+            for ((group_id, topic, partition), offset) in &transaction.offsets {
+                self.offsets
+                    .commit(group_id, topic, *partition, offset.clone());
+            }
+        }
+
+        transaction.clear();
+        Ok(())
+    }
+
+    /// The status of the transaction of `transactional_id`, or `None` if no
+    /// producer has initialized it.
+    pub fn status(&self, transactional_id: &str) -> Option<TransactionStatus> {
+        let inner = self.inner.lock().unwrap();
+        inner
+            .transactions
+            .get(transactional_id)
+            .map(|transaction| transaction.status)
+    }
+
+    /// The producer ids and epochs with a transaction ongoing over `topic`
+    /// and `partition`.
+    pub fn producers(&self, topic: &str, partition: i32) -> Vec<(i64, i16)> {
+        let inner = self.inner.lock().unwrap();
+        inner
+            .transactions
+            .values()
+            .filter(|transaction| {
+                transaction
+                    .partitions
+                    .iter()
+                    .any(|(t, p)| t == topic && *p == partition)
+            })
+            .map(|transaction| (transaction.producer_id, transaction.producer_epoch))
+            .collect()
+    }
+
+    /// Writes a commit or abort marker to every partition in `transaction`.
+    fn write_markers(&self, transaction: &Transaction, committed: bool) -> Result<(), ErrorCode> {
+        for (topic, partition) in &transaction.partitions {
+            self.logs
+                .write_transaction_marker(
+                    topic,
+                    *partition,
+                    transaction.producer_id,
+                    transaction.producer_epoch,
+                    committed,
+                )
+                .map_err(|err| err.error_code())?;
+        }
+
+        Ok(())
+    }
+}
+
+impl TransactionManagerInner {
+    fn allocate_producer_id(&mut self) -> i64 {
+        let producer_id = self.next_producer_id;
+        self.next_producer_id += 1;
+        producer_id
+    }
+
+    /// The transaction of `transactional_id`, checking that it belongs to the
+    /// given producer id and epoch. An older epoch belongs to a producer
+    /// that's been fenced by a newer instance initializing the same
+    /// transactional id.
+    fn transaction(
+        &mut self,
+        transactional_id: &str,
+        producer_id: i64,
+        producer_epoch: i16,
+    ) -> Result<&mut Transaction, ErrorCode> {
+        let transaction = self
+            .transactions
+            .get_mut(transactional_id)
+            .filter(|transaction| transaction.producer_id == producer_id)
+            .ok_or(ErrorCode::InvalidProducerIdMapping)?;
+
+        if producer_epoch < transaction.producer_epoch {
+            return Err(ErrorCode::ProducerFenced);
+        }
+        if producer_epoch != transaction.producer_epoch {
+            return Err(ErrorCode::InvalidProducerEpoch);
+        }
+
+        Ok(transaction)
+    }
+}
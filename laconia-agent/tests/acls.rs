@@ -0,0 +1,150 @@
+//! Checks ACLs created through CreateAcls are listed by DescribeAcls, enforced
+//! by the ACL store, and removed by DeleteAcls.
+
+use std::{collections::BTreeMap, sync::Arc};
+
+use laconia_agent::{
+    ConnectionState, RequestHeader,
+    authorizer::{AclStore, Authorizer, Operation, Resource},
+    protocol::{
+        error::ErrorCode,
+        handlers::{CreateAclsHandler, DeleteAclsHandler, DescribeAclsHandler, RequestHandler},
+        messages::{
+            AclCreation, CreateAclsRequest, DeleteAclsFilter, DeleteAclsRequest,
+            DescribeAclsRequest, DescribeAclsResource,
+        },
+        registry::MessageRegistry,
+    },
+};
+
+/// The code matching any value in an ACL filter.
+const ANY: i8 = 1;
+const TOPIC: i8 = 2;
+const LITERAL: i8 = 3;
+const READ: i8 = 3;
+const ALLOW: i8 = 3;
+
+fn header(api_key: i16) -> RequestHeader {
+    RequestHeader {
+        api_key,
+        version: 3,
+        correlation_id: 0,
+        client_id: "laconia-tests".to_string(),
+        tagged_fields: BTreeMap::new(),
+    }
+}
+
+fn state() -> ConnectionState {
+    ConnectionState::new(Arc::new(MessageRegistry::new()))
+}
+
+/// Lists the ACLs on topics named `events`.
+async fn describe(handler: &DescribeAclsHandler) -> Vec<DescribeAclsResource> {
+    let request = DescribeAclsRequest {
+        resource_type_filter: TOPIC,
+        resource_name_filter: "events".to_string(),
+        pattern_type_filter: ANY,
+        principal_filter: String::new(),
+        host_filter: String::new(),
+        operation: ANY,
+        permission_type: ANY,
+        tagged_fields: BTreeMap::new(),
+    };
+    let response = handler
+        .handle(&header(29), &request, &mut state())
+        .await
+        .unwrap();
+    assert_eq!(response.error_code, ErrorCode::None);
+    response.resources
+}
+
+#[tokio::test]
+async fn acls_are_created_described_and_deleted() {
+    let acls = Arc::new(AclStore::new(vec![]));
+    let create = CreateAclsHandler::new(Some(acls.clone()));
+    let describe_handler = DescribeAclsHandler::new(Some(acls.clone()));
+    let delete = DeleteAclsHandler::new(Some(acls.clone()));
+    let events = Resource::Topic("events".to_string());
+
+    let request = CreateAclsRequest {
+        creations: vec![AclCreation {
+            resource_type: TOPIC,
+            resource_name: "events".to_string(),
+            resource_pattern_type: LITERAL,
+            principal: "User:alice".to_string(),
+            host: "*".to_string(),
+            operation: READ,
+            permission_type: ALLOW,
+            tagged_fields: BTreeMap::new(),
+        }],
+        tagged_fields: BTreeMap::new(),
+    };
+    let response = create
+        .handle(&header(30), &request, &mut state())
+        .await
+        .unwrap();
+    assert_eq!(response.results.len(), 1);
+    assert_eq!(response.results[0].error_code, ErrorCode::None);
+    assert!(acls.authorize("User:alice", Operation::Read, &events));
+    assert!(!acls.authorize("User:bob", Operation::Read, &events));
+
+    let resources = describe(&describe_handler).await;
+    let [resource] = resources.as_slice() else {
+        panic!("expected one resource, got {}", resources.len());
+    };
+    assert_eq!(
+        (resource.resource_type, resource.resource_name.as_str()),
+        (TOPIC, "events")
+    );
+    let [acl] = resource.acls.as_slice() else {
+        panic!("expected one ACL, got {}", resource.acls.len());
+    };
+    assert_eq!(acl.principal, "User:alice");
+    assert_eq!((acl.operation, acl.permission_type), (READ, ALLOW));
+
+    let request = DeleteAclsRequest {
+        filters: vec![DeleteAclsFilter {
+            resource_type_filter: TOPIC,
+            resource_name_filter: "events".to_string(),
+            pattern_type_filter: ANY,
+            principal_filter: "User:alice".to_string(),
+            host_filter: String::new(),
+            operation: ANY,
+            permission_type: ANY,
+            tagged_fields: BTreeMap::new(),
+        }],
+        tagged_fields: BTreeMap::new(),
+    };
+    let response = delete
+        .handle(&header(31), &request, &mut state())
+        .await
+        .unwrap();
+    let [result] = response.filter_results.as_slice() else {
+        panic!("expected one filter result");
+    };
+    assert_eq!(result.error_code, ErrorCode::None);
+    assert_eq!(result.matching_acls.len(), 1);
+    assert_eq!(result.matching_acls[0].principal, "User:alice");
+
+    assert!(describe(&describe_handler).await.is_empty());
+    assert!(!acls.authorize("User:alice", Operation::Read, &events));
+}
+
+#[tokio::test]
+async fn acl_apis_fail_without_an_acl_store() {
+    let request = DescribeAclsRequest {
+        resource_type_filter: ANY,
+        resource_name_filter: String::new(),
+        pattern_type_filter: ANY,
+        principal_filter: String::new(),
+        host_filter: String::new(),
+        operation: ANY,
+        permission_type: ANY,
+        tagged_fields: BTreeMap::new(),
+    };
+    let response = DescribeAclsHandler::new(None)
+        .handle(&header(29), &request, &mut state())
+        .await
+        .unwrap();
+    assert_eq!(response.error_code, ErrorCode::SecurityDisabled);
+}
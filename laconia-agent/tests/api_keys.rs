@@ -0,0 +1,27 @@
+//! Checks the api key enum agrees with the protocol's api key table.
+
+use laconia_agent::protocol::api_keys::{API_KEYS, ApiKey, lookup};
+
+#[test]
+fn known_keys_round_trip() {
+    for info in API_KEYS {
+        let key = info.key.as_i16();
+        assert_eq!(ApiKey::from_i16(key), Some(info.key));
+        assert_eq!(i16::from(info.key), key);
+        assert_eq!(info.key.info().key, info.key);
+        assert_eq!(info.key.to_string(), info.name);
+        assert_eq!(lookup(key).map(|info| info.key), Some(info.key));
+    }
+
+    assert_eq!(ApiKey::ApiVersions.as_i16(), 18);
+    assert_eq!(ApiKey::from_i16(3), Some(ApiKey::Metadata));
+}
+
+#[test]
+fn unknown_keys_are_rejected() {
+    let past_last = API_KEYS.len() as i16;
+    for key in [-1, past_last, 1000, i16::MIN, i16::MAX] {
+        assert_eq!(ApiKey::from_i16(key), None, "api key {}", key);
+        assert!(lookup(key).is_none(), "api key {}", key);
+    }
+}
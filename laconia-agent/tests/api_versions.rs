@@ -0,0 +1,58 @@
+//! Checks ApiVersions responses are encoded with classic arrays before v3 and
+//! compact ones with tagged fields from v3.
+
+use bytes::BytesMut;
+use laconia_agent::{
+    EncoderVersioned,
+    protocol::{
+        error::ErrorCode,
+        messages::{ApiVersionsApiKeys, ApiVersionsResponse},
+    },
+};
+
+fn response() -> ApiVersionsResponse {
+    ApiVersionsResponse {
+        error_code: ErrorCode::None,
+        api_keys: vec![ApiVersionsApiKeys {
+            api_key: 18,
+            min_version: 0,
+            max_version: 4,
+            tagged_fields: Default::default(),
+        }],
+        throttle_time_ms: 7,
+        supported_features: vec![],
+        finalized_features_epoch: -1,
+        finalized_features: vec![],
+        tagged_fields: Default::default(),
+    }
+}
+
+#[test]
+fn v0_uses_a_classic_array_and_no_tagged_fields() {
+    let mut buf = BytesMut::new();
+    response().encode(&mut buf, 0).unwrap();
+
+    #[rustfmt::skip]
+    let expected: &[u8] = &[
+        0, 0, // error_code
+        0, 0, 0, 1, // api_keys length
+        0, 18, 0, 0, 0, 4, // api_key, min_version, max_version
+    ];
+    assert_eq!(&buf[..], expected);
+}
+
+#[test]
+fn v3_uses_a_compact_array_and_tagged_fields() {
+    let mut buf = BytesMut::new();
+    response().encode(&mut buf, 3).unwrap();
+
+    #[rustfmt::skip]
+    let expected: &[u8] = &[
+        0, 0, // error_code
+        2, // api_keys length + 1
+        0, 18, 0, 0, 0, 4, 0, // api_key, min_version, max_version, tagged_fields
+        0, 0, 0, 7, // throttle_time_ms
+        0, // tagged_fields
+    ];
+    assert_eq!(&buf[..], expected);
+}
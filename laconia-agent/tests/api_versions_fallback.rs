@@ -0,0 +1,133 @@
+//! Checks requests the broker can't read the body of, an ApiVersions of a
+//! version it doesn't know or an unknown api key, are answered in v0 so the
+//! client can retry with a version it's told about, and that api keys it
+//! doesn't implement get an error response the client can parse.
+
+use std::sync::Arc;
+
+use bytes::{Buf, BufMut, BytesMut};
+use laconia_agent::{
+    ConnectionState, KafkaRequest, ResponseHeader,
+    protocol::{
+        api_keys::ApiKey,
+        error::ErrorCode,
+        handlers::ApiVersionsHandler,
+        registry::{API_VERSIONS_KEY, MessageRegistry},
+    },
+};
+
+#[tokio::test]
+async fn api_versions_v100_is_answered_in_v0_with_the_key_list() {
+    let mut registry = MessageRegistry::new();
+    registry
+        .register(ApiKey::ApiVersions, ApiVersionsHandler)
+        .unwrap();
+    let registry = Arc::new(registry);
+    let mut state = ConnectionState::new(registry.clone());
+
+    // A v2 header followed by a body only a v100 request would know how to
+    // read.
+    let mut frame = BytesMut::new();
+    frame.put_i16(API_VERSIONS_KEY);
+    frame.put_i16(100);
+    frame.put_i32(7);
+    frame.put_i16(-1);
+    frame.put_u8(0);
+    frame.extend_from_slice(&[0, 0, 0]);
+
+    let request = KafkaRequest::decode_and_handle(&mut frame, &registry, &mut state)
+        .await
+        .unwrap();
+    assert_eq!(request.header.correlation_id, 7);
+    assert_eq!(request.response_version, 0);
+
+    // librdkafka retries with v0 on UNSUPPORTED_VERSION, which only works if
+    // the v0 response says which versions to retry with.
+    let mut body = BytesMut::new();
+    request
+        .response
+        .encode_any(&mut body, request.response_version)
+        .unwrap();
+    assert_eq!(body.get_i16(), ErrorCode::UnsupportedVersion.as_i16());
+    assert_eq!(ErrorCode::UnsupportedVersion.as_i16(), 35);
+
+    let api_keys = (0..body.get_i32())
+        .map(|_| (body.get_i16(), body.get_i16(), body.get_i16()))
+        .collect::<Vec<_>>();
+    assert!(
+        api_keys.contains(&(API_VERSIONS_KEY, 0, 4)),
+        "ApiVersions lists itself: {:?}",
+        api_keys
+    );
+    assert!(body.is_empty());
+}
+
+#[tokio::test]
+async fn unknown_api_key_is_answered_with_its_correlation_id() {
+    let mut registry = MessageRegistry::new();
+    registry
+        .register(ApiKey::ApiVersions, ApiVersionsHandler)
+        .unwrap();
+    let registry = Arc::new(registry);
+    let mut state = ConnectionState::new(registry.clone());
+
+    let mut frame = BytesMut::new();
+    frame.put_i16(999);
+    frame.put_i16(0);
+    frame.put_i32(1);
+    frame.put_i16(-1);
+
+    // The correlation id is all a client can match the response up by.
+    let request = KafkaRequest::decode_and_handle(&mut frame, &registry, &mut state)
+        .await
+        .unwrap();
+    assert_eq!(request.header.correlation_id, 1);
+    assert_eq!(request.response_version, 0);
+
+    let mut body = BytesMut::new();
+    request
+        .response
+        .encode_any(&mut body, request.response_version)
+        .unwrap();
+    assert_eq!(body.get_i16(), ErrorCode::UnsupportedVersion.as_i16());
+}
+
+#[tokio::test]
+async fn unimplemented_api_key_gets_a_parseable_error() {
+    let mut registry = MessageRegistry::new();
+    registry
+        .register(ApiKey::ApiVersions, ApiVersionsHandler)
+        .unwrap();
+    let registry = Arc::new(registry);
+    let mut state = ConnectionState::new(registry.clone());
+
+    // Produce isn't implemented. Its v9 is flexible, so both the response
+    // header and body end in tagged fields, while v3's don't.
+    for (version, header_version, expected) in [(9, 1, &[0, 35, 0][..]), (3, 0, &[0, 35])] {
+        let mut frame = BytesMut::new();
+        frame.put_i16(0);
+        frame.put_i16(version);
+        frame.put_i32(5);
+        frame.put_i16(-1);
+        if version >= 9 {
+            frame.put_u8(0);
+        }
+
+        let request = KafkaRequest::decode_and_handle(&mut frame, &registry, &mut state)
+            .await
+            .unwrap();
+        assert_eq!(request.header.correlation_id, 5);
+        assert_eq!(ResponseHeader::version_for(&request.header), header_version);
+
+        let mut body = BytesMut::new();
+        request
+            .response
+            .encode_any(&mut body, request.response_version)
+            .unwrap();
+        assert_eq!(&body[..], expected);
+        assert_eq!(
+            ErrorCode::from_i16(body.get_i16()),
+            Some(ErrorCode::UnsupportedVersion)
+        );
+    }
+}
@@ -0,0 +1,118 @@
+//! Checks requests are authorized for the connection's principal before
+//! they're handled, and denied ones fail without reaching their handler.
+
+mod common;
+
+use std::{collections::BTreeMap, sync::Arc};
+
+use bytes::{BufMut, BytesMut};
+use laconia_agent::{
+    ConnectionState, KafkaRequest,
+    authorizer::{Authorizer, Operation, Resource},
+    cluster::{BrokerInfo, ClusterInfo},
+    configs::ConfigStore,
+    log::LogStore,
+    protocol::{
+        EncoderVersioned,
+        api_keys::ApiKey,
+        error::ErrorCode,
+        handlers::MetadataHandler,
+        messages::{
+            MetadataRequest, MetadataRequestTopic, MetadataResponse, MetadataResponseTopic,
+        },
+        registry::MessageRegistry,
+    },
+};
+use uuid::Uuid;
+
+/// Allows `User:alice` everything, and everyone else nothing.
+struct AliceOnly;
+
+impl Authorizer for AliceOnly {
+    fn authorize(&self, principal: &str, _operation: Operation, _resource: &Resource) -> bool {
+        principal == "User:alice"
+    }
+}
+
+/// Sends a Metadata v12 request for `events` as `principal`, returning the
+/// encoded response.
+async fn metadata(registry: &Arc<MessageRegistry>, principal: &str) -> BytesMut {
+    let request = MetadataRequest {
+        topics: Some(vec![MetadataRequestTopic {
+            topic_id: Uuid::nil(),
+            name: "events".to_string(),
+            tagged_fields: BTreeMap::new(),
+        }]),
+        allow_auto_topic_creation: false,
+        include_cluster_authorized_operations: false,
+        include_topic_authorized_operations: false,
+        tagged_fields: BTreeMap::new(),
+    };
+
+    // A v2 header with a null client id and no tagged fields.
+    let mut frame = BytesMut::new();
+    frame.put_i16(3);
+    frame.put_i16(12);
+    frame.put_i32(0);
+    frame.put_i16(-1);
+    frame.put_u8(0);
+    request.encode(&mut frame, 12).unwrap();
+
+    let mut state = ConnectionState::new(registry.clone()).with_authorizer(Arc::new(AliceOnly));
+    state.set_principal(principal);
+    let request = KafkaRequest::decode_and_handle(&mut frame, registry, &mut state)
+        .await
+        .unwrap();
+
+    let mut response = BytesMut::new();
+    request.response.encode_any(&mut response, 12).unwrap();
+    response
+}
+
+#[tokio::test]
+async fn metadata_is_denied_to_an_unauthorized_principal() {
+    let configs = Arc::new(ConfigStore::new(0, vec![]));
+    let logs = Arc::new(LogStore::open(None, false, configs.clone()).unwrap());
+    logs.append("events", 0, common::batch(&[b"a"], 0)).unwrap();
+    let cluster = Arc::new(ClusterInfo::single_broker(
+        "laconia",
+        BrokerInfo {
+            node_id: 0,
+            host: "localhost".to_string(),
+            port: 9092,
+            rack: String::new(),
+        },
+    ));
+    let mut registry = MessageRegistry::new();
+    registry
+        .register(
+            ApiKey::Metadata,
+            MetadataHandler::new(logs, cluster, configs),
+        )
+        .unwrap();
+    let registry = Arc::new(registry);
+
+    // The topic is reported as unauthorized, and nothing about the cluster
+    // is given away.
+    let denied = MetadataResponse {
+        throttle_time_ms: 0,
+        brokers: vec![],
+        cluster_id: String::new(),
+        controller_id: -1,
+        topics: vec![MetadataResponseTopic {
+            error_code: ErrorCode::TopicAuthorizationFailed,
+            name: "events".to_string(),
+            topic_id: Uuid::nil(),
+            is_internal: false,
+            partitions: vec![],
+            topic_authorized_operations: i32::MIN,
+            tagged_fields: BTreeMap::new(),
+        }],
+        tagged_fields: BTreeMap::new(),
+    };
+    let mut expected = BytesMut::new();
+    denied.encode(&mut expected, 12).unwrap();
+
+    assert_eq!(metadata(&registry, "User:mallory").await, expected);
+    assert_ne!(metadata(&registry, "User:alice").await, expected);
+}
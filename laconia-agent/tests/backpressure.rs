@@ -0,0 +1,81 @@
+//! Checks a client that stops reading responses stops the server reading its
+//! requests, rather than making it buffer responses without limit.
+
+use std::{collections::BTreeMap, time::Duration};
+
+use bytes::{BufMut, BytesMut};
+use laconia_agent::{
+    DecoderVersioned, EncoderVersioned, RequestHeader, ResponseHeader,
+    protocol::{api_keys::ApiKey, handlers::ApiVersionsHandler},
+    server::KafkaServerBuilder,
+};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+const REQUESTS: i32 = 200;
+
+/// An ApiVersions v0 request, which has an empty body.
+fn api_versions_frame(correlation_id: i32) -> BytesMut {
+    let header = RequestHeader {
+        api_key: ApiKey::ApiVersions.as_i16(),
+        version: 0,
+        correlation_id,
+        client_id: "laconia-tests".to_string(),
+        tagged_fields: BTreeMap::new(),
+    };
+
+    let mut request = BytesMut::new();
+    header.encode(&mut request, 1).unwrap();
+
+    let mut frame = BytesMut::new();
+    frame.put_i32(request.len() as i32);
+    frame.extend_from_slice(&request);
+    frame
+}
+
+#[tokio::test]
+async fn slow_reader_stops_requests_being_read() {
+    let server = KafkaServerBuilder::new()
+        .register(ApiKey::ApiVersions, ApiVersionsHandler)
+        .max_queued_responses(2)
+        .build()
+        .await
+        .unwrap();
+    let connections = server.connections().clone();
+
+    // The pipe holds about a dozen responses, far fewer than are requested.
+    let (client, stream) = tokio::io::duplex(256);
+    server.serve(stream).await.unwrap();
+    let (mut reader, mut writer) = tokio::io::split(client);
+
+    let requests = tokio::spawn(async move {
+        for correlation_id in 0..REQUESTS {
+            let frame = api_versions_frame(correlation_id);
+            writer.write_all(&frame).await.unwrap();
+        }
+        writer
+    });
+
+    // Without anything reading responses, the server stops reading requests
+    // once the pipe and its queue are full, which leaves the client unable
+    // to write the rest.
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    let [connection] = connections.snapshot().try_into().unwrap();
+    assert!(
+        connection.request_count <= 20,
+        "read {} requests without any response being read",
+        connection.request_count
+    );
+    assert!(!requests.is_finished());
+
+    // Every request is answered, in order, once the client reads.
+    for correlation_id in 0..REQUESTS {
+        let len = reader.read_i32().await.unwrap();
+        let mut response = BytesMut::zeroed(len as usize);
+        reader.read_exact(&mut response).await.unwrap();
+
+        let header = ResponseHeader::decode(&mut response, 0).unwrap();
+        assert_eq!(header.correlation_id, correlation_id);
+    }
+
+    requests.await.unwrap();
+}
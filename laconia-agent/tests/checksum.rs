@@ -0,0 +1,30 @@
+//! Checks CRC32C checksums against known vectors.
+
+use laconia_agent::checksum::{self, Crc32c};
+
+/// Inputs and their CRC32C, as checked by Kafka's own `Crc32CTest`.
+const VECTORS: &[(&[u8], u32)] = &[
+    (b"", 0),
+    (b"123456789", 0xe306_9283),
+    (b"The quick brown fox jumps over the lazy dog", 0x2262_0404),
+];
+
+#[test]
+fn known_vectors() {
+    for &(data, crc) in VECTORS {
+        assert_eq!(checksum::compute(data), crc, "{:?}", data);
+    }
+}
+
+#[test]
+fn chunks_checksum_like_the_whole() {
+    for &(data, crc) in VECTORS {
+        for split in 0..=data.len() {
+            let (first, second) = data.split_at(split);
+            let mut checksum = Crc32c::new();
+            checksum.update(first);
+            checksum.update(second);
+            assert_eq!(checksum.finish(), crc, "{:?} split at {}", data, split);
+        }
+    }
+}
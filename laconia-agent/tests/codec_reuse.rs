@@ -0,0 +1,65 @@
+//! Checks a codec reusing its scratch buffer across responses encodes each
+//! exactly as a fresh codec would, with nothing left over from the last.
+
+use std::collections::BTreeMap;
+
+use bytes::BytesMut;
+use laconia_agent::{
+    KafkaMessageCodec, KafkaResponse, RequestHeader,
+    protocol::{
+        error::ErrorCode,
+        messages::{ApiVersionsApiKeys, ApiVersionsResponse},
+    },
+};
+use tokio_util::codec::Encoder;
+
+fn response(correlation_id: i32, api_keys: i16) -> KafkaResponse {
+    let header = RequestHeader {
+        api_key: 18,
+        version: 3,
+        correlation_id,
+        client_id: "laconia-tests".to_string(),
+        tagged_fields: BTreeMap::new(),
+    };
+    let response = ApiVersionsResponse {
+        error_code: ErrorCode::None,
+        api_keys: (0..api_keys)
+            .map(|api_key| ApiVersionsApiKeys {
+                api_key,
+                min_version: 0,
+                max_version: 4,
+                tagged_fields: Default::default(),
+            })
+            .collect(),
+        throttle_time_ms: 0,
+        supported_features: vec![],
+        finalized_features_epoch: -1,
+        finalized_features: vec![],
+        tagged_fields: Default::default(),
+    };
+    KafkaResponse::new(&header, 3, Box::new(response))
+}
+
+#[test]
+fn reused_codec_encodes_like_a_fresh_one() {
+    // Large responses first, so a scratch buffer that isn't cleared would
+    // leak their bytes into the smaller ones after them.
+    let sizes = [100, 1, 50, 0, 100];
+
+    let mut reused = KafkaMessageCodec::new();
+    let mut frames = BytesMut::new();
+    for (correlation_id, &api_keys) in sizes.iter().enumerate() {
+        reused
+            .encode(response(correlation_id as i32, api_keys), &mut frames)
+            .unwrap();
+    }
+
+    let mut expected = BytesMut::new();
+    for (correlation_id, &api_keys) in sizes.iter().enumerate() {
+        KafkaMessageCodec::new()
+            .encode(response(correlation_id as i32, api_keys), &mut expected)
+            .unwrap();
+    }
+
+    assert_eq!(frames, expected);
+}
@@ -0,0 +1,118 @@
+//! Helpers shared by the integration tests. Each test crate uses a different
+//! subset of them.
+#![allow(dead_code)]
+
+use std::path::PathBuf;
+
+use bytes::{BufMut, Bytes, BytesMut};
+use integer_encoding::VarIntWriter;
+use laconia_agent::{checksum, log::RecordBatch};
+use uuid::Uuid;
+
+/// The attributes bit set on batches written as part of a transaction.
+const TRANSACTIONAL_FLAG: i16 = 0x10;
+
+/// A directory under the system's temporary directory no other test uses.
+/// It isn't created, and is left for the test to clean up.
+pub fn temp_dir(prefix: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("{}-{}", prefix, Uuid::new_v4()))
+}
+
+/// A v2 header for an ApiVersions v3 request with `correlation_id`, then its
+/// body: client software name and version as empty compact strings, and no
+/// tagged fields.
+pub fn api_versions_request(correlation_id: i32) -> Bytes {
+    let mut frame = BytesMut::new();
+    frame.put_i16(18);
+    frame.put_i16(3);
+    frame.put_i32(correlation_id);
+    frame.put_i16(-1);
+    frame.put_u8(0);
+    frame.put_u8(1);
+    frame.put_u8(1);
+    frame.put_u8(0);
+    frame.freeze()
+}
+
+/// An uncompressed batch of records holding `values`, with no key and no
+/// producer, all timestamped `timestamp`.
+pub fn batch(values: &[&[u8]], timestamp: i64) -> RecordBatch {
+    encode_batch(0, -1, -1, timestamp, values)
+}
+
+/// A batch of records holding `values` written by `producer_id` as part of
+/// its open transaction.
+pub fn transactional_batch(producer_id: i64, producer_epoch: i16, values: &[&[u8]]) -> RecordBatch {
+    encode_batch(TRANSACTIONAL_FLAG, producer_id, producer_epoch, 0, values)
+}
+
+/// The values of the records in `batch`, which must be as built by these
+/// helpers.
+pub fn values(batch: &RecordBatch) -> Vec<Bytes> {
+    let records = Bytes::from(batch.records().unwrap());
+    let mut values = Vec::new();
+    let mut at = 0;
+    for _ in 0..batch.record_count() {
+        let (len, read) = varint(&records[at..]);
+        let record = records.slice(at + read..at + read + len as usize);
+        at += read + len as usize;
+
+        // Skip the attributes, timestamp delta, offset delta and null key.
+        let mut field = 1;
+        for _ in 0..3 {
+            field += varint(&record[field..]).1;
+        }
+        let (value_len, read) = varint(&record[field..]);
+        field += read;
+        values.push(record.slice(field..field + value_len as usize));
+    }
+    values
+}
+
+fn varint(data: &[u8]) -> (i64, usize) {
+    integer_encoding::VarInt::decode_var(data).expect("complete varint")
+}
+
+fn encode_batch(
+    attributes: i16,
+    producer_id: i64,
+    producer_epoch: i16,
+    timestamp: i64,
+    values: &[&[u8]],
+) -> RecordBatch {
+    let mut records = Vec::new();
+    for (offset_delta, value) in values.iter().enumerate() {
+        let mut record = Vec::new();
+        record.put_i8(0);
+        record.write_varint(0i64).unwrap();
+        record.write_varint(offset_delta as i32).unwrap();
+        record.write_varint(-1i32).unwrap();
+        record.write_varint(value.len() as i32).unwrap();
+        record.extend_from_slice(value);
+        record.write_varint(0i32).unwrap();
+
+        records.write_varint(record.len() as i32).unwrap();
+        records.extend_from_slice(&record);
+    }
+
+    let mut data = BytesMut::new();
+    data.put_i64(0);
+    data.put_i32((49 + records.len()) as i32);
+    data.put_i32(-1);
+    data.put_i8(2);
+    data.put_u32(0);
+    data.put_i16(attributes);
+    data.put_i32(values.len() as i32 - 1);
+    data.put_i64(timestamp);
+    data.put_i64(timestamp);
+    data.put_i64(producer_id);
+    data.put_i16(producer_epoch);
+    data.put_i32(if producer_id < 0 { -1 } else { 0 });
+    data.put_i32(values.len() as i32);
+    data.extend_from_slice(&records);
+
+    let crc = checksum::compute(&data[21..]);
+    data[17..21].copy_from_slice(&crc.to_be_bytes());
+
+    RecordBatch::new(data.freeze()).unwrap()
+}
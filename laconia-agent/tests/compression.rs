@@ -0,0 +1,38 @@
+//! Checks record batches round-trip through each compression codec.
+
+mod common;
+
+use laconia_agent::{compression::Compression, log::RecordBatch};
+
+const CODECS: [Compression; 4] = [
+    Compression::Gzip,
+    Compression::Snappy,
+    Compression::Lz4,
+    Compression::Zstd,
+];
+
+#[test]
+fn batches_round_trip_through_each_codec() {
+    let values: [&[u8]; 3] = [b"first", b"second", &[0x5a; 1000]];
+    let batch = common::batch(&values, 0);
+
+    for codec in CODECS {
+        let compressed = batch.recompress(codec).unwrap();
+        assert_eq!(compressed.compression().unwrap(), codec);
+        compressed.verify_crc().unwrap();
+
+        // Parsing the compressed bytes afresh, as when they arrive in a
+        // Produce request, decompresses to the same records.
+        let parsed = RecordBatch::new(compressed.as_bytes().clone()).unwrap();
+        assert_eq!(
+            parsed.records().unwrap(),
+            batch.records().unwrap(),
+            "{:?}",
+            codec
+        );
+        assert_eq!(common::values(&parsed), values, "{:?}", codec);
+
+        let decompressed = parsed.recompress(Compression::None).unwrap();
+        assert_eq!(decompressed.as_bytes(), batch.as_bytes(), "{:?}", codec);
+    }
+}
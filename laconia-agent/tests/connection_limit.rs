@@ -0,0 +1,68 @@
+//! Checks the connection limit holds back connections past it until one of
+//! the open ones closes.
+
+mod common;
+
+use std::{io, net::Ipv4Addr, time::Duration};
+
+use futures::{SinkExt, StreamExt};
+use laconia_agent::{
+    protocol::{api_keys::ApiKey, handlers::ApiVersionsHandler},
+    server::KafkaServerBuilder,
+};
+use tokio::net::TcpStream;
+use tokio_util::codec::{Framed, LengthDelimitedCodec};
+
+#[tokio::test]
+async fn connection_past_the_limit_waits_for_one_to_close() {
+    let server = KafkaServerBuilder::new()
+        .register(ApiKey::ApiVersions, ApiVersionsHandler)
+        .bind((Ipv4Addr::LOCALHOST, 0).into())
+        .max_connections(1)
+        .build()
+        .await
+        .unwrap();
+    let addr = server.local_addr().unwrap();
+    tokio::spawn(async move {
+        loop {
+            server.accept().await.unwrap();
+        }
+    });
+
+    let connect = || async {
+        let stream = TcpStream::connect(addr).await.unwrap();
+        Framed::new(stream, LengthDelimitedCodec::new())
+    };
+
+    let mut first = connect().await;
+    first.send(common::api_versions_request(1)).await.unwrap();
+    assert!(first.next().await.is_some());
+
+    // The second connection is let in by the OS, but not served.
+    let mut second = connect().await;
+    second.send(common::api_versions_request(2)).await.unwrap();
+    assert!(
+        tokio::time::timeout(Duration::from_millis(100), second.next())
+            .await
+            .is_err(),
+        "the second connection is served while the first is open"
+    );
+
+    // Hanging up the first connection frees its slot.
+    drop(first);
+    tokio::time::timeout(Duration::from_secs(5), second.next())
+        .await
+        .expect("the second connection is served once the first closes")
+        .unwrap()
+        .unwrap();
+}
+
+#[tokio::test]
+async fn zero_max_connections_is_rejected() {
+    let result = KafkaServerBuilder::new()
+        .register(ApiKey::ApiVersions, ApiVersionsHandler)
+        .max_connections(0)
+        .build()
+        .await;
+    assert_eq!(result.err().unwrap().kind(), io::ErrorKind::InvalidInput);
+}
@@ -0,0 +1,54 @@
+//! Checks what a client tells the broker in ApiVersions stays on its
+//! connection for the handlers of later requests.
+
+use std::{collections::BTreeMap, sync::Arc};
+
+use laconia_agent::{
+    ConnectionState, RequestHeader,
+    protocol::{
+        api_keys::ApiKey,
+        handlers::{ApiVersionsHandler, RequestHandler},
+        messages::ApiVersionsRequest,
+        registry::{API_VERSIONS_KEY, MessageRegistry},
+    },
+};
+
+fn state() -> ConnectionState {
+    let mut registry = MessageRegistry::new();
+    registry
+        .register(ApiKey::ApiVersions, ApiVersionsHandler)
+        .unwrap();
+    ConnectionState::new(Arc::new(registry))
+}
+
+async fn api_versions(state: &mut ConnectionState, version: i16) {
+    let header = RequestHeader {
+        api_key: API_VERSIONS_KEY,
+        version,
+        correlation_id: 0,
+        client_id: "laconia-tests".to_string(),
+        tagged_fields: BTreeMap::new(),
+    };
+    let request = ApiVersionsRequest {
+        client_software_name: "laconia-tests".to_string(),
+        client_software_version: "0.1.0".to_string(),
+        tagged_fields: BTreeMap::new(),
+    };
+    ApiVersionsHandler
+        .handle(&header, &request, state)
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn client_software_from_api_versions_v3_is_kept() {
+    let mut state = state();
+    assert_eq!(state.client_software_name(), None);
+    assert_eq!(state.max_version(API_VERSIONS_KEY), None);
+
+    api_versions(&mut state, 3).await;
+
+    assert_eq!(state.client_software_name(), Some("laconia-tests"));
+    assert_eq!(state.client_software_version(), Some("0.1.0"));
+    assert_eq!(state.max_version(API_VERSIONS_KEY), Some(4));
+}
@@ -0,0 +1,53 @@
+//! Checks the server's connection registry follows connections opening and
+//! closing. Only built with the `test-util` feature, which exposes the test
+//! client.
+
+use std::time::Duration;
+
+use laconia_agent::{
+    authorizer::ANONYMOUS_PRINCIPAL,
+    protocol::{api_keys::ApiKey, handlers::ApiVersionsHandler, messages::ApiVersionsRequest},
+    server::KafkaServerBuilder,
+    test_client::TestClient,
+};
+
+#[tokio::test]
+async fn connections_are_registered_while_open() {
+    let server = KafkaServerBuilder::new()
+        .register(ApiKey::ApiVersions, ApiVersionsHandler)
+        .build()
+        .await
+        .unwrap();
+    let connections = server.connections().clone();
+    assert!(connections.is_empty());
+
+    let (client, stream) = tokio::io::duplex(4096);
+    server.serve(stream).await.unwrap();
+    let mut client = TestClient::new(client);
+
+    let request = ApiVersionsRequest {
+        client_software_name: "laconia-tests".to_string(),
+        client_software_version: "0.1.0".to_string(),
+        tagged_fields: Default::default(),
+    };
+    client.send(ApiKey::ApiVersions, 3, &request).await.unwrap();
+
+    let [connection] = connections.snapshot().try_into().unwrap();
+    assert_eq!(connection.peer, "in-process");
+    assert_eq!(
+        connection.client_software_name.as_deref(),
+        Some("laconia-tests")
+    );
+    assert_eq!(connection.principal, ANONYMOUS_PRINCIPAL);
+    assert_eq!(connection.request_count, 1);
+
+    // The connection is removed once its task sees the client hang up.
+    drop(client);
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while !connections.is_empty() {
+            tokio::task::yield_now().await;
+        }
+    })
+    .await
+    .expect("the connection is removed from the registry");
+}
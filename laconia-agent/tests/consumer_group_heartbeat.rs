@@ -0,0 +1,191 @@
+//! Drives ConsumerGroupHeartbeat through the in-process test client, and
+//! checks partitions only move between members once they've been released,
+//! and members that stop heartbeating are fenced. Only built with the
+//! `test-util` feature, which exposes the client and test clock.
+
+use std::{sync::Arc, time::Duration};
+
+use laconia_agent::{
+    clock::TestClock,
+    configs::ConfigStore,
+    groups::{GroupCoordinator, Heartbeat, LEAVE_GROUP_MEMBER_EPOCH},
+    log::LogStore,
+    protocol::{
+        api_keys::ApiKey,
+        error::ErrorCode,
+        handlers::ConsumerGroupHeartbeatHandler,
+        messages::{ConsumerGroupHeartbeatRequest, ConsumerGroupHeartbeatTopicPartitions},
+    },
+    server::KafkaServerBuilder,
+    test_client::TestClient,
+};
+
+fn logs(partitions: i32) -> Arc<LogStore> {
+    let configs = Arc::new(ConfigStore::new(0, vec![]));
+    let logs = Arc::new(LogStore::open(None, false, configs).unwrap());
+    for partition in 0..partitions {
+        logs.get_or_create("events", partition).unwrap();
+    }
+    logs
+}
+
+fn heartbeat_request(member_id: &str, member_epoch: i32) -> ConsumerGroupHeartbeatRequest {
+    ConsumerGroupHeartbeatRequest {
+        group_id: "readers".to_string(),
+        member_id: member_id.to_string(),
+        member_epoch,
+        instance_id: String::new(),
+        rack_id: String::new(),
+        rebalance_timeout_ms: 30_000,
+        subscribed_topic_names: (member_epoch == 0).then(|| vec!["events".to_string()]),
+        server_assignor: String::new(),
+        topic_partitions: None,
+        tagged_fields: Default::default(),
+    }
+}
+
+#[tokio::test]
+async fn single_member_is_assigned_every_partition() {
+    let logs = logs(3);
+    let groups = Arc::new(GroupCoordinator::new(logs.clone()));
+
+    let server = KafkaServerBuilder::new()
+        .register(
+            ApiKey::ConsumerGroupHeartbeat,
+            ConsumerGroupHeartbeatHandler::new(groups, logs.clone()),
+        )
+        .build()
+        .await
+        .unwrap();
+
+    let (client, stream) = tokio::io::duplex(4096);
+    server.serve(stream).await.unwrap();
+    let mut client = TestClient::new(client);
+
+    let joined = client
+        .send(ApiKey::ConsumerGroupHeartbeat, 0, &heartbeat_request("", 0))
+        .await
+        .unwrap();
+
+    assert_eq!(joined.error_code, ErrorCode::None);
+    assert!(!joined.member_id.is_empty());
+    assert_eq!(joined.member_epoch, 1);
+    assert!(joined.heartbeat_interval_ms > 0);
+    let assignment = joined.assignment.expect("joining member is assigned");
+    assert_eq!(
+        assignment.topic_partitions,
+        [ConsumerGroupHeartbeatTopicPartitions {
+            topic_id: logs.topic_id("events").unwrap(),
+            partitions: vec![0, 1, 2],
+            tagged_fields: Default::default(),
+        }]
+    );
+
+    // Nothing has changed, so the next heartbeat carries no assignment.
+    let request = heartbeat_request(&joined.member_id, joined.member_epoch);
+    let response = client
+        .send(ApiKey::ConsumerGroupHeartbeat, 0, &request)
+        .await
+        .unwrap();
+    assert_eq!(response.error_code, ErrorCode::None);
+    assert_eq!(response.member_epoch, 1);
+    assert!(response.assignment.is_none());
+
+    // A stale epoch is fenced.
+    let request = heartbeat_request(&joined.member_id, 7);
+    let response = client
+        .send(ApiKey::ConsumerGroupHeartbeat, 0, &request)
+        .await
+        .unwrap();
+    assert_eq!(response.error_code, ErrorCode::FencedMemberEpoch);
+}
+
+#[test]
+fn partitions_move_once_released() {
+    let groups = GroupCoordinator::new(logs(4));
+    let topics = ["events".to_string()];
+
+    let first = groups.heartbeat("readers", "a", 0, Some(&topics)).unwrap();
+    let owned = |heartbeat: &Heartbeat| {
+        heartbeat.assignment.as_ref().unwrap()["events"]
+            .iter()
+            .copied()
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(owned(&first), [0, 1, 2, 3]);
+
+    // The second member's half of the partitions is still owned by the
+    // first, so it starts with nothing.
+    let second = groups.heartbeat("readers", "b", 0, Some(&topics)).unwrap();
+    assert_eq!(second.member_epoch, 2);
+    assert!(second.assignment.unwrap().is_empty());
+
+    // The first member is told to give up its second half...
+    let first = groups
+        .heartbeat("readers", "a", first.member_epoch, None)
+        .unwrap();
+    assert_eq!(first.member_epoch, 2);
+    assert_eq!(owned(&first), [0, 1]);
+
+    // ...after which the second member picks it up.
+    let second = groups
+        .heartbeat("readers", "b", second.member_epoch, None)
+        .unwrap();
+    assert_eq!(owned(&second), [2, 3]);
+
+    // Once the first member leaves, the second takes everything.
+    groups
+        .heartbeat("readers", "a", LEAVE_GROUP_MEMBER_EPOCH, None)
+        .unwrap();
+    let second = groups
+        .heartbeat("readers", "b", second.member_epoch, None)
+        .unwrap();
+    assert_eq!(second.member_epoch, 3);
+    assert_eq!(owned(&second), [0, 1, 2, 3]);
+}
+
+#[test]
+fn members_past_the_session_timeout_are_fenced() {
+    let clock = Arc::new(TestClock::new());
+    let groups = GroupCoordinator::new(logs(4))
+        .with_session_timeout(Duration::from_secs(10))
+        .with_clock(clock.clone());
+    let topics = ["events".to_string()];
+
+    let first = groups.heartbeat("readers", "a", 0, Some(&topics)).unwrap();
+    let second = groups.heartbeat("readers", "b", 0, Some(&topics)).unwrap();
+    let first = groups
+        .heartbeat("readers", "a", first.member_epoch, None)
+        .unwrap();
+    let second = groups
+        .heartbeat("readers", "b", second.member_epoch, None)
+        .unwrap();
+    assert_eq!(first.member_epoch, 2);
+
+    // Only the second member keeps heartbeating.
+    clock.advance(Duration::from_secs(6));
+    let second = groups
+        .heartbeat("readers", "b", second.member_epoch, None)
+        .unwrap();
+    assert_eq!(second.member_epoch, 2);
+
+    // Once the first member's session has expired, the second takes over
+    // its partitions.
+    clock.advance(Duration::from_secs(6));
+    let second = groups
+        .heartbeat("readers", "b", second.member_epoch, None)
+        .unwrap();
+    assert_eq!(second.member_epoch, 3);
+    assert_eq!(
+        second.assignment.unwrap()["events"]
+            .iter()
+            .copied()
+            .collect::<Vec<_>>(),
+        [0, 1, 2, 3]
+    );
+
+    assert_eq!(
+        groups.heartbeat("readers", "a", first.member_epoch, None),
+        Err(ErrorCode::UnknownMemberId)
+    );
+}
@@ -0,0 +1,68 @@
+//! Checks ControlledShutdown marks the broker as shutting down.
+
+use std::{
+    collections::BTreeMap,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+};
+
+use laconia_agent::{
+    ConnectionState, RequestHeader,
+    cluster::{BrokerInfo, ClusterInfo},
+    protocol::{
+        error::ErrorCode,
+        handlers::{ControlledShutdownHandler, RequestHandler},
+        messages::ControlledShutdownRequest,
+        registry::MessageRegistry,
+    },
+};
+
+fn request(broker_id: i32) -> ControlledShutdownRequest {
+    ControlledShutdownRequest {
+        broker_id,
+        broker_epoch: -1,
+        tagged_fields: BTreeMap::new(),
+    }
+}
+
+#[tokio::test]
+async fn shutdown_succeeds_and_sets_the_flag() {
+    let cluster = Arc::new(ClusterInfo::single_broker(
+        "laconia",
+        BrokerInfo {
+            node_id: 0,
+            host: "localhost".to_string(),
+            port: 9092,
+            rack: String::new(),
+        },
+    ));
+    let shutting_down = Arc::new(AtomicBool::new(false));
+    let handler = ControlledShutdownHandler::new(cluster, shutting_down.clone());
+
+    let header = RequestHeader {
+        api_key: 7,
+        version: 3,
+        correlation_id: 0,
+        client_id: "laconia-tests".to_string(),
+        tagged_fields: BTreeMap::new(),
+    };
+    let mut state = ConnectionState::new(Arc::new(MessageRegistry::new()));
+
+    // Another broker's shutdown isn't this one's to act on.
+    let response = handler
+        .handle(&header, &request(1), &mut state)
+        .await
+        .unwrap();
+    assert_eq!(response.error_code, ErrorCode::BrokerNotAvailable);
+    assert!(!shutting_down.load(Ordering::SeqCst));
+
+    let response = handler
+        .handle(&header, &request(0), &mut state)
+        .await
+        .unwrap();
+    assert_eq!(response.error_code, ErrorCode::None);
+    assert!(response.remaining_partitions.is_empty());
+    assert!(shutting_down.load(Ordering::SeqCst));
+}
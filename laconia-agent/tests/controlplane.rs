@@ -0,0 +1,254 @@
+//! Drives the agent's control plane client against a mock liveness server.
+
+use std::{
+    fs, future,
+    net::{Ipv4Addr, SocketAddr},
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, AtomicU32, Ordering},
+    },
+    time::Duration,
+};
+
+use laconia_agent::controlplane;
+use laconia_liveness::liveness::{
+    CheckinReply, CheckinRequest, DeregisterReply, DeregisterRequest, PingReply, PingRequest,
+    liveness_server::{Liveness, LivenessServer},
+};
+use tokio::{
+    net::TcpListener,
+    sync::{Notify, oneshot},
+    time::{self, Instant},
+};
+use tonic::{
+    Request, Response, Status,
+    transport::{Server, server::TcpIncoming},
+};
+use uuid::Uuid;
+
+/// A control plane that's unavailable for its first few checkins. Clones
+/// share their counters, so a test can keep one while the server runs
+/// another.
+#[derive(Clone, Default)]
+struct MockControlPlane {
+    unavailable_checkins: Arc<AtomicU32>,
+    checkins: Arc<AtomicU32>,
+    checked_in: Arc<Notify>,
+    interval_ms: i32,
+    deregistered: Arc<Mutex<Vec<String>>>,
+}
+
+#[tonic::async_trait]
+impl Liveness for MockControlPlane {
+    async fn checkin(
+        &self,
+        _request: Request<CheckinRequest>,
+    ) -> Result<Response<CheckinReply>, Status> {
+        self.checkins.fetch_add(1, Ordering::Relaxed);
+        self.checked_in.notify_one();
+
+        let unavailable = self
+            .unavailable_checkins
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |left| {
+                left.checked_sub(1)
+            })
+            .is_ok();
+        if unavailable {
+            return Err(Status::unavailable("starting up"));
+        }
+
+        Ok(Response::new(CheckinReply {
+            interval: self.interval_ms,
+        }))
+    }
+
+    async fn ping(&self, _request: Request<PingRequest>) -> Result<Response<PingReply>, Status> {
+        Ok(Response::new(PingReply {}))
+    }
+
+    async fn deregister(
+        &self,
+        request: Request<DeregisterRequest>,
+    ) -> Result<Response<DeregisterReply>, Status> {
+        let id = request.into_inner().id;
+        self.deregistered.lock().unwrap().push(id);
+        Ok(Response::new(DeregisterReply {}))
+    }
+}
+
+/// Serves `control_plane` on an ephemeral port, returning its endpoint.
+async fn serve(control_plane: MockControlPlane) -> String {
+    let listener = TcpListener::bind(SocketAddr::from((Ipv4Addr::LOCALHOST, 0)))
+        .await
+        .unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    tokio::spawn(
+        Server::builder()
+            .add_service(LivenessServer::new(control_plane))
+            .serve_with_incoming(TcpIncoming::from(listener)),
+    );
+
+    format!("http://{}", addr)
+}
+
+#[tokio::test]
+async fn connect_retries_until_the_control_plane_is_up() {
+    let control_plane = MockControlPlane {
+        unavailable_checkins: Arc::new(AtomicU32::new(3)),
+        interval_ms: 5_000,
+        ..Default::default()
+    };
+    let endpoint = serve(control_plane.clone()).await;
+
+    let (_client, reply) = controlplane::connect(endpoint, "agent", Duration::from_secs(30))
+        .await
+        .unwrap();
+    assert_eq!(reply.interval, 5_000);
+    assert_eq!(control_plane.checkins.load(Ordering::Relaxed), 4);
+}
+
+#[tokio::test]
+async fn connect_gives_up_after_the_retry_window() {
+    let control_plane = MockControlPlane {
+        unavailable_checkins: Arc::new(AtomicU32::new(u32::MAX)),
+        ..Default::default()
+    };
+    let endpoint = serve(control_plane.clone()).await;
+
+    let result = controlplane::connect(endpoint, "agent", Duration::from_millis(500)).await;
+    assert!(result.is_err());
+    assert!(control_plane.checkins.load(Ordering::Relaxed) > 1);
+}
+
+#[test]
+fn checkin_interval_is_clamped() {
+    let previous = Duration::from_secs(3);
+    let interval = |ms| controlplane::checkin_interval(&CheckinReply { interval: ms }, previous);
+
+    assert_eq!(interval(5_000), Duration::from_secs(5));
+    assert_eq!(interval(10), controlplane::MIN_CHECKIN_INTERVAL);
+    assert_eq!(interval(0), previous);
+    assert_eq!(interval(-1), previous);
+}
+
+#[tokio::test]
+async fn zero_interval_keeps_checking_in_at_the_previous_interval() {
+    // The mock asks for a zero interval, which would be a tight loop if it
+    // were followed.
+    let control_plane = MockControlPlane::default();
+    let endpoint = serve(control_plane.clone()).await;
+
+    let (client, _) = controlplane::connect(endpoint, "agent", Duration::from_secs(30))
+        .await
+        .unwrap();
+    control_plane.checked_in.notified().await;
+
+    // Time only advances while the runtime is idle, so the checkins are
+    // timed by the interval the task sleeps for.
+    time::pause();
+    let healthy = Arc::new(AtomicBool::new(true));
+    let checkins = controlplane::spawn_checkins(
+        client,
+        "agent".to_string(),
+        controlplane::MIN_CHECKIN_INTERVAL,
+        healthy.clone(),
+    );
+
+    control_plane.checked_in.notified().await;
+    let start = Instant::now();
+    control_plane.checked_in.notified().await;
+    assert!(start.elapsed() >= controlplane::MIN_CHECKIN_INTERVAL);
+    checkins.abort();
+
+    assert!(healthy.load(Ordering::Relaxed));
+}
+
+#[tokio::test]
+async fn deregister_tells_the_control_plane() {
+    let control_plane = MockControlPlane::default();
+    let endpoint = serve(control_plane.clone()).await;
+
+    let (client, _) = controlplane::connect(endpoint, "agent", Duration::from_secs(30))
+        .await
+        .unwrap();
+    controlplane::deregister(client, "agent".to_string())
+        .await
+        .unwrap();
+
+    assert_eq!(*control_plane.deregistered.lock().unwrap(), ["agent"]);
+}
+
+#[tokio::test]
+async fn shutdown_stops_the_checkins_and_deregisters() {
+    let control_plane = MockControlPlane::default();
+    let endpoint = serve(control_plane.clone()).await;
+
+    let (client, _) = controlplane::connect(endpoint, "agent", Duration::from_secs(30))
+        .await
+        .unwrap();
+    let checkins = controlplane::spawn_checkins(
+        client.clone(),
+        "agent".to_string(),
+        controlplane::MIN_CHECKIN_INTERVAL,
+        Arc::new(AtomicBool::new(true)),
+    );
+
+    let (shutdown, signal) = oneshot::channel();
+    shutdown.send(()).unwrap();
+    controlplane::run_until_shutdown(
+        future::pending(),
+        async {
+            let _ = signal.await;
+        },
+        checkins,
+        client,
+        "agent".to_string(),
+    )
+    .await;
+    assert_eq!(*control_plane.deregistered.lock().unwrap(), ["agent"]);
+
+    // No checkins once the task is stopped, however long the agent lingers.
+    let count = control_plane.checkins.load(Ordering::Relaxed);
+    time::pause();
+    time::sleep(controlplane::MIN_CHECKIN_INTERVAL * 5).await;
+    assert_eq!(control_plane.checkins.load(Ordering::Relaxed), count);
+}
+
+#[tokio::test]
+async fn deregisters_when_serving_stops() {
+    let control_plane = MockControlPlane::default();
+    let endpoint = serve(control_plane.clone()).await;
+
+    let (client, _) = controlplane::connect(endpoint, "agent", Duration::from_secs(30))
+        .await
+        .unwrap();
+    let checkins = controlplane::spawn_checkins(
+        client.clone(),
+        "agent".to_string(),
+        controlplane::MIN_CHECKIN_INTERVAL,
+        Arc::new(AtomicBool::new(true)),
+    );
+
+    controlplane::run_until_shutdown(
+        async {},
+        future::pending(),
+        checkins,
+        client,
+        "agent".to_string(),
+    )
+    .await;
+    assert_eq!(*control_plane.deregistered.lock().unwrap(), ["agent"]);
+}
+
+#[test]
+fn agent_id_is_kept_across_runs() {
+    let path = std::env::temp_dir().join(format!("laconia-agent-id-{}", Uuid::new_v4()));
+
+    let first = controlplane::load_or_create_agent_id(&path).unwrap();
+    let second = controlplane::load_or_create_agent_id(&path).unwrap();
+    assert_eq!(first, second);
+    assert_eq!(fs::read_to_string(&path).unwrap(), first.to_string());
+
+    fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,48 @@
+//! Checks appended batches with a mismatched CRC are rejected only when CRC
+//! validation is on.
+
+mod common;
+
+use std::sync::Arc;
+
+use bytes::BytesMut;
+use laconia_agent::{
+    configs::ConfigStore,
+    log::{LogStore, RecordBatch},
+    protocol::error::ErrorCode,
+};
+
+fn store(validate_crc: bool) -> LogStore {
+    let configs = Arc::new(ConfigStore::new(0, vec![]));
+    LogStore::open(None, validate_crc, configs).unwrap()
+}
+
+/// A batch whose last record byte was changed after its CRC was computed.
+fn tampered() -> RecordBatch {
+    let mut data = BytesMut::from(&common::batch(&[b"value"], 0).as_bytes()[..]);
+    let last = data.len() - 2;
+    data[last] ^= 0xff;
+    RecordBatch::new(data.freeze()).unwrap()
+}
+
+#[test]
+fn good_batch_is_appended_either_way() {
+    for validate_crc in [true, false] {
+        let logs = store(validate_crc);
+        let offset = logs.append("events", 0, common::batch(&[b"value"], 0));
+        assert_eq!(offset.unwrap(), 0, "validate_crc: {}", validate_crc);
+    }
+}
+
+#[test]
+fn tampered_batch_is_rejected_when_validating() {
+    let logs = store(true);
+    let err = logs.append("events", 0, tampered()).unwrap_err();
+    assert_eq!(err.error_code(), ErrorCode::CorruptMessage);
+}
+
+#[test]
+fn tampered_batch_is_appended_when_not_validating() {
+    let logs = store(false);
+    assert_eq!(logs.append("events", 0, tampered()).unwrap(), 0);
+}
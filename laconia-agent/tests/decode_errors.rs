@@ -0,0 +1,58 @@
+//! Checks a request whose body can't be decoded is answered with an error
+//! carrying its correlation id.
+
+use std::sync::Arc;
+
+use bytes::{Buf, BufMut, BytesMut};
+use laconia_agent::{
+    ConnectionState, KafkaRequest,
+    cluster::{BrokerInfo, ClusterInfo},
+    protocol::{
+        api_keys::ApiKey, error::ErrorCode, handlers::FindCoordinatorHandler,
+        registry::MessageRegistry,
+    },
+};
+
+#[tokio::test]
+async fn undecodable_body_is_answered_with_an_error() {
+    let cluster = Arc::new(ClusterInfo::single_broker(
+        "laconia",
+        BrokerInfo {
+            node_id: 0,
+            host: "localhost".to_string(),
+            port: 9092,
+            rack: String::new(),
+        },
+    ));
+    let mut registry = MessageRegistry::new();
+    registry
+        .register(
+            ApiKey::FindCoordinator,
+            FindCoordinatorHandler::new(cluster),
+        )
+        .unwrap();
+    let registry = Arc::new(registry);
+    let mut state = ConnectionState::new(registry.clone());
+
+    // A FindCoordinator v1 request whose key claims 5 bytes when only one
+    // follows.
+    let mut frame = BytesMut::new();
+    frame.put_i16(10);
+    frame.put_i16(1);
+    frame.put_i32(9);
+    frame.put_i16(-1);
+    frame.extend_from_slice(&[0, 5, b'g']);
+
+    let request = KafkaRequest::decode_and_handle(&mut frame, &registry, &mut state)
+        .await
+        .unwrap();
+    assert_eq!(request.header.correlation_id, 9);
+
+    let mut body = BytesMut::new();
+    request
+        .response
+        .encode_any(&mut body, request.response_version)
+        .unwrap();
+    assert_eq!(body.get_i32(), 0, "throttle time");
+    assert_eq!(body.get_i16(), ErrorCode::InvalidRequest.as_i16());
+}
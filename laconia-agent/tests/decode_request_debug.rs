@@ -0,0 +1,46 @@
+//! Checks captured request frames can be decoded into a readable description.
+
+use std::sync::Arc;
+
+use bytes::BytesMut;
+use laconia_agent::{
+    cluster::{BrokerInfo, ClusterInfo},
+    configs::ConfigStore,
+    log::LogStore,
+    protocol::{api_keys::ApiKey, handlers::MetadataHandler, registry::MessageRegistry},
+};
+
+/// A Metadata v12 request for the topic `test`, as sent by librdkafka.
+const METADATA_V12: &[u8] = include_bytes!("../fuzz/corpus/decode_request/metadata_v12");
+
+#[test]
+fn captured_metadata_request_is_described() {
+    let configs = Arc::new(ConfigStore::new(0, vec![]));
+    let logs = Arc::new(LogStore::open(None, false, configs.clone()).unwrap());
+    let cluster = Arc::new(ClusterInfo::single_broker(
+        "laconia",
+        BrokerInfo {
+            node_id: 0,
+            host: "localhost".to_string(),
+            port: 9092,
+            rack: String::new(),
+        },
+    ));
+
+    let mut registry = MessageRegistry::new();
+    registry
+        .register(
+            ApiKey::Metadata,
+            MetadataHandler::new(logs, cluster, configs),
+        )
+        .unwrap();
+
+    let mut frame = BytesMut::from(METADATA_V12);
+    let description = registry.decode_request_debug(&mut frame).unwrap();
+
+    assert!(description.starts_with("api key 3 version 12\n"));
+    assert!(description.contains("client_id: \"rdkafka\""));
+    assert!(description.contains("MetadataRequest"));
+    assert!(description.contains("name: \"test\""));
+    assert!(description.contains("allow_auto_topic_creation: true"));
+}
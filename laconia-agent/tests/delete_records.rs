@@ -0,0 +1,77 @@
+//! Checks DeleteRecords moves the start of a partition's log up to the
+//! requested offset, and refuses offsets past its end.
+
+mod common;
+
+use std::{collections::BTreeMap, sync::Arc};
+
+use laconia_agent::{
+    ConnectionState, RequestHeader,
+    configs::ConfigStore,
+    log::LogStore,
+    protocol::{
+        error::ErrorCode,
+        handlers::{DeleteRecordsHandler, RequestHandler},
+        messages::{
+            DeleteRecordsPartition, DeleteRecordsPartitionResult, DeleteRecordsRequest,
+            DeleteRecordsTopic,
+        },
+        registry::MessageRegistry,
+    },
+};
+
+/// Deletes the records of partition 0 of `events` before `offset`.
+async fn delete_records(
+    handler: &DeleteRecordsHandler,
+    offset: i64,
+) -> DeleteRecordsPartitionResult {
+    let header = RequestHeader {
+        api_key: 21,
+        version: 2,
+        correlation_id: 0,
+        client_id: "laconia-tests".to_string(),
+        tagged_fields: BTreeMap::new(),
+    };
+    let request = DeleteRecordsRequest {
+        topics: vec![DeleteRecordsTopic {
+            name: "events".to_string(),
+            partitions: vec![DeleteRecordsPartition {
+                partition_index: 0,
+                offset,
+                tagged_fields: BTreeMap::new(),
+            }],
+            tagged_fields: BTreeMap::new(),
+        }],
+        timeout_ms: 1000,
+        tagged_fields: BTreeMap::new(),
+    };
+
+    let mut state = ConnectionState::new(Arc::new(MessageRegistry::new()));
+    let mut response = handler.handle(&header, &request, &mut state).await.unwrap();
+    response.topics.remove(0).partitions.remove(0)
+}
+
+#[tokio::test]
+async fn deleting_up_to_offset_5_moves_the_earliest_offset() {
+    let configs = Arc::new(ConfigStore::new(0, vec![]));
+    let logs = Arc::new(LogStore::open(None, false, configs).unwrap());
+    for value in 0..10u8 {
+        logs.append("events", 0, common::batch(&[&[value]], 0))
+            .unwrap();
+    }
+    let handler = DeleteRecordsHandler::new(logs.clone());
+
+    let result = delete_records(&handler, 5).await;
+    assert_eq!(result.error_code, ErrorCode::None);
+    assert_eq!(result.low_watermark, 5);
+
+    let log = logs.partition("events", 0).unwrap();
+    assert_eq!(log.earliest_offset(), 5);
+    assert!(log.read(0, 1024 * 1024).is_err());
+    let batches = log.read(5, 1024 * 1024).unwrap();
+    assert_eq!(common::values(&batches[0]), vec![&[5u8][..]]);
+
+    let result = delete_records(&handler, 11).await;
+    assert_eq!(result.error_code, ErrorCode::OffsetOutOfRange);
+    assert_eq!(log.earliest_offset(), 5);
+}
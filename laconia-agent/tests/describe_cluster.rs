@@ -0,0 +1,68 @@
+//! Checks a single broker describes itself the way admin clients expect
+//! DescribeCluster responses to be laid out.
+
+use std::{collections::BTreeMap, sync::Arc};
+
+use bytes::BytesMut;
+use laconia_agent::{
+    ConnectionState, RequestHeader,
+    cluster::{BrokerInfo, ClusterInfo},
+    protocol::{
+        EncoderVersioned,
+        handlers::{DescribeClusterHandler, RequestHandler},
+        messages::DescribeClusterRequest,
+        registry::MessageRegistry,
+    },
+};
+
+#[tokio::test]
+async fn single_broker_response_is_laid_out_for_admin_clients() {
+    let cluster = Arc::new(ClusterInfo::single_broker(
+        "laconia",
+        BrokerInfo {
+            node_id: 0,
+            host: "localhost".to_string(),
+            port: 9092,
+            rack: String::new(),
+        },
+    ));
+    let handler = DescribeClusterHandler::new(cluster);
+
+    let header = RequestHeader {
+        api_key: 60,
+        version: 1,
+        correlation_id: 0,
+        client_id: "laconia-tests".to_string(),
+        tagged_fields: BTreeMap::new(),
+    };
+    let request = DescribeClusterRequest {
+        include_cluster_authorized_operations: false,
+        endpoint_type: 1,
+        include_fenced_brokers: false,
+        tagged_fields: BTreeMap::new(),
+    };
+    let mut state = ConnectionState::new(Arc::new(MessageRegistry::new()));
+    let response = handler.handle(&header, &request, &mut state).await.unwrap();
+
+    let mut buf = BytesMut::new();
+    response.encode(&mut buf, 1).unwrap();
+
+    #[rustfmt::skip]
+    let expected: &[u8] = &[
+        0, 0, 0, 0, // throttle_time_ms
+        0, 0, // error_code
+        0, // error_message: null
+        1, // endpoint_type: brokers
+        8, b'l', b'a', b'c', b'o', b'n', b'i', b'a', // cluster_id
+        0, 0, 0, 0, // controller_id
+        2, // brokers length + 1
+        0, 0, 0, 0, // broker_id
+        10, b'l', b'o', b'c', b'a', b'l', b'h', b'o', b's', b't', // host
+        0, 0, 0x23, 0x84, // port
+        0, // rack: null
+        0, // tagged_fields
+        0x80, 0, 0, 0, // cluster_authorized_operations: not requested
+        0, // tagged_fields
+    ];
+    assert_eq!(&buf[..], expected);
+}
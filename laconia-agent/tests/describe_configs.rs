@@ -0,0 +1,99 @@
+//! Checks DescribeConfigs describes just the keys asked for, and rejects
+//! topics that don't exist.
+
+use std::{collections::BTreeMap, sync::Arc};
+
+use laconia_agent::{
+    ConnectionState, RequestHeader,
+    configs::{ConfigEntry, ConfigStore, ConfigType, ResourceType},
+    log::LogStore,
+    protocol::{
+        error::ErrorCode,
+        handlers::{DescribeConfigsHandler, RequestHandler},
+        messages::{DescribeConfigsRequest, DescribeConfigsResource, DescribeConfigsResponse},
+        registry::MessageRegistry,
+    },
+};
+
+async fn describe(
+    resource_type: ResourceType,
+    resource_name: &str,
+    configuration_keys: Option<Vec<String>>,
+) -> DescribeConfigsResponse {
+    let configs = Arc::new(ConfigStore::new(
+        0,
+        vec![
+            ConfigEntry::static_broker(
+                "connections.max.idle.ms",
+                600_000,
+                ConfigType::Long,
+                "Idle connections timeout.",
+            ),
+            ConfigEntry::static_broker(
+                "max.connections",
+                1024,
+                ConfigType::Int,
+                "The maximum number of connections allowed at any time.",
+            ),
+        ],
+    ));
+    let logs = Arc::new(LogStore::open(None, false, configs.clone()).unwrap());
+    logs.get_or_create("events", 0).unwrap();
+    let handler = DescribeConfigsHandler::new(configs, logs);
+
+    let header = RequestHeader {
+        api_key: 32,
+        version: 4,
+        correlation_id: 0,
+        client_id: "laconia-tests".to_string(),
+        tagged_fields: BTreeMap::new(),
+    };
+    let request = DescribeConfigsRequest {
+        resources: vec![DescribeConfigsResource {
+            resource_type: resource_type as i8,
+            resource_name: resource_name.to_string(),
+            configuration_keys,
+            tagged_fields: BTreeMap::new(),
+        }],
+        include_synonyms: false,
+        include_documentation: false,
+        tagged_fields: BTreeMap::new(),
+    };
+    let mut state = ConnectionState::new(Arc::new(MessageRegistry::new()));
+
+    handler.handle(&header, &request, &mut state).await.unwrap()
+}
+
+#[tokio::test]
+async fn single_broker_config_key_is_described() {
+    let response = describe(
+        ResourceType::Broker,
+        "0",
+        Some(vec!["max.connections".to_string()]),
+    )
+    .await;
+
+    let [result] = &response.results[..] else {
+        panic!("expected one result, got {}", response.results.len());
+    };
+    assert_eq!(result.error_code, ErrorCode::None);
+    let names: Vec<_> = result.configs.iter().map(|config| &config.name).collect();
+    assert_eq!(names, ["max.connections"]);
+    assert_eq!(result.configs[0].value, "1024");
+}
+
+#[tokio::test]
+async fn unknown_topic_is_rejected() {
+    let response = describe(ResourceType::Topic, "missing", None).await;
+
+    let [result] = &response.results[..] else {
+        panic!("expected one result, got {}", response.results.len());
+    };
+    assert_eq!(result.error_code, ErrorCode::UnknownTopicOrPartition);
+    assert!(result.configs.is_empty());
+
+    // Topics that exist are described.
+    let response = describe(ResourceType::Topic, "events", None).await;
+    assert_eq!(response.results[0].error_code, ErrorCode::None);
+    assert!(!response.results[0].configs.is_empty());
+}
@@ -0,0 +1,94 @@
+//! Drives DescribeProducers through the in-process test client. Only built
+//! with the `test-util` feature, which exposes the client.
+
+use std::sync::Arc;
+
+use laconia_agent::{
+    configs::ConfigStore,
+    log::LogStore,
+    offsets::OffsetStore,
+    protocol::{
+        api_keys::ApiKey,
+        error::ErrorCode,
+        handlers::DescribeProducersHandler,
+        messages::{DescribeProducersRequest, DescribeProducersRequestTopic},
+    },
+    server::KafkaServerBuilder,
+    test_client::TestClient,
+    transactions::TransactionManager,
+};
+
+#[tokio::test]
+async fn lists_the_producer_of_an_ongoing_transaction() {
+    let configs = Arc::new(ConfigStore::new(0, vec![]));
+    let logs = Arc::new(LogStore::open(None, false, configs).unwrap());
+    logs.get_or_create("events", 0).unwrap();
+    logs.get_or_create("events", 1).unwrap();
+    let transactions = Arc::new(TransactionManager::new(
+        logs.clone(),
+        Arc::new(OffsetStore::new()),
+    ));
+
+    // There's no Produce handler to write through, so the producer's
+    // transaction is started by adding the partition to it directly.
+    let (producer_id, producer_epoch) = transactions.init_producer_id(Some("txn"));
+    transactions
+        .add_partitions(
+            "txn",
+            producer_id,
+            producer_epoch,
+            [("events".to_string(), 0)],
+        )
+        .unwrap();
+
+    let server = KafkaServerBuilder::new()
+        .register(
+            ApiKey::DescribeProducers,
+            DescribeProducersHandler::new(logs, transactions),
+        )
+        .build()
+        .await
+        .unwrap();
+
+    let (client, stream) = tokio::io::duplex(4096);
+    server.serve(stream).await.unwrap();
+    let mut client = TestClient::new(client);
+
+    let request = DescribeProducersRequest {
+        topics: vec![DescribeProducersRequestTopic {
+            name: "events".to_string(),
+            partition_indexes: vec![0, 1, 2],
+            tagged_fields: Default::default(),
+        }],
+        tagged_fields: Default::default(),
+    };
+    let response = client
+        .send(ApiKey::DescribeProducers, 0, &request)
+        .await
+        .unwrap();
+
+    let [topic] = response.topics.as_slice() else {
+        panic!("expected one topic, got {:?}", response.topics);
+    };
+    assert_eq!(topic.name, "events");
+    let [with_producer, without_producer, unknown] = topic.partitions.as_slice() else {
+        panic!("expected three partitions, got {:?}", topic.partitions);
+    };
+
+    assert_eq!(with_producer.partition_index, 0);
+    assert_eq!(with_producer.error_code, ErrorCode::None);
+    let [producer] = with_producer.active_producers.as_slice() else {
+        panic!(
+            "expected one producer, got {:?}",
+            with_producer.active_producers
+        );
+    };
+    assert_eq!(producer.producer_id, producer_id);
+    assert_eq!(producer.producer_epoch, i32::from(producer_epoch));
+
+    assert_eq!(without_producer.error_code, ErrorCode::None);
+    assert!(without_producer.active_producers.is_empty());
+
+    assert_eq!(unknown.partition_index, 2);
+    assert_eq!(unknown.error_code, ErrorCode::UnknownTopicOrPartition);
+}
@@ -0,0 +1,69 @@
+//! Checks DescribeTopicPartitions pages through the topics with its cursor.
+
+use std::{collections::BTreeMap, sync::Arc};
+
+use laconia_agent::{
+    ConnectionState, RequestHeader,
+    cluster::{BrokerInfo, ClusterInfo},
+    configs::ConfigStore,
+    log::LogStore,
+    protocol::{
+        handlers::{DescribeTopicPartitionsHandler, RequestHandler},
+        messages::DescribeTopicPartitionsRequest,
+        registry::MessageRegistry,
+    },
+};
+
+#[tokio::test]
+async fn five_topics_take_three_pages_of_two() {
+    let configs = Arc::new(ConfigStore::new(0, vec![]));
+    let logs = Arc::new(LogStore::open(None, false, configs).unwrap());
+    for topic in ["a", "b", "c", "d", "e"] {
+        logs.get_or_create(topic, 0).unwrap();
+    }
+    let cluster = Arc::new(ClusterInfo::single_broker(
+        "laconia",
+        BrokerInfo {
+            node_id: 0,
+            host: "localhost".to_string(),
+            port: 9092,
+            rack: String::new(),
+        },
+    ));
+    let handler = DescribeTopicPartitionsHandler::new(logs, cluster);
+
+    let header = RequestHeader {
+        api_key: 75,
+        version: 0,
+        correlation_id: 0,
+        client_id: "laconia-tests".to_string(),
+        tagged_fields: BTreeMap::new(),
+    };
+    let mut state = ConnectionState::new(Arc::new(MessageRegistry::new()));
+
+    let mut pages = Vec::new();
+    let mut cursor = None;
+    loop {
+        let request = DescribeTopicPartitionsRequest {
+            topics: vec![],
+            response_partition_limit: 2,
+            cursor,
+            tagged_fields: BTreeMap::new(),
+        };
+        let response = handler.handle(&header, &request, &mut state).await.unwrap();
+
+        let names: Vec<_> = response
+            .topics
+            .iter()
+            .map(|topic| topic.name.clone())
+            .collect();
+        pages.push(names);
+
+        cursor = response.next_cursor;
+        if cursor.is_none() {
+            break;
+        }
+    }
+
+    assert_eq!(pages, [vec!["a", "b"], vec!["c", "d"], vec!["e"]]);
+}
@@ -0,0 +1,76 @@
+//! Drives ElectLeaders through the in-process test client. Only built with
+//! the `test-util` feature, which exposes the client.
+
+use std::sync::Arc;
+
+use laconia_agent::{
+    configs::ConfigStore,
+    log::LogStore,
+    protocol::{
+        api_keys::ApiKey,
+        error::ErrorCode,
+        handlers::ElectLeadersHandler,
+        messages::{ELECTION_PREFERRED, ElectLeadersRequest, ElectLeadersTopicPartitions},
+    },
+    server::KafkaServerBuilder,
+    test_client::TestClient,
+};
+
+#[tokio::test]
+async fn elects_leaders_for_a_named_topic() {
+    let configs = Arc::new(ConfigStore::new(0, vec![]));
+    let logs = Arc::new(LogStore::open(None, false, configs).unwrap());
+    for partition in 0..2 {
+        logs.get_or_create("events", partition).unwrap();
+    }
+
+    let server = KafkaServerBuilder::new()
+        .register(ApiKey::ElectLeaders, ElectLeadersHandler::new(logs))
+        .build()
+        .await
+        .unwrap();
+
+    let (client, stream) = tokio::io::duplex(4096);
+    server.serve(stream).await.unwrap();
+    let mut client = TestClient::new(client);
+
+    for version in 0..=2 {
+        let request = ElectLeadersRequest {
+            election_type: ELECTION_PREFERRED,
+            topic_partitions: Some(vec![ElectLeadersTopicPartitions {
+                topic: "events".to_string(),
+                partitions: vec![0, 1, 2],
+                tagged_fields: Default::default(),
+            }]),
+            timeout_ms: 1000,
+            tagged_fields: Default::default(),
+        };
+        let response = client
+            .send(ApiKey::ElectLeaders, version, &request)
+            .await
+            .unwrap();
+
+        assert_eq!(response.error_code, ErrorCode::None);
+        let [topic] = response.replica_election_results.as_slice() else {
+            panic!(
+                "expected one topic, got {:?}",
+                response.replica_election_results
+            );
+        };
+        assert_eq!(topic.topic, "events");
+
+        let results: Vec<_> = topic
+            .partition_results
+            .iter()
+            .map(|result| (result.partition_id, result.error_code))
+            .collect();
+        assert_eq!(
+            results,
+            [
+                (0, ErrorCode::None),
+                (1, ErrorCode::None),
+                (2, ErrorCode::UnknownTopicOrPartition),
+            ]
+        );
+    }
+}
@@ -0,0 +1,74 @@
+//! Checks the leader epoch cache finds the end offset of past, current and
+//! future epochs, and survives being checkpointed.
+
+mod common;
+
+use std::fs;
+
+use laconia_agent::log::EpochCache;
+
+/// Epoch 0 starts at offset 0, epoch 2 at 10 and epoch 5 at 25.
+fn cache() -> EpochCache {
+    let mut cache = EpochCache::new();
+    assert!(cache.assign(0, 0));
+    assert!(cache.assign(2, 10));
+    assert!(cache.assign(5, 25));
+    cache
+}
+
+#[test]
+fn only_newer_epochs_start_a_transition() {
+    let mut cache = cache();
+    assert!(!cache.assign(5, 30));
+    assert!(!cache.assign(3, 30));
+    assert!(!cache.assign(-1, 30));
+    assert_eq!(cache.latest_epoch(), Some(5));
+
+    assert!(cache.assign(6, 30));
+    assert_eq!(cache.latest_epoch(), Some(6));
+}
+
+#[test]
+fn end_offsets_of_past_current_and_future_epochs() {
+    let cache = cache();
+
+    // Past epochs end where the next one starts, including epochs that were
+    // skipped.
+    assert_eq!(cache.end_offset_for_epoch(0, 40), Some((0, 10)));
+    assert_eq!(cache.end_offset_for_epoch(1, 40), Some((0, 10)));
+    assert_eq!(cache.end_offset_for_epoch(2, 40), Some((2, 25)));
+
+    // The current epoch, and any later one, ends at the end of the log.
+    assert_eq!(cache.end_offset_for_epoch(5, 40), Some((5, 40)));
+    assert_eq!(cache.end_offset_for_epoch(9, 40), Some((5, 40)));
+
+    // Nothing is known from before the first epoch.
+    assert_eq!(EpochCache::new().end_offset_for_epoch(0, 40), None);
+    let mut cache = EpochCache::new();
+    cache.assign(3, 0);
+    assert_eq!(cache.end_offset_for_epoch(2, 40), None);
+}
+
+#[test]
+fn truncation_forgets_epochs_past_the_end() {
+    let mut cache = cache();
+    cache.truncate_from_end(25);
+    assert_eq!(cache.latest_epoch(), Some(2));
+    assert_eq!(cache.end_offset_for_epoch(5, 20), Some((2, 20)));
+}
+
+#[test]
+fn checkpoint_round_trips() {
+    let dir = common::temp_dir("laconia-epoch-cache");
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("leader-epoch-checkpoint");
+
+    assert_eq!(EpochCache::load(&path).unwrap(), EpochCache::new());
+    cache().write(&path).unwrap();
+    assert_eq!(EpochCache::load(&path).unwrap(), cache());
+
+    fs::write(&path, "0\n3\n0 0\n").unwrap();
+    assert!(EpochCache::load(&path).is_err());
+
+    fs::remove_dir_all(&dir).unwrap();
+}
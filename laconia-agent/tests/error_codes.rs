@@ -0,0 +1,31 @@
+//! Checks every Kafka error code round-trips through its numeric and wire
+//! forms.
+
+use bytes::BytesMut;
+use laconia_agent::protocol::{Decoder, Encoder, error::ErrorCode};
+
+#[test]
+fn every_error_code_round_trips() {
+    let mut variants = 0;
+    for code in i16::MIN..=i16::MAX {
+        let Some(error_code) = ErrorCode::from_i16(code) else {
+            continue;
+        };
+        variants += 1;
+        assert_eq!(error_code.as_i16(), code);
+
+        let mut buf = BytesMut::new();
+        error_code.encode(&mut buf).unwrap();
+        assert_eq!(&buf[..], code.to_be_bytes());
+        assert_eq!(ErrorCode::decode(&mut buf).unwrap(), error_code);
+    }
+
+    // UNKNOWN_SERVER_ERROR, NONE, and 1 through 120.
+    assert_eq!(variants, 122);
+}
+
+#[test]
+fn unknown_error_code_fails_to_decode() {
+    let mut buf = BytesMut::from(&1000i16.to_be_bytes()[..]);
+    assert!(ErrorCode::decode(&mut buf).is_err());
+}
@@ -0,0 +1,93 @@
+//! Checks feature flags are advertised in ApiVersions and negotiated on the
+//! connection. Only built with the `test-util` feature, which exposes the
+//! test client.
+
+use std::{
+    io,
+    sync::{Arc, Mutex},
+};
+
+use laconia_agent::{
+    ConnectionState, RequestHeader, VersionRange,
+    features::Features,
+    protocol::{
+        api_keys::ApiKey,
+        handlers::{ApiVersionsHandler, RequestHandler},
+        messages::{ApiVersionsRequest, ApiVersionsResponse},
+    },
+    server::KafkaServerBuilder,
+    test_client::TestClient,
+};
+
+/// Answers ApiVersions as usual, then records the level of `feature`
+/// negotiated on the connection.
+struct FeatureProbe {
+    feature: &'static str,
+    level: Arc<Mutex<Option<i16>>>,
+}
+
+impl RequestHandler<ApiVersionsRequest> for FeatureProbe {
+    async fn handle(
+        &self,
+        header: &RequestHeader,
+        request: &ApiVersionsRequest,
+        state: &mut ConnectionState,
+    ) -> Result<ApiVersionsResponse, io::Error> {
+        let response = ApiVersionsHandler.handle(header, request, state).await?;
+        *self.level.lock().unwrap() = state.feature_level(self.feature);
+        Ok(response)
+    }
+}
+
+#[tokio::test]
+async fn configured_feature_is_advertised_and_negotiated() {
+    let level = Arc::new(Mutex::new(None));
+    let server = KafkaServerBuilder::new()
+        .register(
+            ApiKey::ApiVersions,
+            FeatureProbe {
+                feature: "transaction.version",
+                level: level.clone(),
+            },
+        )
+        .features(Features::new().with_feature("transaction.version", VersionRange::new(1, 2)))
+        .build()
+        .await
+        .unwrap();
+
+    let (client, stream) = tokio::io::duplex(4096);
+    server.serve(stream).await.unwrap();
+    let mut client = TestClient::new(client);
+
+    let request = ApiVersionsRequest {
+        client_software_name: "laconia-tests".to_string(),
+        client_software_version: "0.1.0".to_string(),
+        tagged_fields: Default::default(),
+    };
+
+    // Version 2 can't carry features, so none are negotiated.
+    let response = client.send(ApiKey::ApiVersions, 2, &request).await.unwrap();
+    assert!(response.supported_features.is_empty());
+    assert_eq!(*level.lock().unwrap(), None);
+
+    let response = client.send(ApiKey::ApiVersions, 3, &request).await.unwrap();
+    let [supported] = response.supported_features.as_slice() else {
+        panic!(
+            "expected one feature, got {:?}",
+            response.supported_features
+        );
+    };
+    assert_eq!(supported.name, "transaction.version");
+    assert_eq!((supported.min_version, supported.max_version), (1, 2));
+
+    let [finalized] = response.finalized_features.as_slice() else {
+        panic!(
+            "expected one feature, got {:?}",
+            response.finalized_features
+        );
+    };
+    assert_eq!(finalized.max_version_level, 2);
+    assert_eq!(response.finalized_features_epoch, 0);
+
+    assert_eq!(*level.lock().unwrap(), Some(2));
+}
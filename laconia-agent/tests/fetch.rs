@@ -0,0 +1,243 @@
+//! Checks Fetch reads records from the partition logs, including through
+//! incremental fetch sessions, that read committed fetches stop at the last
+//! stable offset and are told of aborted transactions, and that fetches wait
+//! no longer than their `max_wait_ms`.
+
+mod common;
+
+use std::{
+    collections::BTreeMap,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use bytes::{BufMut, BytesMut};
+use laconia_agent::{
+    ConnectionState, KafkaRequest, RequestHeader,
+    configs::ConfigStore,
+    fetch_session::FetchSessionCache,
+    log::LogStore,
+    protocol::{
+        api_keys::ApiKey,
+        error::ErrorCode,
+        handlers::{FetchHandler, RequestHandler},
+        messages::{FetchRequest, FetchRequestPartition, FetchRequestTopic, FetchResponse},
+        registry::MessageRegistry,
+    },
+};
+
+fn logs() -> Arc<LogStore> {
+    let configs = Arc::new(ConfigStore::new(0, vec![]));
+    Arc::new(LogStore::open(None, false, configs).unwrap())
+}
+
+/// A fetch of `events` at each of the given partitions and offsets.
+fn request(session_id: i32, session_epoch: i32, partitions: &[(i32, i64)]) -> FetchRequest {
+    let topics = if partitions.is_empty() {
+        vec![]
+    } else {
+        vec![FetchRequestTopic {
+            topic: "events".to_string(),
+            partitions: partitions
+                .iter()
+                .map(|&(partition, fetch_offset)| FetchRequestPartition {
+                    partition,
+                    current_leader_epoch: -1,
+                    fetch_offset,
+                    last_fetched_epoch: -1,
+                    log_start_offset: -1,
+                    partition_max_bytes: 1024 * 1024,
+                    tagged_fields: BTreeMap::new(),
+                })
+                .collect(),
+            tagged_fields: BTreeMap::new(),
+        }]
+    };
+
+    FetchRequest {
+        replica_id: -1,
+        max_wait_ms: 0,
+        min_bytes: 0,
+        max_bytes: 1024 * 1024,
+        isolation_level: 0,
+        session_id,
+        session_epoch,
+        topics,
+        forgotten_topics_data: vec![],
+        rack_id: String::new(),
+        tagged_fields: BTreeMap::new(),
+    }
+}
+
+async fn fetch(handler: &FetchHandler, request: &FetchRequest) -> FetchResponse {
+    let header = RequestHeader {
+        api_key: 1,
+        version: 12,
+        correlation_id: 0,
+        client_id: "laconia-tests".to_string(),
+        tagged_fields: BTreeMap::new(),
+    };
+    let mut state = ConnectionState::new(Arc::new(MessageRegistry::new()));
+    handler.handle(&header, request, &mut state).await.unwrap()
+}
+
+/// The partitions in `response`, and how many bytes of records each has.
+fn returned(response: &FetchResponse) -> Vec<(i32, usize)> {
+    response
+        .responses
+        .iter()
+        .flat_map(|topic| &topic.partitions)
+        .map(|partition| (partition.partition_index, partition.records.len()))
+        .collect()
+}
+
+#[tokio::test]
+async fn incremental_fetch_returns_only_changed_partitions() {
+    let logs = logs();
+    for partition in 0..2 {
+        logs.append("events", partition, common::batch(&[b"a"], 0))
+            .unwrap();
+    }
+    let handler = FetchHandler::new(logs.clone(), Arc::new(FetchSessionCache::new(16)));
+
+    // A full fetch starts the session, returning every partition.
+    let response = fetch(&handler, &request(0, 0, &[(0, 0), (1, 0)])).await;
+    assert_eq!(response.error_code, ErrorCode::None);
+    assert_ne!(response.session_id, 0);
+    let session_id = response.session_id;
+    let partitions = returned(&response);
+    assert_eq!(partitions.len(), 2);
+    assert!(partitions.iter().all(|&(_, bytes)| bytes > 0));
+
+    // Moving past the records read leaves nothing that changed.
+    let response = fetch(&handler, &request(session_id, 1, &[(0, 1), (1, 1)])).await;
+    assert_eq!(response.session_id, session_id);
+    assert!(returned(&response).is_empty());
+
+    // The session remembers the positions, so only the partition with new
+    // records comes back.
+    logs.append("events", 1, common::batch(&[b"b"], 0)).unwrap();
+    let response = fetch(&handler, &request(session_id, 2, &[])).await;
+    let partitions = returned(&response);
+    assert_eq!(partitions.len(), 1);
+    assert_eq!(partitions[0].0, 1);
+    assert!(partitions[0].1 > 0);
+
+    // A stale epoch is rejected, as is a session that doesn't exist.
+    let response = fetch(&handler, &request(session_id, 2, &[])).await;
+    assert_eq!(response.error_code, ErrorCode::InvalidFetchSessionEpoch);
+    let response = fetch(&handler, &request(session_id + 1, 1, &[])).await;
+    assert_eq!(response.error_code, ErrorCode::FetchSessionIdNotFound);
+}
+
+#[tokio::test]
+async fn read_committed_hides_uncommitted_records() {
+    let logs = logs();
+    let committed = common::batch(&[b"a"], 0);
+    let committed_len = committed.len();
+    logs.append("events", 0, committed).unwrap();
+    logs.append("events", 0, common::transactional_batch(1, 0, &[b"b"]))
+        .unwrap();
+    let handler = FetchHandler::new(logs.clone(), Arc::new(FetchSessionCache::new(16)));
+
+    let mut read_committed = request(0, -1, &[(0, 0)]);
+    read_committed.isolation_level = 1;
+    let response = fetch(&handler, &read_committed).await;
+    let partition = &response.responses[0].partitions[0];
+    assert_eq!(partition.high_watermark, 2);
+    assert_eq!(partition.last_stable_offset, 1);
+    assert_eq!(partition.records.len(), committed_len);
+
+    // Reading uncommitted records returns the open transaction's too.
+    let response = fetch(&handler, &request(0, -1, &[(0, 0)])).await;
+    assert!(returned(&response)[0].1 > committed_len);
+
+    // Once the transaction commits, its records are stable.
+    logs.write_transaction_marker("events", 0, 1, 0, true)
+        .unwrap();
+    let response = fetch(&handler, &read_committed).await;
+    let partition = &response.responses[0].partitions[0];
+    assert_eq!(partition.last_stable_offset, partition.high_watermark);
+    assert!(partition.records.len() > committed_len);
+}
+
+#[tokio::test]
+async fn aborted_transaction_is_listed() {
+    let logs = logs();
+    logs.append("events", 0, common::batch(&[b"a"], 0)).unwrap();
+    logs.append(
+        "events",
+        0,
+        common::transactional_batch(7, 0, &[b"b", b"c"]),
+    )
+    .unwrap();
+    logs.write_transaction_marker("events", 0, 7, 0, false)
+        .unwrap();
+    let handler = FetchHandler::new(logs, Arc::new(FetchSessionCache::new(16)));
+
+    let mut read_committed = request(0, -1, &[(0, 0)]);
+    read_committed.isolation_level = 1;
+    let response = fetch(&handler, &read_committed).await;
+    let aborted: Vec<_> = response.responses[0].partitions[0]
+        .aborted_transactions
+        .as_ref()
+        .unwrap()
+        .iter()
+        .map(|aborted| (aborted.producer_id, aborted.first_offset))
+        .collect();
+    assert_eq!(aborted, [(7, 1)]);
+
+    // Only read committed fetches skip aborted records, so only they're told.
+    let response = fetch(&handler, &request(0, -1, &[(0, 0)])).await;
+    assert!(
+        response.responses[0].partitions[0]
+            .aborted_transactions
+            .is_none()
+    );
+}
+
+#[tokio::test]
+async fn fetch_without_records_returns_after_max_wait_ms() {
+    let logs = logs();
+    logs.get_or_create("events", 0).unwrap();
+    let mut registry = MessageRegistry::new();
+    registry
+        .register(
+            ApiKey::Fetch,
+            FetchHandler::new(logs, Arc::new(FetchSessionCache::new(16))),
+        )
+        .unwrap();
+    let registry = Arc::new(registry);
+
+    // A Fetch v4 body waiting up to 100ms for a byte of partition 0 of
+    // `events`, which has none.
+    let mut body = BytesMut::new();
+    body.put_i32(-1);
+    body.put_i32(100);
+    body.put_i32(1);
+    body.put_i32(1024 * 1024);
+    body.put_i8(0);
+    body.put_i32(1);
+    body.put_i16(6);
+    body.put_slice(b"events");
+    body.put_i32(1);
+    body.put_i32(0);
+    body.put_i64(0);
+    body.put_i32(1024 * 1024);
+
+    let header = RequestHeader {
+        api_key: 1,
+        version: 4,
+        correlation_id: 0,
+        client_id: "laconia-tests".to_string(),
+        tagged_fields: BTreeMap::new(),
+    };
+    let mut state = ConnectionState::new(registry.clone());
+    let started = Instant::now();
+    KafkaRequest::handle(header, &mut body, &registry, &mut state)
+        .await
+        .unwrap();
+    let elapsed = started.elapsed();
+    assert!(elapsed >= Duration::from_millis(100), "{:?}", elapsed);
+    assert!(elapsed < Duration::from_secs(1), "{:?}", elapsed);
+}
@@ -0,0 +1,201 @@
+//! Checks a frame is only returned once all of it has arrived, the request
+//! codec parses its header, zero-length frames are skipped rather than failing
+//! the connection, and frames over the maximum request size are rejected
+//! before they're read, as are client ids over the maximum length.
+
+mod common;
+
+use std::{net::Ipv4Addr, sync::Arc, time::Duration};
+
+use bytes::{BufMut, BytesMut};
+use futures::{SinkExt, StreamExt};
+use laconia_agent::{
+    ConnectionState, DecoderVersioned, KafkaMessageCodec, KafkaRequest, KafkaRequestCodec,
+    ResponseHeader,
+    protocol::{
+        DecodeError,
+        api_keys::ApiKey,
+        handlers::ApiVersionsHandler,
+        registry::{API_VERSIONS_KEY, MessageRegistry},
+    },
+    server::KafkaServerBuilder,
+};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpStream,
+};
+use tokio_util::codec::{Decoder, Framed, LengthDelimitedCodec};
+
+/// An ApiVersions request behind its length prefix.
+fn api_versions_frame(correlation_id: i32) -> BytesMut {
+    let request = common::api_versions_request(correlation_id);
+    let mut frame = BytesMut::new();
+    frame.put_i32(request.len() as i32);
+    frame.extend_from_slice(&request);
+    frame
+}
+
+#[test]
+fn partial_frame_waits_for_the_rest() {
+    let frame = api_versions_frame(1);
+    let mut codec = KafkaMessageCodec::new();
+
+    // Everything but the last byte has arrived, so the codec waits for it
+    // and leaves the buffer as is.
+    let mut src = BytesMut::from(&frame[..frame.len() - 1]);
+    assert!(codec.decode(&mut src).unwrap().is_none());
+    assert_eq!(&src[..], &frame[..frame.len() - 1]);
+
+    src.extend_from_slice(&frame[frame.len() - 1..]);
+    let decoded = codec.decode(&mut src).unwrap().unwrap();
+    assert_eq!(&decoded[..], &frame[4..]);
+    assert!(src.is_empty());
+}
+
+#[test]
+fn codec_skips_zero_length_frames() {
+    let frame = api_versions_frame(1);
+    let mut src = BytesMut::new();
+    src.put_i32(0);
+    src.put_i32(0);
+    src.extend_from_slice(&frame);
+
+    let decoded = KafkaMessageCodec::new().decode(&mut src).unwrap().unwrap();
+    assert_eq!(&decoded[..], &frame[4..]);
+    assert!(src.is_empty());
+
+    // A lone zero-length frame leaves nothing to return yet.
+    src.put_i32(0);
+    assert!(KafkaMessageCodec::new().decode(&mut src).unwrap().is_none());
+    assert!(src.is_empty());
+}
+
+#[test]
+fn request_codec_yields_the_header() {
+    let mut src = api_versions_frame(42);
+    src.extend_from_slice(&api_versions_frame(43));
+    let mut registry = MessageRegistry::new();
+    registry
+        .register(ApiKey::ApiVersions, ApiVersionsHandler)
+        .unwrap();
+    let mut codec = KafkaRequestCodec::new(Arc::new(registry));
+
+    for correlation_id in [42, 43] {
+        let (header, body) = codec.decode(&mut src).unwrap().unwrap();
+        assert_eq!(header.api_key, API_VERSIONS_KEY);
+        assert_eq!(header.version, 3);
+        assert_eq!(header.correlation_id, correlation_id);
+        assert_eq!(header.client_id, "");
+        assert_eq!(&body[..], &common::api_versions_request(0)[11..]);
+    }
+    assert!(codec.decode(&mut src).unwrap().is_none());
+}
+
+#[tokio::test]
+async fn request_after_a_zero_length_frame_is_answered() {
+    let server = KafkaServerBuilder::new()
+        .register(ApiKey::ApiVersions, ApiVersionsHandler)
+        .build()
+        .await
+        .unwrap();
+
+    let (mut client, stream) = tokio::io::duplex(4096);
+    server.serve(stream).await.unwrap();
+
+    client.write_i32(0).await.unwrap();
+    client.write_all(&api_versions_frame(7)).await.unwrap();
+
+    let len = client.read_i32().await.unwrap();
+    let mut response = BytesMut::zeroed(len as usize);
+    client.read_exact(&mut response).await.unwrap();
+
+    let header = ResponseHeader::decode(&mut response, 0).unwrap();
+    assert_eq!(header.correlation_id, 7);
+}
+
+#[test]
+fn frames_over_the_maximum_are_invalid() {
+    let mut codec = KafkaMessageCodec::new().with_max_frame_size(1024);
+
+    // Only the length has arrived, so the frame isn't waited for nor room
+    // made for it.
+    let mut src = BytesMut::new();
+    src.put_i32(i32::MAX);
+    let err = codec.decode(&mut src).unwrap_err();
+    assert!(matches!(
+        DecodeError::from_io(&err),
+        Some(DecodeError::Invalid(_))
+    ));
+    assert!(src.capacity() < 1024);
+
+    // The default maximum rejects it too.
+    let mut src = BytesMut::new();
+    src.put_i32(i32::MAX);
+    assert!(KafkaMessageCodec::new().decode(&mut src).is_err());
+    assert!(src.capacity() < 1024);
+}
+
+#[tokio::test]
+async fn oversized_client_id_is_invalid() {
+    let mut registry = MessageRegistry::new();
+    registry
+        .register(ApiKey::ApiVersions, ApiVersionsHandler)
+        .unwrap();
+    let registry = Arc::new(registry);
+    let mut state = ConnectionState::new(registry.clone()).with_max_client_id_len(16);
+
+    // An ApiVersions v0 header announcing a 4000 byte client id of which
+    // only 4 bytes follow, so it's rejected by its length rather than read.
+    let mut request = BytesMut::new();
+    request.put_i16(API_VERSIONS_KEY);
+    request.put_i16(0);
+    request.put_i32(1);
+    request.put_i16(4000);
+    request.extend_from_slice(b"huge");
+    let err = KafkaRequest::decode_and_handle(&mut request, &registry, &mut state)
+        .await
+        .err()
+        .unwrap();
+    assert!(matches!(
+        DecodeError::from_io(&err),
+        Some(DecodeError::Invalid(_))
+    ));
+
+    // A client id within the maximum is fine.
+    let mut request = BytesMut::new();
+    request.put_i16(API_VERSIONS_KEY);
+    request.put_i16(0);
+    request.put_i32(2);
+    request.put_i16(13);
+    request.extend_from_slice(b"laconia-tests");
+    let request = KafkaRequest::decode_and_handle(&mut request, &registry, &mut state)
+        .await
+        .unwrap();
+    assert_eq!(request.header.client_id, "laconia-tests");
+}
+
+#[tokio::test]
+async fn connection_announcing_an_oversized_request_is_closed() {
+    let server = KafkaServerBuilder::new()
+        .register(ApiKey::ApiVersions, ApiVersionsHandler)
+        .bind((Ipv4Addr::LOCALHOST, 0).into())
+        .max_request_size(1024)
+        .build()
+        .await
+        .unwrap();
+    let addr = server.local_addr().unwrap();
+    tokio::spawn(async move { server.accept().await });
+
+    let stream = TcpStream::connect(addr).await.unwrap();
+    let mut client = Framed::new(stream, LengthDelimitedCodec::new());
+
+    // Smaller requests are still answered.
+    client.send(common::api_versions_request(1)).await.unwrap();
+    assert!(client.next().await.is_some());
+
+    client.send(vec![0; 1025].into()).await.unwrap();
+    let closed = tokio::time::timeout(Duration::from_secs(5), client.next())
+        .await
+        .expect("the connection is closed");
+    assert!(closed.is_none_or(|response| response.is_err()));
+}
@@ -0,0 +1,46 @@
+//! Runs the request decoding fuzz target over its seed corpus, and over
+//! truncated and corrupted copies of each seed, so `cargo test` catches
+//! panics on the network-facing decode path without a fuzzer.
+
+use std::fs;
+
+use laconia_agent::fuzz::decode_request;
+
+fn seeds() -> Vec<Vec<u8>> {
+    let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/fuzz/corpus/decode_request");
+    let seeds: Vec<_> = fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| fs::read(entry.unwrap().path()).unwrap())
+        .collect();
+    assert!(!seeds.is_empty(), "the corpus is empty");
+    seeds
+}
+
+#[test]
+fn seeds_decode_without_panicking() {
+    for seed in seeds() {
+        decode_request(&seed);
+    }
+}
+
+#[test]
+fn truncated_seeds_decode_without_panicking() {
+    for seed in seeds() {
+        for len in 0..seed.len() {
+            decode_request(&seed[..len]);
+        }
+    }
+}
+
+#[test]
+fn corrupted_seeds_decode_without_panicking() {
+    for seed in seeds() {
+        for i in 0..seed.len() {
+            for byte in [0x00, 0x7f, 0x80, 0xff] {
+                let mut corrupted = seed.clone();
+                corrupted[i] = byte;
+                decode_request(&corrupted);
+            }
+        }
+    }
+}
@@ -0,0 +1,76 @@
+//! Feeds arbitrary bytes to the primitive decoders, checking they fail with an
+//! error rather than panicking.
+
+use std::collections::BTreeMap;
+
+use bytes::{Bytes, BytesMut};
+use laconia_agent::protocol::{
+    Decoder,
+    primitives::{
+        CompactArray, CompactNullableArray, CompactNullableString, CompactString, NullableArray,
+        NullableString,
+    },
+};
+use proptest::prelude::*;
+
+const CASES: u32 = 10_000;
+const MAX_LEN: usize = 64;
+
+/// Arbitrary bytes, half of them starting with a small byte so lengths and
+/// counts are small often enough for decoding to reach the elements.
+fn input() -> impl Strategy<Value = BytesMut> {
+    let bytes = prop::collection::vec(any::<u8>(), 0..=MAX_LEN);
+    (any::<bool>(), bytes).prop_map(|(small_prefix, mut bytes)| {
+        if let Some(first) = bytes.first_mut()
+            && small_prefix
+        {
+            *first %= 8;
+        }
+        BytesMut::from(&bytes[..])
+    })
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(CASES))]
+
+    #[test]
+    fn strings_never_panic(input in input()) {
+        let _ = String::decode(&mut input.clone());
+        let _ = NullableString::decode(&mut input.clone());
+        let _ = CompactString::decode(&mut input.clone());
+        let _ = CompactNullableString::decode(&mut input.clone());
+    }
+
+    #[test]
+    fn arrays_never_panic(input in input()) {
+        let _ = Vec::<i16>::decode(&mut input.clone());
+        let _ = NullableArray::<i16>::decode(&mut input.clone());
+        let _ = CompactArray::<i16>::decode(&mut input.clone());
+        let _ = CompactNullableArray::<i16>::decode(&mut input.clone());
+        let _ = Vec::<String>::decode(&mut input.clone());
+        let _ = CompactArray::<CompactString>::decode(&mut input.clone());
+    }
+
+    #[test]
+    fn tagged_fields_never_panic(mut input in input()) {
+        let _ = BTreeMap::<i32, Bytes>::decode(&mut input);
+    }
+}
+
+#[test]
+fn negative_array_length_is_rejected() {
+    let mut buf = BytesMut::from(&(-2i32).to_be_bytes()[..]);
+    assert!(Vec::<i16>::decode(&mut buf).is_err());
+
+    // -1 is null, which only nullable arrays allow.
+    let mut buf = BytesMut::from(&(-1i32).to_be_bytes()[..]);
+    assert!(Vec::<i16>::decode(&mut buf).is_err());
+    let mut buf = BytesMut::from(&(-1i32).to_be_bytes()[..]);
+    assert!(NullableArray::<i16>::decode(&mut buf).unwrap().0.is_none());
+}
+
+#[test]
+fn huge_array_length_fails_without_allocating_it() {
+    let mut buf = BytesMut::from(&i32::MAX.to_be_bytes()[..]);
+    assert!(Vec::<i32>::decode(&mut buf).is_err());
+}
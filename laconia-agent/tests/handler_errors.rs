@@ -0,0 +1,55 @@
+//! Checks a handler failing is answered with the matching Kafka error code
+//! rather than by dropping the connection. Only built with the `test-util`
+//! feature, which exposes the client.
+
+use std::io;
+
+use laconia_agent::{
+    ConnectionState, RequestHeader,
+    protocol::{
+        api_keys::ApiKey,
+        error::ErrorCode,
+        handlers::RequestHandler,
+        messages::{ApiVersionsRequest, ApiVersionsResponse},
+    },
+    server::KafkaServerBuilder,
+    test_client::TestClient,
+};
+
+struct NotFoundHandler;
+
+impl RequestHandler<ApiVersionsRequest> for NotFoundHandler {
+    async fn handle(
+        &self,
+        _header: &RequestHeader,
+        _request: &ApiVersionsRequest,
+        _state: &mut ConnectionState,
+    ) -> Result<ApiVersionsResponse, io::Error> {
+        Err(io::Error::new(io::ErrorKind::NotFound, "no such topic"))
+    }
+}
+
+#[tokio::test]
+async fn not_found_is_answered_with_unknown_topic_or_partition() {
+    let server = KafkaServerBuilder::new()
+        .register(ApiKey::ApiVersions, NotFoundHandler)
+        .build()
+        .await
+        .unwrap();
+
+    let (client, stream) = tokio::io::duplex(4096);
+    server.serve(stream).await.unwrap();
+    let mut client = TestClient::new(client);
+
+    let request = ApiVersionsRequest {
+        client_software_name: "laconia-tests".to_string(),
+        client_software_version: "0.1.0".to_string(),
+        tagged_fields: Default::default(),
+    };
+    let response = client.send(ApiKey::ApiVersions, 3, &request).await.unwrap();
+    assert_eq!(response.error_code, ErrorCode::UnknownTopicOrPartition);
+
+    // The connection is still usable.
+    let response = client.send(ApiKey::ApiVersions, 3, &request).await.unwrap();
+    assert_eq!(response.error_code, ErrorCode::UnknownTopicOrPartition);
+}
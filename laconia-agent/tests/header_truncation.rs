@@ -0,0 +1,110 @@
+//! Checks request headers cut short at any byte, or with a tagged field
+//! claiming more bytes than remain, are rejected with an error rather than a
+//! panic.
+
+use std::sync::Arc;
+
+use bytes::{BufMut, BytesMut};
+use laconia_agent::{
+    KafkaRequestCodec,
+    protocol::{
+        DecodeError,
+        api_keys::ApiKey,
+        handlers::ApiVersionsHandler,
+        registry::{API_VERSIONS_KEY, MessageRegistry},
+    },
+};
+use tokio_util::codec::Decoder;
+
+fn codec() -> KafkaRequestCodec {
+    let mut registry = MessageRegistry::new();
+    registry
+        .register(ApiKey::ApiVersions, ApiVersionsHandler)
+        .unwrap();
+    KafkaRequestCodec::new(Arc::new(registry))
+}
+
+/// An ApiVersions header at `version`, encoded at the header version the
+/// registry expects for it. Flexible headers end in one tagged field.
+fn encoded_header(version: i16, header_version: i16) -> BytesMut {
+    let mut buf = BytesMut::new();
+    buf.put_i16(API_VERSIONS_KEY);
+    buf.put_i16(version);
+    buf.put_i32(7);
+    buf.put_i16(13);
+    buf.extend_from_slice(b"laconia-tests");
+    if header_version >= 2 {
+        buf.put_u8(1);
+        buf.put_u8(0);
+        buf.put_u8(3);
+        buf.extend_from_slice(b"tag");
+    }
+    buf
+}
+
+/// Frames every prefix of `header` shorter than the whole, and checks each
+/// fails to decode.
+fn assert_truncations_rejected(header: &[u8]) {
+    for len in 0..header.len() {
+        let mut src = BytesMut::new();
+        src.put_i32(len as i32);
+        src.extend_from_slice(&header[..len]);
+
+        // Zero-length frames are skipped rather than decoded.
+        if len == 0 {
+            assert!(codec().decode(&mut src).unwrap().is_none());
+            continue;
+        }
+
+        assert!(
+            codec().decode(&mut src).is_err(),
+            "header truncated to {} of {} bytes decoded",
+            len,
+            header.len()
+        );
+    }
+}
+
+#[test]
+fn truncated_flexible_header_is_rejected() {
+    // ApiVersions is flexible from v3, with header v2.
+    let header = encoded_header(3, 2);
+    let mut src = BytesMut::new();
+    src.put_i32(header.len() as i32);
+    src.extend_from_slice(&header);
+    let (decoded, _) = codec().decode(&mut src).unwrap().unwrap();
+    assert_eq!(decoded.correlation_id, 7);
+
+    assert_truncations_rejected(&header);
+}
+
+#[test]
+fn truncated_non_flexible_header_is_rejected() {
+    let header = encoded_header(0, 1);
+    let mut src = BytesMut::new();
+    src.put_i32(header.len() as i32);
+    src.extend_from_slice(&header);
+    let (decoded, _) = codec().decode(&mut src).unwrap().unwrap();
+    assert_eq!(decoded.client_id, "laconia-tests");
+
+    assert_truncations_rejected(&header);
+}
+
+#[test]
+fn oversized_tagged_field_in_header_is_invalid() {
+    let mut header = encoded_header(3, 2);
+    // The header ends in its one tagged field: tag, size and the 3 byte value.
+    let size = header.len() - 4;
+    assert_eq!(header[size], 3);
+    header[size] = 100;
+
+    let mut src = BytesMut::new();
+    src.put_i32(header.len() as i32);
+    src.extend_from_slice(&header);
+
+    let err = codec().decode(&mut src).unwrap_err();
+    assert!(matches!(
+        DecodeError::from_io(&err),
+        Some(DecodeError::Invalid(_))
+    ));
+}
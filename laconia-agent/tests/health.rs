@@ -0,0 +1,45 @@
+//! Checks the HTTP health check follows the control-plane connection.
+
+use std::{
+    net::SocketAddr,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+};
+
+use laconia_agent::health::{self, Health};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+};
+
+/// Requests `/healthz` from `addr` and returns the response's status code.
+async fn healthz(addr: SocketAddr) -> u16 {
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    stream
+        .write_all(b"GET /healthz HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+        .await
+        .unwrap();
+
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await.unwrap();
+    let status = response.split(' ').nth(1).unwrap();
+    status.parse().unwrap()
+}
+
+#[tokio::test]
+async fn unhealthy_until_the_controlplane_is_connected() {
+    let controlplane_healthy = Arc::new(AtomicBool::new(false));
+    let health = Arc::new(Health::new(controlplane_healthy.clone()));
+    health.set_listener_bound();
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    health::serve(listener, health);
+
+    assert_eq!(healthz(addr).await, 503);
+
+    controlplane_healthy.store(true, Ordering::Relaxed);
+    assert_eq!(healthz(addr).await, 200);
+}
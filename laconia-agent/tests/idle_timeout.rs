@@ -0,0 +1,61 @@
+//! Checks connections that stop sending requests are closed once the idle
+//! timeout passes, while ones that keep sending stay open.
+//! Only built with the `test-util` feature, which exposes the client.
+
+use std::time::Duration;
+
+use laconia_agent::{
+    protocol::{api_keys::ApiKey, handlers::ApiVersionsHandler, messages::ApiVersionsRequest},
+    server::KafkaServerBuilder,
+    test_client::TestClient,
+};
+use tokio::io::AsyncReadExt;
+
+const IDLE_TIMEOUT: Duration = Duration::from_millis(200);
+
+fn api_versions_request() -> ApiVersionsRequest {
+    ApiVersionsRequest {
+        client_software_name: "laconia-tests".to_string(),
+        client_software_version: "0.1.0".to_string(),
+        tagged_fields: Default::default(),
+    }
+}
+
+#[tokio::test]
+async fn idle_connection_is_closed_while_active_one_survives() {
+    let server = KafkaServerBuilder::new()
+        .register(ApiKey::ApiVersions, ApiVersionsHandler)
+        .idle_timeout(IDLE_TIMEOUT)
+        .build()
+        .await
+        .unwrap();
+
+    let (mut idle, stream) = tokio::io::duplex(4096);
+    server.serve(stream).await.unwrap();
+
+    let (active, stream) = tokio::io::duplex(4096);
+    server.serve(stream).await.unwrap();
+    let mut active = TestClient::new(active);
+
+    // The active client keeps sending requests for several idle timeouts.
+    for _ in 0..10 {
+        active
+            .send(ApiKey::ApiVersions, 3, &api_versions_request())
+            .await
+            .unwrap();
+        tokio::time::sleep(IDLE_TIMEOUT / 4).await;
+    }
+
+    // By now the idle connection has been closed, so reading from it sees
+    // the end of the stream rather than waiting.
+    let mut buf = [0; 1];
+    let read = tokio::time::timeout(Duration::from_secs(5), idle.read(&mut buf))
+        .await
+        .expect("the idle connection is closed");
+    assert_eq!(read.unwrap(), 0);
+
+    active
+        .send(ApiKey::ApiVersions, 3, &api_versions_request())
+        .await
+        .unwrap();
+}
@@ -0,0 +1,83 @@
+//! Checks topic configs set with IncrementalAlterConfigs are reported by
+//! DescribeConfigs afterwards.
+
+use std::{collections::BTreeMap, sync::Arc};
+
+use laconia_agent::{
+    ConnectionState, RequestHeader,
+    configs::{ConfigStore, ResourceType},
+    log::LogStore,
+    protocol::{
+        error::ErrorCode,
+        handlers::{DescribeConfigsHandler, IncrementalAlterConfigsHandler, RequestHandler},
+        messages::{
+            DescribeConfigsRequest, DescribeConfigsResource, IncrementalAlterConfigsConfig,
+            IncrementalAlterConfigsRequest, IncrementalAlterConfigsResource,
+        },
+        registry::MessageRegistry,
+    },
+};
+
+fn header(api_key: i16, version: i16) -> RequestHeader {
+    RequestHeader {
+        api_key,
+        version,
+        correlation_id: 0,
+        client_id: "laconia-tests".to_string(),
+        tagged_fields: BTreeMap::new(),
+    }
+}
+
+#[tokio::test]
+async fn altered_retention_is_described() {
+    let configs = Arc::new(ConfigStore::new(0, vec![]));
+    let logs = Arc::new(LogStore::open(None, false, configs.clone()).unwrap());
+    logs.get_or_create("events", 0).unwrap();
+    let mut state = ConnectionState::new(Arc::new(MessageRegistry::new()));
+
+    let alter = IncrementalAlterConfigsRequest {
+        resources: vec![IncrementalAlterConfigsResource {
+            resource_type: ResourceType::Topic as i8,
+            resource_name: "events".to_string(),
+            configs: vec![IncrementalAlterConfigsConfig {
+                name: "retention.ms".to_string(),
+                config_operation: 0,
+                value: "3600000".to_string(),
+                tagged_fields: BTreeMap::new(),
+            }],
+            tagged_fields: BTreeMap::new(),
+        }],
+        validate_only: false,
+        tagged_fields: BTreeMap::new(),
+    };
+    let response = IncrementalAlterConfigsHandler::new(configs.clone())
+        .handle(&header(44, 1), &alter, &mut state)
+        .await
+        .unwrap();
+    assert_eq!(response.responses[0].error_code, ErrorCode::None);
+
+    let describe = DescribeConfigsRequest {
+        resources: vec![DescribeConfigsResource {
+            resource_type: ResourceType::Topic as i8,
+            resource_name: "events".to_string(),
+            configuration_keys: Some(vec!["retention.ms".to_string()]),
+            tagged_fields: BTreeMap::new(),
+        }],
+        include_synonyms: false,
+        include_documentation: false,
+        tagged_fields: BTreeMap::new(),
+    };
+    let response = DescribeConfigsHandler::new(configs, logs)
+        .handle(&header(32, 4), &describe, &mut state)
+        .await
+        .unwrap();
+
+    let result = &response.results[0];
+    assert_eq!(result.error_code, ErrorCode::None);
+    let [retention] = &result.configs[..] else {
+        panic!("expected one config, got {}", result.configs.len());
+    };
+    assert_eq!(retention.name, "retention.ms");
+    assert_eq!(retention.value, "3600000");
+    assert!(!retention.is_default);
+}
@@ -0,0 +1,55 @@
+//! Checks appending to and reading from a partition log, and the offsets it
+//! reports.
+
+mod common;
+
+use laconia_agent::log::{LogError, MemoryLog, PartitionLog};
+
+#[test]
+fn appended_batches_are_read_back_in_order() {
+    let log = MemoryLog::new();
+    assert_eq!((log.earliest_offset(), log.latest_offset()), (0, 0));
+
+    assert_eq!(log.append(common::batch(&[b"a", b"b"], 0)).unwrap(), 0);
+    assert_eq!(log.append(common::batch(&[b"c"], 0)).unwrap(), 2);
+    assert_eq!((log.earliest_offset(), log.latest_offset()), (0, 3));
+
+    let batches = log.read(0, usize::MAX).unwrap();
+    let offsets: Vec<_> = batches
+        .iter()
+        .map(|batch| (batch.base_offset(), batch.last_offset()))
+        .collect();
+    assert_eq!(offsets, [(0, 1), (2, 2)]);
+    assert_eq!(common::values(&batches[1]), [&b"c"[..]]);
+
+    // Reading from the middle of a batch returns all of it.
+    let batches = log.read(1, usize::MAX).unwrap();
+    assert_eq!(batches.len(), 2);
+    assert_eq!(batches[0].base_offset(), 0);
+}
+
+#[test]
+fn read_returns_at_least_one_batch() {
+    let log = MemoryLog::new();
+    log.append(common::batch(&[b"a"], 0)).unwrap();
+    log.append(common::batch(&[b"b"], 0)).unwrap();
+
+    let batches = log.read(0, 1).unwrap();
+    assert_eq!(batches.len(), 1);
+}
+
+#[test]
+fn reading_at_the_end_returns_nothing_and_past_it_fails() {
+    let log = MemoryLog::new();
+    log.append(common::batch(&[b"a"], 0)).unwrap();
+
+    assert!(log.read(1, usize::MAX).unwrap().is_empty());
+    assert!(matches!(
+        log.read(2, usize::MAX),
+        Err(LogError::OffsetOutOfRange {
+            offset: 2,
+            earliest: 0,
+            latest: 1,
+        })
+    ));
+}
@@ -0,0 +1,234 @@
+//! Checks Metadata describes every topic or only the requested ones,
+//! auto-creates topics with the broker's default number of partitions,
+//! reports the configured peer brokers, and spreads partition leadership
+//! across them.
+
+mod common;
+
+use std::{collections::BTreeMap, sync::Arc};
+
+use laconia_agent::{
+    ConnectionState, RequestHeader,
+    cluster::{BrokerInfo, BrokerRegistry, ClusterInfo},
+    configs::{ConfigEntry, ConfigStore, ConfigType},
+    log::{LEADER_EPOCH, LogStore},
+    protocol::{
+        error::ErrorCode,
+        handlers::{MetadataHandler, RequestHandler},
+        messages::{
+            MetadataRequest, MetadataRequestTopic, MetadataResponse, MetadataResponseTopicPartition,
+        },
+        registry::MessageRegistry,
+    },
+};
+use uuid::Uuid;
+
+/// The logs of broker 0, and a Metadata handler describing them along with
+/// `peers`. Auto-created topics get 3 partitions.
+fn handler(auto_create: bool, peers: Vec<BrokerInfo>) -> (Arc<LogStore>, MetadataHandler) {
+    let configs = Arc::new(ConfigStore::new(
+        0,
+        vec![
+            ConfigEntry::static_broker(
+                "auto.create.topics.enable",
+                auto_create,
+                ConfigType::Boolean,
+                "Enable auto creation of topic on the server.",
+            ),
+            ConfigEntry::static_broker(
+                "num.partitions",
+                3,
+                ConfigType::Int,
+                "The default number of log partitions per topic.",
+            ),
+        ],
+    ));
+    let logs = Arc::new(LogStore::open(None, false, configs.clone()).unwrap());
+    let cluster = Arc::new(ClusterInfo::new(
+        "laconia",
+        BrokerRegistry::new(broker(0), peers),
+    ));
+    (logs.clone(), MetadataHandler::new(logs, cluster, configs))
+}
+
+fn broker(node_id: i32) -> BrokerInfo {
+    BrokerInfo {
+        node_id,
+        host: format!("broker-{}", node_id),
+        port: 9092,
+        rack: String::new(),
+    }
+}
+
+/// Requests the metadata of `topics`, or of every topic if `None`.
+async fn metadata(
+    handler: &MetadataHandler,
+    topics: Option<&[&str]>,
+    allow_auto_topic_creation: bool,
+) -> MetadataResponse {
+    let header = RequestHeader {
+        api_key: 3,
+        version: 12,
+        correlation_id: 0,
+        client_id: "laconia-tests".to_string(),
+        tagged_fields: BTreeMap::new(),
+    };
+    let request = MetadataRequest {
+        topics: topics.map(|topics| {
+            topics
+                .iter()
+                .map(|name| MetadataRequestTopic {
+                    topic_id: Uuid::nil(),
+                    name: name.to_string(),
+                    tagged_fields: BTreeMap::new(),
+                })
+                .collect()
+        }),
+        allow_auto_topic_creation,
+        include_cluster_authorized_operations: false,
+        include_topic_authorized_operations: false,
+        tagged_fields: BTreeMap::new(),
+    };
+    let mut state = ConnectionState::new(Arc::new(MessageRegistry::new()));
+    handler.handle(&header, &request, &mut state).await.unwrap()
+}
+
+/// The name and error of each topic in `response`.
+fn topics(response: &MetadataResponse) -> Vec<(&str, ErrorCode)> {
+    response
+        .topics
+        .iter()
+        .map(|topic| (topic.name.as_str(), topic.error_code))
+        .collect()
+}
+
+#[tokio::test]
+async fn null_topics_lists_every_topic() {
+    let (logs, handler) = handler(false, vec![]);
+    logs.append("events", 0, common::batch(&[b"a"], 0)).unwrap();
+    logs.append("orders", 0, common::batch(&[b"a"], 0)).unwrap();
+
+    let response = metadata(&handler, None, false).await;
+    assert_eq!(
+        topics(&response),
+        [("events", ErrorCode::None), ("orders", ErrorCode::None)]
+    );
+
+    // An empty list asks for no topics at all.
+    let response = metadata(&handler, Some(&[]), false).await;
+    assert!(response.topics.is_empty());
+}
+
+#[tokio::test]
+async fn only_requested_topics_are_described() {
+    let (logs, handler) = handler(false, vec![]);
+    logs.append("events", 0, common::batch(&[b"a"], 0)).unwrap();
+    logs.append("orders", 0, common::batch(&[b"a"], 0)).unwrap();
+
+    let response = metadata(&handler, Some(&["orders"]), false).await;
+    assert_eq!(topics(&response), [("orders", ErrorCode::None)]);
+    assert_eq!(response.topics[0].partitions.len(), 1);
+}
+
+#[tokio::test]
+async fn unknown_topic_is_an_error_without_auto_creation() {
+    let (logs, handler) = handler(true, vec![]);
+
+    let response = metadata(&handler, Some(&["events"]), false).await;
+    assert_eq!(
+        topics(&response),
+        [("events", ErrorCode::UnknownTopicOrPartition)]
+    );
+    assert!(response.topics[0].partitions.is_empty());
+    assert!(logs.topics().is_empty());
+}
+
+#[tokio::test]
+async fn auto_created_topic_is_visible_to_later_requests() {
+    let (_, handler) = handler(true, vec![]);
+
+    let response = metadata(&handler, Some(&["events"]), true).await;
+    let [topic] = response.topics.as_slice() else {
+        panic!("expected one topic, got {}", response.topics.len());
+    };
+    assert_eq!(topic.error_code, ErrorCode::None);
+    assert_eq!(topic.name, "events");
+    let partitions: Vec<i32> = topic
+        .partitions
+        .iter()
+        .map(|partition| partition.partition_index)
+        .collect();
+    assert_eq!(partitions, [0, 1, 2]);
+    let topic_id = topic.topic_id;
+
+    // The topic now exists, so later requests see it without creating it
+    // again, including ones listing every topic.
+    let response = metadata(&handler, Some(&["events"]), false).await;
+    assert_eq!(topics(&response), [("events", ErrorCode::None)]);
+    assert_eq!(response.topics[0].topic_id, topic_id);
+    assert_eq!(response.topics[0].partitions.len(), 3);
+    let response = metadata(&handler, None, false).await;
+    assert_eq!(topics(&response), [("events", ErrorCode::None)]);
+}
+
+#[tokio::test]
+async fn configured_brokers_are_reported() {
+    let (_, handler) = handler(
+        false,
+        vec![BrokerInfo {
+            rack: "rack-1".to_string(),
+            ..broker(1)
+        }],
+    );
+
+    let response = metadata(&handler, Some(&[]), false).await;
+    let brokers: Vec<_> = response
+        .brokers
+        .iter()
+        .map(|broker| (broker.node_id, broker.host.as_str(), broker.rack.as_str()))
+        .collect();
+    assert_eq!(brokers, [(0, "broker-0", ""), (1, "broker-1", "rack-1")]);
+    assert_eq!(response.controller_id, 0);
+}
+
+#[tokio::test]
+async fn partition_leadership_is_spread_across_brokers() {
+    let (logs, handler) = handler(false, vec![broker(1)]);
+    for partition in 0..4 {
+        logs.append("events", partition, common::batch(&[b"a"], 0))
+            .unwrap();
+    }
+
+    let response = metadata(&handler, Some(&["events"]), false).await;
+    let leaders: Vec<_> = response.topics[0]
+        .partitions
+        .iter()
+        .map(|partition| {
+            assert_eq!(partition.replica_nodes, [partition.leader_id]);
+            (partition.partition_index, partition.leader_id)
+        })
+        .collect();
+    assert_eq!(leaders, [(0, 0), (1, 1), (2, 0), (3, 1)]);
+}
+
+#[test]
+fn single_broker_partition_is_led_by_the_broker() {
+    let configs = Arc::new(ConfigStore::new(0, vec![]));
+    let logs = LogStore::open(None, false, configs).unwrap();
+    logs.append("events", 2, common::batch(&[b"a"], 0)).unwrap();
+    let log = logs.partition("events", 2).unwrap();
+
+    let partition = MetadataResponseTopicPartition::single_broker(2, Some(log.as_ref()), 7);
+    assert_eq!(partition.error_code, ErrorCode::None);
+    assert_eq!(partition.partition_index, 2);
+    assert_eq!(partition.leader_id, 7);
+    assert_eq!(partition.leader_epoch, LEADER_EPOCH);
+    assert_eq!(partition.replica_nodes, [7]);
+    assert_eq!(partition.isr_nodes, [7]);
+    assert!(partition.offline_replicas.is_empty());
+
+    let missing = MetadataResponseTopicPartition::single_broker(3, None, 7);
+    assert_eq!(missing.error_code, ErrorCode::UnknownTopicOrPartition);
+    assert_eq!((missing.leader_id, missing.leader_epoch), (-1, -1));
+    assert!(missing.replica_nodes.is_empty());
+}
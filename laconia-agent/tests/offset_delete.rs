@@ -0,0 +1,107 @@
+//! Drives OffsetDelete through the in-process test client. Only built with
+//! the `test-util` feature, which exposes the client.
+
+use std::sync::Arc;
+
+use laconia_agent::{
+    offsets::{CommittedOffset, OffsetStore},
+    protocol::{
+        api_keys::ApiKey,
+        error::ErrorCode,
+        handlers::OffsetDeleteHandler,
+        messages::{OffsetDeleteRequest, OffsetDeleteRequestPartition, OffsetDeleteRequestTopic},
+    },
+    server::KafkaServerBuilder,
+    test_client::TestClient,
+};
+
+fn delete_request(group_id: &str, topic: &str, partition: i32) -> OffsetDeleteRequest {
+    OffsetDeleteRequest {
+        group_id: group_id.to_string(),
+        topics: vec![OffsetDeleteRequestTopic {
+            name: topic.to_string(),
+            partitions: vec![OffsetDeleteRequestPartition {
+                partition_index: partition,
+            }],
+        }],
+    }
+}
+
+#[tokio::test]
+async fn committed_offset_is_deleted() {
+    let offsets = Arc::new(OffsetStore::new());
+    let committed = CommittedOffset {
+        offset: 42,
+        leader_epoch: -1,
+        metadata: String::new(),
+    };
+    offsets.commit("readers", "events", 0, committed.clone());
+    offsets.commit("readers", "events", 1, committed.clone());
+
+    let server = KafkaServerBuilder::new()
+        .register(
+            ApiKey::OffsetDelete,
+            OffsetDeleteHandler::new(offsets.clone()),
+        )
+        .build()
+        .await
+        .unwrap();
+
+    let (client, stream) = tokio::io::duplex(4096);
+    server.serve(stream).await.unwrap();
+    let mut client = TestClient::new(client);
+
+    for version in 0..=1 {
+        let response = client
+            .send(
+                ApiKey::OffsetDelete,
+                version,
+                &delete_request("readers", "events", 0),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.error_code, ErrorCode::None);
+        let [topic] = response.topics.as_slice() else {
+            panic!("expected one topic, got {:?}", response.topics);
+        };
+        assert_eq!(topic.name, "events");
+        let [partition] = topic.partitions.as_slice() else {
+            panic!("expected one partition, got {:?}", topic.partitions);
+        };
+        assert_eq!(partition.partition_index, 0);
+        assert_eq!(partition.error_code, ErrorCode::None);
+    }
+
+    // A fetch of the deleted offset finds nothing, which OffsetFetch reports
+    // as offset -1. Other partitions keep theirs.
+    assert_eq!(offsets.fetch("readers", "events", 0), None);
+    assert_eq!(offsets.fetch("readers", "events", 1), Some(committed));
+}
+
+#[tokio::test]
+async fn unknown_group_is_not_found() {
+    let server = KafkaServerBuilder::new()
+        .register(
+            ApiKey::OffsetDelete,
+            OffsetDeleteHandler::new(Arc::new(OffsetStore::new())),
+        )
+        .build()
+        .await
+        .unwrap();
+
+    let (client, stream) = tokio::io::duplex(4096);
+    server.serve(stream).await.unwrap();
+    let mut client = TestClient::new(client);
+
+    let response = client
+        .send(
+            ApiKey::OffsetDelete,
+            1,
+            &delete_request("nobody", "events", 0),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.error_code, ErrorCode::GroupIdNotFound);
+    assert!(response.topics.is_empty());
+}
@@ -0,0 +1,89 @@
+//! Checks OffsetForLeaderEpoch answers with the end offset of the requested
+//! epoch, which for the single epoch a partition has is the end of its log.
+
+mod common;
+
+use std::{collections::BTreeMap, sync::Arc};
+
+use laconia_agent::{
+    ConnectionState, RequestHeader,
+    configs::ConfigStore,
+    log::{LEADER_EPOCH, LogStore},
+    protocol::{
+        error::ErrorCode,
+        handlers::{OffsetForLeaderEpochHandler, RequestHandler},
+        messages::{
+            EpochEndOffset, OffsetForLeaderEpochRequest, OffsetForLeaderPartition,
+            OffsetForLeaderTopic,
+        },
+        registry::MessageRegistry,
+    },
+};
+
+/// Asks for the end offset of `leader_epoch` of each of `partitions` of
+/// `events`.
+async fn end_offsets(
+    handler: &OffsetForLeaderEpochHandler,
+    leader_epoch: i32,
+    partitions: &[i32],
+) -> Vec<EpochEndOffset> {
+    let header = RequestHeader {
+        api_key: 23,
+        version: 4,
+        correlation_id: 0,
+        client_id: "laconia-tests".to_string(),
+        tagged_fields: BTreeMap::new(),
+    };
+    let request = OffsetForLeaderEpochRequest {
+        replica_id: -1,
+        topics: vec![OffsetForLeaderTopic {
+            topic: "events".to_string(),
+            partitions: partitions
+                .iter()
+                .map(|&partition| OffsetForLeaderPartition {
+                    partition,
+                    current_leader_epoch: -1,
+                    leader_epoch,
+                    tagged_fields: BTreeMap::new(),
+                })
+                .collect(),
+            tagged_fields: BTreeMap::new(),
+        }],
+        tagged_fields: BTreeMap::new(),
+    };
+
+    let mut state = ConnectionState::new(Arc::new(MessageRegistry::new()));
+    let mut response = handler.handle(&header, &request, &mut state).await.unwrap();
+    response.topics.remove(0).partitions
+}
+
+#[tokio::test]
+async fn end_offset_is_the_end_of_the_log() {
+    let configs = Arc::new(ConfigStore::new(0, vec![]));
+    let logs = Arc::new(LogStore::open(None, false, configs).unwrap());
+    logs.append("events", 0, common::batch(&[b"a", b"b"], 0))
+        .unwrap();
+    logs.append("events", 0, common::batch(&[b"c"], 0)).unwrap();
+    let handler = OffsetForLeaderEpochHandler::new(logs);
+
+    let [partition, unknown] = end_offsets(&handler, LEADER_EPOCH, &[0, 1])
+        .await
+        .try_into()
+        .ok()
+        .unwrap();
+    assert_eq!(partition.error_code, ErrorCode::None);
+    assert_eq!(partition.leader_epoch, LEADER_EPOCH);
+    assert_eq!(partition.end_offset, 3);
+    assert_eq!(unknown.error_code, ErrorCode::UnknownTopicOrPartition);
+
+    // A later epoch hasn't started, so it also ends at the end of the log.
+    let [partition] = end_offsets(&handler, LEADER_EPOCH + 1, &[0])
+        .await
+        .try_into()
+        .ok()
+        .unwrap();
+    assert_eq!(
+        (partition.leader_epoch, partition.end_offset),
+        (LEADER_EPOCH, 3)
+    );
+}
@@ -0,0 +1,377 @@
+//! Checks the encoding and decoding helpers shared by the protocol's messages.
+
+use std::collections::BTreeMap;
+
+use bytes::{BufMut, Bytes, BytesMut};
+use laconia_agent::{
+    log::RecordBatch,
+    protocol::{
+        DecodeError, Decoder, DecoderVersioned, Encoder, EncoderVersioned,
+        messages::{MetadataRequest, MetadataRequestTopic},
+        primitives::{
+            CheckedGet, CompactArray, CompactArrayRef, CompactNullableArray,
+            CompactNullableArrayRef, CompactNullableString, CompactStr, CompactString,
+            MAX_STRING_LEN, NullableString, Str, TaggedFields, decode_tagged_if_flexible,
+            with_size_prefix,
+        },
+    },
+};
+
+#[test]
+fn tagged_fields_are_encoded_after_the_fields_in_tag_order() {
+    let mut request = MetadataRequest {
+        topics: None,
+        allow_auto_topic_creation: true,
+        include_cluster_authorized_operations: false,
+        include_topic_authorized_operations: false,
+        tagged_fields: BTreeMap::new(),
+    };
+    request
+        .tagged_fields
+        .encode_tag(1, &CompactString("eu".to_string()))
+        .unwrap();
+    request.tagged_fields.encode_tag(0, &5i32).unwrap();
+
+    let mut buf = BytesMut::new();
+    request.encode(&mut buf, 12).unwrap();
+    #[rustfmt::skip]
+    assert_eq!(
+        &buf[..],
+        [
+            // Null topics, then the two flags.
+            0,
+            1,
+            0,
+            // Two tagged fields, each a tag, a length and the value.
+            2,
+            0, 4, 0, 0, 0, 5,
+            1, 3, 3, b'e', b'u',
+        ]
+    );
+
+    let decoded = MetadataRequest::decode(&mut buf, 12).unwrap();
+    assert_eq!(
+        decoded.tagged_fields.decode_tag::<i32>(0).unwrap().unwrap(),
+        5
+    );
+}
+
+#[test]
+fn huge_tagged_field_count_is_invalid() {
+    // u32::MAX tagged fields, followed by a single byte.
+    let mut buf = BytesMut::from(&[0xff, 0xff, 0xff, 0xff, 0x0f, 0][..]);
+    let err = BTreeMap::<i32, Bytes>::decode(&mut buf).unwrap_err();
+    assert!(matches!(
+        DecodeError::from_io(&err),
+        Some(DecodeError::Invalid(_))
+    ));
+}
+
+#[test]
+fn checked_reads_from_an_empty_buffer_are_incomplete() {
+    let mut buf = BytesMut::new();
+
+    let errors = [
+        buf.checked_get_i8().unwrap_err(),
+        buf.checked_get_u8().unwrap_err(),
+        buf.checked_get_i16().unwrap_err(),
+        buf.checked_get_i32().unwrap_err(),
+        buf.checked_get_u32().unwrap_err(),
+        buf.checked_get_i64().unwrap_err(),
+        buf.checked_get_uvarint().unwrap_err(),
+        buf.checked_split_to(1).unwrap_err(),
+    ];
+    for err in errors {
+        assert!(
+            matches!(err, DecodeError::Incomplete { .. }),
+            "expected an incomplete error, got {}",
+            err
+        );
+    }
+}
+
+#[test]
+fn checked_reads_report_how_much_is_missing() {
+    let mut buf = BytesMut::from(&[0, 1][..]);
+    assert!(matches!(
+        buf.checked_get_i64(),
+        Err(DecodeError::Incomplete { needed: 6 })
+    ));
+
+    // A failed read leaves the buffer as it was.
+    assert_eq!(buf.checked_get_i16().unwrap(), 1);
+}
+
+#[test]
+fn integers_round_trip() {
+    let mut buf = BytesMut::new();
+    (-2i32).encode(&mut buf).unwrap();
+    i64::MAX.encode(&mut buf).unwrap();
+    assert_eq!(i32::decode(&mut buf).unwrap(), -2);
+    assert_eq!(i64::decode(&mut buf).unwrap(), i64::MAX);
+
+    // Too short a buffer is an error rather than a panic.
+    let mut buf = BytesMut::from(&[0, 0, 1][..]);
+    assert!(i32::decode(&mut buf).is_err());
+    assert!(i64::decode(&mut buf).is_err());
+}
+
+#[test]
+fn replica_lists_decode_as_compact_i32_arrays() {
+    let mut buf = BytesMut::new();
+    Encoder::encode(&CompactArrayRef(&[0i32, 1, 2]), &mut buf).unwrap();
+    assert_eq!(&buf[..], [4, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 2]);
+
+    assert_eq!(CompactArray::<i32>::decode(&mut buf).unwrap().0, [0, 1, 2]);
+    assert!(buf.is_empty());
+}
+
+#[test]
+fn compact_nullable_array_tells_null_from_empty() {
+    let mut buf = BytesMut::new();
+    CompactNullableArrayRef::<MetadataRequestTopic>(None)
+        .encode(&mut buf, 12)
+        .unwrap();
+    CompactNullableArrayRef::<MetadataRequestTopic>(Some(&[]))
+        .encode(&mut buf, 12)
+        .unwrap();
+    assert_eq!(&buf[..], [0, 1]);
+
+    assert_eq!(
+        CompactNullableArray::<i16>::decode(&mut buf).unwrap().0,
+        None
+    );
+    assert_eq!(
+        CompactNullableArray::<i16>::decode(&mut buf).unwrap().0,
+        Some(vec![])
+    );
+}
+
+#[test]
+fn compact_array_rejects_null() {
+    let mut buf = BytesMut::from(&[0][..]);
+    assert!(CompactArray::<i16>::decode(&mut buf).is_err());
+
+    let mut buf = BytesMut::from(&[1][..]);
+    assert!(CompactArray::<i16>::decode(&mut buf).unwrap().0.is_empty());
+}
+
+#[test]
+fn metadata_request_tells_all_topics_from_none() {
+    // v12: topics, allow_auto_topic_creation,
+    // include_topic_authorized_operations, tagged fields.
+    let mut all_topics = BytesMut::from(&[0, 1, 0, 0][..]);
+    let request = MetadataRequest::decode(&mut all_topics, 12).unwrap();
+    assert!(request.topics.is_none());
+
+    let mut no_topics = BytesMut::from(&[1, 1, 0, 0][..]);
+    let request = MetadataRequest::decode(&mut no_topics, 12).unwrap();
+    assert!(request.topics.unwrap().is_empty());
+}
+
+#[test]
+fn str_borrows_the_buffer() {
+    let mut buf = BytesMut::new();
+    buf.put_i16(6);
+    buf.extend_from_slice(b"events");
+    buf.put_u8(7);
+    buf.extend_from_slice(b"topics");
+    let start = buf.as_ptr();
+
+    let str = Str::decode(&mut buf).unwrap();
+    assert_eq!(&*str, "events");
+    assert_eq!(str.as_ptr(), start.wrapping_add(2));
+
+    let compact = CompactStr::decode(&mut buf).unwrap();
+    assert_eq!(&*compact.0, "topics");
+    assert_eq!(compact.0.as_ptr(), start.wrapping_add(9));
+}
+
+#[test]
+fn str_rejects_invalid_utf8() {
+    let mut buf = BytesMut::new();
+    buf.put_i16(2);
+    buf.extend_from_slice(&[0xc3, 0x28]);
+
+    let err = Str::decode(&mut buf).err().unwrap();
+    assert!(matches!(
+        DecodeError::from_io(&err),
+        Some(DecodeError::Invalid(_))
+    ));
+}
+
+#[test]
+fn strings_round_trip() {
+    let mut buf = BytesMut::new();
+    "laconia".to_string().encode(&mut buf).unwrap();
+    String::new().encode(&mut buf).unwrap();
+    assert_eq!(&buf[..], b"\0\x07laconia\0\0");
+    assert_eq!(String::decode(&mut buf).unwrap(), "laconia");
+    assert_eq!(String::decode(&mut buf).unwrap(), "");
+
+    let err = "a"
+        .repeat(i16::MAX as usize + 1)
+        .encode(&mut BytesMut::new())
+        .unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+}
+
+#[test]
+fn nullable_strings_round_trip_null_as_empty() {
+    let mut buf = BytesMut::new();
+    NullableString("laconia".to_string())
+        .encode(&mut buf)
+        .unwrap();
+    NullableString(String::new()).encode(&mut buf).unwrap();
+    assert_eq!(&buf[..], b"\0\x07laconia\xff\xff");
+    assert_eq!(NullableString::decode(&mut buf).unwrap().0, "laconia");
+    assert_eq!(NullableString::decode(&mut buf).unwrap().0, "");
+
+    // A non-null empty string decodes the same as null.
+    let mut buf = BytesMut::from(&[0, 0][..]);
+    assert_eq!(NullableString::decode(&mut buf).unwrap().0, "");
+}
+
+#[test]
+fn truncated_strings_are_incomplete() {
+    // Two bytes announced, one present.
+    let mut buf = BytesMut::from(&[0, 2, b'a'][..]);
+    let err = String::decode(&mut buf).unwrap_err();
+    assert!(matches!(
+        DecodeError::from_io(&err),
+        Some(DecodeError::Incomplete { needed: 1 })
+    ));
+
+    let mut buf = BytesMut::from(&[3, b'a'][..]);
+    let err = CompactString::decode(&mut buf).err().unwrap();
+    assert!(matches!(
+        DecodeError::from_io(&err),
+        Some(DecodeError::Incomplete { .. })
+    ));
+}
+
+#[test]
+fn strings_reject_invalid_utf8() {
+    let mut buf = BytesMut::from(&[0, 2, 0xc3, 0x28][..]);
+    let err = String::decode(&mut buf).unwrap_err();
+    assert!(matches!(
+        DecodeError::from_io(&err),
+        Some(DecodeError::Invalid(_))
+    ));
+
+    let mut buf = BytesMut::from(&[3, 0xc3, 0x28][..]);
+    let err = CompactString::decode(&mut buf).err().unwrap();
+    assert!(matches!(
+        DecodeError::from_io(&err),
+        Some(DecodeError::Invalid(_))
+    ));
+}
+
+/// A buffer holding just the unsigned varint `value`, as a compact string's
+/// length prefix.
+fn uvarint(mut value: u32) -> BytesMut {
+    let mut buf = BytesMut::new();
+    while value >= 0x80 {
+        buf.put_u8(value as u8 | 0x80);
+        value >>= 7;
+    }
+    buf.put_u8(value as u8);
+    buf
+}
+
+/// Decodes `buf` as each compact string type, returning their errors.
+fn compact_string_errors(buf: &BytesMut) -> [std::io::Error; 3] {
+    [
+        CompactString::decode(&mut buf.clone()).err().unwrap(),
+        CompactStr::decode(&mut buf.clone()).err().unwrap(),
+        CompactNullableString::decode(&mut buf.clone())
+            .err()
+            .unwrap(),
+    ]
+}
+
+#[test]
+fn compact_strings_of_4gb_are_invalid() {
+    for err in compact_string_errors(&uvarint(u32::MAX)) {
+        assert!(
+            matches!(DecodeError::from_io(&err), Some(DecodeError::Invalid(_))),
+            "{}",
+            err
+        );
+    }
+}
+
+#[test]
+fn compact_strings_longer_than_the_maximum_are_invalid() {
+    // A compact string's length is encoded plus one.
+    for err in compact_string_errors(&uvarint(MAX_STRING_LEN as u32 + 2)) {
+        assert!(
+            matches!(DecodeError::from_io(&err), Some(DecodeError::Invalid(_))),
+            "{}",
+            err
+        );
+    }
+
+    // The longest string allowed is only short of bytes.
+    for err in compact_string_errors(&uvarint(MAX_STRING_LEN as u32 + 1)) {
+        assert!(
+            matches!(
+                DecodeError::from_io(&err),
+                Some(DecodeError::Incomplete { needed }) if *needed == MAX_STRING_LEN
+            ),
+            "{}",
+            err
+        );
+    }
+}
+
+/// One tagged field, tag 1 holding `0xab`, followed by a byte that isn't
+/// part of the tagged fields.
+const TAGGED_FIELDS: &[u8] = &[1, 1, 1, 0xab, 0xff];
+
+#[test]
+fn tagged_fields_are_not_read_below_the_flexible_version() {
+    let mut buf = BytesMut::from(TAGGED_FIELDS);
+    let tagged_fields = decode_tagged_if_flexible(&mut buf, 2, 3).unwrap();
+
+    assert!(tagged_fields.is_empty());
+    assert_eq!(&buf[..], TAGGED_FIELDS);
+}
+
+#[test]
+fn tagged_fields_are_read_from_the_flexible_version() {
+    for version in [3, 4] {
+        let mut buf = BytesMut::from(TAGGED_FIELDS);
+        let tagged_fields = decode_tagged_if_flexible(&mut buf, version, 3).unwrap();
+
+        assert_eq!(
+            tagged_fields.into_iter().collect::<Vec<_>>(),
+            [(1, Bytes::from_static(&[0xab]))]
+        );
+        assert_eq!(&buf[..], [0xff]);
+    }
+}
+
+#[test]
+fn size_prefix_is_backfilled_with_the_body_length() {
+    // Whatever comes before the prefix is left alone.
+    let mut buf = BytesMut::from(&[0xff][..]);
+    with_size_prefix(&mut buf, |buf| {
+        buf.put_i64(7);
+        buf.extend_from_slice(b"body");
+        Ok(())
+    })
+    .unwrap();
+
+    assert_eq!(buf[0], 0xff);
+    let size = i32::from_be_bytes(buf[1..5].try_into().unwrap());
+    assert_eq!(size as usize, buf.len() - 5);
+    assert_eq!(size, 12);
+}
+
+#[test]
+fn transaction_marker_has_a_matching_batch_length() {
+    let marker = RecordBatch::transaction_marker(1, 0, true, 0);
+    let batch = RecordBatch::new(marker.as_bytes().clone()).unwrap();
+    batch.verify_crc().unwrap();
+}
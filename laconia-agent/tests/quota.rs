@@ -0,0 +1,75 @@
+//! Checks request quotas against a manually advanced clock, and that clients
+//! bursting past theirs are told they're throttled. Only built with the
+//! `test-util` feature, which exposes the test clock and client.
+
+use std::{io, sync::Arc, time::Duration};
+
+use laconia_agent::{
+    clock::TestClock,
+    protocol::{api_keys::ApiKey, handlers::ApiVersionsHandler, messages::ApiVersionsRequest},
+    quota::QuotaManager,
+    server::KafkaServerBuilder,
+    test_client::TestClient,
+};
+
+#[test]
+fn rates_the_buckets_cant_refill_at_are_rejected() {
+    for rate in [0.0, -1.0, f64::NAN, f64::INFINITY] {
+        let err = QuotaManager::new(Some(rate)).err().unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput, "rate {}", rate);
+    }
+}
+
+#[test]
+fn positive_rates_and_no_quota_are_accepted() {
+    assert!(QuotaManager::new(Some(0.5)).is_ok());
+    assert!(QuotaManager::new(None).is_ok());
+}
+
+#[test]
+fn throttle_expires_as_the_clock_advances() {
+    let clock = Arc::new(TestClock::new());
+    let quotas = QuotaManager::new(Some(1.0))
+        .unwrap()
+        .with_clock(clock.clone());
+
+    // The first request spends the one-second burst, so the second goes into
+    // debt and is throttled for as long as the bucket takes to refill.
+    assert_eq!(quotas.record("client"), Duration::ZERO);
+    assert_eq!(quotas.record("client"), Duration::from_secs(1));
+
+    // Other clients have buckets of their own.
+    assert_eq!(quotas.record("other"), Duration::ZERO);
+
+    clock.advance(Duration::from_secs(2));
+    assert_eq!(quotas.record("client"), Duration::ZERO);
+}
+
+#[tokio::test]
+async fn bursting_past_the_rate_is_throttled() {
+    let server = KafkaServerBuilder::new()
+        .register(ApiKey::ApiVersions, ApiVersionsHandler)
+        .quotas(QuotaManager::new(Some(10.0)).unwrap())
+        .build()
+        .await
+        .unwrap();
+
+    let (client, stream) = tokio::io::duplex(4096);
+    server.serve(stream).await.unwrap();
+    let mut client = TestClient::new(client);
+
+    let request = ApiVersionsRequest {
+        client_software_name: "laconia-tests".to_string(),
+        client_software_version: "0.1.0".to_string(),
+        tagged_fields: Default::default(),
+    };
+
+    // The first second's worth of requests fit the burst.
+    for _ in 0..10 {
+        let response = client.send(ApiKey::ApiVersions, 3, &request).await.unwrap();
+        assert_eq!(response.throttle_time_ms, 0);
+    }
+
+    let response = client.send(ApiKey::ApiVersions, 3, &request).await.unwrap();
+    assert!(response.throttle_time_ms > 0);
+}
@@ -0,0 +1,158 @@
+//! End-to-end tests driving the server with librdkafka, checking it decodes,
+//! handles and encodes requests the way a real client expects. Only built
+//! with the `rdkafka-tests` feature, since librdkafka is a C library.
+
+use std::{
+    net::{Ipv4Addr, SocketAddr, TcpListener},
+    sync::Arc,
+    time::Duration,
+};
+
+use laconia_agent::{
+    cluster::{BrokerInfo, BrokerRegistry, ClusterInfo},
+    configs::ConfigStore,
+    log::LogStore,
+    server::{BrokerState, KafkaServerBuilder},
+};
+use rdkafka::{
+    ClientConfig, Message, Offset, TopicPartitionList,
+    consumer::{BaseConsumer, Consumer},
+    producer::{BaseProducer, BaseRecord, Producer},
+};
+
+/// Starts a server with a peer broker and a two-partition topic, returning
+/// the address it listens on.
+async fn start_server() -> SocketAddr {
+    // The advertised port must be known before the server is built.
+    let addr = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))
+        .and_then(|listener| listener.local_addr())
+        .unwrap();
+
+    let configs = Arc::new(ConfigStore::new(0, vec![]));
+    let logs = Arc::new(LogStore::open(None, false, configs.clone()).unwrap());
+    for partition in 0..2 {
+        logs.get_or_create("events", partition).unwrap();
+    }
+
+    let brokers = BrokerRegistry::new(
+        BrokerInfo {
+            node_id: 0,
+            host: addr.ip().to_string(),
+            port: addr.port() as i32,
+            rack: String::new(),
+        },
+        vec![BrokerInfo {
+            node_id: 1,
+            host: "peer.example".to_string(),
+            port: 9092,
+            rack: String::new(),
+        }],
+    );
+
+    let cluster = Arc::new(ClusterInfo::new("laconia-test", brokers));
+    let state = BrokerState::new(cluster, configs, logs);
+
+    let server = KafkaServerBuilder::with_default_handlers(state)
+        .bind(addr)
+        .build()
+        .await
+        .unwrap();
+    tokio::spawn(async move {
+        loop {
+            server.accept().await.unwrap();
+        }
+    });
+
+    addr
+}
+
+#[tokio::test]
+async fn fetches_metadata() {
+    let addr = start_server().await;
+
+    let (mut brokers, topics) = tokio::task::spawn_blocking(move || {
+        let consumer = ClientConfig::new()
+            .set("bootstrap.servers", addr.to_string())
+            .create::<BaseConsumer>()
+            .unwrap();
+        let metadata = consumer
+            .fetch_metadata(None, Duration::from_secs(10))
+            .unwrap();
+
+        let brokers: Vec<_> = metadata
+            .brokers()
+            .iter()
+            .map(|broker| (broker.id(), broker.host().to_string(), broker.port()))
+            .collect();
+        let topics: Vec<_> = metadata
+            .topics()
+            .iter()
+            .map(|topic| {
+                let partitions: Vec<_> = topic
+                    .partitions()
+                    .iter()
+                    .map(|partition| (partition.id(), partition.leader()))
+                    .collect();
+                (topic.name().to_string(), partitions)
+            })
+            .collect();
+        (brokers, topics)
+    })
+    .await
+    .unwrap();
+
+    brokers.sort();
+    assert_eq!(
+        brokers,
+        vec![
+            (0, addr.ip().to_string(), addr.port() as i32),
+            (1, "peer.example".to_string(), 9092),
+        ]
+    );
+    assert_eq!(topics, vec![("events".to_string(), vec![(0, 0), (1, 1)])]);
+}
+
+#[tokio::test]
+async fn compressed_batches_round_trip() {
+    for codec in ["gzip", "snappy", "lz4", "zstd"] {
+        let addr = start_server().await;
+
+        let payload = tokio::task::spawn_blocking(move || {
+            let producer = ClientConfig::new()
+                .set("bootstrap.servers", addr.to_string())
+                .set("compression.codec", codec)
+                .create::<BaseProducer>()
+                .unwrap();
+            producer
+                .send(
+                    BaseRecord::<(), [u8]>::to("events")
+                        .partition(0)
+                        .payload(&b"compressed"[..]),
+                )
+                .map_err(|(err, _)| err)
+                .unwrap();
+            producer.flush(Duration::from_secs(10)).unwrap();
+
+            let consumer = ClientConfig::new()
+                .set("bootstrap.servers", addr.to_string())
+                .set("group.id", "laconia-tests")
+                .create::<BaseConsumer>()
+                .unwrap();
+            let mut assignment = TopicPartitionList::new();
+            assignment
+                .add_partition_offset("events", 0, Offset::Beginning)
+                .unwrap();
+            consumer.assign(&assignment).unwrap();
+
+            let message = consumer
+                .poll(Duration::from_secs(10))
+                .expect("a message within the timeout")
+                .unwrap();
+            message.payload().map(<[u8]>::to_vec)
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(payload.as_deref(), Some(&b"compressed"[..]), "{}", codec);
+    }
+}
@@ -0,0 +1,45 @@
+//! Checks registering a second handler for an api key is caught, unless it's
+//! meant to replace the first.
+
+use std::io;
+
+use laconia_agent::{
+    protocol::{api_keys::ApiKey, handlers::ApiVersionsHandler, registry::MessageRegistry},
+    server::KafkaServerBuilder,
+};
+
+#[test]
+fn double_registration_is_an_error() {
+    let mut registry = MessageRegistry::new();
+    registry
+        .register(ApiKey::ApiVersions, ApiVersionsHandler)
+        .unwrap();
+
+    let err = registry
+        .register(ApiKey::ApiVersions, ApiVersionsHandler)
+        .unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::AlreadyExists);
+
+    registry.register_override(ApiKey::ApiVersions, ApiVersionsHandler);
+    assert!(registry.versions(ApiKey::ApiVersions).is_ok());
+}
+
+#[tokio::test]
+async fn double_registration_fails_the_build() {
+    let result = KafkaServerBuilder::new()
+        .register(ApiKey::ApiVersions, ApiVersionsHandler)
+        .register(ApiKey::ApiVersions, ApiVersionsHandler)
+        .build()
+        .await;
+    assert_eq!(
+        result.err().map(|err| err.kind()),
+        Some(io::ErrorKind::AlreadyExists)
+    );
+
+    let result = KafkaServerBuilder::new()
+        .register(ApiKey::ApiVersions, ApiVersionsHandler)
+        .register_override(ApiKey::ApiVersions, ApiVersionsHandler)
+        .build()
+        .await;
+    assert!(result.is_ok());
+}
@@ -0,0 +1,50 @@
+//! Checks v0 request headers, which predate the client id, are decoded
+//! without reading one.
+
+use std::sync::{Arc, atomic::AtomicBool};
+
+use bytes::{BufMut, BytesMut};
+use laconia_agent::{
+    KafkaRequestCodec,
+    cluster::{BrokerInfo, ClusterInfo},
+    protocol::{api_keys::ApiKey, handlers::ControlledShutdownHandler, registry::MessageRegistry},
+};
+use tokio_util::codec::Decoder;
+
+#[test]
+fn v0_header_has_no_client_id() {
+    let cluster = Arc::new(ClusterInfo::single_broker(
+        "laconia",
+        BrokerInfo {
+            node_id: 0,
+            host: "localhost".to_string(),
+            port: 9092,
+            rack: String::new(),
+        },
+    ));
+    let mut registry = MessageRegistry::new();
+    registry
+        .register(
+            ApiKey::ControlledShutdown,
+            ControlledShutdownHandler::new(cluster, Arc::new(AtomicBool::new(false))),
+        )
+        .unwrap();
+    let mut codec = KafkaRequestCodec::new(Arc::new(registry));
+
+    // ControlledShutdown v0 uses header v0: api key, version and correlation
+    // id, followed straight away by the body's broker id.
+    let mut src = BytesMut::new();
+    src.put_i32(12);
+    src.put_i16(7);
+    src.put_i16(0);
+    src.put_i32(9);
+    src.put_i32(1);
+
+    let (header, body) = codec.decode(&mut src).unwrap().unwrap();
+    assert_eq!(header.api_key, 7);
+    assert_eq!(header.version, 0);
+    assert_eq!(header.correlation_id, 9);
+    assert_eq!(header.client_id, "");
+    assert!(header.tagged_fields.is_empty());
+    assert_eq!(&body[..], 1i32.to_be_bytes());
+}
@@ -0,0 +1,46 @@
+//! Checks messages encode and decode to the same bytes at every version.
+//! Only built with the `roundtrip` feature, which exposes the helper.
+
+use std::collections::BTreeMap;
+
+use laconia_agent::{
+    Message,
+    protocol::{
+        messages::{MetadataRequest, MetadataRequestTopic},
+        roundtrip::assert_roundtrip,
+    },
+};
+use uuid::Uuid;
+
+#[test]
+fn metadata_request_round_trips_at_every_version() {
+    let versions = MetadataRequest::VERSIONS;
+    for version in versions.min..=versions.max {
+        // Topics are looked up by name before v10, so the id isn't encoded.
+        let topic_id = if version >= 10 {
+            Uuid::new_v4()
+        } else {
+            Uuid::nil()
+        };
+
+        let request = MetadataRequest {
+            topics: Some(vec![MetadataRequestTopic {
+                topic_id,
+                name: "events".to_string(),
+                tagged_fields: BTreeMap::new(),
+            }]),
+            allow_auto_topic_creation: version >= 4,
+            include_cluster_authorized_operations: (8..11).contains(&version),
+            include_topic_authorized_operations: version >= 8,
+            tagged_fields: BTreeMap::new(),
+        };
+        assert_roundtrip(&request, version);
+
+        // A null topic array asks for every topic from v1.
+        let all_topics = MetadataRequest {
+            topics: None,
+            ..request
+        };
+        assert_roundtrip(&all_topics, version);
+    }
+}
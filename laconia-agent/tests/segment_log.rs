@@ -0,0 +1,91 @@
+//! Checks a partition log persisted to disk recovers its records when it's
+//! opened again, and deletes the segments retention expires.
+
+mod common;
+
+use std::{
+    fs::{self, OpenOptions},
+    io::Write,
+};
+
+use laconia_agent::log::{PartitionLog, SegmentLog};
+
+#[test]
+fn records_survive_a_restart() {
+    let dir = common::temp_dir("laconia-segment-log");
+
+    let log = SegmentLog::open(&dir, 1024 * 1024).unwrap();
+    log.append(common::batch(&[b"a", b"b"], 0)).unwrap();
+    log.append(common::batch(&[b"c"], 0)).unwrap();
+    drop(log);
+
+    let log = SegmentLog::open(&dir, 1024 * 1024).unwrap();
+    assert_eq!((log.earliest_offset(), log.latest_offset()), (0, 3));
+    let values: Vec<_> = log
+        .read(0, usize::MAX)
+        .unwrap()
+        .iter()
+        .flat_map(common::values)
+        .collect();
+    assert_eq!(values, [&b"a"[..], b"b", b"c"]);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn partially_written_batch_is_truncated_on_recovery() {
+    let dir = common::temp_dir("laconia-segment-log");
+
+    let log = SegmentLog::open(&dir, 1024 * 1024).unwrap();
+    log.append(common::batch(&[b"a"], 0)).unwrap();
+    drop(log);
+
+    // A crash midway through appending leaves the start of a batch behind.
+    let partial = common::batch(&[b"b"], 0);
+    let segment = fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .find(|path| path.extension().is_some_and(|extension| extension == "log"))
+        .unwrap();
+    OpenOptions::new()
+        .append(true)
+        .open(&segment)
+        .unwrap()
+        .write_all(&partial.as_bytes()[..20])
+        .unwrap();
+
+    let log = SegmentLog::open(&dir, 1024 * 1024).unwrap();
+    assert_eq!(log.latest_offset(), 1);
+    assert_eq!(log.append(common::batch(&[b"c"], 0)).unwrap(), 1);
+    let values: Vec<_> = log
+        .read(0, usize::MAX)
+        .unwrap()
+        .iter()
+        .flat_map(common::values)
+        .collect();
+    assert_eq!(values, [&b"a"[..], b"c"]);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn expired_segments_are_removed_but_not_the_active_one() {
+    let dir = common::temp_dir("laconia-segment-log");
+
+    // Every batch fills a segment of its own.
+    let log = SegmentLog::open(&dir, 1).unwrap();
+    for timestamp in [1_000, 2_000, 3_000] {
+        log.append(common::batch(&[b"old"], timestamp)).unwrap();
+    }
+
+    assert_eq!(log.enforce_retention(100_000, 10_000, -1).unwrap(), 2);
+    assert_eq!((log.earliest_offset(), log.latest_offset()), (2, 3));
+    let segments = fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|extension| extension == "log"))
+        .count();
+    assert_eq!(segments, 1);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,80 @@
+//! Checks a server bound to TCP ports answers requests sent over each of
+//! them.
+
+mod common;
+
+use std::net::{Ipv4Addr, SocketAddr};
+
+use bytes::{Buf, Bytes, BytesMut};
+use futures::{SinkExt, StreamExt};
+use laconia_agent::{
+    protocol::{
+        api_keys::ApiKey, error::ErrorCode, handlers::ApiVersionsHandler,
+        registry::API_VERSIONS_KEY,
+    },
+    server::{KafkaServerBuilder, ListenerConfig, SecurityProtocol},
+};
+use tokio::net::TcpStream;
+use tokio_util::codec::{Framed, LengthDelimitedCodec};
+
+/// Sends `request` to `addr` on a new connection, returning the response.
+async fn exchange(addr: SocketAddr, request: Bytes) -> BytesMut {
+    let stream = TcpStream::connect(addr).await.unwrap();
+    let mut client = Framed::new(stream, LengthDelimitedCodec::new());
+    client.send(request).await.unwrap();
+    client.next().await.unwrap().unwrap()
+}
+
+#[tokio::test]
+async fn api_versions_over_tcp() {
+    let server = KafkaServerBuilder::new()
+        .register(ApiKey::ApiVersions, ApiVersionsHandler)
+        .bind((Ipv4Addr::LOCALHOST, 0).into())
+        .build()
+        .await
+        .unwrap();
+    let addr = server.local_addr().unwrap();
+    tokio::spawn(async move { server.accept().await });
+
+    let mut response = exchange(addr, common::api_versions_request(7)).await;
+
+    // ApiVersions responses always have a v0 header, with no tagged fields.
+    assert_eq!(response.get_i32(), 7);
+    assert_eq!(response.get_i16(), ErrorCode::None.as_i16());
+
+    // A compact array of (api key, min version, max version, tagged fields).
+    assert_eq!(response.get_u8(), 2);
+    assert_eq!(response.get_i16(), API_VERSIONS_KEY);
+}
+
+#[tokio::test]
+async fn api_versions_on_each_listener() {
+    let server = KafkaServerBuilder::new()
+        .register(ApiKey::ApiVersions, ApiVersionsHandler)
+        .listener(ListenerConfig::plaintext((Ipv4Addr::LOCALHOST, 0).into()))
+        .listener(ListenerConfig::new(
+            (Ipv4Addr::LOCALHOST, 0).into(),
+            SecurityProtocol::SaslPlaintext,
+        ))
+        .build()
+        .await
+        .unwrap();
+    let addrs = server.local_addrs().unwrap();
+    assert_eq!(addrs.len(), 2);
+    tokio::spawn(async move {
+        loop {
+            server.accept().await.unwrap();
+        }
+    });
+
+    for addr in addrs {
+        let mut response = exchange(addr, common::api_versions_request(1)).await;
+        assert_eq!(response.get_i32(), 1);
+        assert_eq!(
+            response.get_i16(),
+            ErrorCode::None.as_i16(),
+            "listener {}",
+            addr
+        );
+    }
+}
@@ -0,0 +1,100 @@
+//! Checks response size hints match what's encoded, and that encoding through
+//! the codec, which preallocates from the hint, gives the same bytes as
+//! encoding without one.
+
+use std::collections::BTreeMap;
+
+use bytes::{BufMut, BytesMut};
+use laconia_agent::{
+    KafkaMessageCodec, KafkaResponse, RequestHeader,
+    protocol::{
+        EncoderVersioned,
+        error::ErrorCode,
+        messages::{
+            MetadataResponse, MetadataResponseBrokers, MetadataResponseTopic,
+            MetadataResponseTopicPartition,
+        },
+    },
+};
+use tokio_util::codec::Encoder;
+use uuid::Uuid;
+
+fn metadata_response(topics: usize) -> MetadataResponse {
+    MetadataResponse {
+        throttle_time_ms: 0,
+        brokers: vec![MetadataResponseBrokers {
+            node_id: 0,
+            host: "localhost".to_string(),
+            port: 9092,
+            rack: String::new(),
+            tagged_fields: BTreeMap::new(),
+        }],
+        cluster_id: "laconia".to_string(),
+        controller_id: 0,
+        topics: (0..topics)
+            .map(|i| MetadataResponseTopic {
+                error_code: ErrorCode::None,
+                name: format!("topic-{}", i),
+                topic_id: Uuid::new_v4(),
+                is_internal: false,
+                partitions: (0..4)
+                    .map(|partition| MetadataResponseTopicPartition {
+                        error_code: ErrorCode::None,
+                        partition_index: partition,
+                        leader_id: 0,
+                        leader_epoch: 0,
+                        replica_nodes: vec![0],
+                        isr_nodes: vec![0],
+                        offline_replicas: vec![],
+                        tagged_fields: BTreeMap::new(),
+                    })
+                    .collect(),
+                topic_authorized_operations: i32::MIN,
+                tagged_fields: BTreeMap::new(),
+            })
+            .collect(),
+        tagged_fields: BTreeMap::new(),
+    }
+}
+
+#[test]
+fn metadata_response_size_hint_matches_its_encoding() {
+    for topics in [0, 1, 200] {
+        let response = metadata_response(topics);
+        let mut buf = BytesMut::new();
+        response.encode(&mut buf, 12).unwrap();
+        assert_eq!(response.size_hint(12), buf.len(), "{} topics", topics);
+    }
+}
+
+#[test]
+fn codec_encoding_matches_encoding_without_a_hint() {
+    let header = RequestHeader {
+        api_key: 3,
+        version: 12,
+        correlation_id: 3,
+        client_id: "laconia-tests".to_string(),
+        tagged_fields: BTreeMap::new(),
+    };
+    let response = metadata_response(20);
+
+    // The frame as encoded without preallocating anything.
+    let mut body = BytesMut::new();
+    response.encode(&mut body, 12).unwrap();
+    let mut expected = BytesMut::new();
+    expected.put_i32(0);
+    expected.put_i32(header.correlation_id);
+    expected.put_u8(0);
+    expected.extend_from_slice(&body);
+    let len = (expected.len() - 4) as i32;
+    expected[..4].copy_from_slice(&len.to_be_bytes());
+
+    let mut frame = BytesMut::new();
+    KafkaMessageCodec::new()
+        .encode(
+            KafkaResponse::new(&header, 12, Box::new(response)),
+            &mut frame,
+        )
+        .unwrap();
+    assert_eq!(frame, expected);
+}
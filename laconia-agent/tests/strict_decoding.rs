@@ -0,0 +1,114 @@
+//! Checks strict decoding rejects requests their decoder doesn't fully
+//! consume. Only built with the `test-util` feature, which exposes the test
+//! client.
+
+use std::io;
+
+use bytes::{BufMut, BytesMut};
+use laconia_agent::{
+    ConnectionState, DecoderVersioned, EncoderVersioned, Message, RequestHeader, VersionRange,
+    protocol::{
+        api_keys::ApiKey, error::ErrorCode, handlers::RequestHandler, request::Request,
+        response::Response,
+    },
+    server::{KafkaServer, KafkaServerBuilder},
+    test_client::TestClient,
+};
+
+/// The api key the request is registered under. SaslHandshake is never
+/// flexible, matching the header version the request uses.
+const UNDER_DECODING_KEY: ApiKey = ApiKey::SaslHandshake;
+
+/// A request whose decoder skips its body entirely, as a buggy decoder
+/// missing a field would.
+#[derive(Debug)]
+struct UnderDecodingRequest;
+
+impl Message for UnderDecodingRequest {
+    const VERSIONS: VersionRange = VersionRange { min: 0, max: 0 };
+    const DEPRECATED_VERSIONS: Option<VersionRange> = None;
+
+    fn header_version(_version: i16) -> i16 {
+        1
+    }
+}
+
+impl DecoderVersioned for UnderDecodingRequest {
+    fn decode(_buf: &mut BytesMut, _version: i16) -> Result<Self, io::Error> {
+        Ok(Self)
+    }
+}
+
+impl Request for UnderDecodingRequest {
+    type Response = ErrorResponse;
+
+    fn error_response(&self, error_code: ErrorCode) -> ErrorResponse {
+        Self::decode_error_response(error_code)
+    }
+
+    fn decode_error_response(error_code: ErrorCode) -> ErrorResponse {
+        ErrorResponse(error_code)
+    }
+}
+
+struct ErrorResponse(ErrorCode);
+
+impl EncoderVersioned for ErrorResponse {
+    fn encode(&self, buf: &mut BytesMut, _version: i16) -> Result<(), io::Error> {
+        buf.put_i16(self.0 as i16);
+        Ok(())
+    }
+}
+
+impl Response for ErrorResponse {}
+
+struct UnderDecodingHandler;
+
+impl RequestHandler<UnderDecodingRequest> for UnderDecodingHandler {
+    async fn handle(
+        &self,
+        _header: &RequestHeader,
+        _request: &UnderDecodingRequest,
+        _state: &mut ConnectionState,
+    ) -> Result<ErrorResponse, io::Error> {
+        Ok(ErrorResponse(ErrorCode::None))
+    }
+}
+
+async fn build_server(strict_decoding: bool) -> KafkaServer {
+    KafkaServerBuilder::new()
+        .register(UNDER_DECODING_KEY, UnderDecodingHandler)
+        .strict_decoding(strict_decoding)
+        .build()
+        .await
+        .unwrap()
+}
+
+/// Sends a request with a body its decoder leaves untouched, returning the
+/// error code of the response.
+async fn send_under_decoded(server: &KafkaServer) -> i16 {
+    let (client, stream) = tokio::io::duplex(4096);
+    server.serve(stream).await.unwrap();
+    let mut client = TestClient::new(client);
+
+    let body = client
+        .send_raw(UNDER_DECODING_KEY.as_i16(), 0, 1, &[1, 2, 3])
+        .await
+        .unwrap();
+    i16::from_be_bytes(body[..].try_into().unwrap())
+}
+
+#[tokio::test]
+async fn leftover_bytes_are_rejected_when_strict() {
+    let server = build_server(true).await;
+    assert_eq!(
+        send_under_decoded(&server).await,
+        ErrorCode::InvalidRequest as i16
+    );
+}
+
+#[tokio::test]
+async fn leftover_bytes_are_ignored_by_default() {
+    let server = build_server(false).await;
+    assert_eq!(send_under_decoded(&server).await, ErrorCode::None as i16);
+}
@@ -0,0 +1,79 @@
+//! Checks a handler can advertise fewer versions than its request decodes,
+//! and that the server can cap them further.
+
+use std::io;
+
+use laconia_agent::{
+    ConnectionState, RequestHeader, VersionRange,
+    protocol::{
+        api_keys::ApiKey,
+        handlers::{RequestHandler, api_versions},
+        messages::{MetadataRequest, MetadataResponse},
+        registry::MessageRegistry,
+    },
+    server::KafkaServerBuilder,
+};
+
+/// A Metadata handler only implementing v9 to v12.
+struct NarrowMetadataHandler;
+
+impl RequestHandler<MetadataRequest> for NarrowMetadataHandler {
+    async fn handle(
+        &self,
+        _header: &RequestHeader,
+        _request: &MetadataRequest,
+        _state: &mut ConnectionState,
+    ) -> Result<MetadataResponse, io::Error> {
+        Err(io::Error::other("not called"))
+    }
+
+    fn supported_versions(&self) -> VersionRange {
+        VersionRange::new(9, 12)
+    }
+}
+
+#[test]
+fn handler_narrows_the_advertised_range() {
+    let mut registry = MessageRegistry::new();
+    registry
+        .register(ApiKey::Metadata, NarrowMetadataHandler)
+        .unwrap();
+
+    let versions = registry.versions(ApiKey::Metadata).unwrap();
+    assert_eq!((versions.min, versions.max), (9, 12));
+
+    let api_keys = api_versions(&registry);
+    let [metadata] = api_keys.as_slice() else {
+        panic!("expected only Metadata to be advertised");
+    };
+    assert_eq!(metadata.api_key, 3);
+    assert_eq!((metadata.min_version, metadata.max_version), (9, 12));
+}
+
+#[test]
+fn capping_metadata_lowers_the_advertised_max() {
+    let mut registry = MessageRegistry::new();
+    registry
+        .register(ApiKey::Metadata, NarrowMetadataHandler)
+        .unwrap();
+    registry.cap_max_version(ApiKey::Metadata, 10).unwrap();
+
+    let api_keys = api_versions(&registry);
+    let [metadata] = api_keys.as_slice() else {
+        panic!("expected only Metadata to be advertised");
+    };
+    assert_eq!((metadata.min_version, metadata.max_version), (9, 10));
+}
+
+#[tokio::test]
+async fn cap_outside_the_handler_versions_fails_the_build() {
+    let result = KafkaServerBuilder::new()
+        .register(ApiKey::Metadata, NarrowMetadataHandler)
+        .max_version(ApiKey::Metadata, 13)
+        .build()
+        .await;
+    assert_eq!(
+        result.err().map(|err| err.kind()),
+        Some(io::ErrorKind::InvalidInput)
+    );
+}
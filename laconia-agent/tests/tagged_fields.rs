@@ -0,0 +1,85 @@
+//! Checks handlers see a request's tagged fields and can decode them.
+
+use std::{collections::BTreeMap, io, sync::Arc};
+
+use bytes::{BufMut, Bytes, BytesMut};
+use laconia_agent::{
+    ConnectionState, RequestHeader,
+    protocol::{
+        error::ErrorCode,
+        handlers::RequestHandler,
+        messages::{ApiVersionsRequest, ApiVersionsResponse},
+        primitives::{CompactString, TaggedFields},
+        registry::MessageRegistry,
+        request::Request,
+    },
+};
+
+/// The tag a client can name its rack with.
+const RACK_TAG: i32 = 0;
+
+/// Only answers clients in the `eu` rack.
+struct RackAwareHandler;
+
+impl RequestHandler<ApiVersionsRequest> for RackAwareHandler {
+    async fn handle(
+        &self,
+        _header: &RequestHeader,
+        request: &ApiVersionsRequest,
+        _state: &mut ConnectionState,
+    ) -> Result<ApiVersionsResponse, io::Error> {
+        let rack = match request.tagged_fields.decode_tag::<CompactString>(RACK_TAG) {
+            Some(rack) => Some(rack?.0),
+            None => None,
+        };
+
+        let error_code = match rack.as_deref() {
+            Some("eu") => ErrorCode::None,
+            _ => ErrorCode::InvalidRequest,
+        };
+        Ok(request.error_response(error_code))
+    }
+}
+
+fn request(rack: Option<&str>) -> ApiVersionsRequest {
+    let mut tagged_fields = BTreeMap::new();
+    if let Some(rack) = rack {
+        let mut buf = BytesMut::new();
+        buf.put_u8(rack.len() as u8 + 1);
+        buf.extend_from_slice(rack.as_bytes());
+        tagged_fields.insert(RACK_TAG, Bytes::from(buf));
+    }
+
+    ApiVersionsRequest {
+        client_software_name: "laconia-tests".to_string(),
+        client_software_version: "0.1.0".to_string(),
+        tagged_fields,
+    }
+}
+
+/// Answers `request` with [`RackAwareHandler`], returning the error code.
+async fn handle(request: &ApiVersionsRequest) -> ErrorCode {
+    let header = RequestHeader {
+        api_key: 18,
+        version: 3,
+        correlation_id: 0,
+        client_id: "laconia-tests".to_string(),
+        tagged_fields: BTreeMap::new(),
+    };
+    let mut state = ConnectionState::new(Arc::new(MessageRegistry::new()));
+    let response = RackAwareHandler
+        .handle(&header, request, &mut state)
+        .await
+        .unwrap();
+    response.error_code
+}
+
+#[tokio::test]
+async fn tagged_field_decides_the_response() {
+    assert_eq!(handle(&request(Some("eu"))).await, ErrorCode::None);
+    assert_eq!(
+        handle(&request(Some("us"))).await,
+        ErrorCode::InvalidRequest
+    );
+    assert_eq!(handle(&request(None)).await, ErrorCode::InvalidRequest);
+}
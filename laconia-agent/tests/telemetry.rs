@@ -0,0 +1,99 @@
+//! Checks handling requests is reflected in the Prometheus metrics.
+//!
+//! Each test records into its own recorder, installed for the current thread
+//! only, and drives the request on a single-threaded runtime so the handler
+//! records into it too.
+
+use std::{future::Future, sync::Arc};
+
+use bytes::{BufMut, BytesMut};
+use laconia_agent::{
+    ConnectionState, KafkaMessageCodec, KafkaRequest, KafkaResponse,
+    protocol::{
+        api_keys::ApiKey,
+        handlers::ApiVersionsHandler,
+        registry::{API_VERSIONS_KEY, MessageRegistry},
+    },
+};
+use metrics_exporter_prometheus::PrometheusBuilder;
+use tokio_util::codec::Encoder;
+
+/// Runs `future` to completion, returning the metrics it recorded in the
+/// Prometheus text format.
+fn recorded(future: impl Future<Output = ()>) -> String {
+    let recorder = PrometheusBuilder::new().build_recorder();
+    let handle = recorder.handle();
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+    metrics::with_local_recorder(&recorder, || runtime.block_on(future));
+    handle.render()
+}
+
+/// A v2 header for ApiVersions v3, then its body: client software name and
+/// version as compact strings, and no tagged fields.
+fn api_versions_frame() -> BytesMut {
+    let mut frame = BytesMut::new();
+    frame.put_i16(API_VERSIONS_KEY);
+    frame.put_i16(3);
+    frame.put_i32(0);
+    frame.put_i16(-1);
+    frame.put_u8(0);
+    frame.put_u8(1);
+    frame.put_u8(1);
+    frame.put_u8(0);
+    frame
+}
+
+#[test]
+fn handled_api_versions_request_is_counted() {
+    let mut registry = MessageRegistry::new();
+    registry
+        .register(ApiKey::ApiVersions, ApiVersionsHandler)
+        .unwrap();
+    let registry = Arc::new(registry);
+
+    let mut frame = api_versions_frame();
+
+    let metrics = recorded(async {
+        let mut state = ConnectionState::new(registry.clone());
+        KafkaRequest::decode_and_handle(&mut frame, &registry, &mut state)
+            .await
+            .unwrap();
+    });
+
+    assert!(
+        metrics.contains("requests_total{api_key=\"18\"} 1"),
+        "{}",
+        metrics
+    );
+}
+
+#[test]
+fn api_versions_response_size_is_observed() {
+    let mut registry = MessageRegistry::new();
+    registry
+        .register(ApiKey::ApiVersions, ApiVersionsHandler)
+        .unwrap();
+    let registry = Arc::new(registry);
+
+    let mut frame = api_versions_frame();
+
+    let metrics = recorded(async {
+        let mut state = ConnectionState::new(registry.clone());
+        let request = KafkaRequest::decode_and_handle(&mut frame, &registry, &mut state)
+            .await
+            .unwrap();
+        let response =
+            KafkaResponse::new(&request.header, request.response_version, request.response);
+        KafkaMessageCodec::new()
+            .encode(response, &mut BytesMut::new())
+            .unwrap();
+    });
+
+    assert!(
+        metrics.contains("response_bytes_count{api_key=\"18\"} 1"),
+        "{}",
+        metrics
+    );
+}
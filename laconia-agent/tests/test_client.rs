@@ -0,0 +1,113 @@
+//! Drives the server in-process with the crate's own test client. Only built
+//! with the `test-util` feature, which exposes the client.
+
+use std::sync::Arc;
+
+use laconia_agent::{
+    DecoderVersioned,
+    protocol::{
+        api_keys::ApiKey,
+        error::ErrorCode,
+        handlers::ApiVersionsHandler,
+        messages::{ApiVersionsRequest, ApiVersionsResponse},
+        registry::{API_VERSIONS_KEY, MessageRegistry},
+    },
+    server::{KafkaServerBuilder, handle_connection},
+    test_client::TestClient,
+};
+
+#[tokio::test]
+async fn api_versions_over_duplex() {
+    let server = KafkaServerBuilder::new()
+        .register(ApiKey::ApiVersions, ApiVersionsHandler)
+        .build()
+        .await
+        .unwrap();
+
+    let (client, stream) = tokio::io::duplex(4096);
+    server.serve(stream).await.unwrap();
+    let mut client = TestClient::new(client);
+
+    for version in [0, 3, 4] {
+        let request = ApiVersionsRequest {
+            client_software_name: "laconia-tests".to_string(),
+            client_software_version: "0.1.0".to_string(),
+            tagged_fields: Default::default(),
+        };
+        let response = client
+            .send(ApiKey::ApiVersions, version, &request)
+            .await
+            .unwrap();
+
+        assert_eq!(response.error_code, ErrorCode::None);
+        let api_versions = response
+            .api_keys
+            .iter()
+            .find(|api_key| api_key.api_key == API_VERSIONS_KEY)
+            .expect("ApiVersions lists itself");
+        assert_eq!((api_versions.min_version, api_versions.max_version), (0, 4));
+    }
+}
+
+#[tokio::test]
+async fn unsupported_api_versions_is_answered_in_v0() {
+    let server = KafkaServerBuilder::new()
+        .register(ApiKey::ApiVersions, ApiVersionsHandler)
+        .build()
+        .await
+        .unwrap();
+
+    let (client, stream) = tokio::io::duplex(4096);
+    server.serve(stream).await.unwrap();
+    let mut client = TestClient::new(client);
+
+    let mut body = client
+        .send_raw(API_VERSIONS_KEY, 99, 2, &[0])
+        .await
+        .unwrap();
+    // The unknown version's body isn't read, so any will do.
+    let response = ApiVersionsResponse::decode(&mut body, 0).unwrap();
+    assert_eq!(response.error_code, ErrorCode::UnsupportedVersion);
+}
+
+#[tokio::test]
+async fn handle_connection_returns_once_the_client_disconnects() {
+    let mut registry = MessageRegistry::new();
+    registry
+        .register(ApiKey::ApiVersions, ApiVersionsHandler)
+        .unwrap();
+
+    let (client, stream) = tokio::io::duplex(4096);
+    let client = async move {
+        let mut client = TestClient::new(client);
+        let request = ApiVersionsRequest {
+            client_software_name: "laconia-tests".to_string(),
+            client_software_version: "0.1.0".to_string(),
+            tagged_fields: Default::default(),
+        };
+        client.send(ApiKey::ApiVersions, 4, &request).await.unwrap()
+    };
+
+    let ((), response) = tokio::join!(handle_connection(stream, Arc::new(registry)), client);
+    assert_eq!(response.error_code, ErrorCode::None);
+}
+
+#[tokio::test]
+async fn api_versions_v0_with_trailing_bytes_is_rejected() {
+    let server = KafkaServerBuilder::new()
+        .register(ApiKey::ApiVersions, ApiVersionsHandler)
+        .build()
+        .await
+        .unwrap();
+
+    let (client, stream) = tokio::io::duplex(4096);
+    server.serve(stream).await.unwrap();
+    let mut client = TestClient::new(client);
+
+    let mut body = client
+        .send_raw(API_VERSIONS_KEY, 0, 1, &[0xde, 0xad])
+        .await
+        .unwrap();
+    let response = ApiVersionsResponse::decode(&mut body, 0).unwrap();
+    assert_eq!(response.error_code, ErrorCode::InvalidRequest);
+}
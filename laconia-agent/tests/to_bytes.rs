@@ -0,0 +1,39 @@
+//! Checks responses encoded to bytes directly match what the codec writes to
+//! the connection.
+
+mod common;
+
+use std::sync::Arc;
+
+use bytes::BytesMut;
+use laconia_agent::{
+    ConnectionState, KafkaMessageCodec, KafkaRequest, KafkaResponse,
+    protocol::{api_keys::ApiKey, handlers::ApiVersionsHandler, registry::MessageRegistry},
+};
+use tokio_util::codec::Encoder;
+
+#[tokio::test]
+async fn to_bytes_matches_the_codec() {
+    let mut registry = MessageRegistry::new();
+    registry
+        .register(ApiKey::ApiVersions, ApiVersionsHandler)
+        .unwrap();
+    let registry = Arc::new(registry);
+    let mut state = ConnectionState::new(registry.clone());
+
+    let mut frame = BytesMut::from(&common::api_versions_request(7)[..]);
+    let request = KafkaRequest::decode_and_handle(&mut frame, &registry, &mut state)
+        .await
+        .unwrap();
+    let request_bytes = request.response_bytes().unwrap();
+
+    let response = KafkaResponse::new(&request.header, request.response_version, request.response);
+    let bytes = response.to_bytes().unwrap();
+    assert_eq!(bytes, request_bytes);
+
+    let mut encoded = BytesMut::new();
+    KafkaMessageCodec::new()
+        .encode(response, &mut encoded)
+        .unwrap();
+    assert_eq!(bytes, encoded);
+}
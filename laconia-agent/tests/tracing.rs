@@ -0,0 +1,118 @@
+//! Checks handled requests are traced in a span naming the request, that
+//! requests carrying credentials are logged without them, and that closed
+//! connections log how many requests they handled.
+
+mod common;
+
+use std::{net::Ipv4Addr, sync::Arc, time::Duration};
+
+use bytes::{BufMut, BytesMut};
+use futures::{SinkExt, StreamExt};
+use laconia_agent::{
+    ConnectionState, KafkaRequest,
+    protocol::{
+        api_keys::ApiKey,
+        handlers::{ApiVersionsHandler, SaslAuthenticateHandler},
+        registry::{API_VERSIONS_KEY, MessageRegistry},
+    },
+    server::KafkaServerBuilder,
+};
+use tokio::net::TcpStream;
+use tokio_util::codec::{Framed, LengthDelimitedCodec};
+use tracing_test::traced_test;
+
+#[tokio::test]
+#[traced_test]
+async fn handled_request_is_traced_with_its_api_key() {
+    let mut registry = MessageRegistry::new();
+    registry
+        .register(ApiKey::ApiVersions, ApiVersionsHandler)
+        .unwrap();
+    let registry = Arc::new(registry);
+    let mut state = ConnectionState::new(registry.clone());
+
+    // A v2 header for ApiVersions v3, then its body: client software name and
+    // version as compact strings, and no tagged fields.
+    let mut frame = BytesMut::new();
+    frame.put_i16(API_VERSIONS_KEY);
+    frame.put_i16(3);
+    frame.put_i32(7);
+    frame.put_i16(-1);
+    frame.put_u8(0);
+    frame.put_u8(1);
+    frame.put_u8(1);
+    frame.put_u8(0);
+
+    KafkaRequest::decode_and_handle(&mut frame, &registry, &mut state)
+        .await
+        .unwrap();
+
+    assert!(logs_contain(
+        "request{api_key=18 api_version=3 correlation_id=7}"
+    ));
+    assert!(logs_contain("Handling ApiVersionsRequest"));
+}
+
+#[tokio::test]
+#[traced_test]
+async fn sasl_credentials_are_not_logged() {
+    let mut registry = MessageRegistry::new();
+    registry
+        .register(ApiKey::SaslAuthenticate, SaslAuthenticateHandler)
+        .unwrap();
+    let registry = Arc::new(registry);
+    let mut state = ConnectionState::new(registry.clone());
+
+    // A v2 header for SaslAuthenticate v2, then SASL PLAIN's authorization
+    // id, user name and password as compact bytes, and no tagged fields.
+    let auth_bytes = b"\0alice\0hunter2";
+    let mut frame = BytesMut::new();
+    frame.put_i16(36);
+    frame.put_i16(2);
+    frame.put_i32(0);
+    frame.put_i16(-1);
+    frame.put_u8(0);
+    frame.put_u8(auth_bytes.len() as u8 + 1);
+    frame.extend_from_slice(auth_bytes);
+    frame.put_u8(0);
+
+    KafkaRequest::decode_and_handle(&mut frame, &registry, &mut state)
+        .await
+        .unwrap();
+
+    assert!(logs_contain("SaslAuthenticateRequest { <redacted> }"));
+    assert!(!logs_contain("hunter2"));
+}
+
+#[tokio::test]
+#[traced_test]
+async fn closed_connection_logs_its_request_count() {
+    let server = KafkaServerBuilder::new()
+        .register(ApiKey::ApiVersions, ApiVersionsHandler)
+        .bind((Ipv4Addr::LOCALHOST, 0).into())
+        .build()
+        .await
+        .unwrap();
+    let addr = server.local_addr().unwrap();
+    tokio::spawn(async move { server.accept().await });
+
+    let stream = TcpStream::connect(addr).await.unwrap();
+    let mut client = Framed::new(stream, LengthDelimitedCodec::new());
+    for correlation_id in 0..3 {
+        client
+            .send(common::api_versions_request(correlation_id))
+            .await
+            .unwrap();
+        client.next().await.unwrap().unwrap();
+    }
+    drop(client);
+
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while !logs_contain("Connection closed") {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .unwrap();
+    assert!(logs_contain("requests=3"));
+}
@@ -0,0 +1,215 @@
+//! Checks a transactional producer can run a transaction through the
+//! handlers, that offsets committed in a transaction share its outcome, and
+//! that re-initializing a transactional id fences the producer that used it
+//! before.
+
+use std::{collections::BTreeMap, sync::Arc};
+
+use laconia_agent::{
+    ConnectionState, RequestHeader,
+    configs::ConfigStore,
+    log::LogStore,
+    offsets::OffsetStore,
+    protocol::{
+        error::ErrorCode,
+        handlers::{
+            AddPartitionsToTxnHandler, EndTxnHandler, InitProducerIdHandler, RequestHandler,
+            TxnOffsetCommitHandler,
+        },
+        messages::{
+            AddPartitionsToTxnRequest, AddPartitionsToTxnTopic, EndTxnRequest,
+            InitProducerIdRequest, TxnOffsetCommitPartition, TxnOffsetCommitRequest,
+            TxnOffsetCommitTopic,
+        },
+        registry::MessageRegistry,
+    },
+    transactions::TransactionManager,
+};
+
+fn transaction_manager() -> TransactionManager {
+    let configs = Arc::new(ConfigStore::new(0, vec![]));
+    let logs = Arc::new(LogStore::open(None, false, configs).unwrap());
+    TransactionManager::new(logs, Arc::new(OffsetStore::new()))
+}
+
+fn header(api_key: i16, version: i16) -> RequestHeader {
+    RequestHeader {
+        api_key,
+        version,
+        correlation_id: 0,
+        client_id: "laconia-tests".to_string(),
+        tagged_fields: BTreeMap::new(),
+    }
+}
+
+#[tokio::test]
+async fn begin_add_partitions_and_commit() {
+    let transactions = Arc::new(transaction_manager());
+    let mut state = ConnectionState::new(Arc::new(MessageRegistry::new()));
+
+    let request = InitProducerIdRequest {
+        transactional_id: "txn".to_string(),
+        transaction_timeout_ms: 60_000,
+        producer_id: -1,
+        producer_epoch: -1,
+        tagged_fields: BTreeMap::new(),
+    };
+    let response = InitProducerIdHandler::new(transactions.clone())
+        .handle(&header(22, 4), &request, &mut state)
+        .await
+        .unwrap();
+    assert_eq!(response.error_code, ErrorCode::None);
+    let (producer_id, producer_epoch) = (response.producer_id, response.producer_epoch);
+
+    let request = AddPartitionsToTxnRequest {
+        transactional_id: "txn".to_string(),
+        producer_id,
+        producer_epoch,
+        topics: vec![AddPartitionsToTxnTopic {
+            name: "events".to_string(),
+            partitions: vec![0, 1],
+            tagged_fields: BTreeMap::new(),
+        }],
+        tagged_fields: BTreeMap::new(),
+    };
+    let response = AddPartitionsToTxnHandler::new(transactions.clone())
+        .handle(&header(24, 3), &request, &mut state)
+        .await
+        .unwrap();
+    let errors: Vec<_> = response
+        .results
+        .iter()
+        .flat_map(|topic| &topic.results)
+        .map(|partition| (partition.partition_index, partition.error_code))
+        .collect();
+    assert_eq!(errors, [(0, ErrorCode::None), (1, ErrorCode::None)]);
+
+    let request = EndTxnRequest {
+        transactional_id: "txn".to_string(),
+        producer_id,
+        producer_epoch,
+        committed: true,
+        tagged_fields: BTreeMap::new(),
+    };
+    let response = EndTxnHandler::new(transactions)
+        .handle(&header(26, 3), &request, &mut state)
+        .await
+        .unwrap();
+    assert_eq!(response.error_code, ErrorCode::None);
+}
+
+/// Commits `offset` for partition 0 of `events` on behalf of `group`,
+/// inside the producer's transaction.
+async fn txn_offset_commit(
+    transactions: &Arc<TransactionManager>,
+    producer_id: i64,
+    producer_epoch: i16,
+    offset: i64,
+) -> ErrorCode {
+    let request = TxnOffsetCommitRequest {
+        transactional_id: "txn".to_string(),
+        group_id: "group".to_string(),
+        producer_id,
+        producer_epoch,
+        generation_id: -1,
+        member_id: String::new(),
+        group_instance_id: String::new(),
+        topics: vec![TxnOffsetCommitTopic {
+            name: "events".to_string(),
+            partitions: vec![TxnOffsetCommitPartition {
+                partition_index: 0,
+                committed_offset: offset,
+                committed_leader_epoch: -1,
+                committed_metadata: String::new(),
+                tagged_fields: BTreeMap::new(),
+            }],
+            tagged_fields: BTreeMap::new(),
+        }],
+        tagged_fields: BTreeMap::new(),
+    };
+    let mut state = ConnectionState::new(Arc::new(MessageRegistry::new()));
+    let response = TxnOffsetCommitHandler::new(transactions.clone())
+        .handle(&header(28, 3), &request, &mut state)
+        .await
+        .unwrap();
+    response.topics[0].partitions[0].error_code
+}
+
+#[tokio::test]
+async fn offsets_committed_in_an_aborted_transaction_are_not_visible() {
+    let configs = Arc::new(ConfigStore::new(0, vec![]));
+    let logs = Arc::new(LogStore::open(None, false, configs).unwrap());
+    let offsets = Arc::new(OffsetStore::new());
+    let transactions = Arc::new(TransactionManager::new(logs, offsets.clone()));
+    let committed = || {
+        offsets
+            .fetch("group", "events", 0)
+            .map(|committed| committed.offset)
+    };
+
+    let (producer_id, epoch) = transactions.init_producer_id(Some("txn"));
+    let error_code = txn_offset_commit(&transactions, producer_id, epoch, 5).await;
+    assert_eq!(error_code, ErrorCode::None);
+    assert_eq!(committed(), None);
+    transactions
+        .end_transaction("txn", producer_id, epoch, false)
+        .unwrap();
+    assert_eq!(committed(), None);
+
+    let error_code = txn_offset_commit(&transactions, producer_id, epoch, 7).await;
+    assert_eq!(error_code, ErrorCode::None);
+    transactions
+        .end_transaction("txn", producer_id, epoch, true)
+        .unwrap();
+    assert_eq!(committed(), Some(7));
+}
+
+#[test]
+fn reinit_bumps_the_epoch_and_fences_the_old_one() {
+    let transactions = transaction_manager();
+    let partitions = || [("events".to_string(), 0)];
+
+    let (producer_id, epoch) = transactions.init_producer_id(Some("txn"));
+    assert_eq!(epoch, 0);
+    transactions
+        .add_partitions("txn", producer_id, epoch, partitions())
+        .unwrap();
+
+    let (new_producer_id, new_epoch) = transactions.init_producer_id(Some("txn"));
+    assert_eq!(new_producer_id, producer_id);
+    assert_eq!(new_epoch, 1);
+
+    assert_eq!(
+        transactions.add_partitions("txn", producer_id, epoch, partitions()),
+        Err(ErrorCode::ProducerFenced)
+    );
+    assert_eq!(
+        transactions.end_transaction("txn", producer_id, epoch, true),
+        Err(ErrorCode::ProducerFenced)
+    );
+
+    transactions
+        .add_partitions("txn", producer_id, new_epoch, partitions())
+        .unwrap();
+    transactions
+        .end_transaction("txn", producer_id, new_epoch, true)
+        .unwrap();
+}
+
+#[test]
+fn exhausted_epoch_gets_a_new_producer_id() {
+    let transactions = transaction_manager();
+
+    let (producer_id, _) = transactions.init_producer_id(Some("txn"));
+    let mut epoch = 0;
+    while epoch < i16::MAX - 1 {
+        let (id, next_epoch) = transactions.init_producer_id(Some("txn"));
+        assert_eq!(id, producer_id);
+        assert_eq!(next_epoch, epoch + 1);
+        epoch = next_epoch;
+    }
+
+    let (new_producer_id, new_epoch) = transactions.init_producer_id(Some("txn"));
+    assert_ne!(new_producer_id, producer_id);
+    assert_eq!(new_epoch, 0);
+}
@@ -1,4 +1,24 @@
+use std::time::Duration;
+
+use laconia_liveness::{liveness::FILE_DESCRIPTOR_SET, server::LivenessServer};
+use tonic::transport::Server;
+
 #[tokio::main]
-async fn main() {
-    println!("Hello, world!");
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let addr = "[::1]:50540".parse()?;
+
+    let liveness = LivenessServer::new(Duration::from_secs(10));
+
+    // Lets tools like grpcurl discover the service without the proto file.
+    let reflection = tonic_reflection::server::Builder::configure()
+        .register_encoded_file_descriptor_set(FILE_DESCRIPTOR_SET)
+        .build_v1()?;
+
+    Server::builder()
+        .add_service(liveness.into_service())
+        .add_service(reflection)
+        .serve(addr)
+        .await?;
+
+    Ok(())
 }
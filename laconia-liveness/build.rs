@@ -1,5 +1,10 @@
+use std::{env, path::PathBuf};
+
 fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let mut builder = tonic_build::configure();
+    let out_dir = PathBuf::from(env::var("OUT_DIR")?);
+
+    let mut builder =
+        tonic_build::configure().file_descriptor_set_path(out_dir.join("liveness_descriptor.bin"));
 
     if cfg!(feature = "client") {
         builder = builder.build_client(true);
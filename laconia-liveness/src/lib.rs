@@ -1,3 +1,11 @@
 pub mod liveness {
     tonic::include_proto!("liveness");
+
+    /// Encoded descriptors for the liveness protocol, as consumed by gRPC
+    /// reflection services.
+    pub const FILE_DESCRIPTOR_SET: &[u8] =
+        tonic::include_file_descriptor_set!("liveness_descriptor");
 }
+
+#[cfg(feature = "server")]
+pub mod server;
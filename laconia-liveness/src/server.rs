@@ -0,0 +1,74 @@
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use tonic::{Request, Response, Status};
+
+use crate::liveness::{
+    CheckinReply, CheckinRequest, DeregisterReply, DeregisterRequest, PingReply, PingRequest,
+    liveness_server::{self, Liveness},
+};
+
+/// Reference implementation of the liveness service.
+///
+/// Tracks the last time each agent checked in and hands out a fixed checkin
+/// interval.
+pub struct LivenessServer {
+    interval: Duration,
+    last_seen: Mutex<HashMap<String, Instant>>,
+}
+
+impl LivenessServer {
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            last_seen: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn last_seen(&self, id: &str) -> Option<Instant> {
+        self.last_seen.lock().unwrap().get(id).copied()
+    }
+
+    pub fn into_service(self) -> liveness_server::LivenessServer<Self> {
+        liveness_server::LivenessServer::new(self)
+    }
+}
+
+#[tonic::async_trait]
+impl Liveness for LivenessServer {
+    async fn checkin(
+        &self,
+        request: Request<CheckinRequest>,
+    ) -> Result<Response<CheckinReply>, Status> {
+        let id = request.into_inner().id;
+        self.last_seen.lock().unwrap().insert(id, Instant::now());
+
+        Ok(Response::new(CheckinReply {
+            interval: self.interval.as_millis() as i32,
+        }))
+    }
+
+    async fn ping(&self, request: Request<PingRequest>) -> Result<Response<PingReply>, Status> {
+        let id = request.into_inner().id;
+
+        match self.last_seen.lock().unwrap().get_mut(&id) {
+            Some(last_seen) => *last_seen = Instant::now(),
+            None => return Err(Status::not_found(format!("unknown agent: {}", id))),
+        }
+
+        Ok(Response::new(PingReply {}))
+    }
+
+    async fn deregister(
+        &self,
+        request: Request<DeregisterRequest>,
+    ) -> Result<Response<DeregisterReply>, Status> {
+        let id = request.into_inner().id;
+        self.last_seen.lock().unwrap().remove(&id);
+
+        Ok(Response::new(DeregisterReply {}))
+    }
+}
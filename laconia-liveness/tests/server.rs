@@ -0,0 +1,44 @@
+//! Checks the reference liveness server tracks checkins and deregistrations.
+
+use std::time::{Duration, Instant};
+
+use laconia_liveness::{
+    liveness::{CheckinRequest, DeregisterRequest, liveness_server::Liveness},
+    server::LivenessServer,
+};
+use tonic::Request;
+
+fn checkin_request(id: &str) -> Request<CheckinRequest> {
+    Request::new(CheckinRequest { id: id.to_string() })
+}
+
+#[tokio::test]
+async fn checkin_updates_last_seen_and_returns_the_interval() {
+    let server = LivenessServer::new(Duration::from_secs(7));
+    assert!(server.last_seen("agent").is_none());
+
+    let before = Instant::now();
+    let reply = server.checkin(checkin_request("agent")).await.unwrap();
+    assert_eq!(reply.into_inner().interval, 7_000);
+
+    let first = server.last_seen("agent").expect("the agent has been seen");
+    assert!(first >= before);
+
+    server.checkin(checkin_request("agent")).await.unwrap();
+    assert!(server.last_seen("agent").unwrap() >= first);
+
+    // Other agents are tracked separately.
+    assert!(server.last_seen("other").is_none());
+}
+
+#[tokio::test]
+async fn deregistered_agent_is_forgotten() {
+    let server = LivenessServer::new(Duration::from_secs(7));
+    server.checkin(checkin_request("agent")).await.unwrap();
+
+    let request = Request::new(DeregisterRequest {
+        id: "agent".to_string(),
+    });
+    server.deregister(request).await.unwrap();
+    assert!(server.last_seen("agent").is_none());
+}